@@ -195,20 +195,87 @@ impl TransactionVersion {
 }
 
 /// A way of identifying a specific block.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+///
+/// Deserializes from any of the following JSON forms:
+/// - `{"block_hash": <hash>}`
+/// - `{"block_number": <number>}`
+/// - `"latest"`
+/// - `"pending"`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(any(test, feature = "full-serde"), derive(Serialize))]
-#[serde(deny_unknown_fields)]
 pub enum BlockId {
-    #[serde(rename = "block_number")]
+    #[cfg_attr(any(test, feature = "full-serde"), serde(rename = "block_number"))]
     Number(BlockNumber),
-    #[serde(rename = "block_hash")]
+    #[cfg_attr(any(test, feature = "full-serde"), serde(rename = "block_hash"))]
     Hash(BlockHash),
-    #[serde(rename = "latest")]
+    #[cfg_attr(any(test, feature = "full-serde"), serde(rename = "latest"))]
     Latest,
-    #[serde(rename = "pending")]
+    #[cfg_attr(any(test, feature = "full-serde"), serde(rename = "pending"))]
     Pending,
 }
 
+/// Hand-rolled so that all malformed inputs produce the same, descriptive error
+/// irrespective of which of the forms was attempted.
+impl<'de> Deserialize<'de> for BlockId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        const EXPECTING: &str = r#"a block id: {"block_hash": <hash>}, {"block_number": <number>}, "latest" or "pending""#;
+
+        struct BlockIdVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for BlockIdVisitor {
+            type Value = BlockId;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str(EXPECTING)
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match v {
+                    "latest" => Ok(BlockId::Latest),
+                    "pending" => Ok(BlockId::Pending),
+                    _ => Err(E::invalid_value(serde::de::Unexpected::Str(v), &self)),
+                }
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let block_id = match map.next_key::<String>()?.as_deref() {
+                    Some("block_hash") => BlockId::Hash(map.next_value()?),
+                    Some("block_number") => BlockId::Number(map.next_value()?),
+                    Some(other) => {
+                        return Err(A::Error::unknown_field(
+                            other,
+                            &["block_hash", "block_number"],
+                        ))
+                    }
+                    None => return Err(A::Error::invalid_length(0, &self)),
+                };
+
+                if map.next_key::<serde::de::IgnoredAny>()?.is_some() {
+                    return Err(A::Error::invalid_length(2, &self));
+                }
+
+                Ok(block_id)
+            }
+        }
+
+        // The visitor's errors differ per attempted form, so they are all replaced by one.
+        deserializer
+            .deserialize_any(BlockIdVisitor)
+            .map_err(|_| D::Error::custom(format!("invalid block id, expected {EXPECTING}")))
+    }
+}
+
 impl BlockNumber {
     pub const GENESIS: BlockNumber = BlockNumber::new_or_panic(0);
     /// The maximum [BlockNumber] we can support. Restricted to `u64::MAX/2` to
@@ -560,5 +627,30 @@ mod tests {
                 serde_json::from_str::<BlockId>(r#"{"block_hash": "0xdeadbeef"}"#).unwrap();
             assert_eq!(result, BlockId::Hash(block_hash!("0xdeadbeef")));
         }
+
+        #[test]
+        fn malformed() {
+            let examples = [
+                (line!(), r#""earliest""#),
+                (line!(), r#"{"block_height": 1}"#),
+                (line!(), r#"{"block_number": 1, "block_hash": "0x1"}"#),
+                (line!(), r#"{}"#),
+                (line!(), r#"{"block_hash": "0xzz"}"#),
+                (line!(), r#"{"block_number": "one"}"#),
+                (line!(), r#"123"#),
+                (line!(), r#"null"#),
+            ];
+
+            for (line, input) in examples {
+                let error = serde_json::from_str::<BlockId>(input)
+                    .expect_err(&format!("example from line {line}"));
+                assert!(
+                    error.to_string().starts_with(
+                        r#"invalid block id, expected a block id: {"block_hash": <hash>}, {"block_number": <number>}, "latest" or "pending""#
+                    ),
+                    "example from line {line}: {error}"
+                );
+            }
+        }
     }
 }
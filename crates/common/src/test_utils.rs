@@ -103,11 +103,12 @@ pub mod metrics {
             }
         }
 
+        /// Only counters are recorded.
         fn register_gauge(&self, _: &Key) -> Gauge {
-            unimplemented!()
+            Gauge::noop()
         }
         fn register_histogram(&self, _: &Key) -> Histogram {
-            unimplemented!()
+            Histogram::noop()
         }
     }
 
@@ -1,4 +1,5 @@
 use crate::state_reader::LruCachedReader;
+use anyhow::Context;
 use blockifier::{block_context::BlockContext, state::cached_state::CachedState};
 use pathfinder_common::{BlockNumber, BlockTimestamp, ChainId, SequencerAddress, StateUpdate};
 use primitive_types::U256;
@@ -15,6 +16,10 @@ pub struct ExecutionState {
     pub state_at_block: Option<BlockNumber>,
     pub gas_price: U256,
     pub pending_update: Option<Arc<StateUpdate>>,
+    /// Storage to override before execution, applied on top of the `pending_update`.
+    ///
+    /// Only the storage updates of this state update are supported.
+    pub state_overrides: Option<StateUpdate>,
 }
 
 impl ExecutionState {
@@ -37,6 +42,11 @@ impl ExecutionState {
             super::pending::apply_pending_update(&mut cached_state, pending_update.as_ref())
         });
 
+        if let Some(state_overrides) = &self.state_overrides {
+            super::pending::apply_pending_update(&mut cached_state, state_overrides)
+                .context("Applying state overrides")?;
+        }
+
         Ok((cached_state, block_context))
     }
 }
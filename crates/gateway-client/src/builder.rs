@@ -9,17 +9,32 @@
 //!   2. [Method](stage::Method) where you select the REST API method.
 //!   3. [Params](stage::Params) where you select the retry behavior.
 //!   4. [Final](stage::Final) where you select the REST operation type, which is then executed.
+use crate::circuit_breaker::CircuitBreaker;
+use crate::failover::{Api, Failover};
 use crate::metrics::{with_metrics, BlockTag, RequestMetadata};
 use pathfinder_common::{BlockId, ClassHash, TransactionHash};
 use starknet_gateway_types::error::SequencerError;
+use std::num::NonZeroUsize;
+use tokio::time::Instant;
 
 /// A Sequencer Request builder.
 pub struct Request<'a, S: RequestState> {
     state: S,
     url: reqwest::Url,
     client: &'a reqwest::Client,
+    /// Retry limit used if retrying is enabled, [None] retries indefinitely.
+    max_retries: Option<NonZeroUsize>,
+    circuit_breaker: Option<&'a CircuitBreaker>,
+    /// Correlation id of the RPC request on whose behalf this request is made.
+    request_id: Option<&'a str>,
+    failover: Option<(&'a Failover, Api)>,
+    /// Time by which the request, including its retries, has to complete.
+    deadline: Option<Instant>,
 }
 
+/// Header which carries the [request id](Request::with_request_id).
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
 pub mod stage {
     use crate::metrics::RequestMetadata;
 
@@ -69,6 +84,11 @@ impl<'a> Request<'a, stage::Init> {
             url,
             client,
             state: stage::Method,
+            max_retries: None,
+            circuit_breaker: None,
+            request_id: None,
+            failover: None,
+            deadline: None,
         }
     }
 }
@@ -131,6 +151,43 @@ impl<'a> Request<'a, stage::Method> {
         get_contract_addresses,
     );
 
+    /// Limits the number of retries if retrying is enabled using [with_retry](Request::with_retry).
+    ///
+    /// Once exhausted, the request fails with [SequencerError::RetriesExhausted]. Retries are
+    /// unlimited by default.
+    pub fn with_max_retries(self, max_retries: Option<NonZeroUsize>) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Fast-fails the request, and each of its retries, while the breaker of its method is open.
+    pub fn with_circuit_breaker(self, circuit_breaker: Option<&'a CircuitBreaker>) -> Self {
+        Self {
+            circuit_breaker,
+            ..self
+        }
+    }
+
+    /// Sends the request, and each of its retries, with the [REQUEST_ID_HEADER] header, which
+    /// allows correlating it with the RPC request it is made on behalf of.
+    pub fn with_request_id(self, request_id: Option<&'a str>) -> Self {
+        Self { request_id, ..self }
+    }
+
+    /// Sends the request, and each of its retries, to the active endpoint of `failover`, which
+    /// fails over to its other endpoints if the active one is unreachable.
+    pub fn with_failover(self, failover: Option<(&'a Failover, Api)>) -> Self {
+        Self { failover, ..self }
+    }
+
+    /// Fails the request with [SequencerError::DeadlineExceeded] if it, including its retries,
+    /// has not completed by `deadline`. The in-flight attempt is then aborted.
+    pub fn with_deadline(self, deadline: Option<Instant>) -> Self {
+        Self { deadline, ..self }
+    }
+
     /// Appends the given method to the request url.
     fn with_method(mut self, method: &'static str) -> Request<'a, stage::Params> {
         self.url
@@ -144,6 +201,11 @@ impl<'a> Request<'a, stage::Method> {
             state: stage::Params {
                 meta: RequestMetadata::new(method),
             },
+            max_retries: self.max_retries,
+            circuit_breaker: self.circuit_breaker,
+            request_id: self.request_id,
+            failover: self.failover,
+            deadline: self.deadline,
         }
     }
 }
@@ -202,6 +264,11 @@ impl<'a> Request<'a, stage::Params> {
                 meta: self.state.meta,
                 retry,
             },
+            max_retries: self.max_retries,
+            circuit_breaker: self.circuit_breaker,
+            request_id: self.request_id,
+            failover: self.failover,
+            deadline: self.deadline,
         }
     }
 }
@@ -215,29 +282,56 @@ impl<'a> Request<'a, stage::Final> {
         async fn send_request<T: serde::de::DeserializeOwned>(
             url: reqwest::Url,
             client: &reqwest::Client,
+            circuit_breaker: Option<&CircuitBreaker>,
+            request_id: Option<&str>,
+            failover: Option<(&Failover, Api)>,
             meta: RequestMetadata,
         ) -> Result<T, SequencerError> {
-            with_metrics(meta, async move {
-                tracing::trace!(%url, "Fetching data from feeder gateway");
-                let response = client.get(url).send().await?;
-                parse::<T>(response).await
+            with_failover(failover, meta.method, url, |url| {
+                send(circuit_breaker, meta, async move {
+                    tracing::trace!(%url, "Fetching data from feeder gateway");
+                    let response = with_request_id(client.get(url), request_id).send().await?;
+                    parse::<T>(response).await
+                })
             })
             .await
         }
 
-        match self.state.retry {
-            false => send_request(self.url, self.client, self.state.meta).await,
-            true => {
-                retry0(
-                    || async {
-                        let clone_url = self.url.clone();
-                        send_request(clone_url, self.client, self.state.meta).await
-                    },
-                    retry_condition,
-                )
-                .await
+        with_deadline(self.deadline, async move {
+            match self.state.retry {
+                false => {
+                    send_request(
+                        self.url,
+                        self.client,
+                        self.circuit_breaker,
+                        self.request_id,
+                        self.failover,
+                        self.state.meta,
+                    )
+                    .await
+                }
+                true => {
+                    retry0(
+                        || async {
+                            let clone_url = self.url.clone();
+                            send_request(
+                                clone_url,
+                                self.client,
+                                self.circuit_breaker,
+                                self.request_id,
+                                self.failover,
+                                self.state.meta,
+                            )
+                            .await
+                        },
+                        retry_condition,
+                        self.max_retries,
+                    )
+                    .await
+                }
             }
-        }
+        })
+        .await
     }
 
     /// Sends the Sequencer request as a REST `GET` operation and returns the response's bytes.
@@ -245,30 +339,57 @@ impl<'a> Request<'a, stage::Final> {
         async fn get_as_bytes_inner(
             url: reqwest::Url,
             client: &reqwest::Client,
+            circuit_breaker: Option<&CircuitBreaker>,
+            request_id: Option<&str>,
+            failover: Option<(&Failover, Api)>,
             meta: RequestMetadata,
         ) -> Result<bytes::Bytes, SequencerError> {
-            with_metrics(meta, async {
-                let response = client.get(url).send().await?;
-                let response = parse_raw(response).await?;
-                let bytes = response.bytes().await?;
-                Ok(bytes)
+            with_failover(failover, meta.method, url, |url| {
+                send(circuit_breaker, meta, async move {
+                    let response = with_request_id(client.get(url), request_id).send().await?;
+                    let response = parse_raw(response).await?;
+                    let bytes = response.bytes().await?;
+                    Ok(bytes)
+                })
             })
             .await
         }
 
-        match self.state.retry {
-            false => get_as_bytes_inner(self.url, self.client, self.state.meta).await,
-            true => {
-                retry0(
-                    || async {
-                        let clone_url = self.url.clone();
-                        get_as_bytes_inner(clone_url, self.client, self.state.meta).await
-                    },
-                    retry_condition,
-                )
-                .await
+        with_deadline(self.deadline, async move {
+            match self.state.retry {
+                false => {
+                    get_as_bytes_inner(
+                        self.url,
+                        self.client,
+                        self.circuit_breaker,
+                        self.request_id,
+                        self.failover,
+                        self.state.meta,
+                    )
+                    .await
+                }
+                true => {
+                    retry0(
+                        || async {
+                            let clone_url = self.url.clone();
+                            get_as_bytes_inner(
+                                clone_url,
+                                self.client,
+                                self.circuit_breaker,
+                                self.request_id,
+                                self.failover,
+                                self.state.meta,
+                            )
+                            .await
+                        },
+                        retry_condition,
+                        self.max_retries,
+                    )
+                    .await
+                }
             }
-        }
+        })
+        .await
     }
 
     /// Sends the Sequencer request as a REST `POST` operation, in addition to the specified
@@ -281,6 +402,9 @@ impl<'a> Request<'a, stage::Final> {
         async fn post_with_json_inner<T, J>(
             url: reqwest::Url,
             client: &reqwest::Client,
+            circuit_breaker: Option<&CircuitBreaker>,
+            request_id: Option<&str>,
+            failover: Option<(&Failover, Api)>,
             meta: RequestMetadata,
             json: &J,
         ) -> Result<T, SequencerError>
@@ -288,26 +412,55 @@ impl<'a> Request<'a, stage::Final> {
             T: serde::de::DeserializeOwned,
             J: serde::Serialize + ?Sized,
         {
-            with_metrics(meta, async {
-                let response = client.post(url).json(json).send().await?;
-                parse::<T>(response).await
+            with_failover(failover, meta.method, url, |url| {
+                send(circuit_breaker, meta, async move {
+                    let response = with_request_id(client.post(url), request_id)
+                        .json(json)
+                        .send()
+                        .await?;
+                    parse::<T>(response).await
+                })
             })
             .await
         }
 
-        match self.state.retry {
-            false => post_with_json_inner(self.url, self.client, self.state.meta, json).await,
-            true => {
-                retry0(
-                    || async {
-                        let clone_url = self.url.clone();
-                        post_with_json_inner(clone_url, self.client, self.state.meta, json).await
-                    },
-                    retry_condition,
-                )
-                .await
+        with_deadline(self.deadline, async move {
+            match self.state.retry {
+                false => {
+                    post_with_json_inner(
+                        self.url,
+                        self.client,
+                        self.circuit_breaker,
+                        self.request_id,
+                        self.failover,
+                        self.state.meta,
+                        json,
+                    )
+                    .await
+                }
+                true => {
+                    retry0(
+                        || async {
+                            let clone_url = self.url.clone();
+                            post_with_json_inner(
+                                clone_url,
+                                self.client,
+                                self.circuit_breaker,
+                                self.request_id,
+                                self.failover,
+                                self.state.meta,
+                                json,
+                            )
+                            .await
+                        },
+                        retry_condition,
+                        self.max_retries,
+                    )
+                    .await
+                }
             }
-        }
+        })
+        .await
     }
 }
 
@@ -321,17 +474,32 @@ where
     Ok(response)
 }
 
+/// Sets the [REQUEST_ID_HEADER] header, if a request id is given.
+fn with_request_id(
+    request: reqwest::RequestBuilder,
+    request_id: Option<&str>,
+) -> reqwest::RequestBuilder {
+    match request_id {
+        Some(request_id) => request.header(REQUEST_ID_HEADER, request_id),
+        None => request,
+    }
+}
+
 /// Helper function which allows skipping deserialization when required.
 async fn parse_raw(response: reqwest::Response) -> Result<reqwest::Response, SequencerError> {
     use starknet_gateway_types::error::StarknetError;
 
     // Starknet specific errors end with a 400 or 500 status code
     // but the body contains a JSON object with the error description
-    if response.status() == reqwest::StatusCode::INTERNAL_SERVER_ERROR
-        || response.status() == reqwest::StatusCode::BAD_REQUEST
+    let status = response.status();
+    if status == reqwest::StatusCode::INTERNAL_SERVER_ERROR
+        || status == reqwest::StatusCode::BAD_REQUEST
     {
         let error = match response.json::<StarknetError>().await {
-            Ok(e) => SequencerError::StarknetError(e),
+            Ok(e) => SequencerError::StarknetError(StarknetError {
+                status: Some(status.as_u16()),
+                ..e
+            }),
             Err(e) if e.is_decode() => SequencerError::InvalidStarknetErrorVariant,
             Err(e) => SequencerError::ReqwestError(e),
         };
@@ -344,10 +512,60 @@ async fn parse_raw(response: reqwest::Response) -> Result<reqwest::Response, Seq
 
 pub trait RequestState {}
 
+/// Fails with [SequencerError::DeadlineExceeded] unless `f` completes by `deadline`, dropping `f`
+/// and thereby aborting its in-flight request.
+async fn with_deadline<T>(
+    deadline: Option<Instant>,
+    f: impl futures::Future<Output = Result<T, SequencerError>>,
+) -> Result<T, SequencerError> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, f)
+            .await
+            .unwrap_or(Err(SequencerError::DeadlineExceeded)),
+        None => f.await,
+    }
+}
+
+/// Sends a single request attempt to `url`, or to the active endpoint of `failover`.
+async fn with_failover<T, Fut>(
+    failover: Option<(&Failover, Api)>,
+    method: &'static str,
+    url: reqwest::Url,
+    f: impl Fn(reqwest::Url) -> Fut,
+) -> Result<T, SequencerError>
+where
+    Fut: futures::Future<Output = Result<T, SequencerError>>,
+{
+    match failover {
+        Some((failover, api)) => failover.call(api, method, url, f).await,
+        None => f(url).await,
+    }
+}
+
+/// Sends a single request attempt, recording its metrics, unless the circuit breaker is open.
+async fn send<T>(
+    circuit_breaker: Option<&CircuitBreaker>,
+    meta: RequestMetadata,
+    f: impl futures::Future<Output = Result<T, SequencerError>>,
+) -> Result<T, SequencerError> {
+    match circuit_breaker {
+        Some(circuit_breaker) => {
+            circuit_breaker
+                .call(meta.method, with_metrics(meta, f))
+                .await
+        }
+        None => with_metrics(meta, f).await,
+    }
+}
+
 /// Wrapper function to allow retrying sequencer queries in an exponential manner.
+///
+/// Retries indefinitely unless `max_retries` is set, in which case a request which is still
+/// failing once the retries are exhausted fails with [SequencerError::RetriesExhausted].
 async fn retry0<T, Fut, FutureFactory, Ret>(
-    future_factory: FutureFactory,
+    mut future_factory: FutureFactory,
     retry_condition: Ret,
+    max_retries: Option<NonZeroUsize>,
 ) -> Result<T, SequencerError>
 where
     Fut: futures::Future<Output = Result<T, SequencerError>>,
@@ -357,11 +575,34 @@ where
     use pathfinder_retry::Retry;
     use std::num::NonZeroU64;
 
-    Retry::exponential(future_factory, NonZeroU64::new(2).unwrap())
+    let mut attempts = 0;
+    let counted_future_factory = || {
+        attempts += 1;
+        future_factory()
+    };
+
+    let retry = Retry::exponential(counted_future_factory, NonZeroU64::new(2).unwrap())
         .factor(NonZeroU64::new(15).unwrap())
-        .max_delay(std::time::Duration::from_secs(10 * 60))
-        .when(retry_condition)
-        .await
+        .max_delay(std::time::Duration::from_secs(10 * 60));
+    let retry = match max_retries {
+        Some(max_retries) => retry.max_num_retries(max_retries),
+        None => retry,
+    };
+    let result = retry.when(retry_condition).await;
+
+    match result {
+        // Starknet errors are not retried, so they cannot have exhausted the retries.
+        Err(e)
+            if max_retries.is_some_and(|max| attempts > max.get())
+                && !matches!(e, SequencerError::StarknetError(_)) =>
+        {
+            Err(SequencerError::RetriesExhausted {
+                attempts,
+                source: Box::new(e),
+            })
+        }
+        other => other,
+    }
 }
 
 /// Determines if an error is retryable or not.
@@ -403,6 +644,13 @@ fn retry_condition(e: &SequencerError) -> bool {
             error!(reason=%e, "Request failed, retrying");
             true
         }
+        // Never produced by an individual request.
+        SequencerError::RetriesExhausted { .. } | SequencerError::DeadlineExceeded => false,
+        // Backing off gives the breaker's cooldown time to elapse.
+        SequencerError::CircuitOpen { .. } => {
+            debug!(reason=%e, "Request failed, retrying");
+            true
+        }
     }
 }
 
@@ -476,6 +724,7 @@ mod tests {
                     builder::parse::<String>(response).await
                 },
                 retry_condition,
+                None,
             )
             .await
             .unwrap();
@@ -512,6 +761,7 @@ mod tests {
                     builder::parse::<String>(response).await
                 },
                 retry_condition,
+                None,
             )
             .await
             .unwrap_err();
@@ -550,6 +800,7 @@ mod tests {
                     builder::parse::<String>(response).await
                 },
                 retry_condition,
+                None,
             );
 
             // The retry loops forever, so wrap it in a timeout and check the counter.
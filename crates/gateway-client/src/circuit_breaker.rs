@@ -0,0 +1,230 @@
+//! Fast-fails requests to gateway methods which are consistently failing.
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::Future;
+use starknet_gateway_types::error::SequencerError;
+use tokio::time::Instant;
+
+use crate::builder::{stage::Method, Request};
+
+const METRIC_STATE: &str = "gateway_circuit_breaker_state";
+
+/// A circuit breaker per gateway method.
+///
+/// Once a method's requests have failed `threshold` times in a row, the breaker _opens_ and
+/// subsequent requests fail immediately with [SequencerError::CircuitOpen] for the `cooldown`
+/// period. After that a single request is let through as a probe, which closes the breaker
+/// if it succeeds and re-opens it otherwise.
+///
+/// Only transport failures count, i.e. the gateway being unreachable, timing out or
+/// responding with an error status. Starknet errors are valid gateway responses.
+///
+/// The state of each method's breaker is reported by the `gateway_circuit_breaker_state`
+/// gauge: `0` when closed, `1` when open and `2` while probing.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    threshold: NonZeroUsize,
+    cooldown: Duration,
+    states: Arc<Mutex<HashMap<&'static str, State>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed {
+        failures: usize,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A probe is in flight. Should it never complete, e.g. because it was cancelled,
+    /// another probe is let through once `until` has passed.
+    Probing {
+        until: Instant,
+    },
+}
+
+impl State {
+    fn metric(self) -> f64 {
+        match self {
+            State::Closed { .. } => 0.0,
+            State::Open { .. } => 1.0,
+            State::Probing { .. } => 2.0,
+        }
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: NonZeroUsize, cooldown: Duration) -> Self {
+        Request::<'_, Method>::METHODS.iter().for_each(|&method| {
+            metrics::gauge!(METRIC_STATE, 0.0, "method" => method);
+        });
+
+        Self {
+            threshold,
+            cooldown,
+            states: Default::default(),
+        }
+    }
+
+    /// Whether the breaker of any method is open, or probing whether the gateway has recovered.
+    pub fn is_open(&self) -> bool {
+        self.states
+            .lock()
+            .unwrap()
+            .values()
+            .any(|state| !matches!(state, State::Closed { .. }))
+    }
+
+    /// Sends the request `f` to `method`, unless its breaker is open.
+    pub async fn call<T>(
+        &self,
+        method: &'static str,
+        f: impl Future<Output = Result<T, SequencerError>>,
+    ) -> Result<T, SequencerError> {
+        self.acquire(method)?;
+
+        let result = f.await;
+        self.record(method, result.as_ref().err().map_or(false, is_failure));
+        result
+    }
+
+    fn acquire(&self, method: &'static str) -> Result<(), SequencerError> {
+        let mut states = self.states.lock().unwrap();
+        let state = states
+            .entry(method)
+            .or_insert(State::Closed { failures: 0 });
+
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } | State::Probing { until } if Instant::now() >= until => {
+                tracing::debug!(%method, "Cooldown elapsed, probing gateway");
+                let next = State::Probing {
+                    until: Instant::now() + self.cooldown,
+                };
+                Self::transition(method, state, next);
+                Ok(())
+            }
+            State::Open { .. } | State::Probing { .. } => {
+                Err(SequencerError::CircuitOpen { method })
+            }
+        }
+    }
+
+    fn record(&self, method: &'static str, failed: bool) {
+        let mut states = self.states.lock().unwrap();
+        let state = states
+            .entry(method)
+            .or_insert(State::Closed { failures: 0 });
+
+        let next = match (*state, failed) {
+            (_, false) => State::Closed { failures: 0 },
+            (State::Closed { failures }, true) if failures + 1 < self.threshold.get() => {
+                State::Closed {
+                    failures: failures + 1,
+                }
+            }
+            (State::Closed { .. } | State::Probing { .. }, true) => {
+                tracing::warn!(%method, cooldown=?self.cooldown, "Gateway keeps failing, fast-failing requests");
+                State::Open {
+                    until: Instant::now() + self.cooldown,
+                }
+            }
+            // Requests which were sent before the breaker opened.
+            (State::Open { until }, true) => State::Open { until },
+        };
+        Self::transition(method, state, next);
+    }
+
+    fn transition(method: &'static str, state: &mut State, next: State) {
+        if state.metric() != next.metric() {
+            metrics::gauge!(METRIC_STATE, next.metric(), "method" => method);
+        }
+        *state = next;
+    }
+}
+
+/// Whether the error indicates that the gateway itself is failing.
+fn is_failure(e: &SequencerError) -> bool {
+    match e {
+        SequencerError::ReqwestError(e) => !e.is_decode(),
+        SequencerError::StarknetError(_)
+        | SequencerError::InvalidStarknetErrorVariant
+        | SequencerError::RetriesExhausted { .. }
+        | SequencerError::CircuitOpen { .. }
+        | SequencerError::DeadlineExceeded => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOLDOWN: Duration = Duration::from_secs(30);
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(NonZeroUsize::new(2).unwrap(), COOLDOWN)
+    }
+
+    fn state(breaker: &CircuitBreaker, method: &'static str) -> State {
+        breaker.states.lock().unwrap()[method]
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn opens_after_consecutive_failures() {
+        let breaker = breaker();
+
+        breaker.acquire("get_block").unwrap();
+        breaker.record("get_block", true);
+        breaker.acquire("get_block").unwrap();
+        breaker.record("get_block", false);
+        breaker.acquire("get_block").unwrap();
+        breaker.record("get_block", true);
+        assert_eq!(
+            state(&breaker, "get_block"),
+            State::Closed { failures: 1 },
+            "Success resets the failure count"
+        );
+
+        breaker.acquire("get_block").unwrap();
+        breaker.record("get_block", true);
+        assert_matches::assert_matches!(
+            breaker.acquire("get_block"),
+            Err(SequencerError::CircuitOpen {
+                method: "get_block"
+            })
+        );
+
+        // Other methods are unaffected.
+        breaker.acquire("get_transaction").unwrap();
+        assert!(breaker.is_open());
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn probes_after_cooldown() {
+        let breaker = breaker();
+        for _ in 0..2 {
+            breaker.acquire("get_block").unwrap();
+            breaker.record("get_block", true);
+        }
+
+        tokio::time::advance(COOLDOWN).await;
+        breaker.acquire("get_block").unwrap();
+        assert_matches::assert_matches!(state(&breaker, "get_block"), State::Probing { .. });
+        // Only a single probe is let through at a time.
+        breaker.acquire("get_block").unwrap_err();
+
+        // A failed probe re-opens the breaker.
+        breaker.record("get_block", true);
+        breaker.acquire("get_block").unwrap_err();
+
+        tokio::time::advance(COOLDOWN).await;
+        breaker.acquire("get_block").unwrap();
+        breaker.record("get_block", false);
+        assert_eq!(state(&breaker, "get_block"), State::Closed { failures: 0 });
+        assert!(!breaker.is_open());
+        breaker.acquire("get_block").unwrap();
+    }
+}
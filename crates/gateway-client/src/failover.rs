@@ -0,0 +1,212 @@
+//! Fails over to secondary gateway endpoints when the active one is unreachable.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use futures::Future;
+use reqwest::Url;
+use starknet_gateway_types::error::SequencerError;
+
+const METRIC_ACTIVE_ENDPOINT: &str = "gateway_active_endpoint";
+
+/// The gateway API a request is sent to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Api {
+    Gateway,
+    FeederGateway,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    gateway: Url,
+    feeder_gateway: Url,
+}
+
+impl Endpoint {
+    /// Points the request `url` of `method` at this endpoint.
+    fn rebase(&self, api: Api, method: &str, url: &Url) -> Url {
+        let mut rebased = match api {
+            Api::Gateway => self.gateway.clone(),
+            Api::FeederGateway => self.feeder_gateway.clone(),
+        };
+        rebased
+            .path_segments_mut()
+            .expect("Base URL is valid")
+            .push(method);
+        rebased.set_query(url.query());
+        rebased
+    }
+}
+
+/// A list of gateway endpoints, of which requests are sent to the _active_ one.
+///
+/// Requests which fail to connect to the active endpoint are sent to the other endpoints, in
+/// order, and the first one reachable becomes the active endpoint. Requests therefore keep using
+/// a secondary endpoint until it becomes unreachable in turn.
+///
+/// The index of the active endpoint is reported by the `gateway_active_endpoint` gauge, where
+/// `0` is the primary endpoint.
+#[derive(Debug, Clone)]
+pub struct Failover {
+    endpoints: Arc<[Endpoint]>,
+    active: Arc<AtomicUsize>,
+}
+
+impl Failover {
+    /// Creates a [Failover] for the given `(gateway, feeder_gateway)` URL pairs, the first of
+    /// which is the primary endpoint.
+    pub fn new(endpoints: impl IntoIterator<Item = (Url, Url)>) -> Self {
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(gateway, feeder_gateway)| Endpoint {
+                gateway,
+                feeder_gateway,
+            })
+            .collect::<Arc<[_]>>();
+        assert!(!endpoints.is_empty(), "At least one endpoint is required");
+
+        metrics::gauge!(METRIC_ACTIVE_ENDPOINT, 0.0);
+
+        Self {
+            endpoints,
+            active: Default::default(),
+        }
+    }
+
+    /// Sends the request `f` to the active endpoint, failing over to the other endpoints if it
+    /// cannot connect.
+    ///
+    /// `url` is the request URL of `method`, whose query is retained on each endpoint.
+    pub async fn call<T, Fut>(
+        &self,
+        api: Api,
+        method: &'static str,
+        url: Url,
+        f: impl Fn(Url) -> Fut,
+    ) -> Result<T, SequencerError>
+    where
+        Fut: Future<Output = Result<T, SequencerError>>,
+    {
+        let active = self.active.load(Ordering::Relaxed);
+        let mut error = None;
+
+        for index in (0..self.endpoints.len()).map(|i| (active + i) % self.endpoints.len()) {
+            let url = self.endpoints[index].rebase(api, method, &url);
+            match f(url).await {
+                Err(SequencerError::ReqwestError(e)) if e.is_connect() => {
+                    tracing::debug!(%method, endpoint=%index, error=%e, "Gateway endpoint is unreachable");
+                    error = Some(e);
+                }
+                result => {
+                    self.activate(index);
+                    return result;
+                }
+            }
+        }
+
+        Err(SequencerError::ReqwestError(
+            error.expect("There is at least one endpoint"),
+        ))
+    }
+
+    fn activate(&self, index: usize) {
+        let previous = self.active.swap(index, Ordering::Relaxed);
+        if previous != index {
+            tracing::warn!(from=%previous, to=%index, "Failing over to another gateway endpoint");
+            metrics::gauge!(METRIC_ACTIVE_ENDPOINT, index as f64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn endpoint(host: &str) -> (Url, Url) {
+        let base = Url::parse(&format!("http://{host}/")).unwrap();
+        (
+            base.join("gateway").unwrap(),
+            base.join("feeder_gateway").unwrap(),
+        )
+    }
+
+    /// A real connection failure, as these cannot be constructed otherwise.
+    async fn connection_refused() -> SequencerError {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        reqwest::get(format!("http://{addr}"))
+            .await
+            .unwrap_err()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_reachable_endpoint() {
+        let failover = Failover::new([endpoint("primary"), endpoint("secondary")]);
+        let url = Url::parse("http://primary/feeder_gateway/get_block?blockNumber=1").unwrap();
+        let requested = Mutex::new(Vec::new());
+
+        let f = |url: Url| {
+            requested.lock().unwrap().push(url.to_string());
+            async move {
+                match url.host_str() {
+                    Some("primary") => Err(connection_refused().await),
+                    _ => Ok(()),
+                }
+            }
+        };
+
+        failover
+            .call(Api::FeederGateway, "get_block", url.clone(), f)
+            .await
+            .unwrap();
+        // The secondary endpoint remains active.
+        failover
+            .call(Api::FeederGateway, "get_block", url, f)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *requested.lock().unwrap(),
+            vec![
+                "http://primary/feeder_gateway/get_block?blockNumber=1",
+                "http://secondary/feeder_gateway/get_block?blockNumber=1",
+                "http://secondary/feeder_gateway/get_block?blockNumber=1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn other_errors_do_not_fail_over() {
+        let failover = Failover::new([endpoint("primary"), endpoint("secondary")]);
+        let url = Url::parse("http://primary/gateway/add_transaction").unwrap();
+
+        let error = failover
+            .call(Api::Gateway, "add_transaction", url, |url| async move {
+                assert_eq!(url.host_str(), Some("primary"));
+                Err::<(), _>(SequencerError::InvalidStarknetErrorVariant)
+            })
+            .await
+            .unwrap_err();
+
+        assert_matches::assert_matches!(error, SequencerError::InvalidStarknetErrorVariant);
+    }
+
+    #[tokio::test]
+    async fn unreachable_endpoints_fail() {
+        let failover = Failover::new([endpoint("primary"), endpoint("secondary")]);
+        let url = Url::parse("http://primary/gateway/add_transaction").unwrap();
+
+        let error = failover
+            .call(Api::Gateway, "add_transaction", url, |_| async {
+                Err::<(), _>(connection_refused().await)
+            })
+            .await
+            .unwrap_err();
+
+        assert_matches::assert_matches!(error, SequencerError::ReqwestError(e) => assert!(e.is_connect()));
+    }
+}
@@ -12,11 +12,16 @@ use starknet_gateway_types::{
         AddTransaction, ContractDefinition, Declare, DeployAccount, InvokeFunction,
     },
 };
-use std::{fmt::Debug, result::Result, time::Duration};
+use std::{fmt::Debug, num::NonZeroUsize, result::Result, sync::Arc, time::Duration};
 
 mod builder;
+mod circuit_breaker;
+mod failover;
 mod metrics;
 
+use circuit_breaker::CircuitBreaker;
+use failover::{Api, Failover};
+
 #[allow(unused_variables)]
 #[mockall::automock]
 #[async_trait::async_trait]
@@ -287,6 +292,9 @@ impl<T: GatewayApi + Sync + Send> GatewayApi for std::sync::Arc<T> {
 /// `backoff [secs] = min((2 ^ N) * 15, 600) [secs]`
 ///
 /// where `N` is the consecutive retry iteration number `{1, 2, ...}`.
+///
+/// Retries are unlimited by default, use [with_max_retries](Client::with_max_retries) to
+/// bound them.
 #[derive(Debug, Clone)]
 pub struct Client {
     /// This client is internally refcounted
@@ -298,9 +306,22 @@ pub struct Client {
     /// Whether __read only__ requests should be retried, defaults to __true__ for production.
     /// Use [disable_retry_for_tests](Client::disable_retry_for_tests) to disable retry logic for all __read only__ requests when testing.
     retry: bool,
+    /// Limits the number of retries of __read only__ requests, [None] retries indefinitely.
+    max_retries: Option<NonZeroUsize>,
+    /// Fast-fails requests to methods which keep failing, shared between clones.
+    circuit_breaker: Option<CircuitBreaker>,
+    /// Sent with each request, to correlate it with the RPC request it is made for.
+    request_id: Option<Arc<str>>,
+    /// Fails over to secondary endpoints if the active one is unreachable, shared between clones.
+    failover: Option<Failover>,
+    /// Time by which each request made by this client has to complete.
+    deadline: Option<tokio::time::Instant>,
 }
 
 impl Client {
+    /// The default timeout of an individual request.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
     /// Creates a [Client] for [Chain::Mainnet].
     pub fn mainnet() -> Self {
         Self::with_base_url(Url::parse("https://alpha-mainnet.starknet.io/").unwrap()).unwrap()
@@ -334,16 +355,94 @@ impl Client {
         metrics::register();
 
         Ok(Self {
-            inner: reqwest::Client::builder()
-                .timeout(Duration::from_secs(120))
-                .user_agent(pathfinder_common::consts::USER_AGENT)
-                .build()?,
+            inner: http_client(Self::DEFAULT_TIMEOUT)?,
             gateway,
             feeder_gateway,
             retry: true,
+            max_retries: None,
+            circuit_breaker: None,
+            request_id: None,
+            failover: None,
+            deadline: None,
+        })
+    }
+
+    /// Sets the timeout of each individual request, including each retry attempt.
+    pub fn with_timeout(self, timeout: Duration) -> anyhow::Result<Self> {
+        Ok(Self {
+            inner: http_client(timeout)?,
+            ..self
         })
     }
 
+    /// Limits the number of retries of __read only__ requests.
+    ///
+    /// Requests which are still failing once the retries are exhausted fail with
+    /// [SequencerError::RetriesExhausted].
+    pub fn with_max_retries(self, max_retries: NonZeroUsize) -> Self {
+        Self {
+            max_retries: Some(max_retries),
+            ..self
+        }
+    }
+
+    /// Fast-fails requests to a method with [SequencerError::CircuitOpen] for `cooldown`,
+    /// once `threshold` requests to it have failed in a row. A single request is then let
+    /// through to probe whether the gateway has recovered.
+    ///
+    /// The breaker applies to each individual attempt of retried requests.
+    pub fn with_circuit_breaker(self, threshold: NonZeroUsize, cooldown: Duration) -> Self {
+        Self {
+            circuit_breaker: Some(CircuitBreaker::new(threshold, cooldown)),
+            ..self
+        }
+    }
+
+    /// Whether requests to any method are currently fast-failed by the circuit breaker, see
+    /// [with_circuit_breaker](Client::with_circuit_breaker). [None] if no breaker is configured.
+    pub fn is_circuit_open(&self) -> Option<bool> {
+        self.circuit_breaker.as_ref().map(CircuitBreaker::is_open)
+    }
+
+    /// Fails over to the given secondary endpoints, each a base url like that of
+    /// [with_base_url](Client::with_base_url), if requests cannot connect to the active endpoint.
+    ///
+    /// Requests then keep using the endpoint which was reachable, until it fails in turn. The
+    /// `gateway_active_endpoint` gauge reports the index of the active endpoint, where `0` is
+    /// this client's own.
+    pub fn with_fallback_urls(self, fallbacks: Vec<Url>) -> anyhow::Result<Self> {
+        let mut endpoints = vec![(self.gateway.clone(), self.feeder_gateway.clone())];
+        for base in fallbacks {
+            endpoints.push((base.join("gateway")?, base.join("feeder_gateway")?));
+        }
+
+        Ok(Self {
+            failover: Some(Failover::new(endpoints)),
+            ..self
+        })
+    }
+
+    /// Sends `request_id` in the `x-request-id` header of each request made by this client,
+    /// which allows correlating gateway requests with the RPC request they are made for.
+    pub fn with_request_id(self, request_id: impl Into<Arc<str>>) -> Self {
+        Self {
+            request_id: Some(request_id.into()),
+            ..self
+        }
+    }
+
+    /// Aborts each request made by this client, including its retries, which has not completed
+    /// by `deadline` and fails it with [SequencerError::DeadlineExceeded].
+    ///
+    /// This bounds the gateway requests made on behalf of an RPC request by the time its client
+    /// is willing to wait. Each attempt's own timeout is thereby cut short to the time remaining.
+    pub fn with_deadline(self, deadline: tokio::time::Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
     /// Use this method to disable retry logic for all __non write__ requests when testing.
     pub fn disable_retry_for_tests(self) -> Self {
         Self {
@@ -354,10 +453,20 @@ impl Client {
 
     fn gateway_request(&self) -> builder::Request<'_, builder::stage::Method> {
         builder::Request::builder(&self.inner, self.gateway.clone())
+            .with_max_retries(self.max_retries)
+            .with_circuit_breaker(self.circuit_breaker.as_ref())
+            .with_request_id(self.request_id.as_deref())
+            .with_failover(self.failover.as_ref().map(|f| (f, Api::Gateway)))
+            .with_deadline(self.deadline)
     }
 
     fn feeder_gateway_request(&self) -> builder::Request<'_, builder::stage::Method> {
         builder::Request::builder(&self.inner, self.feeder_gateway.clone())
+            .with_max_retries(self.max_retries)
+            .with_circuit_breaker(self.circuit_breaker.as_ref())
+            .with_request_id(self.request_id.as_deref())
+            .with_failover(self.failover.as_ref().map(|f| (f, Api::FeederGateway)))
+            .with_deadline(self.deadline)
     }
 
     async fn block_with_retry_behaviour(
@@ -397,6 +506,13 @@ impl Client {
     }
 }
 
+fn http_client(timeout: Duration) -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent(pathfinder_common::consts::USER_AGENT)
+        .build()
+}
+
 #[async_trait::async_trait]
 impl GatewayApi for Client {
     #[tracing::instrument(skip(self))]
@@ -661,6 +777,7 @@ pub mod test_utils {
         let e = StarknetError {
             code: code.into(),
             message: "".to_string(),
+            status: None,
         };
         (serde_json::to_string(&e).unwrap(), 500)
     }
@@ -1771,4 +1888,251 @@ mod tests {
             sequencer.chain().await.unwrap_err();
         }
     }
+
+    mod timeout_and_retries {
+        use crate::{Client, GatewayApi};
+        use starknet_gateway_types::error::SequencerError;
+        use std::num::NonZeroUsize;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        const TIMEOUT: Duration = Duration::from_millis(100);
+
+        /// Serves `get_contract_addresses`, timing out on the first `slow_requests` requests.
+        fn setup_server(slow_requests: usize) -> (tokio::task::JoinHandle<()>, Client) {
+            use warp::Filter;
+
+            let count = Arc::new(AtomicUsize::new(0));
+            let filter = warp::any().then(move || {
+                let count = count.clone();
+                async move {
+                    if count.fetch_add(1, Ordering::Relaxed) < slow_requests {
+                        tokio::time::sleep(TIMEOUT * 10).await;
+                    }
+                    r#"{"Starknet": "0xde29d060d45901fb19ed6c6e959eb22d8626708e"}"#
+                }
+            });
+
+            let (addr, serve_fut) = warp::serve(filter).bind_ephemeral(([127, 0, 0, 1], 0));
+            let server_handle = tokio::spawn(serve_fut);
+            let client =
+                Client::with_base_url(reqwest::Url::parse(&format!("http://{addr}")).unwrap())
+                    .unwrap()
+                    .with_timeout(TIMEOUT)
+                    .unwrap()
+                    .with_max_retries(NonZeroUsize::new(2).unwrap());
+
+            (server_handle, client)
+        }
+
+        #[tokio::test(flavor = "current_thread")]
+        async fn succeeds_after_timeout() {
+            tokio::time::pause();
+
+            let (_server_handle, client) = setup_server(2);
+            client.eth_contract_addresses().await.unwrap();
+        }
+
+        #[tokio::test(flavor = "current_thread")]
+        async fn retries_exhausted() {
+            tokio::time::pause();
+
+            let (_server_handle, client) = setup_server(usize::MAX);
+            let error = client.eth_contract_addresses().await.unwrap_err();
+            assert_matches::assert_matches!(
+                error,
+                SequencerError::RetriesExhausted { attempts: 3, source } => {
+                    assert_matches::assert_matches!(*source, SequencerError::ReqwestError(e) => assert!(e.is_timeout()))
+                }
+            );
+        }
+    }
+
+    mod circuit_breaker {
+        use super::*;
+        use std::time::Duration;
+
+        const COOLDOWN: Duration = Duration::from_millis(200);
+        const ADDRESSES: &str = r#"{"Starknet": "0xde29d060d45901fb19ed6c6e959eb22d8626708e"}"#;
+
+        #[tokio::test]
+        async fn opens_and_closes() {
+            let (_jh, client) = setup_with_varied_responses([(
+                "/feeder_gateway/get_contract_addresses".to_owned(),
+                [
+                    ("".to_owned(), 503),
+                    ("".to_owned(), 503),
+                    (ADDRESSES.to_owned(), 200),
+                    (ADDRESSES.to_owned(), 200),
+                ],
+            )]);
+            assert_eq!(client.is_circuit_open(), None);
+            let client = client.with_circuit_breaker(NonZeroUsize::new(2).unwrap(), COOLDOWN);
+
+            for _ in 0..2 {
+                let error = client.eth_contract_addresses().await.unwrap_err();
+                assert_matches!(error, SequencerError::ReqwestError(e) => assert!(e.is_status()));
+            }
+
+            // The gateway is not queried while the breaker is open.
+            assert_eq!(client.is_circuit_open(), Some(true));
+            let error = client.eth_contract_addresses().await.unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::CircuitOpen {
+                    method: "get_contract_addresses"
+                }
+            );
+
+            // Once the cooldown elapses, the successful probe closes the breaker.
+            tokio::time::sleep(COOLDOWN).await;
+            client.eth_contract_addresses().await.unwrap();
+            client.eth_contract_addresses().await.unwrap();
+            assert_eq!(client.is_circuit_open(), Some(false));
+        }
+    }
+
+    mod failover {
+        use super::*;
+        use warp::Filter;
+
+        const ADDRESSES: &str = r#"{"Starknet": "0xde29d060d45901fb19ed6c6e959eb22d8626708e"}"#;
+
+        #[tokio::test]
+        async fn unreachable_primary_fails_over_to_secondary() {
+            let filter = warp::path!("feeder_gateway" / "get_contract_addresses")
+                .map(|| warp::reply::with_header(ADDRESSES, "content-type", "application/json"));
+            let (secondary, server) = warp::serve(filter).bind_ephemeral(([127, 0, 0, 1], 0));
+            let _jh = tokio::spawn(server);
+
+            // Nothing is listening on the primary.
+            let primary = std::net::TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap();
+
+            let client = Client::with_base_url(Url::parse(&format!("http://{primary}")).unwrap())
+                .unwrap()
+                .with_fallback_urls(vec![Url::parse(&format!("http://{secondary}")).unwrap()])
+                .unwrap()
+                .disable_retry_for_tests();
+
+            client.eth_contract_addresses().await.unwrap();
+        }
+    }
+
+    mod starknet_error_status {
+        use super::*;
+
+        #[tokio::test]
+        async fn is_recorded() {
+            let (body, _) = response_from(KnownStarknetErrorCode::MalformedRequest);
+            let (_jh, client) = setup_with_varied_responses([(
+                "/feeder_gateway/get_contract_addresses".to_owned(),
+                [(body.clone(), 400), (body, 500)],
+            )]);
+
+            let error = client.eth_contract_addresses().await.unwrap_err();
+            assert_matches!(error, SequencerError::StarknetError(e) => {
+                assert_eq!(e.status, Some(400));
+                assert!(e.is_client_error());
+            });
+
+            let error = client.eth_contract_addresses().await.unwrap_err();
+            assert_matches!(error, SequencerError::StarknetError(e) => {
+                assert_eq!(e.status, Some(500));
+                assert!(!e.is_client_error());
+            });
+        }
+    }
+
+    mod deadline {
+        use super::*;
+        use std::time::Duration;
+        use tokio::io::AsyncReadExt;
+        use tokio::time::Instant;
+
+        const ADDRESSES: &str = r#"{"Starknet": "0xde29d060d45901fb19ed6c6e959eb22d8626708e"}"#;
+
+        #[tokio::test]
+        async fn aborts_request() {
+            // Reads the request, but never responds.
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let server = tokio::spawn(async move {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 1024];
+                while matches!(socket.read(&mut buf).await, Ok(n) if n > 0) {}
+            });
+
+            let client = Client::with_base_url(Url::parse(&format!("http://{addr}")).unwrap())
+                .unwrap()
+                .with_deadline(Instant::now() + Duration::from_millis(100));
+
+            let error = client.eth_contract_addresses().await.unwrap_err();
+            assert_matches!(error, SequencerError::DeadlineExceeded);
+
+            // The connection is closed instead of the request continuing in the background,
+            // even though the client itself is still alive.
+            tokio::time::timeout(Duration::from_secs(5), server)
+                .await
+                .expect("Request was aborted")
+                .unwrap();
+            drop(client);
+        }
+
+        #[tokio::test]
+        async fn does_not_affect_timely_requests() {
+            let (_jh, client) =
+                setup([("/feeder_gateway/get_contract_addresses", (ADDRESSES, 200))]);
+            let client = client.with_deadline(Instant::now() + Duration::from_secs(60));
+
+            client.eth_contract_addresses().await.unwrap();
+        }
+    }
+
+    mod request_id {
+        use super::*;
+        use std::convert::Infallible;
+        use warp::Filter;
+
+        const ADDRESSES: &str = r#"{"Starknet": "0xde29d060d45901fb19ed6c6e959eb22d8626708e"}"#;
+
+        async fn received_request_id(client: impl FnOnce(Client) -> Client) -> Option<String> {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let filter = warp::path!("feeder_gateway" / "get_contract_addresses")
+                .and(warp::header::optional::<String>("x-request-id"))
+                .and_then(move |request_id: Option<String>| {
+                    tx.send(request_id).unwrap();
+                    async move {
+                        Ok::<_, Infallible>(warp::reply::with_header(
+                            ADDRESSES,
+                            "content-type",
+                            "application/json",
+                        ))
+                    }
+                });
+            let (addr, server) = warp::serve(filter).bind_ephemeral(([127, 0, 0, 1], 0));
+            let _jh = tokio::spawn(server);
+
+            let url = Url::parse(&format!("http://{addr}")).unwrap();
+            let client = client(Client::with_base_url(url).unwrap());
+            client.eth_contract_addresses().await.unwrap();
+
+            rx.recv().await.unwrap()
+        }
+
+        #[tokio::test]
+        async fn is_sent() {
+            let request_id = received_request_id(|c| c.with_request_id("abc-123")).await;
+            assert_eq!(request_id.as_deref(), Some("abc-123"));
+        }
+
+        #[tokio::test]
+        async fn is_not_sent_by_default() {
+            let request_id = received_request_id(|c| c).await;
+            assert_eq!(request_id, None);
+        }
+    }
 }
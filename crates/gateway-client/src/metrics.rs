@@ -164,6 +164,12 @@ pub async fn with_metrics<T>(
                 increment_failed(meta, REASON_RATE_LIMITING);
             }
             SequencerError::ReqwestError(_) => {}
+            // Only produced by the retry loop, which wraps the individual requests.
+            SequencerError::RetriesExhausted { .. } => {}
+            // Produced instead of sending a request.
+            SequencerError::CircuitOpen { .. } => {}
+            // Produced by dropping the request once its deadline has passed.
+            SequencerError::DeadlineExceeded => {}
         }
 
         e
@@ -14,6 +14,18 @@ pub enum SequencerError {
     /// not informative enough or bloated
     #[error("error decoding response body: invalid error variant")]
     InvalidStarknetErrorVariant,
+    /// A retried request which kept failing until its retry limit was reached.
+    #[error("Gateway request failed after {attempts} attempts: {source}")]
+    RetriesExhausted {
+        attempts: usize,
+        source: Box<SequencerError>,
+    },
+    /// The request was not sent, because requests to this method have been failing.
+    #[error("Gateway requests to {method} are failing, try again later")]
+    CircuitOpen { method: &'static str },
+    /// The request, including its retries, did not complete by its deadline and was aborted.
+    #[error("Gateway request did not complete by its deadline")]
+    DeadlineExceeded,
 }
 
 /// Used for deserializing specific Starknet sequencer error data.
@@ -23,6 +35,17 @@ pub struct StarknetError {
     pub message: String,
     // The `problems` field is intentionally omitted here
     // Let's deserialize it if it proves necessary
+    /// The HTTP status of the gateway's response, if known.
+    #[serde(skip)]
+    pub status: Option<u16>,
+}
+
+impl StarknetError {
+    /// Whether the gateway rejected the request itself, i.e. responded with a 4xx status,
+    /// as opposed to failing to process it.
+    pub fn is_client_error(&self) -> bool {
+        matches!(self.status, Some(400..=499))
+    }
 }
 
 impl std::error::Error for StarknetError {}
@@ -162,6 +162,7 @@ fn execute(storage: Storage, chain_id: ChainId, rx: crossbeam_channel::Receiver<
             state_at_block: work.state_at_block,
             gas_price: work.gas_price,
             pending_update: None,
+            state_overrides: None,
         };
 
         let db_tx = execution_state
@@ -59,6 +59,14 @@ Examples:
     )]
     rpc_address: SocketAddr,
 
+    #[arg(
+        long = "rpc.unix-socket",
+        long_help = "Serves HTTP-RPC on a Unix domain socket at this path instead of the TCP listening address. The socket file must not exist yet.",
+        value_name = "PATH",
+        env = "PATHFINDER_RPC_UNIX_SOCKET"
+    )]
+    rpc_unix_socket: Option<PathBuf>,
+
     #[arg(
         long = "rpc.websocket",
         long_help = "Enable RPC WebSocket transport",
@@ -114,6 +122,14 @@ Examples:
     )]
     monitor_address: Option<SocketAddr>,
 
+    #[arg(
+        long = "monitor.shutdown-grace-period",
+        long_help = "Keeps the monitoring server up for at most this many seconds when shutting down, e.g. on SIGTERM, until the metrics have been scraped one final time. Metrics recorded since the previous scrape are otherwise lost. Disabled by default.",
+        value_name = "SECONDS",
+        env = "PATHFINDER_MONITOR_SHUTDOWN_GRACE_PERIOD"
+    )]
+    monitor_shutdown_grace_period: Option<std::num::NonZeroU64>,
+
     #[clap(flatten)]
     network: NetworkCli,
 
@@ -153,6 +169,310 @@ Examples:
     )]
     max_rpc_connections: std::num::NonZeroUsize,
 
+    #[arg(
+        long = "rpc.max-declare-class-size",
+        long_help = "Maximum size in bytes of the contract class in a declare transaction, measured as the class's JSON definition with the program of Cairo 0 classes decompressed. Larger classes are rejected with CONTRACT_CLASS_SIZE_IS_TOO_LARGE instead of being forwarded to the gateway.",
+        env = "PATHFINDER_RPC_MAX_DECLARE_CLASS_SIZE",
+        default_value = "4194304"
+    )]
+    rpc_max_declare_class_size: std::num::NonZeroUsize,
+
+    #[arg(
+        long = "rpc.max-transactions-by-hashes",
+        long_help = "Maximum number of transaction hashes a single pathfinder_getTransactionsByHashes request may contain.",
+        env = "PATHFINDER_RPC_MAX_TRANSACTIONS_BY_HASHES",
+        default_value = "100"
+    )]
+    rpc_max_transactions_by_hashes: std::num::NonZeroUsize,
+
+    #[arg(
+        long = "rpc.max-classes-declared",
+        long_help = "Maximum number of class hashes a single pathfinder_classesDeclared request may contain.",
+        env = "PATHFINDER_RPC_MAX_CLASSES_DECLARED",
+        default_value = "1000"
+    )]
+    rpc_max_classes_declared: std::num::NonZeroUsize,
+
+    #[arg(
+        long = "rpc.max-events-block-range",
+        long_help = "Maximum number of blocks a single starknet_getEvents request may search. Requests spanning more blocks are rejected with a BLOCK_RANGE_TOO_LARGE error.",
+        env = "PATHFINDER_RPC_MAX_EVENTS_BLOCK_RANGE",
+        default_value = "100000"
+    )]
+    rpc_max_events_block_range: std::num::NonZeroU64,
+
+    #[arg(
+        long = "rpc.max-event-key-segments",
+        long_help = "Maximum number of key segments, i.e. the length of the outer array of keys, a single starknet_getEvents filter may contain. Filters with more segments are rejected with a TOO_MANY_KEYS_IN_FILTER error. Values above 256 have no effect, as more segments are never supported.",
+        env = "PATHFINDER_RPC_MAX_EVENT_KEY_SEGMENTS",
+        default_value = "256"
+    )]
+    rpc_max_event_key_segments: std::num::NonZeroUsize,
+
+    #[arg(
+        long = "rpc.disable-execution",
+        long_help = "Disables RPC methods which execute contracts, such as starknet_call. These are answered with an EXECUTION_DISABLED error instead.",
+        action = clap::ArgAction::Set,
+        default_value = "false",
+        env = "PATHFINDER_RPC_DISABLE_EXECUTION"
+    )]
+    rpc_disable_execution: bool,
+
+    #[arg(
+        long = "rpc.resubmission-ttl",
+        long_help = "Answers identical resubmissions of an invoke transaction accepted by the gateway within this many seconds with the original result, instead of forwarding them again. This lets clients safely retry submissions. Disabled by default.",
+        env = "PATHFINDER_RPC_RESUBMISSION_TTL"
+    )]
+    rpc_resubmission_ttl: Option<std::num::NonZeroU64>,
+
+    #[arg(
+        long = "rpc.read-replica",
+        long_help = "Serves RPC reads and executions from the database at this path, opened read-only, instead of the node's own database. This must be a replica of the node's database which is kept up to date externally, for example by a streaming replication tool. Sync and all writes still use the node's own database.",
+        value_name = "PATH",
+        env = "PATHFINDER_RPC_READ_REPLICA"
+    )]
+    rpc_read_replica: Option<PathBuf>,
+
+    #[arg(
+        long = "rpc.max-concurrent-calls",
+        long_help = "Limits the number of RPC method calls executing concurrently. Calls within a batch request count individually, which prevents large batches from starving single requests. Unlimited by default.",
+        env = "PATHFINDER_RPC_MAX_CONCURRENT_CALLS"
+    )]
+    rpc_max_concurrent_calls: Option<std::num::NonZeroUsize>,
+
+    #[arg(
+        long = "rpc.max-concurrent-requests-per-ip",
+        long_help = "Limits the number of RPC requests a single IP address may have in flight at once, over all of its connections. Requests beyond the limit are answered with 429 Too Many Requests. Unlimited by default.",
+        env = "PATHFINDER_RPC_MAX_CONCURRENT_REQUESTS_PER_IP"
+    )]
+    rpc_max_concurrent_requests_per_ip: Option<std::num::NonZeroUsize>,
+
+    #[arg(
+        long = "rpc.max-open-connections",
+        long_help = "Limits the number of simultaneously open RPC connections, including idle keep-alive connections. Connections beyond the limit are closed immediately. Unlimited by default.",
+        env = "PATHFINDER_RPC_MAX_OPEN_CONNECTIONS"
+    )]
+    rpc_max_open_connections: Option<std::num::NonZeroUsize>,
+
+    #[arg(
+        long = "rpc.max-batch-response-size",
+        long_help = "Limits the combined size in bytes of the responses to a batch request. Batches exceeding the limit are answered with a single error instead. Unlimited by default.",
+        env = "PATHFINDER_RPC_MAX_BATCH_RESPONSE_SIZE"
+    )]
+    rpc_max_batch_response_size: Option<std::num::NonZeroUsize>,
+
+    #[arg(
+        long = "rpc.max-batch-params-size",
+        long_help = "Limits the combined size in bytes of the params of a batch request. Batches exceeding the limit are answered with a single error instead, without executing any of their requests. Unlimited by default.",
+        env = "PATHFINDER_RPC_MAX_BATCH_PARAMS_SIZE"
+    )]
+    rpc_max_batch_params_size: Option<std::num::NonZeroUsize>,
+
+    #[arg(
+        long = "rpc.keep-alive-timeout",
+        long_help = "Closes RPC keep-alive connections which have been idle for longer than this many seconds. By default idle connections are kept open until the client closes them.",
+        env = "PATHFINDER_RPC_KEEP_ALIVE_TIMEOUT"
+    )]
+    rpc_keep_alive_timeout: Option<std::num::NonZeroU64>,
+
+    #[arg(
+        long = "rpc.read-timeout",
+        long_help = "Closes RPC connections which take longer than this many seconds to send the headers of a request, e.g. clients trickling bytes to keep the connection open.",
+        env = "PATHFINDER_RPC_READ_TIMEOUT",
+        default_value = "30"
+    )]
+    rpc_read_timeout: std::num::NonZeroU64,
+
+    #[arg(
+        long = "rpc.write-timeout",
+        long_help = "Closes RPC connections which make no progress receiving a response for this many seconds. Large responses are not cut off as long as the client keeps receiving them.",
+        env = "PATHFINDER_RPC_WRITE_TIMEOUT",
+        default_value = "30"
+    )]
+    rpc_write_timeout: std::num::NonZeroU64,
+
+    #[arg(
+        long = "rpc.body-read-timeout",
+        long_help = "Aborts RPC requests whose body makes no progress for this many seconds, e.g. clients which stall part way through sending it. Large bodies are not cut off as long as the client keeps sending them.",
+        env = "PATHFINDER_RPC_BODY_READ_TIMEOUT",
+        default_value = "30"
+    )]
+    rpc_body_read_timeout: std::num::NonZeroU64,
+
+    #[arg(
+        long = "rpc.max-requests-per-connection",
+        long_help = "Closes RPC connections once they have served this many requests, forcing clients to reconnect. Unlimited by default.",
+        env = "PATHFINDER_RPC_MAX_REQUESTS_PER_CONNECTION"
+    )]
+    rpc_max_requests_per_connection: Option<std::num::NonZeroUsize>,
+
+    #[arg(
+        long = "rpc.default-block-tag",
+        long_help = "Block tag used by starknet_call and starknet_getStorageAt when the caller omits the block id. By default the block id is required, as per the specification.",
+        value_enum,
+        env = "PATHFINDER_RPC_DEFAULT_BLOCK_TAG"
+    )]
+    rpc_default_block_tag: Option<RpcBlockTag>,
+
+    #[arg(
+        long = "rpc.strict-requests",
+        long_help = "Rejects RPC requests which repeat a top-level key, such as method or id, with an invalid request error. By default the last value of a repeated key is used.",
+        action = clap::ArgAction::Set,
+        default_value = "false",
+        env = "PATHFINDER_RPC_STRICT_REQUESTS"
+    )]
+    rpc_strict_requests: bool,
+
+    #[arg(
+        long = "rpc.version-header",
+        long_help = "Reports the RPC version which handled a request in the x-pathfinder-version response header. Intended for debugging.",
+        action = clap::ArgAction::Set,
+        default_value = "false",
+        env = "PATHFINDER_RPC_VERSION_HEADER"
+    )]
+    rpc_version_header: bool,
+
+    #[arg(
+        long = "rpc.server-timing",
+        long_help = "Reports the time spent reading, parsing, dispatching and serializing each RPC request in the Server-Timing response header, which browser devtools display. Intended for debugging latency.",
+        action = clap::ArgAction::Set,
+        default_value = "false",
+        env = "PATHFINDER_RPC_SERVER_TIMING"
+    )]
+    rpc_server_timing: bool,
+
+    #[arg(
+        long = "rpc.get-requests",
+        long_help = "Additionally serves GET requests naming an RPC method in their query, e.g. `GET /rpc/v0.5?method=starknet_blockNumber`, for monitoring tools which cannot send POST requests. The method is invoked without params, so methods which require params fail.",
+        action = clap::ArgAction::Set,
+        default_value = "false",
+        env = "PATHFINDER_RPC_GET_REQUESTS"
+    )]
+    rpc_get_requests: bool,
+
+    #[arg(
+        long = "rpc.omit-null-fields",
+        long_help = "Omits the fields of RPC results which are null, instead of serializing them as null. By default this depends on the method and API version.",
+        action = clap::ArgAction::Set,
+        default_value = "false",
+        env = "PATHFINDER_RPC_OMIT_NULL_FIELDS"
+    )]
+    rpc_omit_null_fields: bool,
+
+    #[arg(
+        long = "rpc.method-in-errors",
+        long_help = "Adds the name of the failed method to the data of RPC errors, e.g. {\"method\": \"starknet_call\"}, which helps debugging. This is not part of the specification, and errors whose data is not an object are unaffected.",
+        action = clap::ArgAction::Set,
+        default_value = "false",
+        env = "PATHFINDER_RPC_METHOD_IN_ERRORS"
+    )]
+    rpc_method_in_errors: bool,
+
+    #[arg(
+        long = "rpc.error-messages-file",
+        long_help = "Path to a JSON file of translated RPC error messages, which are returned to callers whose Accept-Language header prefers one of its languages. The file maps language tags to objects which map error codes to messages, e.g. {\"de\": {\"-32602\": \"Ungültige Parameter\"}}. Error codes without a translation, and other languages, keep their English message.",
+        value_name = "PATH",
+        env = "PATHFINDER_RPC_ERROR_MESSAGES_FILE"
+    )]
+    rpc_error_messages_file: Option<std::path::PathBuf>,
+
+    #[arg(
+        long = "rpc.merged-path",
+        long_help = "Additionally serves the v0.3, v0.4 and v0.5 RPC APIs on this path, e.g. /rpc, selecting the version of each request by its x-starknet-rpc-version header, e.g. v0.5. Requests without the header, or for another version, are served by the root version. Disabled by default.",
+        value_name = "PATH",
+        value_parser = parse_merged_path,
+        env = "PATHFINDER_RPC_MERGED_PATH"
+    )]
+    rpc_merged_path: Option<String>,
+
+    #[arg(
+        long = "rpc.slow-request-threshold",
+        long_help = "Logs a warning, including the method and its params, for each RPC call which takes longer than this many milliseconds to execute. Disabled by default.",
+        env = "PATHFINDER_RPC_SLOW_REQUEST_THRESHOLD"
+    )]
+    rpc_slow_request_threshold: Option<std::num::NonZeroU64>,
+
+    #[arg(
+        long = "rpc.large-response-threshold",
+        long_help = "Logs a warning, including the method and the size, for each RPC response whose result is larger than this many bytes. Such responses are also counted by the `rpc_large_responses_total` metric. This is independent of the batch response size limit. Disabled by default.",
+        env = "PATHFINDER_RPC_LARGE_RESPONSE_THRESHOLD"
+    )]
+    rpc_large_response_threshold: Option<std::num::NonZeroUsize>,
+
+    #[arg(
+        long = "rpc.batch-timeout",
+        long_help = "Limits the time in seconds spent executing an RPC batch. Requests which have not completed by then are answered with a timeout error, while the responses of completed requests are still returned. Should be shorter than the overall request timeout of 120 seconds. Unlimited by default.",
+        env = "PATHFINDER_RPC_BATCH_TIMEOUT"
+    )]
+    rpc_batch_timeout: Option<std::num::NonZeroU64>,
+
+    #[arg(
+        long = "rpc.single-flight-methods",
+        long_help = "Comma separated list of RPC methods, e.g. starknet_getBlockWithTxs, whose concurrent calls with identical params share a single execution and its result. Results are not kept once the execution completes. Disabled by default.",
+        value_name = "METHOD LIST",
+        value_delimiter = ',',
+        env = "PATHFINDER_RPC_SINGLE_FLIGHT_METHODS"
+    )]
+    rpc_single_flight_methods: Vec<String>,
+
+    #[arg(
+        long = "rpc.head-cached-methods",
+        long_help = "Comma separated list of RPC methods without params, e.g. starknet_blockNumber,starknet_chainId, whose results are cached until the next block is synced or a reorg occurs. Only suitable for methods whose results depend on nothing but the latest block. Disabled by default.",
+        value_name = "METHOD LIST",
+        value_delimiter = ',',
+        env = "PATHFINDER_RPC_HEAD_CACHED_METHODS"
+    )]
+    rpc_head_cached_methods: Vec<String>,
+
+    #[arg(
+        long = "rpc.supported-tx-versions",
+        long_help = "Comma separated list of transaction versions, e.g. 1,2, which may be submitted via starknet_addInvokeTransaction, starknet_addDeclareTransaction and starknet_addDeployAccountTransaction. Other versions are rejected with an UNSUPPORTED_TX_VERSION error instead of being forwarded to the gateway. All versions are forwarded by default.",
+        value_name = "VERSION LIST",
+        value_delimiter = ',',
+        env = "PATHFINDER_RPC_SUPPORTED_TX_VERSIONS"
+    )]
+    rpc_supported_tx_versions: Vec<u128>,
+
+    #[arg(
+        long = "gateway.request-timeout",
+        long_help = "Timeout in seconds of each individual Starknet gateway and feeder gateway request.",
+        env = "PATHFINDER_GATEWAY_REQUEST_TIMEOUT",
+        default_value = "120"
+    )]
+    gateway_timeout: std::num::NonZeroU64,
+
+    #[arg(
+        long = "rpc.gateway-max-retries",
+        long_help = "Maximum number of retries of failed read-only gateway requests made on behalf of RPC methods. Transactions submitted to the gateway are never retried.",
+        env = "PATHFINDER_RPC_GATEWAY_MAX_RETRIES",
+        default_value = "2"
+    )]
+    rpc_gateway_max_retries: std::num::NonZeroUsize,
+
+    #[arg(
+        long = "rpc.gateway-circuit-breaker-threshold",
+        long_help = "Once this many consecutive gateway requests made on behalf of RPC methods have failed, further requests to the same gateway method fail immediately for the duration of `--rpc.gateway-circuit-breaker-cooldown`, after which a single request probes whether the gateway has recovered. Disabled by default.",
+        env = "PATHFINDER_RPC_GATEWAY_CIRCUIT_BREAKER_THRESHOLD"
+    )]
+    rpc_gateway_circuit_breaker_threshold: Option<std::num::NonZeroUsize>,
+
+    #[arg(
+        long = "rpc.gateway-circuit-breaker-cooldown",
+        long_help = "Time in seconds for which gateway requests fail immediately once the circuit breaker has opened.",
+        env = "PATHFINDER_RPC_GATEWAY_CIRCUIT_BREAKER_COOLDOWN",
+        default_value = "30"
+    )]
+    rpc_gateway_circuit_breaker_cooldown: std::num::NonZeroU64,
+
+    #[arg(
+        long = "rpc.gateway-fallback-urls",
+        long_help = "Comma separated list of secondary Starknet gateway base urls, each serving both the gateway and feeder gateway, e.g. https://alpha-mainnet.starknet.io. Gateway requests made on behalf of RPC methods fail over to these, in order, if the active gateway is unreachable. The `gateway_active_endpoint` metric reports the index of the active gateway, where 0 is the primary.",
+        value_name = "URL LIST",
+        value_delimiter = ',',
+        env = "PATHFINDER_RPC_GATEWAY_FALLBACK_URLS"
+    )]
+    rpc_gateway_fallback_urls: Vec<Url>,
+
     #[arg(
         long = "sync.poll-interval",
         long_help = "New block poll interval in seconds",
@@ -228,6 +548,12 @@ pub enum RpcVersion {
     V05,
 }
 
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum RpcBlockTag {
+    Latest,
+    Pending,
+}
+
 #[derive(clap::Args)]
 struct NetworkCli {
     #[arg(
@@ -403,6 +729,17 @@ pub fn parse_cors_or_exit(input: Vec<String>) -> Option<AllowedOrigins> {
     }
 }
 
+/// Rejects paths which are already served, as these cannot be merged.
+fn parse_merged_path(path: &str) -> Result<String, String> {
+    match path {
+        "/" | "/rpc/v0.3" | "/rpc/v0.4" | "/rpc/v0.5" | "/rpc/pathfinder/v0.1" => {
+            Err(format!("{path} is already served"))
+        }
+        path if !path.starts_with('/') => Err("The path must start with '/'".to_owned()),
+        path => Ok(path.to_owned()),
+    }
+}
+
 #[derive(Debug, thiserror::Error, PartialEq)]
 #[error("Invalid domain for CORS: {0}")]
 struct InvalidCorsDomainError(String);
@@ -421,15 +758,55 @@ pub struct Config {
     pub data_directory: PathBuf,
     pub ethereum: Ethereum,
     pub rpc_address: SocketAddr,
+    pub rpc_unix_socket: Option<PathBuf>,
     pub rpc_cors_domains: Option<AllowedOrigins>,
     pub rpc_root_version: RpcVersion,
     pub ws: Option<WebSocket>,
     pub monitor_address: Option<SocketAddr>,
+    pub monitor_shutdown_grace_period: Option<std::time::Duration>,
     pub network: Option<NetworkConfig>,
     pub poll_pending: bool,
     pub execution_concurrency: Option<std::num::NonZeroU32>,
     pub sqlite_wal: JournalMode,
     pub max_rpc_connections: std::num::NonZeroUsize,
+    pub rpc_max_declare_class_size: std::num::NonZeroUsize,
+    pub rpc_max_transactions_by_hashes: std::num::NonZeroUsize,
+    pub rpc_max_classes_declared: std::num::NonZeroUsize,
+    pub rpc_max_events_block_range: std::num::NonZeroU64,
+    pub rpc_max_event_key_segments: std::num::NonZeroUsize,
+    pub rpc_disable_execution: bool,
+    pub rpc_resubmission_ttl: Option<std::time::Duration>,
+    pub rpc_read_replica: Option<PathBuf>,
+    pub rpc_max_concurrent_calls: Option<std::num::NonZeroUsize>,
+    pub rpc_max_concurrent_requests_per_ip: Option<std::num::NonZeroUsize>,
+    pub rpc_max_open_connections: Option<std::num::NonZeroUsize>,
+    pub rpc_max_batch_response_size: Option<std::num::NonZeroUsize>,
+    pub rpc_max_batch_params_size: Option<std::num::NonZeroUsize>,
+    pub rpc_keep_alive_timeout: Option<std::time::Duration>,
+    pub rpc_read_timeout: std::time::Duration,
+    pub rpc_write_timeout: std::time::Duration,
+    pub rpc_body_read_timeout: std::time::Duration,
+    pub rpc_max_requests_per_connection: Option<std::num::NonZeroUsize>,
+    pub rpc_default_block_tag: Option<RpcBlockTag>,
+    pub rpc_strict_requests: bool,
+    pub rpc_version_header: bool,
+    pub rpc_server_timing: bool,
+    pub rpc_get_requests: bool,
+    pub rpc_omit_null_fields: bool,
+    pub rpc_method_in_errors: bool,
+    pub rpc_error_messages_file: Option<std::path::PathBuf>,
+    pub rpc_merged_path: Option<String>,
+    pub rpc_slow_request_threshold: Option<std::time::Duration>,
+    pub rpc_large_response_threshold: Option<std::num::NonZeroUsize>,
+    pub rpc_batch_timeout: Option<std::time::Duration>,
+    pub rpc_single_flight_methods: Option<HashSet<String>>,
+    pub rpc_head_cached_methods: Option<HashSet<String>>,
+    pub rpc_supported_tx_versions: Option<HashSet<u128>>,
+    pub gateway_timeout: std::time::Duration,
+    pub rpc_gateway_max_retries: std::num::NonZeroUsize,
+    pub rpc_gateway_circuit_breaker_threshold: Option<std::num::NonZeroUsize>,
+    pub rpc_gateway_circuit_breaker_cooldown: std::time::Duration,
+    pub rpc_gateway_fallback_urls: Vec<Url>,
     pub poll_interval: std::time::Duration,
     pub color: Color,
     pub p2p: P2PConfig,
@@ -593,12 +970,16 @@ impl Config {
                 url: cli.ethereum_url,
             },
             rpc_address: cli.rpc_address,
+            rpc_unix_socket: cli.rpc_unix_socket,
             rpc_cors_domains: parse_cors_or_exit(cli.rpc_cors_domains),
             rpc_root_version: cli.rpc_root_version,
             ws: cli.ws.then_some(WebSocket {
                 capacity: cli.ws_capacity,
             }),
             monitor_address: cli.monitor_address,
+            monitor_shutdown_grace_period: cli
+                .monitor_shutdown_grace_period
+                .map(|period| std::time::Duration::from_secs(period.get())),
             network,
             #[cfg(feature = "p2p")]
             poll_pending: false,
@@ -610,6 +991,63 @@ impl Config {
                 false => JournalMode::Rollback,
             },
             max_rpc_connections: cli.max_rpc_connections,
+            rpc_max_declare_class_size: cli.rpc_max_declare_class_size,
+            rpc_max_transactions_by_hashes: cli.rpc_max_transactions_by_hashes,
+            rpc_max_classes_declared: cli.rpc_max_classes_declared,
+            rpc_max_events_block_range: cli.rpc_max_events_block_range,
+            rpc_max_event_key_segments: cli.rpc_max_event_key_segments,
+            rpc_disable_execution: cli.rpc_disable_execution,
+            rpc_resubmission_ttl: cli
+                .rpc_resubmission_ttl
+                .map(|ttl| std::time::Duration::from_secs(ttl.get())),
+            rpc_read_replica: cli.rpc_read_replica,
+            rpc_max_concurrent_calls: cli.rpc_max_concurrent_calls,
+            rpc_max_concurrent_requests_per_ip: cli.rpc_max_concurrent_requests_per_ip,
+            rpc_max_open_connections: cli.rpc_max_open_connections,
+            rpc_max_batch_response_size: cli.rpc_max_batch_response_size,
+            rpc_max_batch_params_size: cli.rpc_max_batch_params_size,
+            rpc_keep_alive_timeout: cli
+                .rpc_keep_alive_timeout
+                .map(|timeout| std::time::Duration::from_secs(timeout.get())),
+            rpc_read_timeout: std::time::Duration::from_secs(cli.rpc_read_timeout.get()),
+            rpc_write_timeout: std::time::Duration::from_secs(cli.rpc_write_timeout.get()),
+            rpc_body_read_timeout: std::time::Duration::from_secs(cli.rpc_body_read_timeout.get()),
+            rpc_max_requests_per_connection: cli.rpc_max_requests_per_connection,
+            rpc_default_block_tag: cli.rpc_default_block_tag,
+            rpc_strict_requests: cli.rpc_strict_requests,
+            rpc_version_header: cli.rpc_version_header,
+            rpc_server_timing: cli.rpc_server_timing,
+            rpc_get_requests: cli.rpc_get_requests,
+            rpc_omit_null_fields: cli.rpc_omit_null_fields,
+            rpc_method_in_errors: cli.rpc_method_in_errors,
+            rpc_error_messages_file: cli.rpc_error_messages_file,
+            rpc_merged_path: cli.rpc_merged_path,
+            rpc_slow_request_threshold: cli
+                .rpc_slow_request_threshold
+                .map(|threshold| std::time::Duration::from_millis(threshold.get())),
+            rpc_large_response_threshold: cli.rpc_large_response_threshold,
+            rpc_batch_timeout: cli
+                .rpc_batch_timeout
+                .map(|timeout| std::time::Duration::from_secs(timeout.get())),
+            rpc_single_flight_methods: match cli.rpc_single_flight_methods.is_empty() {
+                true => None,
+                false => Some(cli.rpc_single_flight_methods.into_iter().collect()),
+            },
+            rpc_head_cached_methods: match cli.rpc_head_cached_methods.is_empty() {
+                true => None,
+                false => Some(cli.rpc_head_cached_methods.into_iter().collect()),
+            },
+            rpc_supported_tx_versions: match cli.rpc_supported_tx_versions.is_empty() {
+                true => None,
+                false => Some(cli.rpc_supported_tx_versions.into_iter().collect()),
+            },
+            gateway_timeout: std::time::Duration::from_secs(cli.gateway_timeout.get()),
+            rpc_gateway_max_retries: cli.rpc_gateway_max_retries,
+            rpc_gateway_circuit_breaker_threshold: cli.rpc_gateway_circuit_breaker_threshold,
+            rpc_gateway_circuit_breaker_cooldown: std::time::Duration::from_secs(
+                cli.rpc_gateway_circuit_breaker_cooldown.get(),
+            ),
+            rpc_gateway_fallback_urls: cli.rpc_gateway_fallback_urls,
             poll_interval: std::time::Duration::from_secs(cli.poll_interval.get()),
             color: cli.color,
             p2p: P2PConfig::parse_or_exit(cli.p2p),
@@ -622,7 +1060,7 @@ impl Config {
 #[cfg(test)]
 mod tests {
     use super::{AllowedOrigins, RpcCorsDomainsParseError};
-    use crate::config::parse_cors;
+    use crate::config::{parse_cors, parse_merged_path};
 
     #[test]
     fn parse_cors_domains() {
@@ -698,4 +1136,12 @@ mod tests {
             )
         });
     }
+
+    #[test]
+    fn parse_merged_paths() {
+        assert_eq!(parse_merged_path("/rpc").unwrap(), "/rpc");
+        parse_merged_path("rpc").unwrap_err();
+        parse_merged_path("/").unwrap_err();
+        parse_merged_path("/rpc/v0.4").unwrap_err();
+    }
 }
@@ -74,6 +74,9 @@ async fn async_main() -> anyhow::Result<()> {
             .context("Using default Starknet network based on Ethereum configuration")?,
     };
 
+    // Notified whenever the metrics are scraped, which allows for a final scrape on shutdown.
+    let metrics_scraped = Arc::new(tokio::sync::Notify::new());
+
     // Spawn monitoring if configured.
     if let Some(address) = config.monitor_address {
         let network_label = match &network {
@@ -83,15 +86,24 @@ async fn async_main() -> anyhow::Result<()> {
             NetworkConfig::Integration => "integration",
             NetworkConfig::Custom { .. } => "custom",
         };
-        spawn_monitoring(network_label, address, readiness.clone())
-            .await
-            .context("Starting monitoring task")?;
+        spawn_monitoring(
+            network_label,
+            address,
+            readiness.clone(),
+            metrics_scraped.clone(),
+        )
+        .await
+        .context("Starting monitoring task")?;
     }
 
-    let pathfinder_context =
+    let mut pathfinder_context =
         PathfinderContext::configure_and_proxy_check(network, config.data_directory)
             .await
             .context("Configuring pathfinder")?;
+    pathfinder_context.gateway = pathfinder_context
+        .gateway
+        .with_timeout(config.gateway_timeout)
+        .context("Configuring gateway client timeout")?;
 
     verify_networks(pathfinder_context.network, ethereum.chain)?;
 
@@ -119,20 +131,35 @@ Hint: This is usually caused by exceeding the file descriptor limit of your syst
         .expect("usize should cast to u32");
     let rpc_storage = std::cmp::max(10, max_rpc_connections / 8);
     let rpc_storage = NonZeroU32::new(rpc_storage).expect("A non-zero minimum is set");
-    let rpc_storage = storage_manager.create_pool(rpc_storage).context(
-        r"Creating database connection pool for RPC
-
-Hint: This is usually caused by exceeding the file descriptor limit of your system.
-      Try increasing the file limit to using `ulimit` or similar tooling.",
-    )?;
-
     let execution_storage_pool_size = config.execution_concurrency.unwrap_or_else(|| {
         std::num::NonZeroU32::new(available_parallelism.get() as u32)
             .expect("The number of CPU cores should be non-zero")
     });
-    let execution_storage = storage_manager
-        .create_pool(execution_storage_pool_size)
-        .context(r"")?;
+
+    // RPC only reads from the database, so it can be served from a read-only replica.
+    let (rpc_storage, execution_storage) = match &config.rpc_read_replica {
+        Some(replica) => {
+            info!(location=?replica, "Serving RPC from read replica.");
+            let rpc_storage = Storage::open_read_only(replica.clone(), rpc_storage)
+                .context("Opening read replica connection pool for RPC")?;
+            let execution_storage =
+                Storage::open_read_only(replica.clone(), execution_storage_pool_size)
+                    .context("Opening read replica connection pool for execution")?;
+            (rpc_storage, execution_storage)
+        }
+        None => {
+            let rpc_storage = storage_manager.create_pool(rpc_storage).context(
+                r"Creating database connection pool for RPC
+
+Hint: This is usually caused by exceeding the file descriptor limit of your system.
+      Try increasing the file limit to using `ulimit` or similar tooling.",
+            )?;
+            let execution_storage = storage_manager
+                .create_pool(execution_storage_pool_size)
+                .context(r"")?;
+            (rpc_storage, execution_storage)
+        }
+    };
 
     let p2p_storage = storage_manager
         .create_pool(NonZeroU32::new(1).unwrap())
@@ -155,13 +182,41 @@ Hint: This is usually caused by exceeding the file descriptor limit of your syst
     let sync_state = Arc::new(SyncState::default());
     let pending_state = PendingData::default();
 
+    // Unlike sync, RPC methods should not wait on the gateway indefinitely.
+    let rpc_gateway = pathfinder_context
+        .gateway
+        .clone()
+        .with_max_retries(config.rpc_gateway_max_retries);
+    let rpc_gateway = match config.rpc_gateway_circuit_breaker_threshold {
+        Some(threshold) => {
+            rpc_gateway.with_circuit_breaker(threshold, config.rpc_gateway_circuit_breaker_cooldown)
+        }
+        None => rpc_gateway,
+    };
+    let rpc_gateway = match config.rpc_gateway_fallback_urls.is_empty() {
+        true => rpc_gateway,
+        false => rpc_gateway
+            .with_fallback_urls(config.rpc_gateway_fallback_urls.clone())
+            .context("Configuring gateway fallback urls")?,
+    };
+
     let context = pathfinder_rpc::context::RpcContext::new(
         rpc_storage,
         execution_storage,
         sync_state.clone(),
         pathfinder_context.network_id,
-        pathfinder_context.gateway.clone(),
-    );
+        rpc_gateway,
+    )
+    .with_config(pathfinder_rpc::context::RpcConfig {
+        max_declare_class_size: config.rpc_max_declare_class_size.get(),
+        max_transactions_by_hashes: config.rpc_max_transactions_by_hashes.get(),
+        max_classes_declared: config.rpc_max_classes_declared.get(),
+        max_events_block_range: config.rpc_max_events_block_range.get(),
+        max_event_key_segments: config.rpc_max_event_key_segments.get(),
+        execution_disabled: config.rpc_disable_execution,
+        resubmission_ttl: config.rpc_resubmission_ttl,
+        supported_tx_versions: config.rpc_supported_tx_versions,
+    });
 
     let context = match config.poll_pending {
         true => context.with_pending_data(pending_state.clone()),
@@ -185,6 +240,127 @@ Hint: This is usually caused by exceeding the file descriptor limit of your syst
         None => rpc_server,
     };
 
+    let rpc_server = match config.rpc_max_concurrent_calls {
+        Some(limit) => rpc_server.with_max_concurrent_calls(limit),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_max_concurrent_requests_per_ip {
+        Some(limit) => rpc_server.with_max_concurrent_requests_per_ip(limit),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_max_batch_response_size {
+        Some(limit) => rpc_server.with_max_batch_response_size(limit),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_max_batch_params_size {
+        Some(limit) => rpc_server.with_max_batch_params_size(limit),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_max_open_connections {
+        Some(limit) => rpc_server.with_max_open_connections(limit),
+        None => rpc_server,
+    };
+
+    let rpc_server = rpc_server
+        .with_read_timeout(config.rpc_read_timeout)
+        .with_write_timeout(config.rpc_write_timeout)
+        .with_body_read_timeout(config.rpc_body_read_timeout);
+
+    let rpc_server = match config.rpc_keep_alive_timeout {
+        Some(timeout) => rpc_server.with_keep_alive_timeout(timeout),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_max_requests_per_connection {
+        Some(limit) => rpc_server.with_max_requests_per_connection(limit),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_default_block_tag {
+        Some(config::RpcBlockTag::Latest) => {
+            rpc_server.with_default_block_tag(pathfinder_rpc::BlockTag::Latest)
+        }
+        Some(config::RpcBlockTag::Pending) => {
+            rpc_server.with_default_block_tag(pathfinder_rpc::BlockTag::Pending)
+        }
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_strict_requests {
+        true => rpc_server.with_strict_requests(),
+        false => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_version_header {
+        true => rpc_server.with_version_header(),
+        false => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_server_timing {
+        true => rpc_server.with_server_timing(),
+        false => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_get_requests {
+        true => rpc_server.with_get_requests(),
+        false => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_omit_null_fields {
+        true => rpc_server.with_omitted_null_fields(),
+        false => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_method_in_errors {
+        true => rpc_server.with_method_in_errors(),
+        false => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_error_messages_file {
+        Some(path) => {
+            let messages = std::fs::read_to_string(&path)
+                .with_context(|| format!("Reading RPC error messages from {}", path.display()))?;
+            let localization =
+                serde_json::from_str(&messages).context("Parsing RPC error messages")?;
+            rpc_server.with_localization(localization)
+        }
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_merged_path {
+        Some(path) => rpc_server.with_merged_path(path),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_slow_request_threshold {
+        Some(threshold) => rpc_server.with_slow_request_threshold(threshold),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_large_response_threshold {
+        Some(threshold) => rpc_server.with_large_response_threshold(threshold),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_batch_timeout {
+        Some(timeout) => rpc_server.with_batch_timeout(timeout),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_single_flight_methods {
+        Some(methods) => rpc_server.with_single_flight(methods),
+        None => rpc_server,
+    };
+
+    let rpc_server = match config.rpc_head_cached_methods {
+        Some(methods) => rpc_server.with_head_cache(methods),
+        None => rpc_server,
+    };
+
     let (p2p_handle, sequencer) = start_p2p(
         pathfinder_context.network_id,
         p2p_storage,
@@ -216,47 +392,92 @@ Hint: This is usually caused by exceeding the file descriptor limit of your syst
 
     let sync_handle = tokio::spawn(state::sync(sync_context, state::l1::sync, state::l2::sync));
 
-    let (rpc_handle, local_addr) = rpc_server
-        .with_max_connections(config.max_rpc_connections.get())
-        .spawn()
-        .context("Starting the RPC server")?;
-
-    info!("📡 HTTP-RPC server started on: {}", local_addr);
+    let rpc_server = rpc_server.with_max_connections(config.max_rpc_connections.get());
+    let rpc_handle = match &config.rpc_unix_socket {
+        Some(path) => {
+            let rpc_handle = rpc_server
+                .spawn_unix(path)
+                .context("Starting the RPC server")?;
+            info!("📡 HTTP-RPC server started on: {}", path.display());
+            rpc_handle
+        }
+        None => {
+            let (rpc_handle, local_addr) = rpc_server.spawn().context("Starting the RPC server")?;
+            info!("📡 HTTP-RPC server started on: {}", local_addr);
+            rpc_handle
+        }
+    };
 
     let update_handle = tokio::spawn(update::poll_github_for_releases());
 
     // We are now ready.
     readiness.store(true, std::sync::atomic::Ordering::Relaxed);
 
-    // Monitor our spawned process tasks.
-    tokio::select! {
+    // Monitor our spawned process tasks, until one of them ends or we are asked to stop.
+    let requested = tokio::select! {
         result = sync_handle => {
             match result {
                 Ok(task_result) => tracing::error!("Sync process ended unexpected with: {:?}", task_result),
                 Err(err) => tracing::error!("Sync process ended unexpected; failed to join task handle: {:?}", err),
             }
+            false
         }
         result = rpc_handle => {
             match result {
                 Ok(_) => tracing::error!("RPC server process ended unexpectedly"),
                 Err(err) => tracing::error!(error=%err, "RPC server process ended unexpectedly"),
             }
+            false
         }
         result = update_handle => {
             match result {
                 Ok(_) => tracing::error!("Release monitoring process ended unexpectedly"),
                 Err(err) => tracing::error!(error=%err, "Release monitoring process ended unexpectedly"),
             }
+            false
         }
         result = p2p_handle => {
             match result {
                 Ok(_) => tracing::error!("P2P process ended unexpectedly"),
                 Err(err) => tracing::error!(error=%err, "P2P process ended unexpectedly"),
             }
+            false
+        }
+        () = shutdown_signal() => {
+            info!("Shutdown signal received, shutting down");
+            true
         }
+    };
+
+    if let (Some(_), Some(grace_period)) =
+        (config.monitor_address, config.monitor_shutdown_grace_period)
+    {
+        monitoring::drain(&readiness, &metrics_scraped, grace_period).await;
+    }
+
+    match requested {
+        true => Ok(()),
+        false => anyhow::bail!("Unexpected shutdown"),
     }
+}
+
+/// Completes once the process is asked to shut down, i.e. on Ctrl-C or, on unix, SIGTERM as
+/// sent by container orchestrators.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Installing SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-    anyhow::bail!("Unexpected shutdown");
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result.expect("Installing Ctrl-C handler"),
+        () = terminate => {}
+    }
 }
 
 #[cfg(feature = "tokio-console")]
@@ -397,13 +618,14 @@ async fn spawn_monitoring(
     network: &str,
     address: SocketAddr,
     readiness: Arc<AtomicBool>,
+    scraped: Arc<tokio::sync::Notify>,
 ) -> anyhow::Result<tokio::task::JoinHandle<()>> {
     let prometheus_handle = PrometheusBuilder::new()
         .add_global_label("network", network)
         .install_recorder()
         .context("Creating Prometheus recorder")?;
 
-    let handle = monitoring::spawn_server(address, readiness, prometheus_handle).await;
+    let handle = monitoring::spawn_server(address, readiness, prometheus_handle, scraped).await;
     Ok(handle)
 }
 
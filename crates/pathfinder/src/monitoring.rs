@@ -1,27 +1,65 @@
 use std::sync::atomic::AtomicBool;
+use std::time::Duration;
 
 use metrics_exporter_prometheus::PrometheusHandle;
+use tokio::sync::Notify;
 use warp::Filter;
 
-/// Spawns a server which hosts a `/health` endpoint.
+/// Spawns a server which hosts the `/health`, `/ready` and `/metrics` endpoints.
+///
+/// `scraped` is notified whenever the metrics are scraped, see [drain].
 pub async fn spawn_server(
     addr: impl Into<std::net::SocketAddr> + 'static,
     readiness: std::sync::Arc<AtomicBool>,
     prometheus_handle: PrometheusHandle,
+    scraped: std::sync::Arc<Notify>,
 ) -> tokio::task::JoinHandle<()> {
-    let server = warp::serve(routes(readiness, prometheus_handle));
+    let server = warp::serve(routes(readiness, prometheus_handle, scraped));
     let server = server.bind(addr);
 
     tokio::spawn(server)
 }
 
+/// Keeps the monitoring server up until the metrics have been scraped once more, or until
+/// `grace_period` has elapsed, whichever comes first. Intended to be awaited before shutting
+/// down.
+///
+/// Prometheus pulls metrics, so anything recorded since the last scrape would otherwise be lost
+/// on shutdown. Readiness is withdrawn first, so that no new traffic is routed to the node while
+/// it drains.
+pub async fn drain(readiness: &AtomicBool, scraped: &Notify, grace_period: Duration) {
+    readiness.store(false, std::sync::atomic::Ordering::Relaxed);
+
+    if grace_period.is_zero() {
+        return;
+    }
+
+    tracing::info!(
+        ?grace_period,
+        "Waiting for a final metrics scrape before shutting down"
+    );
+    match tokio::time::timeout(grace_period, scraped.notified()).await {
+        Ok(()) => {
+            // The scrape is notified before its response is sent.
+            tokio::time::sleep(SCRAPE_RESPONSE_DELAY).await;
+            tracing::debug!("Final metrics scrape completed");
+        }
+        Err(_) => tracing::warn!("Metrics were not scraped within the shutdown grace period"),
+    }
+}
+
+/// The time given to the response of the final scrape to be sent, once its metrics have been
+/// rendered, before [drain] completes.
+const SCRAPE_RESPONSE_DELAY: Duration = Duration::from_millis(500);
+
 fn routes(
     readiness: std::sync::Arc<AtomicBool>,
     prometheus_handle: PrometheusHandle,
+    scraped: std::sync::Arc<Notify>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     health_route()
         .or(ready_route(readiness))
-        .or(metrics_route(prometheus_handle))
+        .or(metrics_route(prometheus_handle, scraped))
 }
 
 /// Always returns `Ok(200)` at `/health`.
@@ -44,16 +82,31 @@ fn ready_route(
         })
 }
 
-/// Returns Prometheus merics snapshot at `/metrics`.
+/// Content type of version 0.0.4 of the Prometheus text exposition format.
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Returns Prometheus metrics snapshot at `/metrics`, and notifies `scraped` once it is rendered.
 fn metrics_route(
     handle: PrometheusHandle,
+    scraped: std::sync::Arc<Notify>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
     warp::get()
         .and(warp::path!("metrics"))
-        .map(move || -> PrometheusHandle { handle.clone() })
-        .and_then(|handle: PrometheusHandle| async move {
-            Ok::<_, std::convert::Infallible>(warp::http::Response::builder().body(handle.render()))
+        .map(move || -> (PrometheusHandle, std::sync::Arc<Notify>) {
+            (handle.clone(), scraped.clone())
         })
+        .and_then(
+            |(handle, scraped): (PrometheusHandle, std::sync::Arc<Notify>)| async move {
+                let metrics = handle.render();
+                scraped.notify_waiters();
+
+                Ok::<_, std::convert::Infallible>(
+                    warp::http::Response::builder()
+                        .header(warp::http::header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)
+                        .body(metrics),
+                )
+            },
+        )
 }
 
 #[cfg(test)]
@@ -67,7 +120,7 @@ mod tests {
         let recorder = PrometheusBuilder::new().build_recorder();
         let handle = recorder.handle();
         let readiness = Arc::new(AtomicBool::new(false));
-        let filter = super::routes(readiness, handle);
+        let filter = super::routes(readiness, handle, Default::default());
         let response = warp::test::request().path("/health").reply(&filter).await;
 
         assert_eq!(response.status(), http::StatusCode::OK);
@@ -78,7 +131,7 @@ mod tests {
         let recorder = PrometheusBuilder::new().build_recorder();
         let handle = recorder.handle();
         let readiness = Arc::new(AtomicBool::new(false));
-        let filter = super::routes(readiness.clone(), handle);
+        let filter = super::routes(readiness.clone(), handle, Default::default());
         let response = warp::test::request().path("/ready").reply(&filter).await;
         assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
 
@@ -102,10 +155,70 @@ mod tests {
         counter.increment(123);
 
         let readiness = Arc::new(AtomicBool::new(false));
-        let filter = super::routes(readiness.clone(), handle);
+        let filter = super::routes(readiness.clone(), handle, Default::default());
         let response = warp::test::request().path("/metrics").reply(&filter).await;
 
         assert_eq!(response.status(), http::StatusCode::OK);
+        assert_eq!(
+            response.headers().get(http::header::CONTENT_TYPE).unwrap(),
+            super::PROMETHEUS_CONTENT_TYPE
+        );
         assert_eq!(response.body(), "# TYPE x counter\nx 123\n\n");
     }
+
+    mod drain {
+        use std::time::Duration;
+
+        use tokio::sync::Notify;
+
+        use super::*;
+        use crate::monitoring::{drain, routes, SCRAPE_RESPONSE_DELAY};
+
+        const GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+        #[tokio::test(start_paused = true)]
+        async fn completes_once_metrics_are_scraped() {
+            let handle = PrometheusBuilder::new().build_recorder().handle();
+            let readiness = Arc::new(AtomicBool::new(true));
+            let scraped = Arc::new(Notify::new());
+            let filter = routes(readiness.clone(), handle, scraped.clone());
+
+            let started = tokio::time::Instant::now();
+            let drain = drain(&readiness, &scraped, GRACE_PERIOD);
+            let scrape = async {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                warp::test::request().path("/metrics").reply(&filter).await
+            };
+            let ((), response) = tokio::join!(drain, scrape);
+
+            assert_eq!(response.status(), http::StatusCode::OK);
+            assert!(started.elapsed() >= Duration::from_secs(1) + SCRAPE_RESPONSE_DELAY);
+            assert!(started.elapsed() < GRACE_PERIOD);
+            assert!(!readiness.load(std::sync::atomic::Ordering::Relaxed));
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn gives_up_after_grace_period() {
+            let readiness = AtomicBool::new(true);
+            let scraped = Notify::new();
+
+            let started = tokio::time::Instant::now();
+            drain(&readiness, &scraped, GRACE_PERIOD).await;
+
+            assert_eq!(started.elapsed(), GRACE_PERIOD);
+            assert!(!readiness.load(std::sync::atomic::Ordering::Relaxed));
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn is_skipped_without_grace_period() {
+            let readiness = AtomicBool::new(true);
+            let scraped = Notify::new();
+
+            let started = tokio::time::Instant::now();
+            drain(&readiness, &scraped, Duration::ZERO).await;
+
+            assert_eq!(started.elapsed(), Duration::ZERO);
+            assert!(!readiness.load(std::sync::atomic::Ordering::Relaxed));
+        }
+    }
 }
@@ -126,6 +126,7 @@ mod error {
         SequencerError::StarknetError(StarknetError {
             code: StarknetErrorCode::Known(KnownStarknetErrorCode::BlockNotFound),
             message: message.to_string(),
+            status: None,
         })
     }
 
@@ -133,6 +134,7 @@ mod error {
         SequencerError::StarknetError(StarknetError {
             code: StarknetErrorCode::Known(KnownStarknetErrorCode::UndeclaredClass),
             message: message.to_string(),
+            status: None,
         })
     }
 }
@@ -402,6 +402,7 @@ async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) ->
                     Syncing::False(_) => {}
                     Syncing::Status(status) => {
                         status.current = NumberedBlock::from((block_hash, block_number));
+                        state.rate.lock().unwrap().record(block_number);
 
                         metrics::gauge!("current_block", block_number.get() as f64);
 
@@ -411,6 +412,9 @@ async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) ->
                         }
                     }
                 }
+                // Only once the block is committed, so that results cached for the new head
+                // include it.
+                state.head_changed();
 
                 let now_timestamp = time::OffsetDateTime::now_utc().unix_timestamp() as u64;
                 let latency = now_timestamp.saturating_sub(block_timestamp.get());
@@ -460,6 +464,7 @@ async fn consumer(mut events: Receiver<SyncEvent>, context: ConsumerContext) ->
                 l2_reorg(&mut db_conn, reorg_tail)
                     .await
                     .with_context(|| format!("Reorg L2 state to {reorg_tail:?}"))?;
+                state.head_changed();
 
                 next_number = reorg_tail;
 
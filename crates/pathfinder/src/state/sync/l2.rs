@@ -889,6 +889,7 @@ mod tests {
             SequencerError::StarknetError(StarknetError {
                 code: KnownStarknetErrorCode::BlockNotFound.into(),
                 message: String::new(),
+                status: None,
             })
         }
 
@@ -0,0 +1,58 @@
+use std::net::SocketAddr;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use pathfinder_rpc::context::RpcContext;
+use pathfinder_rpc::{DefaultVersion, RpcServer};
+
+const METHODS: [&str; 2] = ["starknet_blockNumber", "starknet_blockHashAndNumber"];
+
+/// Spawns a server on the test storage, and returns the url of its v0.4 API.
+fn spawn(runtime: &tokio::runtime::Runtime, cached: bool) -> String {
+    let _guard = runtime.enter();
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let server = RpcServer::new(addr, RpcContext::for_tests(), DefaultVersion::V04);
+    let server = match cached {
+        true => server.with_head_cache(METHODS.into_iter().map(str::to_owned).collect()),
+        false => server,
+    };
+    let (_handle, addr) = server.spawn().unwrap();
+
+    format!("http://{addr}/rpc/v0.4")
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let client = reqwest::Client::new();
+
+    for method in METHODS {
+        for cached in [false, true] {
+            let url = spawn(&runtime, cached);
+            let request = format!(r#"{{"jsonrpc":"2.0","method":"{method}","id":1}}"#);
+            let name = match cached {
+                true => format!("{method}_cached"),
+                false => format!("{method}_uncached"),
+            };
+
+            c.bench_function(&name, |b| {
+                b.iter(|| {
+                    runtime.block_on(async {
+                        client
+                            .post(&url)
+                            .header("Content-Type", "application/json")
+                            .body(request.clone())
+                            .send()
+                            .await
+                            .unwrap()
+                            .bytes()
+                            .await
+                            .unwrap()
+                    })
+                });
+            });
+        }
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
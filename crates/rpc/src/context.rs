@@ -1,16 +1,83 @@
 use crate::gas_price;
 use crate::jsonrpc::websocket::WebsocketSenders;
+use crate::jsonrpc::Principal;
+use crate::recent_submissions::RecentSubmissions;
 use crate::SyncState;
-use pathfinder_common::ChainId;
+use pathfinder_common::{ChainId, TransactionVersion};
 use pathfinder_storage::Storage;
 use starknet_gateway_types::pending::PendingData;
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 
 type SequencerClient = starknet_gateway_client::Client;
 
+/// Tunables which affect the behaviour of individual RPC methods.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RpcConfig {
+    /// Maximum size in bytes of the contract class in a declare transaction, measured as its
+    /// JSON definition with Cairo 0 programs decompressed.
+    pub max_declare_class_size: usize,
+    /// Maximum number of transaction hashes accepted by `pathfinder_getTransactionsByHashes`.
+    pub max_transactions_by_hashes: usize,
+    /// Maximum number of class hashes accepted by `pathfinder_classesDeclared`.
+    pub max_classes_declared: usize,
+    /// Maximum number of blocks `starknet_getEvents` may search in a single request.
+    pub max_events_block_range: u64,
+    /// Maximum number of key segments, i.e. the outer dimension of its `keys`, a
+    /// `starknet_getEvents` filter may contain. Capped at
+    /// [EVENT_KEY_FILTER_LIMIT](pathfinder_storage::EVENT_KEY_FILTER_LIMIT).
+    pub max_event_key_segments: usize,
+    /// Rejects calls requiring execution, such as `starknet_call`, with
+    /// [ExecutionDisabled](crate::error::RpcError::ExecutionDisabled).
+    pub execution_disabled: bool,
+    /// Answers identical resubmissions of an invoke transaction accepted within this duration
+    /// from a cache, instead of forwarding them to the gateway again. Disabled if [None].
+    pub resubmission_ttl: Option<std::time::Duration>,
+    /// Transaction versions accepted by the methods submitting transactions to the gateway.
+    /// Other versions are rejected with
+    /// [UnsupportedTxVersion](crate::error::RpcError::UnsupportedTxVersion) without being
+    /// forwarded. All versions are forwarded if [None].
+    pub supported_tx_versions: Option<HashSet<u128>>,
+}
+
+impl RpcConfig {
+    pub const DEFAULT_MAX_DECLARE_CLASS_SIZE: usize = 4 * 1024 * 1024;
+    pub const DEFAULT_MAX_TRANSACTIONS_BY_HASHES: usize = 100;
+    pub const DEFAULT_MAX_CLASSES_DECLARED: usize = 1000;
+    pub const DEFAULT_MAX_EVENTS_BLOCK_RANGE: u64 = 100_000;
+    pub const DEFAULT_MAX_EVENT_KEY_SEGMENTS: usize = pathfinder_storage::EVENT_KEY_FILTER_LIMIT;
+
+    /// Whether transactions of `version` may be submitted, see
+    /// [supported_tx_versions](Self::supported_tx_versions).
+    pub fn is_supported_tx_version(&self, version: TransactionVersion) -> bool {
+        self.supported_tx_versions
+            .as_ref()
+            .map_or(true, |versions| {
+                versions.contains(&version.without_query_version())
+            })
+    }
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            max_declare_class_size: Self::DEFAULT_MAX_DECLARE_CLASS_SIZE,
+            max_transactions_by_hashes: Self::DEFAULT_MAX_TRANSACTIONS_BY_HASHES,
+            max_classes_declared: Self::DEFAULT_MAX_CLASSES_DECLARED,
+            max_events_block_range: Self::DEFAULT_MAX_EVENTS_BLOCK_RANGE,
+            max_event_key_segments: Self::DEFAULT_MAX_EVENT_KEY_SEGMENTS,
+            execution_disabled: false,
+            resubmission_ttl: None,
+            supported_tx_versions: None,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct RpcContext {
+    /// Read-only access to the database, which may be served from a replica.
     pub storage: Storage,
+    /// Like `storage`, but reserved for transaction execution.
     pub execution_storage: Storage,
     pub pending_data: Option<PendingData>,
     pub sync_status: Arc<SyncState>,
@@ -18,6 +85,11 @@ pub struct RpcContext {
     pub eth_gas_price: gas_price::Cached,
     pub sequencer: SequencerClient,
     pub websocket: WebsocketSenders,
+    pub config: RpcConfig,
+    pub recent_submissions: Arc<Mutex<RecentSubmissions>>,
+    /// The caller of the current request, as identified by the router's
+    /// [Authenticator](crate::jsonrpc::Authenticator).
+    pub principal: Arc<Principal>,
 }
 
 impl RpcContext {
@@ -37,6 +109,9 @@ impl RpcContext {
             eth_gas_price: gas_price::Cached::new(sequencer.clone()),
             sequencer,
             websocket: WebsocketSenders::with_capacity(1),
+            config: RpcConfig::default(),
+            recent_submissions: Default::default(),
+            principal: Default::default(),
         }
     }
 
@@ -73,6 +148,30 @@ impl RpcContext {
         }
     }
 
+    /// Tags the gateway requests made on behalf of the RPC request `request_id`.
+    pub fn with_request_id(self, request_id: &str) -> Self {
+        Self {
+            sequencer: self.sequencer.with_request_id(request_id),
+            ..self
+        }
+    }
+
+    /// Sets the caller of the current request.
+    pub fn with_principal(self, principal: Principal) -> Self {
+        Self {
+            principal: Arc::new(principal),
+            ..self
+        }
+    }
+
+    /// Aborts the gateway requests made on behalf of the RPC request once `deadline` passes.
+    pub fn with_deadline(self, deadline: tokio::time::Instant) -> Self {
+        Self {
+            sequencer: self.sequencer.with_deadline(deadline),
+            ..self
+        }
+    }
+
     pub fn with_pending_data(self, pending_data: PendingData) -> Self {
         Self {
             pending_data: Some(pending_data),
@@ -91,4 +190,8 @@ impl RpcContext {
     pub fn with_websocket(self, websocket: WebsocketSenders) -> Self {
         Self { websocket, ..self }
     }
+
+    pub fn with_config(self, config: RpcConfig) -> Self {
+        Self { config, ..self }
+    }
 }
@@ -4,10 +4,132 @@
 //! by each JSON-RPC method to trivially create its subset of [RpcError] along with the boilerplate involved.
 #![macro_use]
 
-#[derive(serde::Serialize, Clone, Copy, Debug)]
+/// Why no trace is available for a transaction, reported as the `status` in the `data` of
+/// [RpcError::NoTraceAvailable].
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TraceError {
+    /// The transaction was received by the sequencer, but has not been executed yet.
     Received,
+    /// The transaction was rejected by the sequencer, and will never be executed.
     Rejected,
+    /// The transaction is part of the pending block, whose transactions are only traced once
+    /// the block has been accepted.
+    Pending,
+}
+
+/// Why a continuation token was rejected, reported as the `reason` in the `data` of
+/// [RpcError::InvalidContinuationToken].
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContinuationTokenError {
+    /// The token could not be parsed, so it was not issued by this node.
+    Malformed,
+    /// The token's block precedes the filter's `from_block`, e.g. because it was issued for a
+    /// different query.
+    OutOfRange,
+    /// No events remain at the token's position, e.g. because it was issued for a different
+    /// query or refers to a pending block which has since been replaced.
+    Stale,
+}
+
+/// Reported as the `data` of [RpcError::ContractClassSizeIsTooLarge] when the class was rejected
+/// by pathfinder's own size limit.
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClassSizeLimit {
+    /// Size of the submitted class in bytes.
+    pub size: usize,
+    /// The configured maximum class size in bytes.
+    pub limit: usize,
+}
+
+impl ClassSizeLimit {
+    /// Checks the [definition size](crate::v02::types::CairoContractClass::definition_size) of
+    /// a declared class against the configured `limit`.
+    pub fn check(size: usize, limit: usize) -> Result<(), Self> {
+        match size > limit {
+            true => Err(Self { size, limit }),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Reported as the `data` of [RpcError::ValidationFailure].
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ValidationFailureData {
+    pub reason: ValidationFailureReason,
+    /// The gateway's original error message.
+    pub message: String,
+}
+
+/// Reported as the `data` of [RpcError::CompiledClassHashMismatch].
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompiledClassHashMismatchData {
+    /// The hash of the class as compiled by the sequencer.
+    pub expected: pathfinder_common::CasmHash,
+    /// The hash supplied in the transaction.
+    pub supplied: pathfinder_common::CasmHash,
+}
+
+impl CompiledClassHashMismatchData {
+    /// Extracts both hashes from a gateway error, whose message reports the computed hash
+    /// followed by the supplied one, e.g.
+    /// `Computed compiled_class_hash: 0x1, does not match the given value: 0x2.`
+    ///
+    /// Returns [None] if the message does not contain exactly two hashes.
+    pub fn from_gateway(error: &starknet_gateway_types::error::StarknetError) -> Option<Self> {
+        let hashes = error
+            .message
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| word.starts_with("0x"))
+            .map(stark_hash::Felt::from_hex_str)
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+
+        match hashes.as_slice() {
+            [expected, supplied] => Some(Self {
+                expected: pathfinder_common::CasmHash(*expected),
+                supplied: pathfinder_common::CasmHash(*supplied),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Why the account contract's validation failed, as far as can be determined.
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ValidationFailureReason {
+    InvalidSignature,
+    InvalidNonce,
+    InsufficientBalance,
+    Other,
+}
+
+impl ValidationFailureData {
+    /// Classifies a gateway validation error.
+    ///
+    /// The gateway reports most account validation failures as `VALIDATE_FAILURE`, so these are
+    /// classified using their message.
+    pub fn from_gateway(error: starknet_gateway_types::error::StarknetError) -> Self {
+        use starknet_gateway_types::error::KnownStarknetErrorCode::InvalidSignature;
+
+        let message = error.message.to_lowercase();
+        let reason = if error.code == InvalidSignature.into() || message.contains("signature") {
+            ValidationFailureReason::InvalidSignature
+        } else if message.contains("nonce") {
+            ValidationFailureReason::InvalidNonce
+        } else if message.contains("balance") {
+            ValidationFailureReason::InsufficientBalance
+        } else {
+            ValidationFailureReason::Other
+        };
+
+        Self {
+            reason,
+            message: error.message,
+        }
+    }
 }
 
 /// The Starknet JSON-RPC error variants.
@@ -38,7 +160,7 @@ pub enum RpcError {
     #[error("No trace available")]
     NoTraceAvailable(TraceError),
     #[error("The supplied continuation token is invalid or unknown")]
-    InvalidContinuationToken,
+    InvalidContinuationToken(ContinuationTokenError),
     #[error("Too many keys provided in a filter")]
     TooManyKeysInFilter { limit: usize, requested: usize },
     #[error("Contract error")]
@@ -54,69 +176,165 @@ pub enum RpcError {
     #[error("Account balance is smaller than the transaction's max_fee")]
     InsufficientAccountBalance,
     #[error("Account validation failed")]
-    ValidationFailure,
+    ValidationFailure(Option<ValidationFailureData>),
     #[error("Compilation failed")]
     CompilationFailed,
     #[error("Contract class size it too large")]
-    ContractClassSizeIsTooLarge,
+    ContractClassSizeIsTooLarge(Option<ClassSizeLimit>),
     #[error("Sender address in not an account contract")]
     NonAccount,
     #[error("A transaction with the same hash already exists in the mempool")]
     DuplicateTransaction,
+    /// Carries both hashes, if the gateway reported them.
     #[error("The compiled class hash did not match the one supplied in the transaction")]
-    CompiledClassHashMismatch,
+    CompiledClassHashMismatch(Option<CompiledClassHashMismatchData>),
+    /// Carries the rejected version, if known.
     #[error("The transaction version is not supported")]
-    UnsupportedTxVersion,
+    UnsupportedTxVersion(Option<pathfinder_common::TransactionVersion>),
     #[error("The contract class version is not supported")]
     UnsupportedContractClassVersion,
     #[error("An unexpected error occurred")]
     UnexpectedError { data: String },
     #[error("Too many storage keys requested")]
     ProofLimitExceeded { limit: u32, requested: u32 },
+    #[error("Too many transactions requested")]
+    TransactionLimitExceeded { limit: usize, requested: usize },
+    #[error("Execution is disabled on this node")]
+    ExecutionDisabled,
+    #[error("Too many class hashes requested")]
+    ClassLimitExceeded { limit: usize, requested: usize },
+    /// The gateway responded with a 4xx status, i.e. the request forwarded to it was invalid.
+    #[error("The gateway rejected the request")]
+    GatewayRejected(starknet_gateway_types::error::StarknetError),
+    #[error("Block range too large")]
+    BlockRangeTooLarge { limit: u64, requested: u64 },
+    /// The database was locked by another connection, e.g. while a block is being written.
+    #[error("The database is busy")]
+    DatabaseBusy,
     #[error(transparent)]
     GatewayError(starknet_gateway_types::error::StarknetError),
     #[error(transparent)]
     Internal(anyhow::Error),
 }
 
+/// Generates [RpcError::code], [RpcError::name] and [RpcError::variants] from a single table of
+/// variants and their codes, so that these cannot diverge.
+///
+/// Variants which carry data are listed with example values for their fields, which are used
+/// by [RpcError::variants]. A variant missing from the table fails to compile.
+macro_rules! rpc_error_codes {
+    ($(
+        $variant:ident
+        $(($($arg:expr),*))?
+        $({$($field:ident: $value:expr),*})?
+        = $code:literal,
+    )*) => {
+        impl RpcError {
+            pub fn code(&self) -> i32 {
+                match self {
+                    $(RpcError::$variant { .. } => $code,)*
+                    // https://www.jsonrpc.org/specification#error_object
+                    RpcError::GatewayError(_) | RpcError::Internal(_) => -32603,
+                }
+            }
+
+            /// The name of the variant, e.g. `BlockNotFound`.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(RpcError::$variant { .. } => stringify!($variant),)*
+                    RpcError::GatewayError(_) => "GatewayError",
+                    RpcError::Internal(_) => "Internal",
+                }
+            }
+
+            /// An instance of each variant, in the order of their codes.
+            ///
+            /// [RpcError::GatewayError] and [RpcError::Internal] are omitted as these are reported
+            /// as the generic JSON-RPC internal error.
+            pub fn variants() -> Vec<RpcError> {
+                vec![$(
+                    RpcError::$variant $(($($arg),*))? $({$($field: $value),*})?
+                ),*]
+            }
+        }
+    };
+}
+
+rpc_error_codes! {
+    // Taken from the official starknet json rpc api.
+    // https://github.com/starkware-libs/starknet-specs
+    FailedToReceiveTxn = 1,
+    NoTraceAvailable(TraceError::Received) = 10,
+    ContractNotFound = 20,
+    BlockNotFound = 24,
+    TxnHashNotFoundV03 = 25,
+    InvalidTxnHash = 25,
+    InvalidBlockHash = 26,
+    InvalidTxnIndex = 27,
+    ClassHashNotFound = 28,
+    TxnHashNotFoundV04 = 29,
+    PageSizeTooBig = 31,
+    NoBlocks = 32,
+    InvalidContinuationToken(ContinuationTokenError::Malformed) = 33,
+    TooManyKeysInFilter { limit: 0, requested: 0 } = 34,
+    ContractError = 40,
+    InvalidContractClass = 50,
+    ClassAlreadyDeclared = 51,
+    InvalidTransactionNonce = 52,
+    InsufficientMaxFee = 53,
+    InsufficientAccountBalance = 54,
+    ValidationFailure(None) = 55,
+    CompilationFailed = 56,
+    ContractClassSizeIsTooLarge(None) = 57,
+    NonAccount = 58,
+    DuplicateTransaction = 59,
+    CompiledClassHashMismatch(None) = 60,
+    UnsupportedTxVersion(None) = 61,
+    UnsupportedContractClassVersion = 62,
+    UnexpectedError { data: String::new() } = 63,
+    // doc/rpc/pathfinder_rpc_api.json
+    ProofLimitExceeded { limit: 0, requested: 0 } = 10000,
+    TransactionLimitExceeded { limit: 0, requested: 0 } = 10001,
+    ExecutionDisabled = 10002,
+    ClassLimitExceeded { limit: 0, requested: 0 } = 10003,
+    GatewayRejected(starknet_gateway_types::error::StarknetError {
+        code: starknet_gateway_types::error::KnownStarknetErrorCode::MalformedRequest.into(),
+        message: String::new(),
+        status: Some(400),
+    }) = 10004,
+    BlockRangeTooLarge { limit: 0, requested: 0 } = 10005,
+    DatabaseBusy = 10006,
+}
+
 impl RpcError {
-    pub fn code(&self) -> i32 {
+    /// Additional information about the error, reported as the JSON-RPC error's `data` member.
+    pub fn data(&self) -> Option<serde_json::Value> {
         match self {
-            // Taken from the official starknet json rpc api.
-            // https://github.com/starkware-libs/starknet-specs
-            RpcError::FailedToReceiveTxn => 1,
-            RpcError::NoTraceAvailable(_) => 10,
-            RpcError::ContractNotFound => 20,
-            RpcError::BlockNotFound => 24,
-            RpcError::TxnHashNotFoundV03 => 25,
-            RpcError::InvalidTxnHash => 25,
-            RpcError::InvalidBlockHash => 26,
-            RpcError::InvalidTxnIndex => 27,
-            RpcError::ClassHashNotFound => 28,
-            RpcError::TxnHashNotFoundV04 => 29,
-            RpcError::PageSizeTooBig => 31,
-            RpcError::NoBlocks => 32,
-            RpcError::InvalidContinuationToken => 33,
-            RpcError::TooManyKeysInFilter { .. } => 34,
-            RpcError::ContractError => 40,
-            RpcError::InvalidContractClass => 50,
-            RpcError::ClassAlreadyDeclared => 51,
-            RpcError::InvalidTransactionNonce => 52,
-            RpcError::InsufficientMaxFee => 53,
-            RpcError::InsufficientAccountBalance => 54,
-            RpcError::ValidationFailure => 55,
-            RpcError::CompilationFailed => 56,
-            RpcError::ContractClassSizeIsTooLarge => 57,
-            RpcError::NonAccount => 58,
-            RpcError::DuplicateTransaction => 59,
-            RpcError::CompiledClassHashMismatch => 60,
-            RpcError::UnsupportedTxVersion => 61,
-            RpcError::UnsupportedContractClassVersion => 62,
-            RpcError::UnexpectedError { .. } => 63,
-            // doc/rpc/pathfinder_rpc_api.json
-            RpcError::ProofLimitExceeded { .. } => 10000,
-            // https://www.jsonrpc.org/specification#error_object
-            RpcError::GatewayError(_) | RpcError::Internal(_) => -32603,
+            RpcError::ContractClassSizeIsTooLarge(Some(limit)) => Some(serde_json::json!(limit)),
+            RpcError::ValidationFailure(Some(data)) => Some(serde_json::json!(data)),
+            RpcError::CompiledClassHashMismatch(Some(data)) => Some(serde_json::json!(data)),
+            RpcError::TransactionLimitExceeded { limit, requested }
+            | RpcError::ClassLimitExceeded { limit, requested } => Some(serde_json::json!({
+                "limit": limit,
+                "requested": requested,
+            })),
+            RpcError::BlockRangeTooLarge { limit, requested } => Some(serde_json::json!({
+                "limit": limit,
+                "requested": requested,
+            })),
+            RpcError::UnsupportedTxVersion(Some(version)) => Some(serde_json::json!({
+                "version": format!("{:#x}", version.without_query_version()),
+            })),
+            RpcError::NoTraceAvailable(status) => Some(serde_json::json!({ "status": status })),
+            RpcError::InvalidContinuationToken(reason) => {
+                Some(serde_json::json!({ "reason": reason }))
+            }
+            RpcError::GatewayRejected(e) => Some(serde_json::json!({
+                "code": e.code,
+                "message": e.message,
+            })),
+            RpcError::DatabaseBusy => Some(serde_json::json!({ "retryable": true })),
+            _ => None,
         }
     }
 }
@@ -275,6 +493,165 @@ pub(super) use generate_rpc_error_subset;
 
 #[cfg(test)]
 mod tests {
+    mod validation_failure {
+        use super::super::*;
+        use starknet_gateway_types::error::{KnownStarknetErrorCode, StarknetError};
+
+        #[rstest::rstest]
+        #[case::signature(
+            KnownStarknetErrorCode::ValidateFailure,
+            "Signature (0x1, 0x2) is invalid, with respect to the public key 0x3",
+            ValidationFailureReason::InvalidSignature
+        )]
+        #[case::signature_code(
+            KnownStarknetErrorCode::InvalidSignature,
+            "",
+            ValidationFailureReason::InvalidSignature
+        )]
+        #[case::nonce(
+            KnownStarknetErrorCode::ValidateFailure,
+            "Invalid transaction nonce. Expected: 3, got: 2.",
+            ValidationFailureReason::InvalidNonce
+        )]
+        #[case::balance(
+            KnownStarknetErrorCode::ValidateFailure,
+            "Account balance is too low",
+            ValidationFailureReason::InsufficientBalance
+        )]
+        #[case::other(
+            KnownStarknetErrorCode::ValidateFailure,
+            "Error in the called contract",
+            ValidationFailureReason::Other
+        )]
+        fn classification(
+            #[case] code: KnownStarknetErrorCode,
+            #[case] message: &str,
+            #[case] expected: ValidationFailureReason,
+        ) {
+            let error = StarknetError {
+                code: code.into(),
+                message: message.to_owned(),
+                status: None,
+            };
+
+            let data = ValidationFailureData::from_gateway(error);
+
+            assert_eq!(data.reason, expected);
+            assert_eq!(data.message, message);
+        }
+
+        #[test]
+        fn data() {
+            let error = RpcError::ValidationFailure(Some(ValidationFailureData {
+                reason: ValidationFailureReason::InvalidSignature,
+                message: "Invalid signature".to_owned(),
+            }));
+
+            assert_eq!(error.code(), 55);
+            assert_eq!(
+                error.data(),
+                Some(serde_json::json!({
+                    "reason": "INVALID_SIGNATURE",
+                    "message": "Invalid signature",
+                }))
+            );
+        }
+    }
+
+    mod compiled_class_hash_mismatch {
+        use super::super::{CompiledClassHashMismatchData, RpcError};
+        use pathfinder_common::macro_prelude::*;
+        use starknet_gateway_types::error::{KnownStarknetErrorCode, StarknetError};
+
+        fn gateway_error(message: &str) -> StarknetError {
+            StarknetError {
+                code: KnownStarknetErrorCode::InvalidCompiledClassHash.into(),
+                message: message.to_owned(),
+                status: None,
+            }
+        }
+
+        #[test]
+        fn from_gateway() {
+            let error = gateway_error(
+                "Computed compiled_class_hash: 0x1234, does not match the given value: 0xabcd.",
+            );
+
+            let data = CompiledClassHashMismatchData::from_gateway(&error);
+
+            assert_eq!(
+                data,
+                Some(CompiledClassHashMismatchData {
+                    expected: casm_hash!("0x1234"),
+                    supplied: casm_hash!("0xabcd"),
+                })
+            );
+        }
+
+        #[rstest::rstest]
+        #[case::no_hashes("Invalid compiled class hash")]
+        #[case::single_hash("Invalid compiled class hash: 0x1234")]
+        fn from_gateway_without_both_hashes(#[case] message: &str) {
+            assert_eq!(
+                CompiledClassHashMismatchData::from_gateway(&gateway_error(message)),
+                None
+            );
+        }
+
+        #[test]
+        fn data() {
+            let error = RpcError::CompiledClassHashMismatch(Some(CompiledClassHashMismatchData {
+                expected: casm_hash!("0x1234"),
+                supplied: casm_hash!("0xabcd"),
+            }));
+
+            assert_eq!(error.code(), 60);
+            assert_eq!(
+                error.data(),
+                Some(serde_json::json!({
+                    "expected": "0x1234",
+                    "supplied": "0xabcd",
+                }))
+            );
+        }
+    }
+
+    mod no_trace_available {
+        use super::super::{RpcError, TraceError};
+
+        #[rstest::rstest]
+        #[case::received(TraceError::Received, "RECEIVED")]
+        #[case::rejected(TraceError::Rejected, "REJECTED")]
+        #[case::pending(TraceError::Pending, "PENDING")]
+        fn data(#[case] status: TraceError, #[case] expected: &str) {
+            let error = RpcError::NoTraceAvailable(status);
+
+            assert_eq!(error.code(), 10);
+            assert_eq!(
+                error.data(),
+                Some(serde_json::json!({ "status": expected }))
+            );
+        }
+    }
+
+    mod invalid_continuation_token {
+        use super::super::{ContinuationTokenError, RpcError};
+
+        #[rstest::rstest]
+        #[case::malformed(ContinuationTokenError::Malformed, "MALFORMED")]
+        #[case::out_of_range(ContinuationTokenError::OutOfRange, "OUT_OF_RANGE")]
+        #[case::stale(ContinuationTokenError::Stale, "STALE")]
+        fn data(#[case] reason: ContinuationTokenError, #[case] expected: &str) {
+            let error = RpcError::InvalidContinuationToken(reason);
+
+            assert_eq!(error.code(), 33);
+            assert_eq!(
+                error.data(),
+                Some(serde_json::json!({ "reason": expected }))
+            );
+        }
+    }
+
     mod rpc_error_subset {
         use super::super::{generate_rpc_error_subset, RpcError};
         use assert_matches::assert_matches;
@@ -307,4 +684,35 @@ mod tests {
             assert_matches!(contract_error, RpcError::ContractError);
         }
     }
+
+    mod variants {
+        use super::super::*;
+
+        #[test]
+        fn names_are_unique() {
+            let names = RpcError::variants()
+                .iter()
+                .map(RpcError::name)
+                .collect::<std::collections::HashSet<_>>();
+
+            assert_eq!(names.len(), RpcError::variants().len());
+        }
+
+        #[test]
+        fn name() {
+            assert_eq!(RpcError::BlockNotFound.name(), "BlockNotFound");
+            assert_eq!(
+                RpcError::ValidationFailure(None).name(),
+                "ValidationFailure"
+            );
+            assert_eq!(
+                RpcError::TooManyKeysInFilter {
+                    limit: 1,
+                    requested: 2
+                }
+                .name(),
+                "TooManyKeysInFilter"
+            );
+        }
+    }
 }
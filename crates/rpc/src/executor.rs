@@ -69,6 +69,7 @@ pub(crate) async fn execution_state(
         state_at_block: Some(block.number),
         gas_price,
         pending_update,
+        state_overrides: None,
     };
 
     Ok(execution_state)
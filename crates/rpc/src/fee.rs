@@ -0,0 +1,48 @@
+//! Fee amounts which carry their unit in their type, so that fees cannot be mixed up with
+//! amounts of gas or with fees paid in another token.
+
+use primitive_types::U256;
+use serde_with::serde_as;
+
+/// An amount of WEI, the unit of fees paid in ETH.
+///
+/// Serialized as a hex string, exactly like the [U256] it wraps.
+#[serde_as]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct FeeWei(#[serde_as(as = "pathfinder_serde::U256AsHexStr")] pub U256);
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[rstest::rstest]
+    #[case::zero(U256::zero(), "0x0")]
+    #[case::small(U256::from(3700), "0xe74")]
+    #[case::max(
+        U256::MAX,
+        "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+    )]
+    fn wire_format(#[case] amount: U256, #[case] expected: &str) {
+        let fee = FeeWei(amount);
+
+        assert_eq!(serde_json::to_value(fee).unwrap(), json!(expected));
+        assert_eq!(
+            serde_json::from_value::<FeeWei>(json!(expected)).unwrap(),
+            fee
+        );
+    }
+}
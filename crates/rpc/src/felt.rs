@@ -278,3 +278,28 @@ mod deserialization {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::felt;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Felts exceed the integers many JSON clients can represent exactly, and must therefore
+    /// always be serialized as hex strings.
+    #[rstest::rstest]
+    #[case::zero(Felt::ZERO, "0x0")]
+    #[case::beyond_safe_integers(felt!("0x20000000000001"), "0x20000000000001")]
+    #[case::beyond_u64(felt!("0x10000000000000000"), "0x10000000000000000")]
+    #[case::max(
+        felt!("0x800000000000011000000000000000000000000000000000000000000000000"),
+        "0x800000000000011000000000000000000000000000000000000000000000000"
+    )]
+    fn serializes_as_hex_string(#[case] felt: Felt, #[case] expected: &str) {
+        assert_eq!(
+            serde_json::to_value(RpcFelt(felt)).unwrap(),
+            json!(expected)
+        );
+    }
+}
@@ -1,13 +1,26 @@
+mod auth;
 mod error;
+mod head_cache;
+mod localization;
 mod request;
 mod response;
 mod router;
+mod single_flight;
+#[cfg(test)]
+pub(crate) mod test_utils;
 pub mod websocket;
 
+pub use auth::{ApiKeys, Authenticator, NoAuth, Principal, Unauthenticated};
+pub(crate) use error::RouterError;
 pub use error::RpcError;
-pub use request::RpcRequest;
+pub use localization::{Localization, MessageTable};
+pub use request::{BlockTag, ParamsLimits, RpcRequest, UnknownFields};
 pub use response::{RpcResponse, RpcResult};
-pub use router::{rpc_handler, IntoRpcMethod, RpcMethodHandler, RpcRouter, RpcRouterBuilder};
+pub use router::{
+    rpc_get_handler, rpc_handler, versioned_rpc_handler, IntoRpcMethod, MethodFilter,
+    MethodOptions, RpcMethodHandler, RpcRouter, RpcRouterBuilder, Scope, VersionedRouter,
+    RESERVED_METHOD_PREFIXES,
+};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum RequestId<'a> {
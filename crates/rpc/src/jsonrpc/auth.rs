@@ -0,0 +1,90 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::body::Body;
+use axum::headers::authorization::Bearer;
+use axum::headers::{Authorization, HeaderMapExt};
+use axum::http::Request;
+
+use crate::jsonrpc::Scope;
+
+/// The identity of a caller, along with the scopes it is granted.
+///
+/// Available to methods via [RpcContext::principal](crate::context::RpcContext::principal).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Principal {
+    /// Identifies the caller, e.g. for logging. [None] for anonymous callers.
+    pub name: Option<String>,
+    /// The scopes granted in addition to [Scope::Public].
+    pub scopes: HashSet<Scope>,
+}
+
+impl Principal {
+    /// A caller which was not identified, and may therefore only call public methods.
+    pub fn anonymous() -> Self {
+        Self::default()
+    }
+
+    pub fn is_granted(&self, scope: Scope) -> bool {
+        scope == Scope::Public || self.scopes.contains(&scope)
+    }
+}
+
+/// Rejects a request outright, before any of its calls are dispatched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unauthenticated;
+
+/// Identifies the caller of each request before its calls are dispatched.
+///
+/// Implementations may use any part of the request other than its body, such as bearer tokens,
+/// signature headers, or client certificates placed in the request's extensions by the
+/// connection layer.
+pub trait Authenticator: Send + Sync {
+    /// Returns the caller's principal, which is checked against the scope of each method it
+    /// calls. Requests which fail with [Unauthenticated] are answered with `401 Unauthorized`.
+    fn authenticate(&self, request: &Request<Body>) -> Result<Principal, Unauthenticated>;
+}
+
+/// Treats every caller as [anonymous](Principal::anonymous), which leaves all public methods
+/// open. This is the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoAuth;
+
+impl Authenticator for NoAuth {
+    fn authenticate(&self, _request: &Request<Body>) -> Result<Principal, Unauthenticated> {
+        Ok(Principal::anonymous())
+    }
+}
+
+/// Maps API keys, provided by callers as bearer tokens, to the scopes they are granted.
+///
+/// Callers without a known key are anonymous, rather than rejected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApiKeys(HashMap<String, HashSet<Scope>>);
+
+impl ApiKeys {
+    pub fn with_key(
+        mut self,
+        key: impl Into<String>,
+        scopes: impl IntoIterator<Item = Scope>,
+    ) -> Self {
+        self.0.insert(key.into(), scopes.into_iter().collect());
+        self
+    }
+}
+
+impl Authenticator for ApiKeys {
+    fn authenticate(&self, request: &Request<Body>) -> Result<Principal, Unauthenticated> {
+        let scopes = request
+            .headers()
+            .typed_get::<Authorization<Bearer>>()
+            .and_then(|Authorization(bearer)| self.0.get(bearer.token()));
+
+        Ok(match scopes {
+            Some(scopes) => Principal {
+                name: None,
+                scopes: scopes.clone(),
+            },
+            None => Principal::anonymous(),
+        })
+    }
+}
@@ -9,7 +9,11 @@ pub enum RpcError {
     MethodNotFound,
     InvalidParams,
     InternalError(anyhow::Error),
-    ApplicationError { code: i32, message: String },
+    ApplicationError {
+        code: i32,
+        message: String,
+        data: Option<serde_json::Value>,
+    },
 }
 
 impl PartialEq for RpcError {
@@ -20,17 +24,118 @@ impl PartialEq for RpcError {
                 Self::ApplicationError {
                     code: l_code,
                     message: l_message,
+                    data: l_data,
                 },
                 Self::ApplicationError {
                     code: r_code,
                     message: r_message,
+                    data: r_data,
                 },
-            ) => l_code == r_code && l_message == r_message,
+            ) => l_code == r_code && l_message == r_message && l_data == r_data,
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
 }
 
+/// Internal errors are cloned as their message only, which is all that is returned to
+/// the caller.
+impl Clone for RpcError {
+    fn clone(&self) -> Self {
+        match self {
+            Self::ParseError => Self::ParseError,
+            Self::InvalidRequest => Self::InvalidRequest,
+            Self::MethodNotFound => Self::MethodNotFound,
+            Self::InvalidParams => Self::InvalidParams,
+            Self::InternalError(e) => Self::InternalError(anyhow::anyhow!(e.to_string())),
+            Self::ApplicationError {
+                code,
+                message,
+                data,
+            } => Self::ApplicationError {
+                code: *code,
+                message: message.clone(),
+                data: data.clone(),
+            },
+        }
+    }
+}
+
+/// An error raised by the router itself rather than by a method.
+///
+/// These lie in the range reserved by the JSON-RPC specification for server errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RouterError {
+    pub code: i32,
+    pub name: &'static str,
+    pub message: &'static str,
+}
+
+impl RouterError {
+    /// Calls to methods whose scope is not granted to the caller.
+    pub const UNAUTHORIZED: Self = Self {
+        code: -32001,
+        name: "Unauthorized",
+        message: "Unauthorized",
+    };
+
+    /// Batch requests whose combined responses exceed the configured size.
+    pub const BATCH_RESPONSE_TOO_LARGE: Self = Self {
+        code: -32002,
+        name: "BatchResponseTooLarge",
+        message: "Batch response too large",
+    };
+
+    /// Batch requests which did not complete before the batch's deadline.
+    pub const REQUEST_TIMED_OUT: Self = Self {
+        code: -32003,
+        name: "RequestTimedOut",
+        message: "Request timed out",
+    };
+
+    /// Batch requests whose combined params exceed the configured size.
+    pub const BATCH_PARAMS_TOO_LARGE: Self = Self {
+        code: -32004,
+        name: "BatchParamsTooLarge",
+        message: "Batch params too large",
+    };
+
+    /// Every router error, in the order of their codes.
+    pub const ALL: [Self; 4] = [
+        Self::UNAUTHORIZED,
+        Self::BATCH_RESPONSE_TOO_LARGE,
+        Self::REQUEST_TIMED_OUT,
+        Self::BATCH_PARAMS_TOO_LARGE,
+    ];
+
+    pub fn with_data(self, data: Option<serde_json::Value>) -> RpcError {
+        RpcError::ApplicationError {
+            code: self.code,
+            message: self.message.to_owned(),
+            data,
+        }
+    }
+}
+
+/// Codes of errors which are caused by the node or its dependencies rather than by the request,
+/// and which the caller therefore cannot fix. All other codes are client errors.
+fn server_error_codes() -> [i32; 6] {
+    use crate::error::RpcError as ApplicationError;
+
+    [
+        // Internal error, including gateway errors with a 5xx status.
+        -32603,
+        // Limits set by the node's operator.
+        RouterError::BATCH_RESPONSE_TOO_LARGE.code,
+        RouterError::REQUEST_TIMED_OUT.code,
+        RouterError::BATCH_PARAMS_TOO_LARGE.code,
+        ApplicationError::UnexpectedError {
+            data: String::new(),
+        }
+        .code(),
+        ApplicationError::DatabaseBusy.code(),
+    ]
+}
+
 impl RpcError {
     pub fn code(&self) -> i32 {
         // From the json-rpc specification: https://www.jsonrpc.org/specification#error_object
@@ -44,6 +149,18 @@ impl RpcError {
         }
     }
 
+    /// Buckets the error by its [code](RpcError::code) into either a `client` or `server`
+    /// error, see [server_error_codes].
+    ///
+    /// This is intended for use as a metrics label, and is therefore deliberately
+    /// coarse to keep the label's cardinality low.
+    pub fn class(&self) -> &'static str {
+        match server_error_codes().contains(&self.code()) {
+            true => "server",
+            false => "client",
+        }
+    }
+
     pub fn message(&self) -> Cow<'_, str> {
         match self {
             RpcError::ParseError => "Parse error".into(),
@@ -56,6 +173,13 @@ impl RpcError {
             RpcError::ApplicationError { message, .. } => message.into(),
         }
     }
+
+    pub fn data(&self) -> Option<&serde_json::Value> {
+        match self {
+            RpcError::ApplicationError { data, .. } => data.as_ref(),
+            _ => None,
+        }
+    }
 }
 
 impl Serialize for RpcError {
@@ -65,9 +189,14 @@ impl Serialize for RpcError {
     {
         use serde::ser::SerializeMap;
 
-        let mut obj = serializer.serialize_map(Some(2))?;
+        let data = self.data();
+
+        let mut obj = serializer.serialize_map(Some(2 + data.is_some() as usize))?;
         obj.serialize_entry("code", &self.code())?;
         obj.serialize_entry("message", &self.message())?;
+        if let Some(data) = data {
+            obj.serialize_entry("data", data)?;
+        }
         obj.end()
     }
 }
@@ -78,12 +207,142 @@ where
 {
     fn from(value: E) -> Self {
         match value.into() {
+            // The request forwarded to the gateway was invalid, which the client should fix.
+            crate::error::RpcError::GatewayError(x) if x.is_client_error() => {
+                crate::error::RpcError::GatewayRejected(x).into()
+            }
+            // The gateway failed to process the request, which may succeed if retried.
             crate::error::RpcError::GatewayError(x) => RpcError::InternalError(x.into()),
+            // Another connection held a lock for longer than storage was willing to wait.
+            crate::error::RpcError::Internal(x) if pathfinder_storage::is_busy(&x) => {
+                crate::error::RpcError::DatabaseBusy.into()
+            }
             crate::error::RpcError::Internal(x) => RpcError::InternalError(x),
             other => RpcError::ApplicationError {
                 code: other.code(),
                 message: format!("{other}"),
+                data: other.data(),
             },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    mod class {
+        use super::super::RpcError;
+
+        fn application_error(code: i32) -> RpcError {
+            RpcError::ApplicationError {
+                code,
+                message: String::new(),
+                data: None,
+            }
+        }
+
+        #[rstest::rstest]
+        #[case::parse_error(RpcError::ParseError, "client")]
+        #[case::invalid_params(RpcError::InvalidParams, "client")]
+        #[case::block_not_found(crate::error::RpcError::BlockNotFound.into(), "client")]
+        #[case::unauthorized(application_error(-32001), "client")]
+        #[case::internal(RpcError::InternalError(anyhow::anyhow!("Failure")), "server")]
+        #[case::batch_response_too_large(application_error(-32002), "server")]
+        #[case::batch_timed_out(application_error(-32003), "server")]
+        #[case::batch_params_too_large(application_error(-32004), "server")]
+        #[case::unexpected(crate::error::RpcError::UnexpectedError { data: String::new() }.into(), "server")]
+        #[case::database_busy(crate::error::RpcError::DatabaseBusy.into(), "server")]
+        #[case::gateway_rejected(
+            crate::error::RpcError::GatewayRejected(starknet_gateway_types::error::StarknetError {
+                code: starknet_gateway_types::error::KnownStarknetErrorCode::MalformedRequest.into(),
+                message: String::new(),
+                status: Some(400),
+            })
+            .into(),
+            "client"
+        )]
+        fn by_code(#[case] error: RpcError, #[case] expected: &str) {
+            assert_eq!(error.class(), expected);
+        }
+    }
+
+    mod database_busy {
+        use super::super::RpcError;
+        use crate::context::RpcContext;
+        use crate::v02::method::block_number;
+        use pathfinder_common::macro_prelude::*;
+        use pathfinder_common::{BlockHeader, BlockNumber};
+
+        #[tokio::test]
+        async fn locked_database_is_retryable() {
+            let context = RpcContext::for_tests();
+
+            // The test database is in-memory with a shared cache, so readers of a table which
+            // another connection is writing to fail immediately instead of waiting for it.
+            let mut connection = context.storage.connection().unwrap();
+            let tx = connection.transaction().unwrap();
+            let header = BlockHeader::builder()
+                .with_number(BlockNumber::new_or_panic(3))
+                .finalize_with_hash(block_hash_bytes!(b"uncommitted"));
+            tx.insert_block_header(&header).unwrap();
+
+            let error = block_number(context).await.unwrap_err();
+
+            assert_eq!(
+                RpcError::from(error),
+                RpcError::ApplicationError {
+                    code: 10006,
+                    message: "The database is busy".to_owned(),
+                    data: Some(serde_json::json!({"retryable": true})),
+                }
+            );
+        }
+
+        #[test]
+        fn other_internal_errors_are_unchanged() {
+            let error = crate::error::RpcError::Internal(anyhow::anyhow!("database is locked"));
+
+            assert_eq!(RpcError::from(error).code(), -32603);
+        }
+    }
+
+    mod gateway_error {
+        use super::super::RpcError;
+        use starknet_gateway_types::error::{KnownStarknetErrorCode, StarknetError};
+
+        fn gateway_error(status: Option<u16>) -> crate::error::RpcError {
+            crate::error::RpcError::GatewayError(StarknetError {
+                code: KnownStarknetErrorCode::MalformedRequest.into(),
+                message: "Invalid request".to_owned(),
+                status,
+            })
+        }
+
+        #[rstest::rstest]
+        #[case::bad_request(400)]
+        #[case::too_large(413)]
+        fn client_error_is_application_error(#[case] status: u16) {
+            let error = RpcError::from(gateway_error(Some(status)));
+
+            assert_eq!(
+                error,
+                RpcError::ApplicationError {
+                    code: 10004,
+                    message: "The gateway rejected the request".to_owned(),
+                    data: Some(serde_json::json!({
+                        "code": "StarkErrorCode.MALFORMED_REQUEST",
+                        "message": "Invalid request",
+                    })),
+                }
+            );
+        }
+
+        #[rstest::rstest]
+        #[case::server_error(Some(500))]
+        #[case::unknown(None)]
+        fn server_error_is_internal_error(#[case] status: Option<u16>) {
+            let error = RpcError::from(gateway_error(status));
+
+            assert_eq!(error.code(), -32603);
+        }
+    }
+}
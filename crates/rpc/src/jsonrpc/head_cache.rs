@@ -0,0 +1,82 @@
+//! Keeps the results of methods without params until the chain head changes.
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+/// Serves the results of methods without params, whose results only change along with the
+/// chain head, e.g. `starknet_blockNumber`, without invoking them again.
+///
+/// Results are tagged with the [head version](crate::SyncState::head_version) at which their
+/// invocation started, and are only served for that version. Invocations which race with a new
+/// head are therefore never served once the head has changed.
+pub struct HeadCache {
+    /// Methods whose results are cached.
+    methods: HashSet<String>,
+    results: Mutex<HashMap<&'static str, (u64, Value)>>,
+}
+
+impl HeadCache {
+    pub fn new(methods: HashSet<String>) -> Self {
+        Self {
+            methods,
+            results: Default::default(),
+        }
+    }
+
+    pub fn applies_to(&self, method_name: &str) -> bool {
+        self.methods.contains(method_name)
+    }
+
+    /// The result of `method_name` at the given head version, if it was cached.
+    pub fn get(&self, method_name: &str, head_version: u64) -> Option<Value> {
+        self.results
+            .lock()
+            .unwrap()
+            .get(method_name)
+            .filter(|(version, _)| *version == head_version)
+            .map(|(_, result)| result.clone())
+    }
+
+    /// Caches the result of `method_name`, whose invocation started at the given head version.
+    pub fn insert(&self, method_name: &'static str, head_version: u64, result: Value) {
+        let mut results = self.results.lock().unwrap();
+        // Keeps a result of a newer head, in case this invocation was overtaken.
+        match results.get(method_name) {
+            Some((version, _)) if *version > head_version => {}
+            _ => {
+                results.insert(method_name, (head_version, result));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn cache() -> HeadCache {
+        HeadCache::new(HashSet::from(["starknet_blockNumber".to_owned()]))
+    }
+
+    #[test]
+    fn served_until_head_changes() {
+        let cache = cache();
+        cache.insert("starknet_blockNumber", 1, json!(10));
+
+        assert_eq!(cache.get("starknet_blockNumber", 1), Some(json!(10)));
+        assert_eq!(cache.get("starknet_blockNumber", 2), None);
+    }
+
+    #[test]
+    fn newer_head_is_not_overwritten() {
+        let cache = cache();
+        cache.insert("starknet_blockNumber", 2, json!(11));
+        cache.insert("starknet_blockNumber", 1, json!(10));
+
+        assert_eq!(cache.get("starknet_blockNumber", 2), Some(json!(11)));
+        assert_eq!(cache.get("starknet_blockNumber", 1), None);
+    }
+}
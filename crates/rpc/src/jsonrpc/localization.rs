@@ -0,0 +1,178 @@
+//! Localizes the messages of JSON-RPC errors according to the caller's `Accept-Language`.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::jsonrpc::error::RpcError;
+
+/// Error messages in a single language, keyed by error code.
+///
+/// Errors whose code has no message keep their English message, see [RpcError::message].
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(transparent)]
+pub struct MessageTable(HashMap<i32, String>);
+
+impl MessageTable {
+    pub fn with_message(mut self, code: i32, message: impl Into<String>) -> Self {
+        self.0.insert(code, message.into());
+        self
+    }
+
+    /// Replaces the message of `error` if this table has one for its code. The code and data
+    /// are kept as is.
+    pub fn localize(&self, error: RpcError) -> RpcError {
+        let Some(message) = self.0.get(&error.code()) else {
+            return error;
+        };
+
+        RpcError::ApplicationError {
+            code: error.code(),
+            message: message.clone(),
+            data: error.data().cloned(),
+        }
+    }
+}
+
+/// The translations of error messages, keyed by language.
+///
+/// English is the default, and is used for callers which prefer it or whose languages have no
+/// table. Tables are looked up by the caller's language tags, e.g. `de-CH`, and then by their
+/// primary language, e.g. `de`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(from = "HashMap<String, MessageTable>")]
+pub struct Localization(HashMap<String, Arc<MessageTable>>);
+
+impl From<HashMap<String, MessageTable>> for Localization {
+    fn from(tables: HashMap<String, MessageTable>) -> Self {
+        tables
+            .into_iter()
+            .fold(Self::default(), |localization, (language, table)| {
+                localization.with_language(language, table)
+            })
+    }
+}
+
+impl Localization {
+    /// Adds the table of `language`, which is an
+    /// [IETF language tag](https://www.rfc-editor.org/rfc/rfc5646) such as `de` or `pt-BR`.
+    pub fn with_language(mut self, language: impl AsRef<str>, table: MessageTable) -> Self {
+        self.0
+            .insert(language.as_ref().to_ascii_lowercase(), Arc::new(table));
+        self
+    }
+
+    /// Returns the table of the caller's most preferred language given the value of its
+    /// `Accept-Language` header, or [None] if English should be used.
+    pub fn negotiate(&self, accept_language: &str) -> Option<Arc<MessageTable>> {
+        let mut languages = accept_language
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';').map(str::trim);
+                let language = parts.next().filter(|language| !language.is_empty())?;
+                let quality = parts
+                    .find_map(|param| param.strip_prefix("q="))
+                    .map_or(Some(1.0), |quality| quality.parse::<f32>().ok())?;
+                Some((language.to_ascii_lowercase(), quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect::<Vec<_>>();
+        // Stable, so that languages of equal quality keep their order.
+        languages.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        for (language, _) in languages {
+            let primary = language.split('-').next().unwrap_or_default();
+            if primary == "en" || primary == "*" {
+                return None;
+            }
+
+            if let Some(table) = self.0.get(&language).or_else(|| self.0.get(primary)) {
+                return Some(table.clone());
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn localization() -> Localization {
+        Localization::default()
+            .with_language(
+                "de",
+                MessageTable::default().with_message(-32602, "Ungültige Parameter"),
+            )
+            .with_language(
+                "pt-BR",
+                MessageTable::default().with_message(-32602, "Parâmetros inválidos"),
+            )
+    }
+
+    #[rstest::rstest]
+    #[case::exact("de", Some("Ungültige Parameter"))]
+    #[case::case_insensitive("DE", Some("Ungültige Parameter"))]
+    #[case::region_falls_back_to_primary("de-CH", Some("Ungültige Parameter"))]
+    #[case::region("pt-BR", Some("Parâmetros inválidos"))]
+    #[case::other_region("pt-PT", None)]
+    #[case::first_supported("fr, de;q=0.5", Some("Ungültige Parameter"))]
+    #[case::by_quality("de;q=0.5, pt-BR;q=0.8", Some("Parâmetros inválidos"))]
+    #[case::english_preferred("en-US, de;q=0.5", None)]
+    #[case::wildcard("*, de;q=0.5", None)]
+    #[case::rejected("de;q=0", None)]
+    #[case::unsupported("fr", None)]
+    #[case::empty("", None)]
+    fn negotiate(#[case] accept_language: &str, #[case] expected: Option<&str>) {
+        let message = localization().negotiate(accept_language).map(|table| {
+            table
+                .localize(RpcError::InvalidParams)
+                .message()
+                .into_owned()
+        });
+
+        assert_eq!(message.as_deref(), expected);
+    }
+
+    #[test]
+    fn localize_keeps_code_and_data() {
+        let table = MessageTable::default().with_message(24, "Block nicht gefunden");
+        let error = RpcError::ApplicationError {
+            code: 24,
+            message: "Block not found".to_owned(),
+            data: Some(serde_json::json!({"block": 1})),
+        };
+
+        assert_eq!(
+            table.localize(error),
+            RpcError::ApplicationError {
+                code: 24,
+                message: "Block nicht gefunden".to_owned(),
+                data: Some(serde_json::json!({"block": 1})),
+            }
+        );
+    }
+
+    #[test]
+    fn localize_falls_back_to_english() {
+        let table = MessageTable::default().with_message(24, "Block nicht gefunden");
+
+        assert_eq!(
+            table.localize(RpcError::InvalidParams),
+            RpcError::InvalidParams
+        );
+    }
+
+    #[test]
+    fn deserialize() {
+        let localization: Localization =
+            serde_json::from_str(r#"{"DE": {"-32602": "Ungültige Parameter"}}"#).unwrap();
+
+        assert_eq!(
+            localization,
+            Localization::default().with_language(
+                "de",
+                MessageTable::default().with_message(-32602, "Ungültige Parameter")
+            )
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
+use serde::de::IgnoredAny;
 use serde::Deserialize;
 use serde_json::value::RawValue;
+use serde_json::Value;
 
 use crate::jsonrpc::{RequestId, RpcError};
 
@@ -12,10 +16,222 @@ pub struct RpcRequest<'a> {
     pub id: RequestId<'a>,
 }
 
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
 pub struct RawParams<'a>(#[serde(borrow)] Option<&'a RawValue>);
 
+/// Limits the shape of request params, which guards against resource exhaustion while
+/// deserializing deeply nested or huge params.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamsLimits {
+    /// Maximum nesting depth of arrays and objects. The params themselves are at depth 1.
+    pub max_depth: usize,
+    /// Maximum number of array elements and object members, summed over all levels.
+    pub max_elements: usize,
+}
+
+impl Default for ParamsLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 64,
+            max_elements: 1_000_000,
+        }
+    }
+}
+
+/// How a method treats named params which its input does not define.
+///
+/// See [MethodOptions::strict](crate::jsonrpc::MethodOptions::strict).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownFields {
+    /// Defers to the input's own serde attributes, i.e. unknown fields are ignored unless the
+    /// input denies them.
+    #[default]
+    Tolerate,
+    /// Rejects unknown fields as invalid params.
+    ///
+    /// Only the top-level fields of inputs which deserialize as a struct are checked, so inputs
+    /// with flattened fields, maps or positional params are left to their own serde attributes.
+    Reject,
+}
+
+/// A deserializer which only records the fields of the struct it is asked to deserialize.
+///
+/// Deserialization always fails, as the probe holds no data.
+struct StructFieldsProbe<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de> serde::Deserializer<'de> for StructFieldsProbe<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(fields);
+        Err(serde::de::Error::custom("probe only"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// A block tag which is supplied in place of an omitted block id.
+///
+/// See [RpcRouter::with_default_block_tag](crate::jsonrpc::RpcRouter::with_default_block_tag).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTag {
+    Latest,
+    Pending,
+}
+
+impl BlockTag {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BlockTag::Latest => "latest",
+            BlockTag::Pending => "pending",
+        }
+    }
+}
+
+impl<'a> From<&'a RawValue> for RawParams<'a> {
+    fn from(params: &'a RawValue) -> Self {
+        Self(Some(params))
+    }
+}
+
 impl<'a> RawParams<'a> {
+    /// The params as received, truncated to `max_chars` characters, for logging.
+    pub fn summary(&self, max_chars: usize) -> String {
+        let params = self.0.map_or("", |params| params.get());
+        match params.char_indices().nth(max_chars) {
+            Some((end, _)) => format!("{}...", &params[..end]),
+            None => params.to_owned(),
+        }
+    }
+
+    /// Size of the params in bytes, as received.
+    pub fn size(&self) -> usize {
+        self.0.map_or(0, |params| params.get().len())
+    }
+
+    /// Returns the params with `tag` supplied as the block id, or [None] if the block id is
+    /// present and the params can be used as they are.
+    ///
+    /// The block id is either the named param `block_id`, or the trailing positional param at
+    /// `index`. It is considered omitted if it is absent or `null`. Params which are malformed
+    /// are also returned as [None], and are left for the method to reject.
+    pub fn with_default_block_id(&self, index: usize, tag: BlockTag) -> Option<Box<RawValue>> {
+        let params = match self.0 {
+            Some(params) => serde_json::from_str::<Value>(params.get()).ok()?,
+            None => Value::Array(Vec::new()),
+        };
+
+        let params = match params {
+            Value::Object(mut named) => {
+                if !named.get("block_id").map_or(true, Value::is_null) {
+                    return None;
+                }
+                named.insert("block_id".to_owned(), tag.as_str().into());
+                Value::Object(named)
+            }
+            Value::Array(mut positional) => {
+                // Only the trailing param may be omitted, the params before it must be present.
+                let omitted = matches!(self.optional_positional::<&RawValue>(index), Ok(None));
+                if !omitted || positional.len() < index || positional.len() > index + 1 {
+                    return None;
+                }
+                positional.truncate(index);
+                positional.push(tag.as_str().into());
+                Value::Array(positional)
+            }
+            _ => return None,
+        };
+
+        serde_json::value::to_raw_value(&params).ok()
+    }
+
+    /// Returns true if the params nest deeper or have more elements than `limits` allow.
+    ///
+    /// The raw JSON is scanned without being deserialized, and has already been validated
+    /// while parsing the request.
+    pub fn exceeds(&self, limits: &ParamsLimits) -> bool {
+        let Some(params) = self.0 else {
+            return false;
+        };
+
+        let mut depth = 0usize;
+        let mut elements = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        // Whether the previous token opened an array or object.
+        let mut opened = false;
+
+        for byte in params.get().bytes() {
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            if byte.is_ascii_whitespace() {
+                continue;
+            }
+
+            // The first element of a non-empty array or object.
+            if opened && !matches!(byte, b']' | b'}') {
+                elements += 1;
+            }
+            opened = false;
+
+            match byte {
+                b'"' => in_string = true,
+                b'[' | b'{' => {
+                    depth += 1;
+                    opened = true;
+                }
+                b']' | b'}' => depth = depth.saturating_sub(1),
+                b',' => elements += 1,
+                _ => {}
+            }
+
+            if depth > limits.max_depth || elements > limits.max_elements {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// The params as received, or [None] if they were omitted.
+    pub fn raw(&self) -> Option<&'a RawValue> {
+        self.0
+    }
+
+    /// Returns true if the params are omitted, or either an array or an object as required by
+    /// the JSON-RPC specification. Scalar params such as `5` or `"text"` are not.
+    pub fn is_structured(&self) -> bool {
+        let Some(params) = self.0 else {
+            return true;
+        };
+
+        matches!(
+            params.get().trim_start().as_bytes().first(),
+            Some(b'[' | b'{')
+        )
+    }
+
     /// Returns true if there are no params or the list of params is empty.
     pub fn is_empty(&self) -> bool {
         let Some(params) = self.0 else {
@@ -50,6 +266,104 @@ impl<'a> RawParams<'a> {
 
         serde_json::from_str::<T>(s).map_err(|_| RpcError::InvalidParams)
     }
+
+    /// Like [deserialize](Self::deserialize), but also rejects named params which are not
+    /// fields of `T` if `unknown_fields` is [UnknownFields::Reject].
+    pub fn deserialize_with<T: Deserialize<'a>>(
+        self,
+        unknown_fields: UnknownFields,
+    ) -> Result<T, RpcError> {
+        if unknown_fields == UnknownFields::Reject && self.has_unknown_fields::<T>() {
+            return Err(RpcError::InvalidParams);
+        }
+
+        self.deserialize()
+    }
+
+    fn has_unknown_fields<T: Deserialize<'a>>(&self) -> bool {
+        let Some(params) = self.0 else {
+            return false;
+        };
+
+        let mut fields = None;
+        let _ = T::deserialize(StructFieldsProbe(&mut fields));
+        let Some(fields) = fields else {
+            return false;
+        };
+
+        // Positional params are left to the input's deserialization.
+        let Ok(named) = serde_json::from_str::<HashMap<String, IgnoredAny>>(params.get()) else {
+            return false;
+        };
+
+        named.keys().any(|name| !fields.contains(&name.as_str()))
+    }
+
+    /// Deserializes the positional param at `index`, which is useful for optional trailing params
+    /// e.g. `params.optional_positional::<BlockId>(1)?.unwrap_or(BlockId::Latest)`.
+    ///
+    /// Returns [None] if there are no params, if there are fewer than `index + 1` params or if
+    /// the param is `null`. Named params are rejected with [RpcError::InvalidParams].
+    pub fn optional_positional<T: Deserialize<'a>>(
+        &self,
+        index: usize,
+    ) -> Result<Option<T>, RpcError> {
+        let Some(params) = self.0 else {
+            return Ok(None);
+        };
+
+        let params = serde_json::from_str::<Vec<&'a RawValue>>(params.get())
+            .map_err(|_| RpcError::InvalidParams)?;
+
+        match params.get(index) {
+            Some(param) => {
+                serde_json::from_str::<Option<T>>(param.get()).map_err(|_| RpcError::InvalidParams)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Returns true if the request is an object which repeats one of its top-level keys.
+///
+/// Such requests are otherwise accepted, with the last value of the key silently taking
+/// precedence. Anything which is not an object is left for the regular parsing to reject.
+pub fn has_duplicate_keys(request: &str) -> bool {
+    struct DuplicateKeys(bool);
+
+    impl<'de> Deserialize<'de> for DuplicateKeys {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            struct Visitor;
+
+            impl<'de> serde::de::Visitor<'de> for Visitor {
+                type Value = DuplicateKeys;
+
+                fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    formatter.write_str("a JSON-RPC request object")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: serde::de::MapAccess<'de>,
+                {
+                    let mut keys = std::collections::HashSet::new();
+                    let mut duplicate = false;
+                    while let Some(key) = map.next_key::<Cow<'de, str>>()? {
+                        map.next_value::<serde::de::IgnoredAny>()?;
+                        duplicate |= !keys.insert(key);
+                    }
+                    Ok(DuplicateKeys(duplicate))
+                }
+            }
+
+            deserializer.deserialize_map(Visitor)
+        }
+    }
+
+    serde_json::from_str::<DuplicateKeys>(request).is_ok_and(|keys| keys.0)
 }
 
 impl<'de> Deserialize<'de> for RpcRequest<'de> {
@@ -99,6 +413,10 @@ impl<'de> Deserialize<'de> for RpcRequest<'de> {
             return Err(D::Error::custom("Jsonrpc version must be 2.0"));
         }
 
+        if helper.method.is_empty() {
+            return Err(D::Error::custom("Method name must not be empty"));
+        }
+
         let id = match helper.id {
             Some(Some(IdHelper::Number(x))) => RequestId::Number(x),
             Some(Some(IdHelper::String(x))) => RequestId::String(x),
@@ -193,6 +511,17 @@ mod tests {
         serde_json::from_str::<RpcRequest<'_>>(&json).unwrap_err();
     }
 
+    #[test]
+    fn empty_method() {
+        let json = json!({
+            "jsonrpc": "2.0",
+            "method": "",
+            "id": 456
+        })
+        .to_string();
+        serde_json::from_str::<RpcRequest<'_>>(&json).unwrap_err();
+    }
+
     #[test]
     fn no_params() {
         let json = json!({
@@ -211,6 +540,27 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    mod has_duplicate_keys {
+        use super::*;
+
+        #[rstest::rstest]
+        #[case::id(r#"{"jsonrpc": "2.0", "method": "a", "id": 1, "id": 2}"#)]
+        #[case::method(r#"{"jsonrpc": "2.0", "method": "a", "method": "b", "id": 1}"#)]
+        #[case::escaped(r#"{"jsonrpc": "2.0", "method": "a", "\u006dethod": "b"}"#)]
+        fn duplicated(#[case] request: &str) {
+            assert!(has_duplicate_keys(request));
+        }
+
+        #[rstest::rstest]
+        #[case::unique(r#"{"jsonrpc": "2.0", "method": "a", "params": [], "id": 1}"#)]
+        #[case::nested(r#"{"jsonrpc": "2.0", "method": "a", "params": {"a": 1, "a": 2}}"#)]
+        #[case::not_an_object("[1, 2]")]
+        #[case::malformed(r#"{"id": 1, "id""#)]
+        fn unique(#[case] request: &str) {
+            assert!(!has_duplicate_keys(request));
+        }
+    }
+
     mod raw_params {
         use super::*;
 
@@ -237,5 +587,206 @@ mod tests {
 
             assert!(!uut.is_empty());
         }
+
+        #[rstest::rstest]
+        #[case::array("[1]")]
+        #[case::object(r#"{"a": 1}"#)]
+        #[case::empty_array("[]")]
+        #[case::empty_object("{}")]
+        fn structured(#[case] s: &str) {
+            let raw_value = RawValue::from_string(s.to_owned()).unwrap();
+            let uut = RawParams(Some(&raw_value));
+
+            assert!(uut.is_structured());
+        }
+
+        #[rstest::rstest]
+        #[case::number("5")]
+        #[case::string(r#""text""#)]
+        #[case::boolean("true")]
+        fn not_structured(#[case] s: &str) {
+            let raw_value = RawValue::from_string(s.to_owned()).unwrap();
+            let uut = RawParams(Some(&raw_value));
+
+            assert!(!uut.is_structured());
+        }
+
+        #[test]
+        fn omitted_is_structured() {
+            assert!(RawParams(None).is_structured());
+        }
+
+        mod exceeds {
+            use super::*;
+
+            const LIMITS: ParamsLimits = ParamsLimits {
+                max_depth: 3,
+                max_elements: 5,
+            };
+
+            fn exceeds(s: &str) -> bool {
+                let raw_value = RawValue::from_string(s.to_owned()).unwrap();
+                RawParams(Some(&raw_value)).exceeds(&LIMITS)
+            }
+
+            #[rstest::rstest]
+            #[case::empty_array("[]")]
+            #[case::empty_object("{}")]
+            #[case::max_depth(r#"[{"a": [1]}]"#)]
+            #[case::max_elements(r#"[1, "2", [3, 4]]"#)]
+            #[case::brackets_in_strings(r#"["[[[[", "]]]]", "\"[[[[,,,,"]"#)]
+            fn within(#[case] s: &str) {
+                assert!(!exceeds(s));
+            }
+
+            #[rstest::rstest]
+            #[case::too_deep(r#"[{"a": [[1]]}]"#)]
+            #[case::too_many_elements("[1, 2, 3, 4, 5, 6]")]
+            #[case::too_many_nested_elements(r#"[[1, 2], {"a": 3, "b": [4, 5]}]"#)]
+            fn exceeded(#[case] s: &str) {
+                assert!(exceeds(s));
+            }
+
+            #[test]
+            fn deeply_nested() {
+                let s = format!("{}{}", "[".repeat(100), "]".repeat(100));
+                assert!(exceeds(&s));
+            }
+
+            #[test]
+            fn absent() {
+                assert!(!RawParams(None).exceeds(&LIMITS));
+            }
+        }
+
+        mod with_default_block_id {
+            use super::*;
+
+            fn defaulted(params: Option<&str>) -> Option<String> {
+                let raw_value = params.map(|s| RawValue::from_string(s.to_owned()).unwrap());
+                let uut = RawParams(raw_value.as_deref());
+
+                uut.with_default_block_id(1, BlockTag::Pending)
+                    .map(|params| params.get().to_owned())
+            }
+
+            #[rstest::rstest]
+            #[case::named_omitted(r#"{"a": "0x1"}"#, r#"{"a":"0x1","block_id":"pending"}"#)]
+            #[case::named_null(
+                r#"{"a": "0x1", "block_id": null}"#,
+                r#"{"a":"0x1","block_id":"pending"}"#
+            )]
+            #[case::positional_omitted(r#"["0x1"]"#, r#"["0x1","pending"]"#)]
+            #[case::positional_null(r#"["0x1", null]"#, r#"["0x1","pending"]"#)]
+            fn omitted(#[case] params: &str, #[case] expected: &str) {
+                assert_eq!(defaulted(Some(params)).as_deref(), Some(expected));
+            }
+
+            #[rstest::rstest]
+            #[case::no_params(None)]
+            #[case::named_present(r#"{"a": "0x1", "block_id": "latest"}"#.into())]
+            #[case::positional_present(r#"["0x1", "latest"]"#.into())]
+            #[case::positional_too_few("[]".into())]
+            #[case::positional_too_many(r#"["0x1", "latest", "0x2"]"#.into())]
+            #[case::not_a_collection(r#""0x1""#.into())]
+            fn unchanged(#[case] params: Option<&str>) {
+                assert_eq!(defaulted(params), None);
+            }
+        }
+
+        mod deserialize_with {
+            use super::*;
+
+            /// Ignores unknown fields by default, like most inputs.
+            #[derive(Debug, Deserialize, PartialEq)]
+            struct Input {
+                a: u32,
+                #[serde(default)]
+                b: Option<u32>,
+            }
+
+            fn deserialize<T: serde::de::DeserializeOwned>(
+                s: &str,
+                unknown_fields: UnknownFields,
+            ) -> Result<T, RpcError> {
+                let raw_value = RawValue::from_string(s.to_owned()).unwrap();
+                RawParams(Some(&raw_value)).deserialize_with(unknown_fields)
+            }
+
+            #[rstest::rstest]
+            #[case::named(r#"{"a": 1}"#)]
+            #[case::all_named(r#"{"a": 1, "b": 2}"#)]
+            #[case::positional("[1]")]
+            fn known_fields_are_accepted(
+                #[case] params: &str,
+                #[values(UnknownFields::Tolerate, UnknownFields::Reject)]
+                unknown_fields: UnknownFields,
+            ) {
+                let input = deserialize::<Input>(params, unknown_fields).unwrap();
+                assert_eq!(input.a, 1);
+            }
+
+            #[test]
+            fn tolerated_unknown_field_is_ignored() {
+                let input =
+                    deserialize::<Input>(r#"{"a": 1, "c": 3}"#, UnknownFields::Tolerate).unwrap();
+                assert_eq!(input, Input { a: 1, b: None });
+            }
+
+            #[test]
+            fn rejected_unknown_field_is_invalid() {
+                let error =
+                    deserialize::<Input>(r#"{"a": 1, "c": 3}"#, UnknownFields::Reject).unwrap_err();
+                assert_eq!(error, RpcError::InvalidParams);
+            }
+
+            #[test]
+            fn inputs_which_are_not_structs_are_unchecked() {
+                let input = deserialize::<HashMap<String, u32>>(
+                    r#"{"a": 1, "c": 3}"#,
+                    UnknownFields::Reject,
+                )
+                .unwrap();
+                assert_eq!(input.len(), 2);
+            }
+        }
+
+        mod optional_positional {
+            use super::*;
+            use pathfinder_common::macro_prelude::*;
+            use pathfinder_common::BlockId;
+
+            fn block_id(params: Option<&str>) -> Result<BlockId, RpcError> {
+                let raw_value = params.map(|s| RawValue::from_string(s.to_owned()).unwrap());
+                let uut = RawParams(raw_value.as_deref());
+
+                uut.optional_positional::<BlockId>(1)
+                    .map(|x| x.unwrap_or(BlockId::Latest))
+            }
+
+            #[test]
+            fn present() {
+                let result = block_id(Some(r#"["0x1", {"block_hash": "0xabc"}]"#)).unwrap();
+                assert_eq!(result, BlockId::Hash(block_hash!("0xabc")));
+            }
+
+            #[rstest::rstest]
+            #[case::no_params(None)]
+            #[case::empty("[]".into())]
+            #[case::trailing_param_omitted(r#"["0x1"]"#.into())]
+            #[case::null(r#"["0x1", null]"#.into())]
+            fn absent(#[case] params: Option<&str>) {
+                let result = block_id(params).unwrap();
+                assert_eq!(result, BlockId::Latest);
+            }
+
+            #[rstest::rstest]
+            #[case::invalid_param(r#"["0x1", "invalid"]"#)]
+            #[case::named_params(r#"{"block_id": "pending"}"#)]
+            fn invalid(#[case] params: &str) {
+                let result = block_id(Some(params)).unwrap_err();
+                assert_eq!(result, RpcError::InvalidParams);
+            }
+        }
     }
 }
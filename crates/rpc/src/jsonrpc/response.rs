@@ -2,7 +2,8 @@ use axum::response::IntoResponse;
 use serde::Serialize;
 use serde_json::Value;
 
-use crate::jsonrpc::error::RpcError;
+use crate::jsonrpc::error::{RouterError, RpcError};
+use crate::jsonrpc::localization::MessageTable;
 use crate::jsonrpc::RequestId;
 
 #[derive(Debug, PartialEq)]
@@ -35,6 +36,65 @@ impl<'a> RpcResponse<'a> {
             id,
         }
     }
+
+    /// Answers a request whose body is not valid UTF-8, e.g. because it was encoded as latin-1.
+    ///
+    /// This is a parse error whose `data` distinguishes it from malformed JSON, which is
+    /// answered with a plain [PARSE_ERROR](Self::PARSE_ERROR).
+    pub fn invalid_utf8(error: std::str::Utf8Error) -> RpcResponse<'a> {
+        Self {
+            output: Err(RpcError::ApplicationError {
+                code: RpcError::ParseError.code(),
+                message: RpcError::ParseError.message().into_owned(),
+                data: Some(serde_json::json!({
+                    "reason": "INVALID_UTF8",
+                    "valid_up_to": error.valid_up_to(),
+                })),
+            }),
+            id: RequestId::Null,
+        }
+    }
+
+    /// Replaces the responses of a batch whose combined params exceed `limit` bytes.
+    pub fn batch_params_too_large(limit: usize) -> RpcResponse<'a> {
+        Self {
+            output: Err(RouterError::BATCH_PARAMS_TOO_LARGE
+                .with_data(Some(serde_json::json!({ "limit": limit })))),
+            id: RequestId::Null,
+        }
+    }
+
+    /// Answers a batch request which did not complete before the batch's deadline.
+    pub fn timed_out(id: RequestId<'a>) -> RpcResponse<'a> {
+        Self {
+            output: Err(RouterError::REQUEST_TIMED_OUT.with_data(None)),
+            id,
+        }
+    }
+
+    /// Localizes the message of an error response using `messages`, or keeps it in English if
+    /// [None].
+    pub fn localized(self, messages: Option<&MessageTable>) -> Self {
+        match (self.output, messages) {
+            (Err(error), Some(messages)) => Self {
+                output: Err(messages.localize(error)),
+                id: self.id,
+            },
+            (output, _) => Self {
+                output,
+                id: self.id,
+            },
+        }
+    }
+
+    /// Replaces the responses of a batch whose combined size exceeds `limit` bytes.
+    pub fn batch_response_too_large(limit: usize) -> RpcResponse<'a> {
+        Self {
+            output: Err(RouterError::BATCH_RESPONSE_TOO_LARGE
+                .with_data(Some(serde_json::json!({ "limit": limit })))),
+            id: RequestId::Null,
+        }
+    }
 }
 
 pub type RpcResult = Result<Value, RpcError>;
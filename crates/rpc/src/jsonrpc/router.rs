@@ -1,56 +1,367 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use axum::async_trait;
-use axum::extract::State;
-use axum::headers::ContentType;
+use axum::extract::{FromRequest, Query, RawQuery, State};
+use axum::headers::{ContentLength, ContentType};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::TypedHeader;
-use futures::{Future, FutureExt};
+use futures::{Future, FutureExt, StreamExt};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::value::RawValue;
 use serde_json::Value;
+use tracing::Instrument;
 
 use crate::context::RpcContext;
-use crate::jsonrpc::error::RpcError;
-use crate::jsonrpc::request::{RawParams, RpcRequest};
+use crate::jsonrpc::auth::{ApiKeys, Authenticator, NoAuth};
+use crate::jsonrpc::error::{RouterError, RpcError};
+use crate::jsonrpc::head_cache::HeadCache;
+use crate::jsonrpc::localization::{Localization, MessageTable};
+use crate::jsonrpc::request::{
+    has_duplicate_keys, BlockTag, ParamsLimits, RawParams, RpcRequest, UnknownFields,
+};
 use crate::jsonrpc::response::{RpcResponse, RpcResult};
+use crate::jsonrpc::single_flight::SingleFlight;
+use crate::jsonrpc::RequestId;
+use crate::middleware::read_timeout::ReadTimeoutBody;
+
+/// The id of responses to notifications, when these are answered for debugging purposes.
+///
+/// See [RpcRouter::with_debug_notifications].
+const DEBUG_NOTIFICATION_ID: &str = "debug-notification";
+
+/// Response header reporting the version of the router which handled the request.
+///
+/// See [RpcRouter::with_version_header].
+const VERSION_HEADER: &str = "x-pathfinder-version";
+
+/// Request header selecting the version of the API, e.g. `v0.5`, on paths serving several.
+///
+/// See [VersionedRouter].
+const RPC_VERSION_HEADER: &str = "x-starknet-rpc-version";
+
+/// Number of characters of the params logged for slow requests.
+///
+/// See [RpcRouter::with_slow_request_threshold].
+const SLOW_REQUEST_PARAMS_CHARS: usize = 256;
+
+/// Request and response header carrying the id which correlates the logs of a request, and the
+/// gateway requests made on its behalf. Generated if not provided by the caller.
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest request id accepted from callers, longer ids are replaced by a generated one.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Prefixes of method names which are never dispatched by a router, even if registered. Calls
+/// of these are answered with method not found.
+///
+/// - `rpc.` is reserved by the JSON-RPC specification for rpc-internal methods and extensions.
+/// - `pathfinder_subscribe` and `pathfinder_unsubscribe` manage subscriptions, and are only
+///   handled by the websocket transport.
+pub const RESERVED_METHOD_PREFIXES: &[&str] =
+    &["rpc.", "pathfinder_subscribe", "pathfinder_unsubscribe"];
 
 #[derive(Clone)]
 pub struct RpcRouter {
     context: RpcContext,
-    methods: &'static HashMap<&'static str, Box<dyn RpcMethod>>,
+    methods: &'static HashMap<&'static str, RegisteredMethod>,
     version: &'static str,
+    method_filter: Option<Arc<MethodFilter>>,
+    call_limiter: Option<Arc<tokio::sync::Semaphore>>,
+    max_body_size: Option<u64>,
+    body_read_timeout: Option<Duration>,
+    authenticator: Arc<dyn Authenticator>,
+    max_batch_response_size: Option<usize>,
+    max_batch_params_size: Option<usize>,
+    debug_notifications: bool,
+    params_limits: Option<ParamsLimits>,
+    default_block_tag: Option<BlockTag>,
+    strict_requests: bool,
+    version_header: bool,
+    server_timing: bool,
+    omit_null_fields: bool,
+    slow_request_threshold: Option<Duration>,
+    large_response_threshold: Option<usize>,
+    batch_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+    single_flight: Option<Arc<SingleFlight>>,
+    head_cache: Option<Arc<HeadCache>>,
+    localization: Option<Arc<Localization>>,
+    method_in_errors: bool,
+}
+
+/// The scope a caller requires to invoke a method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Scope {
+    /// Available to all callers.
+    #[default]
+    Public,
+    /// Only available to callers whose API key grants this scope.
+    Write,
+}
+
+/// Restricts the set of methods a router exposes.
+///
+/// Methods which are filtered out are treated as if they were never registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MethodFilter {
+    /// Only the listed methods are exposed.
+    Allow(HashSet<String>),
+    /// All methods except the listed ones are exposed.
+    Deny(HashSet<String>),
+}
+
+impl MethodFilter {
+    fn is_allowed(&self, method: &str) -> bool {
+        match self {
+            MethodFilter::Allow(methods) => methods.contains(method),
+            MethodFilter::Deny(methods) => !methods.contains(method),
+        }
+    }
 }
 
 pub struct RpcRouterBuilder {
-    methods: HashMap<&'static str, Box<dyn RpcMethod>>,
+    methods: HashMap<&'static str, RegisteredMethod>,
     version: &'static str,
+    method_schema: Option<&'static str>,
+}
+
+/// Generates the JSON Schema of a method's params.
+type SchemaGenerator = fn() -> schemars::schema::RootSchema;
+
+/// The router specific configuration of a method, see [RpcRouterBuilder::register_with].
+///
+/// Options combine, e.g. `MethodOptions::default().strict().scope(Scope::Write)` registers a
+/// strict method which is only available to callers granted the write scope.
+#[derive(Debug, Clone, Copy)]
+pub struct MethodOptions {
+    notification: bool,
+    scope: Scope,
+    unknown_fields: UnknownFields,
+    block_id_index: Option<usize>,
+    schema: Option<SchemaGenerator>,
+    access_log: bool,
+}
+
+impl Default for MethodOptions {
+    fn default() -> Self {
+        Self {
+            notification: false,
+            scope: Scope::Public,
+            unknown_fields: UnknownFields::Tolerate,
+            block_id_index: None,
+            schema: None,
+            access_log: true,
+        }
+    }
+}
+
+impl MethodOptions {
+    /// Calls are not recorded in the access log.
+    ///
+    /// Intended for cheap, high-volume methods such as health polls, which would otherwise
+    /// flood the log. Methods which modify state should always be logged.
+    pub fn unlogged(self) -> Self {
+        Self {
+            access_log: false,
+            ..self
+        }
+    }
+
+    /// The method may only be called by callers granted `scope`.
+    ///
+    /// Callers are identified by the router's authenticator, see
+    /// [RpcRouter::with_authenticator].
+    pub fn scope(self, scope: Scope) -> Self {
+        Self { scope, ..self }
+    }
+
+    /// Named params which the method's input does not define are rejected as invalid params,
+    /// see [UnknownFields::Reject].
+    ///
+    /// Intended for methods which modify state, where an unknown field most likely indicates a
+    /// client bug. Other methods defer to their input's serde attributes.
+    pub fn strict(self) -> Self {
+        Self {
+            unknown_fields: UnknownFields::Reject,
+            ..self
+        }
+    }
+
+    /// The method's trailing param is a block id, which callers may omit if the router is
+    /// configured with a default block tag, see [RpcRouter::with_default_block_tag].
+    ///
+    /// `block_id_index` is the position of the block id within the positional params.
+    pub fn default_block_id(self, block_id_index: usize) -> Self {
+        Self {
+            block_id_index: Some(block_id_index),
+            ..self
+        }
+    }
+
+    /// The method is intended to be called as a notification.
+    ///
+    /// Unlike other methods, these are still executed when called as a notification i.e.
+    /// without an `id`. In this case no response is sent and any failure is logged instead.
+    /// The method can still be called as a normal request, in which case it behaves like any
+    /// other method.
+    pub fn notification(self) -> Self {
+        Self {
+            notification: true,
+            ..self
+        }
+    }
+
+    /// The method's params are described by the JSON Schema of `Input`, which callers can
+    /// retrieve using the method registered with
+    /// [register_method_schema](RpcRouterBuilder::register_method_schema).
+    pub fn schema<Input: schemars::JsonSchema>(self) -> Self {
+        Self {
+            schema: Some(|| schemars::schema_for!(Input)),
+            ..self
+        }
+    }
+}
+
+/// A method along with its router specific configuration.
+struct RegisteredMethod {
+    method: Box<dyn RpcMethod>,
+    /// Whether this method is also executed when called as a notification.
+    notification: bool,
+    scope: Scope,
+    /// Position of the method's trailing block id param, which may be supplied by the
+    /// router's default block tag.
+    block_id_index: Option<usize>,
+    /// Only available for methods registered using [MethodOptions::schema].
+    schema: Option<SchemaGenerator>,
+    /// Whether calls are recorded in the access log, see [MethodOptions::unlogged].
+    access_log: bool,
+}
+
+impl RegisteredMethod {
+    fn new(method: Box<dyn RpcMethod>, options: MethodOptions) -> Self {
+        Self {
+            method,
+            notification: options.notification,
+            scope: options.scope,
+            block_id_index: options.block_id_index,
+            schema: options.schema,
+            access_log: options.access_log,
+        }
+    }
 }
 
 impl RpcRouterBuilder {
+    /// Registers a method with the default [MethodOptions].
     pub fn register<I, O, S, M: IntoRpcMethod<'static, I, O, S>>(
-        mut self,
+        self,
+        method_name: &'static str,
+        method: M,
+    ) -> Self {
+        self.register_with(method_name, method, MethodOptions::default())
+    }
+
+    /// Registers a method with the given `options`.
+    pub fn register_with<I, O, S, M: IntoRpcMethod<'static, I, O, S>>(
+        self,
         method_name: &'static str,
         method: M,
+        options: MethodOptions,
     ) -> Self {
-        self.methods
-            .insert(method_name, IntoRpcMethod::into_method(method));
+        let method = IntoRpcMethod::into_method(method, options.unknown_fields);
+        self.insert(method_name, RegisteredMethod::new(method, options))
+    }
+
+    /// Registers a method which returns the JSON Schema of the params of any of this router's
+    /// methods, given its name.
+    ///
+    /// The result is `null` for methods which were not registered with a schema, see
+    /// [MethodOptions::schema]. Unknown methods are rejected as
+    /// invalid params.
+    pub fn register_method_schema(self, method_name: &'static str) -> Self {
+        Self {
+            method_schema: Some(method_name),
+            ..self
+        }
+    }
+
+    /// Applies a set of registrations shared between versions, such as
+    /// [crate::v03::register_shared_routes].
+    ///
+    /// Overriding a shared method panics like any other repeated registration, so methods which
+    /// change in a version must be left out of the shared set.
+    pub fn register_all(self, routes: impl FnOnce(Self) -> Self) -> Self {
+        routes(self)
+    }
+
+    /// Panics if a method with the same name is already registered, as this indicates
+    /// colliding route tables.
+    fn insert(mut self, method_name: &'static str, method: RegisteredMethod) -> Self {
+        if self.methods.insert(method_name, method).is_some() {
+            panic!("Method {method_name} is registered more than once");
+        }
         self
     }
 
     pub fn build(self, context: RpcContext) -> RpcRouter {
+        // Registered last, so that it knows about all other methods.
+        let this = match self.method_schema {
+            Some(method_name) => {
+                let schema: SchemaGenerator = || schemars::schema_for!(MethodSchemaInput);
+                let mut schemas = self
+                    .methods
+                    .iter()
+                    .map(|(&name, method)| (name, method.schema))
+                    .collect::<HashMap<_, _>>();
+                schemas.insert(method_name, Some(schema));
+
+                let options = MethodOptions {
+                    schema: Some(schema),
+                    ..Default::default()
+                };
+                self.insert(
+                    method_name,
+                    RegisteredMethod::new(Box::new(MethodSchema(schemas)), options),
+                )
+            }
+            None => self,
+        };
+
         // Intentionally leak the hashmap to give it a static lifetime.
         //
         // Since the router is expected to be long lived, this shouldn't be an issue.
-        let methods = Box::new(self.methods);
+        let methods = Box::new(this.methods);
         let methods = Box::leak(methods);
 
         RpcRouter {
             context,
             methods,
-            version: self.version,
+            version: this.version,
+            method_filter: None,
+            call_limiter: None,
+            max_body_size: None,
+            body_read_timeout: None,
+            authenticator: Arc::new(NoAuth),
+            max_batch_response_size: None,
+            max_batch_params_size: None,
+            debug_notifications: false,
+            params_limits: None,
+            default_block_tag: None,
+            strict_requests: false,
+            version_header: false,
+            server_timing: false,
+            omit_null_fields: false,
+            slow_request_threshold: None,
+            large_response_threshold: None,
+            batch_timeout: None,
+            request_timeout: None,
+            single_flight: None,
+            head_cache: None,
+            localization: None,
+            method_in_errors: false,
         }
     }
 
@@ -58,6 +369,7 @@ impl RpcRouterBuilder {
         RpcRouterBuilder {
             methods: Default::default(),
             version,
+            method_schema: None,
         }
     }
 }
@@ -67,602 +379,4258 @@ impl RpcRouter {
         RpcRouterBuilder::new(version)
     }
 
-    /// Parses and executes a request. Returns [None] if its a notification.
-    async fn run_request<'a>(&self, request: &'a str) -> Option<RpcResponse<'a>> {
-        let Ok(request) = serde_json::from_str::<RpcRequest<'_>>(request) else {
-            return Some(RpcResponse::INVALID_REQUEST);
-        };
-
-        // Ignore notification requests.
-        if request.id.is_notification() {
-            return None;
+    /// Restricts the methods exposed by this router using the given filter.
+    pub fn with_method_filter(self, filter: MethodFilter) -> Self {
+        Self {
+            method_filter: Some(Arc::new(filter)),
+            ..self
         }
+    }
 
-        // Also grab the method_name as it is a static str, which is required by the metrics.
-        let Some((&method_name, method)) = self.methods.get_key_value(request.method.as_ref())
-        else {
-            return Some(RpcResponse::method_not_found(request.id));
-        };
-
-        metrics::increment_counter!("rpc_method_calls_total", "method" => method_name, "version" => self.version);
-
-        let method = method.invoke(self.context.clone(), request.params);
-        let result = std::panic::AssertUnwindSafe(method).catch_unwind().await;
+    /// Limits the number of method calls executing concurrently to the permits of `limiter`.
+    ///
+    /// Permits are acquired per call, and not per HTTP request, so that the elements of a
+    /// batch queue alongside single requests instead of occupying a permit for the entire
+    /// batch. Since the semaphore is fair, a single request waits for at most the batch
+    /// elements already queued ahead of it and not for the remainder of the batch.
+    ///
+    /// The same semaphore may be shared between routers to apply a server wide limit.
+    pub fn with_call_limiter(self, limiter: Arc<tokio::sync::Semaphore>) -> Self {
+        Self {
+            call_limiter: Some(limiter),
+            ..self
+        }
+    }
 
-        let output = match result {
-            Ok(output) => output,
-            Err(_e) => {
-                tracing::warn!(method=%request.method, "RPC method panic'd");
-                Err(RpcError::InternalError(anyhow::anyhow!("Internal error")))
-            }
-        };
+    /// Rejects requests whose `Content-Length` exceeds the given size before reading the body.
+    ///
+    /// Requests without a `Content-Length`, such as chunked requests, are not affected. These
+    /// must instead be guarded while streaming the body, e.g. using [axum::extract::DefaultBodyLimit].
+    pub fn with_max_body_size(self, max_body_size: u64) -> Self {
+        Self {
+            max_body_size: Some(max_body_size),
+            ..self
+        }
+    }
 
-        if output.is_err() {
-            metrics::increment_counter!("rpc_method_calls_failed_total", "method" => method_name, "version" => self.version);
+    /// Identifies the caller of each request using `authenticator`, which determines the
+    /// scoped methods it may call. Defaults to [NoAuth].
+    ///
+    /// Without an authenticator granting scopes, methods registered using
+    /// a non-public [scope](MethodOptions::scope) cannot be called at all.
+    pub fn with_authenticator(self, authenticator: Arc<dyn Authenticator>) -> Self {
+        Self {
+            authenticator,
+            ..self
         }
+    }
 
-        Some(RpcResponse {
-            output,
-            id: request.id,
-        })
+    /// Sets the API keys which grant callers access to scoped methods, see [ApiKeys].
+    pub fn with_api_keys(self, api_keys: Arc<ApiKeys>) -> Self {
+        self.with_authenticator(api_keys)
     }
-}
 
-#[axum::debug_handler]
-pub async fn rpc_handler(
-    State(state): State<RpcRouter>,
-    TypedHeader(content_type): TypedHeader<ContentType>,
-    body: axum::body::Bytes,
-) -> impl axum::response::IntoResponse {
-    // Only json content allowed.
-    if content_type != ContentType::json() {
-        return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+    /// Aborts requests whose body makes no progress for longer than `timeout`, e.g. because the
+    /// client stalled part way through sending it.
+    ///
+    /// Slow bodies are not cut off as long as they keep arriving.
+    pub fn with_body_read_timeout(self, timeout: Duration) -> Self {
+        Self {
+            body_read_timeout: Some(timeout),
+            ..self
+        }
     }
 
-    // Unfortunately due to this https://github.com/serde-rs/json/issues/497
-    // we cannot use an enum with borrowed raw values inside to do a single deserialization
-    // for us. Instead we have to distinguish manually between a single request and a batch
-    // request which we do by checking the first byte.
-    if body.as_ref().first() != Some(&b'[') {
-        let Ok(request) = serde_json::from_slice::<&RawValue>(&body) else {
-            return RpcResponse::PARSE_ERROR.into_response();
-        };
+    /// Limits the combined size in bytes of the serialized responses of a batch request.
+    ///
+    /// Batches exceeding the limit are answered with a single error instead.
+    pub fn with_max_batch_response_size(self, max_batch_response_size: usize) -> Self {
+        Self {
+            max_batch_response_size: Some(max_batch_response_size),
+            ..self
+        }
+    }
 
-        match state.run_request(request.get()).await {
-            Some(response) => response.into_response(),
-            None => ().into_response(),
+    /// Limits the combined size in bytes of the params of a batch request.
+    ///
+    /// Batches exceeding the limit are answered with a single error before any of their
+    /// requests are executed.
+    pub fn with_max_batch_params_size(self, max_batch_params_size: usize) -> Self {
+        Self {
+            max_batch_params_size: Some(max_batch_params_size),
+            ..self
         }
-    } else {
-        let Ok(requests) = serde_json::from_slice::<Vec<&RawValue>>(&body) else {
-            return RpcResponse::PARSE_ERROR.into_response();
-        };
+    }
 
-        if requests.is_empty() {
-            return RpcResponse::INVALID_REQUEST.into_response();
+    /// Rejects calls whose params exceed the given limits with an invalid params error, before
+    /// the params are deserialized.
+    pub fn with_params_limits(self, params_limits: ParamsLimits) -> Self {
+        Self {
+            params_limits: Some(params_limits),
+            ..self
         }
+    }
 
-        let mut responses = Vec::new();
+    /// Supplies `tag` as the block id of calls which omit it, for methods registered with
+    /// [MethodOptions::default_block_id].
+    ///
+    /// By default the router is strict, and such calls are rejected with an invalid params
+    /// error as the specification requires an explicit block id.
+    pub fn with_default_block_tag(self, tag: BlockTag) -> Self {
+        Self {
+            default_block_tag: Some(tag),
+            ..self
+        }
+    }
 
-        for request in requests {
-            // Notifications return none and are skipped.
-            if let Some(response) = state.run_request(request.get()).await {
-                responses.push(response);
-            }
+    /// Rejects requests which repeat a top-level key, such as `method` or `id`, with an invalid
+    /// request error.
+    ///
+    /// By default the last value of a repeated key is used, which can hide client bugs.
+    pub fn with_strict_requests(self) -> Self {
+        Self {
+            strict_requests: true,
+            ..self
         }
+    }
 
-        // All requests were notifications.
-        if responses.is_empty() {
-            return ().into_response();
+    /// Reports the version of this router in the `x-pathfinder-version` header of its
+    /// responses, which lets clients confirm which route handled their request.
+    ///
+    /// The JSON-RPC responses themselves are unaffected.
+    pub fn with_version_header(self) -> Self {
+        Self {
+            version_header: true,
+            ..self
         }
+    }
 
-        serde_json::to_string(&responses).unwrap().into_response()
+    /// Reports the time spent in each phase of handling a request in the `Server-Timing`
+    /// header of its response, as specified by the W3C, which browser devtools display.
+    ///
+    /// The phases are `read`, `parse`, `dispatch` and `serialize`. Streamed batch responses
+    /// only report the phases completed before their headers are sent, and GET requests only
+    /// report `dispatch`. The JSON-RPC responses themselves are unaffected.
+    pub fn with_server_timing(self) -> Self {
+        Self {
+            server_timing: true,
+            ..self
+        }
     }
-}
 
-#[axum::async_trait]
-pub trait RpcMethod: Send + Sync {
-    async fn invoke<'a>(&self, state: RpcContext, input: RawParams<'a>) -> RpcResult;
-}
+    /// Localizes the messages of error responses according to the `Accept-Language` header of
+    /// each request. The error codes and data are unaffected, and messages without a
+    /// translation remain in English.
+    pub fn with_localization(self, localization: Localization) -> Self {
+        Self {
+            localization: Some(Arc::new(localization)),
+            ..self
+        }
+    }
 
-/// Utility trait which automates the serde of an RPC methods input and output.
-///
-/// This trait is sealed to prevent attempts at implementing it manually. This will
-/// likely clash with the existing blanket implementations with very unhelpful error
-/// messages.
-///
-/// This trait is automatically implemented for the following methods:
-/// ```
-/// async fn input_and_context(ctx: RpcContext, input: impl Deserialize) -> Result<impl Serialize, Into<RpcError>>;
-/// async fn input_only(input: impl Deserialize) -> Result<impl Serialize, Into<RpcError>>;
-/// async fn context_only(ctx: RpcContext) -> Result<impl Serialize, Into<RpcError>>;
-/// ```
-///
-/// The generics allow us to achieve a form of variadic specilization and can be ignored by callers.
-/// See [sealed::Sealed] to add more method signatures or more information on how this works.
-pub trait IntoRpcMethod<'a, I, O, S>: sealed::Sealed<I, O, S> {
-    fn into_method(self) -> Box<dyn RpcMethod>;
-}
+    /// The messages of the language negotiated by the `Accept-Language` header in `headers`, or
+    /// [None] for English.
+    fn messages(&self, headers: &axum::http::HeaderMap) -> Option<Arc<MessageTable>> {
+        let accept_language = headers
+            .get(axum::http::header::ACCEPT_LANGUAGE)?
+            .to_str()
+            .ok()?;
+        self.localization.as_ref()?.negotiate(accept_language)
+    }
 
-impl<'a, T, I, O, S> IntoRpcMethod<'a, I, O, S> for T
-where
-    T: sealed::Sealed<I, O, S>,
-{
-    fn into_method(self) -> Box<dyn RpcMethod> {
-        sealed::Sealed::<I, O, S>::into_method(self)
+    /// Adds the name of the method to the `data` of its errors, e.g. `{"method": "starknet_call"}`,
+    /// which helps to tell apart the errors of a batch while debugging.
+    ///
+    /// Only errors of registered methods are named, and errors whose `data` is not an object
+    /// are left as is. Disabled by default, as this is not part of the specification.
+    pub fn with_method_in_errors(self) -> Self {
+        Self {
+            method_in_errors: true,
+            ..self
+        }
     }
-}
 
-mod sealed {
-    use std::marker::PhantomData;
+    /// Omits the fields of result objects which are `null`, instead of serializing them.
+    ///
+    /// Whether optional fields are omitted or `null` otherwise depends on the method and API
+    /// version. This applies to nested objects as well, while `null` array elements and
+    /// `null` results are retained.
+    pub fn with_omitted_null_fields(self) -> Self {
+        Self {
+            omit_null_fields: true,
+            ..self
+        }
+    }
 
-    use crate::jsonrpc::error::RpcError;
+    /// Logs a warning for each method call whose result serializes to more than `threshold`
+    /// bytes, and counts these in the `rpc_large_responses_total` metric.
+    ///
+    /// This only reports large responses, which are still sent in full. Batches are limited
+    /// as a whole by [with_max_batch_response_size](Self::with_max_batch_response_size).
+    pub fn with_large_response_threshold(self, threshold: usize) -> Self {
+        Self {
+            large_response_threshold: Some(threshold),
+            ..self
+        }
+    }
 
-    use super::*;
+    /// Logs a warning for each method call which takes longer than `threshold` to execute,
+    /// including its params, which helps identify pathological requests.
+    ///
+    /// The time spent waiting for the [call limiter](Self::with_call_limiter) is excluded.
+    pub fn with_slow_request_threshold(self, threshold: Duration) -> Self {
+        Self {
+            slow_request_threshold: Some(threshold),
+            ..self
+        }
+    }
 
-    /// Sealed implementation of [RpcMethod].
+    /// Limits the time spent executing a batch. Requests which have not completed once the
+    /// timeout elapses are answered with a timeout error instead, while the responses of the
+    /// completed requests are still returned.
     ///
-    /// The generics allow for a form of specialization over a methods Input, Output and State
-    /// by treating each as a tuple. Varying the tuple length allows us to target a specific method
-    /// signature. This same could be achieved with a single generic but it becomes less clear as
-    /// each permuation would require a different tuple length.
+    /// This should be shorter than the server's overall request timeout, which fails the
+    /// entire batch.
+    pub fn with_batch_timeout(self, timeout: Duration) -> Self {
+        Self {
+            batch_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Aborts the gateway requests made on behalf of an RPC request once `timeout` has elapsed
+    /// since the request was received, instead of leaving them running after the server has
+    /// given up on the request.
     ///
-    /// By convention, the lack of a type is equivalent to the unit tuple (). So if we want to target functions
-    /// with no input params, no input state and an output:
-    /// ```
-    /// Sealed<I = (), S = (), O = ((), Ouput)>
-    /// ```
-    pub trait Sealed<I, O, S> {
-        fn into_method(self) -> Box<dyn RpcMethod>;
+    /// This should match the server's overall request timeout.
+    pub fn with_request_timeout(self, timeout: Duration) -> Self {
+        Self {
+            request_timeout: Some(timeout),
+            ..self
+        }
     }
 
-    /// ```
-    /// async fn example(RpcContext, impl Deserialize) -> Result<Output, Into<RpcError>>
-    /// ```
-    impl<'a, F, Input, Output, Error, Fut> Sealed<((), Input), ((), Output), ((), RpcContext)> for F
-    where
-        F: Fn(RpcContext, Input) -> Fut + Sync + Send + 'static,
-        Input: DeserializeOwned + Send + Sync + 'static,
-        Output: Serialize + Send + Sync + 'static,
-        Error: Into<RpcError> + Send + Sync + 'static,
-        Fut: Future<Output = Result<Output, Error>> + Send,
-    {
-        fn into_method(self) -> Box<dyn RpcMethod> {
-            struct Helper<F, Input, Output, Error> {
-                f: F,
-                _marker: PhantomData<(Input, Output, Error)>,
-            }
+    /// Lets concurrent calls of the given methods with identical params share a single
+    /// invocation, and its result. This avoids duplicate work when many clients request the
+    /// same data at once, e.g. the latest block.
+    ///
+    /// Results are not kept once the invocation completes. As the invocation is shared, it does
+    /// not see the principal, deadline or request id of any of its callers.
+    pub fn with_single_flight(self, methods: HashSet<String>) -> Self {
+        Self {
+            single_flight: Some(Arc::new(SingleFlight::new(methods, self.context.clone()))),
+            ..self
+        }
+    }
 
-            #[axum::async_trait]
-            impl<F, Input, Output, Error, Fut> RpcMethod for Helper<F, Input, Output, Error>
-            where
-                F: Fn(RpcContext, Input) -> Fut + Sync + Send,
-                Input: DeserializeOwned + Send + Sync,
-                Output: Serialize + Send + Sync,
-                Error: Into<RpcError> + Send + Sync,
-                Fut: Future<Output = Result<Output, Error>> + Send,
-            {
-                async fn invoke<'a>(&self, state: RpcContext, input: RawParams<'a>) -> RpcResult {
-                    let input = input.deserialize()?;
-                    let output = (self.f)(state, input).await.map_err(Into::into)?;
-                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
-                }
-            }
+    /// Serves the results of calls of the given methods without params from a cache, until
+    /// the chain head changes, see [SyncState::head_version](crate::SyncState::head_version).
+    ///
+    /// Intended for frequently called methods whose results only depend on the chain head,
+    /// e.g. `starknet_blockNumber` and `starknet_chainId`, which are then neither invoked nor
+    /// serialized again. Errors are not cached.
+    pub fn with_head_cache(self, methods: HashSet<String>) -> Self {
+        Self {
+            head_cache: Some(Arc::new(HeadCache::new(methods))),
+            ..self
+        }
+    }
 
-            Box::new(Helper {
-                f: self,
-                _marker: Default::default(),
-            })
+    /// Executes notifications like regular requests and responds to them using the synthetic
+    /// id `"debug-notification"`.
+    ///
+    /// __This violates the JSON-RPC specification__, which forbids responding to notifications.
+    /// It is intended only for debugging notification-style methods, and must not be enabled
+    /// on routers serving regular clients.
+    pub fn with_debug_notifications(self) -> Self {
+        Self {
+            debug_notifications: true,
+            ..self
         }
     }
 
-    /// ```
-    /// async fn example(impl Deserialize) -> Result<Output, Into<RpcError>>
-    /// ```
-    #[async_trait]
-    impl<'a, F, Input, Output, Error, Fut> Sealed<((), Input), ((), Output), ()> for F
-    where
-        F: Fn(Input) -> Fut + Sync + Send + 'static,
-        Input: DeserializeOwned + Sync + Send + 'static,
-        Output: Serialize + Sync + Send + 'static,
-        Error: Into<RpcError> + Sync + Send + 'static,
-        Fut: Future<Output = Result<Output, Error>> + Send,
-    {
-        fn into_method(self) -> Box<dyn RpcMethod> {
-            struct Helper<F, Input, Output, Error> {
-                f: F,
-                _marker: PhantomData<(Input, Output, Error)>,
-            }
+    /// The names of all registered methods, including filtered ones.
+    #[cfg(test)]
+    pub(crate) fn method_names(&self) -> impl Iterator<Item = &'static str> {
+        self.methods.keys().copied()
+    }
 
-            #[axum::async_trait]
-            impl<F, Input, Output, Error, Fut> RpcMethod for Helper<F, Input, Output, Error>
-            where
-                F: Fn(Input) -> Fut + Sync + Send,
-                Input: DeserializeOwned + Send + Sync,
-                Output: Serialize + Send + Sync,
-                Error: Into<RpcError> + Send + Sync,
-                Fut: Future<Output = Result<Output, Error>> + Send,
-            {
-                async fn invoke<'a>(&self, _state: RpcContext, input: RawParams<'a>) -> RpcResult {
-                    let input = input.deserialize()?;
-                    let output = (self.f)(input).await.map_err(Into::into)?;
-                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
-                }
+    fn is_allowed(&self, method: &str) -> bool {
+        let reserved = RESERVED_METHOD_PREFIXES
+            .iter()
+            .any(|prefix| method.starts_with(prefix));
+
+        !reserved
+            && self
+                .method_filter
+                .as_ref()
+                .map_or(true, |filter| filter.is_allowed(method))
+    }
+
+    /// The deadline of a batch which starts executing now, see [RpcRouter::with_batch_timeout].
+    fn batch_deadline(&self) -> Option<tokio::time::Instant> {
+        self.batch_timeout
+            .map(|timeout| tokio::time::Instant::now() + timeout)
+    }
+
+    /// Executes a request of a batch like [run_request](Self::run_request), unless the batch's
+    /// `deadline` elapses first. The request is then answered with a timeout error instead.
+    async fn run_batch_request<'a>(
+        &self,
+        request: &'a str,
+        deadline: Option<tokio::time::Instant>,
+    ) -> Option<RpcResponse<'a>> {
+        let Some(deadline) = deadline else {
+            return self.run_request(request).await;
+        };
+
+        // Requests are not started at all once the deadline has elapsed.
+        if tokio::time::Instant::now() < deadline {
+            let response = tokio::time::timeout_at(deadline, self.run_request(request)).await;
+            if let Ok(response) = response {
+                return response;
             }
+        }
 
-            Box::new(Helper {
-                f: self,
-                _marker: Default::default(),
-            })
+        match serde_json::from_str::<RpcRequest<'_>>(request) {
+            Ok(request) if request.id.is_notification() => None,
+            Ok(request) => Some(RpcResponse::timed_out(request.id)),
+            Err(_) => Some(RpcResponse::INVALID_REQUEST),
         }
     }
 
-    /// ```
-    /// async fn example(RpcContext) -> Result<Output, Into<RpcError>>
-    /// ```
-    #[async_trait]
-    impl<'a, F, Output, Error, Fut> Sealed<(), ((), Output), ((), RpcContext)> for F
-    where
-        F: Fn(RpcContext) -> Fut + Sync + Send + 'static,
-        Output: Serialize + Sync + Send + 'static,
-        Error: Into<RpcError> + Send + Sync + 'static,
-        Fut: Future<Output = Result<Output, Error>> + Send,
-    {
-        fn into_method(self) -> Box<dyn RpcMethod> {
-            struct Helper<F, Output, Error> {
-                f: F,
-                _marker: PhantomData<(Output, Error)>,
+    /// Parses and executes a request. Returns [None] if its a notification.
+    ///
+    /// Notifications are ignored, unless the method was registered with
+    /// [MethodOptions::notification], in which case it is executed
+    /// but still produces no response.
+    ///
+    /// The caller's [principal](RpcContext::principal) must be granted the scope of the method.
+    async fn run_request<'a>(&self, request: &'a str) -> Option<RpcResponse<'a>> {
+        if self.strict_requests && has_duplicate_keys(request) {
+            metrics::increment_counter!("rpc_invalid_requests_total", "version" => self.version);
+            return Some(RpcResponse::INVALID_REQUEST);
+        }
+
+        let Ok(mut request) = serde_json::from_str::<RpcRequest<'_>>(request) else {
+            metrics::increment_counter!("rpc_invalid_requests_total", "version" => self.version);
+            return Some(RpcResponse::INVALID_REQUEST);
+        };
+
+        if self.debug_notifications && request.id.is_notification() {
+            tracing::debug!(method=%request.method, "Executing notification for debugging");
+            request.id = RequestId::String(DEBUG_NOTIFICATION_ID.into());
+        }
+
+        // Also grab the method_name as it is a static str, which is required by the metrics.
+        let method = self
+            .methods
+            .get_key_value(request.method.as_ref())
+            .filter(|(name, _)| self.is_allowed(name));
+
+        // Ignore notification requests.
+        if request.id.is_notification() && !method.is_some_and(|(_, m)| m.notification) {
+            return None;
+        }
+
+        let Some((&method_name, method)) = method else {
+            // Not labeled by the method name, as that is chosen by the client.
+            metrics::increment_counter!("rpc_method_not_found_total", "version" => self.version);
+            return Some(RpcResponse::method_not_found(request.id));
+        };
+
+        let named = |response: RpcResponse<'a>| match self.method_in_errors {
+            true => RpcResponse {
+                output: response
+                    .output
+                    .map_err(|error| with_method(error, method_name)),
+                id: response.id,
+            },
+            false => response,
+        };
+
+        if !self.context.principal.is_granted(method.scope) {
+            return Some(named(RpcResponse {
+                output: Err(RouterError::UNAUTHORIZED.with_data(None)),
+                id: request.id,
+            }));
+        }
+
+        // The specification only permits params by-position or by-name. Methods would otherwise
+        // handle scalar params inconsistently, as some of their inputs accept them.
+        if !request.params.is_structured() {
+            if request.id.is_notification() {
+                return None;
+            }
+            return Some(named(RpcResponse::invalid_params(request.id)));
+        }
+
+        if let Some(limits) = &self.params_limits {
+            if request.params.exceeds(limits) {
+                if request.id.is_notification() {
+                    tracing::warn!(method=%request.method, "RPC notification params exceed limits");
+                    return None;
+                }
+                return Some(named(RpcResponse::invalid_params(request.id)));
             }
+        }
 
-            #[axum::async_trait]
-            impl<F, Output, Error, Fut> RpcMethod for Helper<F, Output, Error>
-            where
-                F: Fn(RpcContext) -> Fut + Sync + Send,
-                Output: Serialize + Send + Sync,
-                Error: Into<RpcError> + Send + Sync,
-                Fut: Future<Output = Result<Output, Error>> + Send,
-            {
-                async fn invoke<'a>(&self, state: RpcContext, input: RawParams<'a>) -> RpcResult {
-                    if !input.is_empty() {
-                        return Err(RpcError::InvalidParams);
+        metrics::increment_counter!("rpc_method_calls_total", "method" => method_name, "version" => self.version);
+
+        let _permit = match &self.call_limiter {
+            Some(limiter) => Some(
+                limiter
+                    .acquire()
+                    .await
+                    .expect("Call limiter is never closed"),
+            ),
+            None => None,
+        };
+
+        let defaulted = match (self.default_block_tag, method.block_id_index) {
+            (Some(tag), Some(index)) => request.params.with_default_block_id(index, tag),
+            _ => None,
+        };
+        let params = match &defaulted {
+            Some(params) => RawParams::from(params.as_ref()),
+            None => request.params,
+        };
+
+        // Read before the method is invoked, so that results which race with a new head are
+        // cached for the old one.
+        let head_cache = self
+            .head_cache
+            .as_deref()
+            .filter(|cache| cache.applies_to(method_name) && params.is_empty())
+            .map(|cache| (cache, self.context.sync_status.head_version()));
+
+        let started = Instant::now();
+        let cached = head_cache.and_then(|(cache, version)| cache.get(method_name, version));
+        let result = match cached {
+            Some(output) => Ok(Ok(output)),
+            None => {
+                let result = match &self.single_flight {
+                    Some(single_flight) if single_flight.applies_to(method_name) => single_flight
+                        .invoke(method_name, method.method.as_ref(), params)
+                        .await
+                        .ok_or(()),
+                    _ => {
+                        let method = method.method.invoke(self.context.clone(), params);
+                        std::panic::AssertUnwindSafe(method)
+                            .catch_unwind()
+                            .await
+                            .map_err(|_| ())
                     }
-                    let output = (self.f)(state).await.map_err(Into::into)?;
-                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+                };
+
+                if let (Some((cache, version)), Ok(Ok(output))) = (head_cache, &result) {
+                    cache.insert(method_name, version, output.clone());
                 }
+
+                result
             }
+        };
 
-            Box::new(Helper {
-                f: self,
-                _marker: Default::default(),
-            })
+        let elapsed = started.elapsed();
+        if let Some(threshold) = self.slow_request_threshold {
+            if elapsed > threshold {
+                tracing::warn!(
+                    method=%request.method,
+                    id=?request.id,
+                    params=%params.summary(SLOW_REQUEST_PARAMS_CHARS),
+                    ?elapsed,
+                    "Slow RPC request"
+                );
+            }
         }
-    }
 
-    /// ```
-    /// async fn example() -> Result<Output, Into<RpcError>>
-    /// ```
-    #[async_trait]
-    impl<'a, F, Output, Error, Fut> Sealed<(), (), ((), Output)> for F
-    where
-        F: Fn() -> Fut + Sync + Send + 'static,
-        Output: Serialize + Sync + Send + 'static,
-        Error: Into<RpcError> + Sync + Send + 'static,
-        Fut: Future<Output = Result<Output, Error>> + Send,
-    {
-        fn into_method(self) -> Box<dyn RpcMethod> {
-            struct Helper<F, Output, Error> {
-                f: F,
-                _marker: PhantomData<(Output, Error)>,
+        let output = match result {
+            Ok(output) => output,
+            Err(()) => {
+                tracing::warn!(method=%request.method, "RPC method panic'd");
+                Err(RpcError::InternalError(anyhow::anyhow!("Internal error")))
             }
+        };
+        let output = match self.omit_null_fields {
+            true => output.map(omit_null_fields),
+            false => output,
+        };
 
-            #[axum::async_trait]
-            impl<F, Output, Error, Fut> RpcMethod for Helper<F, Output, Error>
-            where
-                F: Fn() -> Fut + Sync + Send,
-                Output: Serialize + Send + Sync,
-                Error: Into<RpcError> + Send + Sync,
-                Fut: Future<Output = Result<Output, Error>> + Send,
-            {
-                async fn invoke<'a>(&self, _state: RpcContext, input: RawParams<'a>) -> RpcResult {
-                    if !input.is_empty() {
-                        return Err(RpcError::InvalidParams);
-                    }
-                    let output = (self.f)().await.map_err(Into::into)?;
-                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
-                }
+        if let Err(e) = &output {
+            metrics::increment_counter!("rpc_method_calls_failed_total", "method" => method_name, "version" => self.version, "error_class" => e.class());
+        }
+
+        if method.access_log {
+            tracing::debug!(method=%request.method, id=?request.id, ?elapsed, failed=output.is_err(), "RPC call");
+        }
+
+        if request.id.is_notification() {
+            if let Err(e) = output {
+                tracing::warn!(method=%request.method, error=?e, "RPC notification failed");
             }
+            return None;
+        }
 
-            Box::new(Helper {
-                f: self,
-                _marker: Default::default(),
-            })
+        if let (Some(threshold), Ok(result)) = (self.large_response_threshold, &output) {
+            let size = serde_json::to_vec(result).map_or(0, |result| result.len());
+            if size > threshold {
+                tracing::warn!(method=%request.method, id=?request.id, %size, "Large RPC response");
+                metrics::increment_counter!("rpc_large_responses_total", "method" => method_name, "version" => self.version);
+            }
         }
+
+        Some(named(RpcResponse {
+            output,
+            id: request.id,
+        }))
     }
+}
 
-    /// ```
-    /// fn example() -> &'static str
-    /// ```
-    #[async_trait]
-    impl<'a, F> Sealed<(), (), ((), (), &'static str)> for F
-    where
-        F: Fn() -> &'static str + Sync + Send + 'static,
-    {
-        fn into_method(self) -> Box<dyn RpcMethod> {
-            struct Helper<F> {
-                f: F,
+/// Adds the name of the method which failed to the `data` of `error`, unless it already has a
+/// `method` field. See [RpcRouter::with_method_in_errors].
+///
+/// Errors whose `data` is not an object are left as is, as adding a field would change its type.
+fn with_method(error: RpcError, method_name: &str) -> RpcError {
+    let data = match error.data() {
+        None => serde_json::json!({ "method": method_name }),
+        Some(Value::Object(fields)) => {
+            let mut fields = fields.clone();
+            fields.entry("method").or_insert_with(|| method_name.into());
+            Value::Object(fields)
+        }
+        Some(_) => return error,
+    };
+
+    RpcError::ApplicationError {
+        code: error.code(),
+        message: error.message().into_owned(),
+        data: Some(data),
+    }
+}
+
+/// Removes the `null` fields of all objects within `value`.
+fn omit_null_fields(mut value: Value) -> Value {
+    fn omit(value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                fields.retain(|_, field| !field.is_null());
+                fields.values_mut().for_each(omit);
             }
+            Value::Array(elements) => elements.iter_mut().for_each(omit),
+            _ => {}
+        }
+    }
+
+    omit(&mut value);
+    value
+}
+
+/// The durations of the phases of handling a request, in the order they completed.
+///
+/// See [RpcRouter::with_server_timing].
+#[derive(Debug, Default)]
+struct ServerTiming(Vec<(&'static str, Duration)>);
+
+impl ServerTiming {
+    /// Records the time since `started` as the duration of `phase`, and returns the start of
+    /// the next phase.
+    fn record(&mut self, phase: &'static str, started: Instant) -> Instant {
+        let now = Instant::now();
+        self.0.push((phase, now - started));
+        now
+    }
+
+    /// Formats the durations as a `Server-Timing` header, in milliseconds.
+    fn header_value(&self) -> axum::http::HeaderValue {
+        let value = self
+            .0
+            .iter()
+            .map(|(phase, duration)| format!("{phase};dur={:.3}", duration.as_secs_f64() * 1000.0))
+            .collect::<Vec<_>>()
+            .join(", ");
+        axum::http::HeaderValue::from_str(&value).expect("Phases are visible ASCII")
+    }
+}
+
+/// Media type of batch responses streamed as newline-delimited JSON.
+const NDJSON: &str = "application/x-ndjson";
+
+/// Returns true if the query requests pretty-printed responses using `pretty`, `pretty=1` or
+/// `pretty=true`.
+fn is_pretty(query: Option<&str>) -> bool {
+    query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .any(|pair| matches!(pair, "pretty" | "pretty=1" | "pretty=true"))
+}
+
+#[axum::debug_handler]
+pub async fn rpc_handler(
+    State(mut state): State<RpcRouter>,
+    TypedHeader(content_type): TypedHeader<ContentType>,
+    RawQuery(query): RawQuery,
+    content_length: Option<TypedHeader<ContentLength>>,
+    request: axum::http::Request<axum::body::Body>,
+) -> impl axum::response::IntoResponse {
+    // Only json content allowed.
+    if content_type != ContentType::json() {
+        return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+    }
+
+    // Reject oversized requests up front instead of buffering the body first.
+    if let (Some(TypedHeader(ContentLength(length))), Some(max)) =
+        (content_length, state.max_body_size)
+    {
+        if length > max {
+            return StatusCode::PAYLOAD_TOO_LARGE.into_response();
+        }
+    }
+
+    // Before the body is read, so that unauthenticated clients cannot make us buffer it.
+    let request_id = match state.begin_request(&request) {
+        Ok(request_id) => request_id,
+        Err(response) => return response,
+    };
+
+    let messages = state.messages(request.headers());
+
+    // Batch responses are streamed on request, which is not part of the JSON-RPC specification.
+    let stream = request
+        .headers()
+        .get(axum::http::header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains(NDJSON));
+
+    let mut timing = ServerTiming::default();
+    let started = Instant::now();
+
+    // Still subject to the body limit while streaming, for requests without a content length.
+    let body = match state.body_read_timeout {
+        Some(timeout) => {
+            let request = request.map(|body| ReadTimeoutBody::new(body, timeout));
+            axum::body::Bytes::from_request(request, &state).await
+        }
+        None => axum::body::Bytes::from_request(request, &state).await,
+    };
+    let body = match body {
+        Ok(body) => body,
+        Err(rejection) => return rejection.into_response(),
+    };
+    let started = timing.record("read", started);
+
+    // Record the version selected by the request path, as this is otherwise lost by the time
+    // the method is invoked.
+    let span = tracing::info_span!("rpc", version = state.version, %request_id);
+    let response = process_body(
+        &state,
+        query.as_deref(),
+        &body,
+        stream,
+        messages,
+        &mut timing,
+        started,
+    )
+    .instrument(span)
+    .await;
+
+    state.finish_response(response, &request_id, &timing)
+}
+
+impl RpcRouter {
+    /// Authenticates the caller of `request`, and scopes the context to the request. Shared by
+    /// all handlers, and done before the body is read.
+    ///
+    /// Returns the id of the request, as sent by the client or generated otherwise, or the
+    /// response rejecting an unauthenticated caller.
+    fn begin_request(
+        &mut self,
+        request: &axum::http::Request<axum::body::Body>,
+    ) -> Result<String, axum::response::Response> {
+        let Ok(principal) = self.authenticator.authenticate(request) else {
+            return Err(StatusCode::UNAUTHORIZED.into_response());
+        };
+
+        let request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|id| id.to_str().ok())
+            .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        let context = self
+            .context
+            .clone()
+            .with_principal(principal)
+            .with_request_id(&request_id);
+        self.context = match self.request_timeout {
+            Some(timeout) => context.with_deadline(tokio::time::Instant::now() + timeout),
+            None => context,
+        };
+
+        Ok(request_id)
+    }
+
+    /// Adds the headers describing how the request was handled to its `response`, which are
+    /// shared by all handlers.
+    fn finish_response(
+        &self,
+        mut response: axum::response::Response,
+        request_id: &str,
+        timing: &ServerTiming,
+    ) -> axum::response::Response {
+        if self.server_timing {
+            response
+                .headers_mut()
+                .insert("server-timing", timing.header_value());
+        }
+
+        if self.version_header {
+            response.headers_mut().insert(
+                VERSION_HEADER,
+                axum::http::HeaderValue::from_static(self.version),
+            );
+        }
+        response.headers_mut().insert(
+            REQUEST_ID_HEADER,
+            axum::http::HeaderValue::from_str(request_id).expect("Request id is visible ASCII"),
+        );
+
+        response
+    }
+}
+
+/// The query of a [rpc_get_handler] request.
+#[derive(Debug, serde::Deserialize)]
+pub struct GetQuery {
+    method: String,
+}
+
+/// Invokes the method named by the `method` query parameter without params, e.g.
+/// `GET /rpc/v0.5?method=starknet_blockNumber`, and returns its JSON-RPC response.
+///
+/// Intended for monitoring tools which can only issue GET requests. Methods which require
+/// params fail with an invalid params error, exactly as if they were called without params
+/// using [rpc_handler].
+pub async fn rpc_get_handler(
+    State(mut state): State<RpcRouter>,
+    Query(query): Query<GetQuery>,
+    request: axum::http::Request<axum::body::Body>,
+) -> axum::response::Response {
+    let request_id = match state.begin_request(&request) {
+        Ok(request_id) => request_id,
+        Err(response) => return response,
+    };
+    let messages = state.messages(request.headers());
+
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": query.method,
+        "id": 0,
+    })
+    .to_string();
+
+    let mut timing = ServerTiming::default();
+    let started = Instant::now();
+
+    let span = tracing::info_span!("rpc", version = state.version, %request_id);
+    let response = match state.run_request(&request).instrument(span).await {
+        Some(response) => response.localized(messages.as_deref()).into_response(),
+        None => ().into_response(),
+    };
+    timing.record("dispatch", started);
+
+    state.finish_response(response, &request_id, &timing)
+}
+
+/// Serves several versions of the API on a single path, selecting the [RpcRouter] of each
+/// request by its `x-starknet-rpc-version` header, e.g. `v0.5` or `0.5`.
+///
+/// Requests without the header, or for a version which is not served, use the default router.
+#[derive(Clone)]
+pub struct VersionedRouter {
+    routers: Vec<RpcRouter>,
+    default: RpcRouter,
+}
+
+impl VersionedRouter {
+    pub fn new(routers: Vec<RpcRouter>, default: RpcRouter) -> Self {
+        Self { routers, default }
+    }
+
+    fn select(&self, version: Option<&str>) -> &RpcRouter {
+        let version = version.map(|version| version.trim_start_matches('v'));
+        self.routers
+            .iter()
+            .find(|router| Some(router.version.trim_start_matches('v')) == version)
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Like [rpc_handler], using the router selected by the request's version header.
+pub async fn versioned_rpc_handler(
+    State(state): State<VersionedRouter>,
+    content_type: TypedHeader<ContentType>,
+    query: RawQuery,
+    content_length: Option<TypedHeader<ContentLength>>,
+    request: axum::http::Request<axum::body::Body>,
+) -> axum::response::Response {
+    let version = request
+        .headers()
+        .get(RPC_VERSION_HEADER)
+        .and_then(|version| version.to_str().ok());
+    let router = state.select(version).clone();
+
+    rpc_handler(State(router), content_type, query, content_length, request)
+        .await
+        .into_response()
+}
+
+/// Executes the single or batch request contained in the body.
+///
+/// If `stream` is set, batch responses are streamed instead, see [stream_batch]. Error messages
+/// are localized using `messages`, if any. The phases following `started` are recorded in
+/// `timing`.
+async fn process_body(
+    state: &RpcRouter,
+    query: Option<&str>,
+    body: &[u8],
+    stream: bool,
+    messages: Option<Arc<MessageTable>>,
+    timing: &mut ServerTiming,
+    started: Instant,
+) -> axum::response::Response {
+    let localized = |response: RpcResponse<'static>| response.localized(messages.as_deref());
+
+    // Validated up front, as JSON parse errors do not tell encoding problems apart.
+    let body = match std::str::from_utf8(body) {
+        Ok(body) => body,
+        Err(e) => {
+            metrics::increment_counter!("rpc_parse_errors_total", "version" => state.version);
+            return localized(RpcResponse::invalid_utf8(e)).into_response();
+        }
+    };
+
+    // Unfortunately due to this https://github.com/serde-rs/json/issues/497
+    // we cannot use an enum with borrowed raw values inside to do a single deserialization
+    // for us. Instead we have to distinguish manually between a single request and a batch
+    // request which we do by checking the first byte.
+    if !body.starts_with('[') {
+        let Ok(request) = serde_json::from_str::<&RawValue>(body) else {
+            metrics::increment_counter!("rpc_parse_errors_total", "version" => state.version);
+            return localized(RpcResponse::PARSE_ERROR).into_response();
+        };
+        let started = timing.record("parse", started);
+
+        let response = state
+            .run_request(request.get())
+            .await
+            .map(|response| response.localized(messages.as_deref()));
+        let started = timing.record("dispatch", started);
+
+        let response = match response {
+            // Only intended for debugging, so the formatting cost is acceptable.
+            Some(response) if is_pretty(query) => serde_json::to_vec_pretty(&response)
+                .unwrap()
+                .into_response(),
+            Some(response) => response.into_response(),
+            None => ().into_response(),
+        };
+        timing.record("serialize", started);
+
+        response
+    } else {
+        let Ok(requests) = serde_json::from_str::<Vec<&RawValue>>(body) else {
+            metrics::increment_counter!("rpc_parse_errors_total", "version" => state.version);
+            return localized(RpcResponse::PARSE_ERROR).into_response();
+        };
+        let started = timing.record("parse", started);
+
+        if requests.is_empty() {
+            metrics::increment_counter!("rpc_invalid_requests_total", "version" => state.version);
+            return localized(RpcResponse::INVALID_REQUEST).into_response();
+        }
+
+        if let Some(max) = state.max_batch_params_size {
+            if batch_params_size(&requests) > max {
+                return localized(RpcResponse::batch_params_too_large(max)).into_response();
+            }
+        }
+
+        if stream {
+            let requests = requests.into_iter().map(ToOwned::to_owned).collect();
+            return stream_batch(state.clone(), requests, messages.clone());
+        }
+
+        let mut responses = Vec::new();
+        let mut responses_size = 0;
+        let deadline = state.batch_deadline();
+
+        // The requests are executed as part of the handler rather than spawned, so that the
+        // remaining requests are dropped along with the handler if the client disconnects.
+        for request in requests {
+            // Notifications return none and are skipped.
+            let response = state
+                .run_batch_request(request.get(), deadline)
+                .await
+                .map(|response| response.localized(messages.as_deref()));
+            if let Some(response) = response {
+                if let Some(max) = state.max_batch_response_size {
+                    responses_size += serde_json::to_vec(&response).unwrap().len();
+                    if responses_size > max {
+                        return localized(RpcResponse::batch_response_too_large(max))
+                            .into_response();
+                    }
+                }
+                responses.push(response);
+            }
+        }
+
+        let started = timing.record("dispatch", started);
+
+        // All requests were notifications.
+        if responses.is_empty() {
+            return ().into_response();
+        }
+
+        let response = match is_pretty(query) {
+            true => serde_json::to_string_pretty(&responses),
+            false => serde_json::to_string(&responses),
+        }
+        .unwrap()
+        .into_response();
+        timing.record("serialize", started);
+
+        response
+    }
+}
+
+/// Sums up the size of the params of all requests in a batch.
+///
+/// Malformed requests count as having no params, and are rejected once executed.
+fn batch_params_size(requests: &[&RawValue]) -> usize {
+    #[derive(serde::Deserialize)]
+    struct Params<'a> {
+        #[serde(default, borrow)]
+        params: RawParams<'a>,
+    }
+
+    requests
+        .iter()
+        .filter_map(|request| serde_json::from_str::<Params<'_>>(request.get()).ok())
+        .map(|request| request.params.size())
+        .sum()
+}
+
+/// Executes the requests of a batch one by one, and streams each response as a line of JSON
+/// as soon as it completes. Notifications produce no line.
+///
+/// If the responses exceed the batch response size limit, the stream ends with a batch response
+/// too large error instead and the remaining requests are not executed.
+///
+/// The server's concurrency limits hold their slots until the stream has been sent, see
+/// [GuardedBody](crate::middleware::guarded_body::GuardedBody).
+fn stream_batch(
+    state: RpcRouter,
+    requests: Vec<Box<RawValue>>,
+    messages: Option<Arc<MessageTable>>,
+) -> axum::response::Response {
+    let max_size = state.max_batch_response_size;
+    // The server's request timeout only covers the handler, which returns before the stream
+    // is executed. The request timeout therefore bounds the stream if no batch timeout is set.
+    let deadline = state
+        .batch_timeout
+        .or(state.request_timeout)
+        .map(|timeout| tokio::time::Instant::now() + timeout);
+    let span = tracing::Span::current();
+    let too_large_messages = messages.clone();
+
+    let lines = futures::stream::iter(requests)
+        .then(move |request| {
+            let state = state.clone();
+            let messages = messages.clone();
+            async move {
+                state
+                    .run_batch_request(request.get(), deadline)
+                    .await
+                    .map(|response| response.localized(messages.as_deref()))
+                    .map(|response| serde_json::to_vec(&response).unwrap())
+            }
+            .instrument(span.clone())
+        })
+        // Notifications return none and are skipped.
+        .filter_map(futures::future::ready)
+        // The size is cleared once the limit is exceeded, which ends the stream.
+        .scan(Some(0), move |size: &mut Option<usize>, line| {
+            let line = match (size.as_mut(), max_size) {
+                (None, _) => None,
+                (Some(size_so_far), Some(max)) => {
+                    *size_so_far += line.len();
+                    if *size_so_far > max {
+                        *size = None;
+                        let response = RpcResponse::batch_response_too_large(max)
+                            .localized(too_large_messages.as_deref());
+                        Some(serde_json::to_vec(&response).unwrap())
+                    } else {
+                        Some(line)
+                    }
+                }
+                (Some(_), None) => Some(line),
+            };
+
+            futures::future::ready(line.map(|mut line| {
+                line.push(b'\n');
+                Ok::<_, std::convert::Infallible>(line)
+            }))
+        });
+
+    (
+        [(axum::http::header::CONTENT_TYPE, NDJSON)],
+        axum::body::StreamBody::new(lines),
+    )
+        .into_response()
+}
+
+#[axum::async_trait]
+pub trait RpcMethod: Send + Sync {
+    async fn invoke<'a>(&self, state: RpcContext, input: RawParams<'a>) -> RpcResult;
+}
+
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct MethodSchemaInput {
+    /// Name of the method whose params schema is requested.
+    method: String,
+}
+
+/// See [RpcRouterBuilder::register_method_schema].
+struct MethodSchema(HashMap<&'static str, Option<SchemaGenerator>>);
+
+#[axum::async_trait]
+impl RpcMethod for MethodSchema {
+    async fn invoke<'a>(&self, _state: RpcContext, input: RawParams<'a>) -> RpcResult {
+        let input = input.deserialize::<MethodSchemaInput>()?;
+        let schema = self
+            .0
+            .get(input.method.as_str())
+            .ok_or(RpcError::InvalidParams)?
+            .map(|schema| schema());
+
+        serde_json::to_value(schema).map_err(|e| RpcError::InternalError(e.into()))
+    }
+}
+
+/// Utility trait which automates the serde of an RPC methods input and output.
+///
+/// This trait is sealed to prevent attempts at implementing it manually. This will
+/// likely clash with the existing blanket implementations with very unhelpful error
+/// messages.
+///
+/// This trait is automatically implemented for the following methods:
+/// ```
+/// async fn input_and_context(ctx: RpcContext, input: impl Deserialize) -> Result<impl Serialize, Into<RpcError>>;
+/// async fn input_only(input: impl Deserialize) -> Result<impl Serialize, Into<RpcError>>;
+/// async fn context_only(ctx: RpcContext) -> Result<impl Serialize, Into<RpcError>>;
+/// ```
+///
+/// as well as their synchronous counterparts, which are run on the blocking thread pool:
+/// ```
+/// fn input_and_context(ctx: RpcContext, input: impl Deserialize) -> Result<impl Serialize, Into<RpcError>>;
+/// fn input_only(input: impl Deserialize) -> Result<impl Serialize, Into<RpcError>>;
+/// fn context_only(ctx: RpcContext) -> Result<impl Serialize, Into<RpcError>>;
+/// ```
+///
+/// The generics allow us to achieve a form of variadic specilization and can be ignored by callers.
+/// See [sealed::Sealed] to add more method signatures or more information on how this works.
+pub trait IntoRpcMethod<'a, I, O, S>: sealed::Sealed<I, O, S> {
+    fn into_method(self, unknown_fields: UnknownFields) -> Box<dyn RpcMethod>;
+}
+
+impl<'a, T, I, O, S> IntoRpcMethod<'a, I, O, S> for T
+where
+    T: sealed::Sealed<I, O, S>,
+{
+    fn into_method(self, unknown_fields: UnknownFields) -> Box<dyn RpcMethod> {
+        sealed::Sealed::<I, O, S>::into_method(self, unknown_fields)
+    }
+}
+
+mod sealed {
+    use std::marker::PhantomData;
+
+    use crate::jsonrpc::error::RpcError;
+
+    use super::*;
+
+    /// Sealed implementation of [RpcMethod].
+    ///
+    /// The generics allow for a form of specialization over a methods Input, Output and State
+    /// by treating each as a tuple. Varying the tuple length allows us to target a specific method
+    /// signature. This same could be achieved with a single generic but it becomes less clear as
+    /// each permuation would require a different tuple length.
+    ///
+    /// By convention, the lack of a type is equivalent to the unit tuple (). So if we want to target functions
+    /// with no input params, no input state and an output:
+    /// ```
+    /// Sealed<I = (), S = (), O = ((), Ouput)>
+    /// ```
+    ///
+    /// Synchronous methods are distinguished from async ones by an additional unit in their
+    /// output tuple, i.e. `O = ((), (), Output)`.
+    pub trait Sealed<I, O, S> {
+        /// `unknown_fields` is applied when deserializing the method's input, if it has one.
+        fn into_method(self, unknown_fields: UnknownFields) -> Box<dyn RpcMethod>;
+    }
+
+    /// ```
+    /// async fn example(RpcContext, impl Deserialize) -> Result<Output, Into<RpcError>>
+    /// ```
+    impl<'a, F, Input, Output, Error, Fut> Sealed<((), Input), ((), Output), ((), RpcContext)> for F
+    where
+        F: Fn(RpcContext, Input) -> Fut + Sync + Send + 'static,
+        Input: DeserializeOwned + Send + Sync + 'static,
+        Output: Serialize + Send + Sync + 'static,
+        Error: Into<RpcError> + Send + Sync + 'static,
+        Fut: Future<Output = Result<Output, Error>> + Send,
+    {
+        fn into_method(self, unknown_fields: UnknownFields) -> Box<dyn RpcMethod> {
+            struct Helper<F, Input, Output, Error> {
+                f: F,
+                unknown_fields: UnknownFields,
+                _marker: PhantomData<(Input, Output, Error)>,
+            }
+
+            #[axum::async_trait]
+            impl<F, Input, Output, Error, Fut> RpcMethod for Helper<F, Input, Output, Error>
+            where
+                F: Fn(RpcContext, Input) -> Fut + Sync + Send,
+                Input: DeserializeOwned + Send + Sync,
+                Output: Serialize + Send + Sync,
+                Error: Into<RpcError> + Send + Sync,
+                Fut: Future<Output = Result<Output, Error>> + Send,
+            {
+                async fn invoke<'a>(&self, state: RpcContext, input: RawParams<'a>) -> RpcResult {
+                    let input = input.deserialize_with(self.unknown_fields)?;
+                    let output = (self.f)(state, input).await.map_err(Into::into)?;
+                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+                }
+            }
+
+            Box::new(Helper {
+                f: self,
+                unknown_fields,
+                _marker: Default::default(),
+            })
+        }
+    }
+
+    /// ```
+    /// async fn example(impl Deserialize) -> Result<Output, Into<RpcError>>
+    /// ```
+    #[async_trait]
+    impl<'a, F, Input, Output, Error, Fut> Sealed<((), Input), ((), Output), ()> for F
+    where
+        F: Fn(Input) -> Fut + Sync + Send + 'static,
+        Input: DeserializeOwned + Sync + Send + 'static,
+        Output: Serialize + Sync + Send + 'static,
+        Error: Into<RpcError> + Sync + Send + 'static,
+        Fut: Future<Output = Result<Output, Error>> + Send,
+    {
+        fn into_method(self, unknown_fields: UnknownFields) -> Box<dyn RpcMethod> {
+            struct Helper<F, Input, Output, Error> {
+                f: F,
+                unknown_fields: UnknownFields,
+                _marker: PhantomData<(Input, Output, Error)>,
+            }
+
+            #[axum::async_trait]
+            impl<F, Input, Output, Error, Fut> RpcMethod for Helper<F, Input, Output, Error>
+            where
+                F: Fn(Input) -> Fut + Sync + Send,
+                Input: DeserializeOwned + Send + Sync,
+                Output: Serialize + Send + Sync,
+                Error: Into<RpcError> + Send + Sync,
+                Fut: Future<Output = Result<Output, Error>> + Send,
+            {
+                async fn invoke<'a>(&self, _state: RpcContext, input: RawParams<'a>) -> RpcResult {
+                    let input = input.deserialize_with(self.unknown_fields)?;
+                    let output = (self.f)(input).await.map_err(Into::into)?;
+                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+                }
+            }
+
+            Box::new(Helper {
+                f: self,
+                unknown_fields,
+                _marker: Default::default(),
+            })
+        }
+    }
+
+    /// ```
+    /// async fn example(RpcContext) -> Result<Output, Into<RpcError>>
+    /// ```
+    #[async_trait]
+    impl<'a, F, Output, Error, Fut> Sealed<(), ((), Output), ((), RpcContext)> for F
+    where
+        F: Fn(RpcContext) -> Fut + Sync + Send + 'static,
+        Output: Serialize + Sync + Send + 'static,
+        Error: Into<RpcError> + Send + Sync + 'static,
+        Fut: Future<Output = Result<Output, Error>> + Send,
+    {
+        fn into_method(self, _unknown_fields: UnknownFields) -> Box<dyn RpcMethod> {
+            struct Helper<F, Output, Error> {
+                f: F,
+                _marker: PhantomData<(Output, Error)>,
+            }
+
+            #[axum::async_trait]
+            impl<F, Output, Error, Fut> RpcMethod for Helper<F, Output, Error>
+            where
+                F: Fn(RpcContext) -> Fut + Sync + Send,
+                Output: Serialize + Send + Sync,
+                Error: Into<RpcError> + Send + Sync,
+                Fut: Future<Output = Result<Output, Error>> + Send,
+            {
+                async fn invoke<'a>(&self, state: RpcContext, input: RawParams<'a>) -> RpcResult {
+                    if !input.is_empty() {
+                        return Err(RpcError::InvalidParams);
+                    }
+                    let output = (self.f)(state).await.map_err(Into::into)?;
+                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+                }
+            }
+
+            Box::new(Helper {
+                f: self,
+                _marker: Default::default(),
+            })
+        }
+    }
+
+    /// ```
+    /// async fn example() -> Result<Output, Into<RpcError>>
+    /// ```
+    #[async_trait]
+    impl<'a, F, Output, Error, Fut> Sealed<(), (), ((), Output)> for F
+    where
+        F: Fn() -> Fut + Sync + Send + 'static,
+        Output: Serialize + Sync + Send + 'static,
+        Error: Into<RpcError> + Sync + Send + 'static,
+        Fut: Future<Output = Result<Output, Error>> + Send,
+    {
+        fn into_method(self, _unknown_fields: UnknownFields) -> Box<dyn RpcMethod> {
+            struct Helper<F, Output, Error> {
+                f: F,
+                _marker: PhantomData<(Output, Error)>,
+            }
+
+            #[axum::async_trait]
+            impl<F, Output, Error, Fut> RpcMethod for Helper<F, Output, Error>
+            where
+                F: Fn() -> Fut + Sync + Send,
+                Output: Serialize + Send + Sync,
+                Error: Into<RpcError> + Send + Sync,
+                Fut: Future<Output = Result<Output, Error>> + Send,
+            {
+                async fn invoke<'a>(&self, _state: RpcContext, input: RawParams<'a>) -> RpcResult {
+                    if !input.is_empty() {
+                        return Err(RpcError::InvalidParams);
+                    }
+                    let output = (self.f)().await.map_err(Into::into)?;
+                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+                }
+            }
+
+            Box::new(Helper {
+                f: self,
+                _marker: Default::default(),
+            })
+        }
+    }
+
+    /// ```
+    /// fn example() -> &'static str
+    /// ```
+    #[async_trait]
+    impl<'a, F> Sealed<(), (), ((), (), &'static str)> for F
+    where
+        F: Fn() -> &'static str + Sync + Send + 'static,
+    {
+        fn into_method(self, _unknown_fields: UnknownFields) -> Box<dyn RpcMethod> {
+            struct Helper<F> {
+                f: F,
+            }
+
+            #[axum::async_trait]
+            impl<F> RpcMethod for Helper<F>
+            where
+                F: Fn() -> &'static str + Sync + Send,
+            {
+                async fn invoke<'a>(&self, _state: RpcContext, input: RawParams<'a>) -> RpcResult {
+                    if !input.is_empty() {
+                        return Err(RpcError::InvalidParams);
+                    }
+                    let output = (self.f)();
+                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+                }
+            }
+            Box::new(Helper { f: self })
+        }
+    }
+
+    /// Runs a synchronous method on the blocking thread pool, so that it does not block the
+    /// async runtime. Panics are resumed, so that they are handled like those of async methods.
+    async fn run_blocking<T: Send + 'static>(
+        f: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<T, RpcError> {
+        let span = tracing::Span::current();
+        match tokio::task::spawn_blocking(move || {
+            let _g = span.enter();
+            f()
+        })
+        .await
+        {
+            Ok(output) => Ok(output),
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => Err(RpcError::InternalError(e.into())),
+        }
+    }
+
+    /// ```
+    /// fn example(RpcContext, impl Deserialize) -> Result<Output, Into<RpcError>>
+    /// ```
+    impl<'a, F, Input, Output, Error> Sealed<((), Input), ((), (), Output), ((), RpcContext)> for F
+    where
+        F: Fn(RpcContext, Input) -> Result<Output, Error> + Sync + Send + 'static,
+        Input: DeserializeOwned + Send + Sync + 'static,
+        Output: Serialize + Send + Sync + 'static,
+        Error: Into<RpcError> + Send + Sync + 'static,
+    {
+        fn into_method(self, unknown_fields: UnknownFields) -> Box<dyn RpcMethod> {
+            struct Helper<F, Input, Output, Error> {
+                f: Arc<F>,
+                unknown_fields: UnknownFields,
+                _marker: PhantomData<(Input, Output, Error)>,
+            }
+
+            #[axum::async_trait]
+            impl<F, Input, Output, Error> RpcMethod for Helper<F, Input, Output, Error>
+            where
+                F: Fn(RpcContext, Input) -> Result<Output, Error> + Sync + Send + 'static,
+                Input: DeserializeOwned + Send + Sync + 'static,
+                Output: Serialize + Send + Sync + 'static,
+                Error: Into<RpcError> + Send + Sync + 'static,
+            {
+                async fn invoke<'a>(&self, state: RpcContext, input: RawParams<'a>) -> RpcResult {
+                    let input = input.deserialize_with(self.unknown_fields)?;
+                    let f = self.f.clone();
+                    let output = run_blocking(move || f(state, input)).await?;
+                    let output = output.map_err(Into::into)?;
+                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+                }
+            }
+
+            Box::new(Helper {
+                f: Arc::new(self),
+                unknown_fields,
+                _marker: Default::default(),
+            })
+        }
+    }
+
+    /// ```
+    /// fn example(impl Deserialize) -> Result<Output, Into<RpcError>>
+    /// ```
+    impl<'a, F, Input, Output, Error> Sealed<((), Input), ((), (), Output), ()> for F
+    where
+        F: Fn(Input) -> Result<Output, Error> + Sync + Send + 'static,
+        Input: DeserializeOwned + Send + Sync + 'static,
+        Output: Serialize + Send + Sync + 'static,
+        Error: Into<RpcError> + Send + Sync + 'static,
+    {
+        fn into_method(self, unknown_fields: UnknownFields) -> Box<dyn RpcMethod> {
+            struct Helper<F, Input, Output, Error> {
+                f: Arc<F>,
+                unknown_fields: UnknownFields,
+                _marker: PhantomData<(Input, Output, Error)>,
+            }
+
+            #[axum::async_trait]
+            impl<F, Input, Output, Error> RpcMethod for Helper<F, Input, Output, Error>
+            where
+                F: Fn(Input) -> Result<Output, Error> + Sync + Send + 'static,
+                Input: DeserializeOwned + Send + Sync + 'static,
+                Output: Serialize + Send + Sync + 'static,
+                Error: Into<RpcError> + Send + Sync + 'static,
+            {
+                async fn invoke<'a>(&self, _state: RpcContext, input: RawParams<'a>) -> RpcResult {
+                    let input = input.deserialize_with(self.unknown_fields)?;
+                    let f = self.f.clone();
+                    let output = run_blocking(move || f(input)).await?;
+                    let output = output.map_err(Into::into)?;
+                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+                }
+            }
+
+            Box::new(Helper {
+                f: Arc::new(self),
+                unknown_fields,
+                _marker: Default::default(),
+            })
+        }
+    }
+
+    /// ```
+    /// fn example(RpcContext) -> Result<Output, Into<RpcError>>
+    /// ```
+    impl<'a, F, Output, Error> Sealed<(), ((), (), Output), ((), RpcContext)> for F
+    where
+        F: Fn(RpcContext) -> Result<Output, Error> + Sync + Send + 'static,
+        Output: Serialize + Send + Sync + 'static,
+        Error: Into<RpcError> + Send + Sync + 'static,
+    {
+        fn into_method(self, _unknown_fields: UnknownFields) -> Box<dyn RpcMethod> {
+            struct Helper<F, Output, Error> {
+                f: Arc<F>,
+                _marker: PhantomData<(Output, Error)>,
+            }
+
+            #[axum::async_trait]
+            impl<F, Output, Error> RpcMethod for Helper<F, Output, Error>
+            where
+                F: Fn(RpcContext) -> Result<Output, Error> + Sync + Send + 'static,
+                Output: Serialize + Send + Sync + 'static,
+                Error: Into<RpcError> + Send + Sync + 'static,
+            {
+                async fn invoke<'a>(&self, state: RpcContext, input: RawParams<'a>) -> RpcResult {
+                    if !input.is_empty() {
+                        return Err(RpcError::InvalidParams);
+                    }
+                    let f = self.f.clone();
+                    let output = run_blocking(move || f(state)).await?;
+                    let output = output.map_err(Into::into)?;
+                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+                }
+            }
+
+            Box::new(Helper {
+                f: Arc::new(self),
+                _marker: Default::default(),
+            })
+        }
+    }
+
+    /// ```
+    /// fn example() -> Result<Output, Into<RpcError>>
+    /// ```
+    impl<'a, F, Output, Error> Sealed<(), ((), (), Output), ()> for F
+    where
+        F: Fn() -> Result<Output, Error> + Sync + Send + 'static,
+        Output: Serialize + Send + Sync + 'static,
+        Error: Into<RpcError> + Send + Sync + 'static,
+    {
+        fn into_method(self, _unknown_fields: UnknownFields) -> Box<dyn RpcMethod> {
+            struct Helper<F, Output, Error> {
+                f: Arc<F>,
+                _marker: PhantomData<(Output, Error)>,
+            }
+
+            #[axum::async_trait]
+            impl<F, Output, Error> RpcMethod for Helper<F, Output, Error>
+            where
+                F: Fn() -> Result<Output, Error> + Sync + Send + 'static,
+                Output: Serialize + Send + Sync + 'static,
+                Error: Into<RpcError> + Send + Sync + 'static,
+            {
+                async fn invoke<'a>(&self, _state: RpcContext, input: RawParams<'a>) -> RpcResult {
+                    if !input.is_empty() {
+                        return Err(RpcError::InvalidParams);
+                    }
+                    let f = self.f.clone();
+                    let output = run_blocking(move || f()).await?;
+                    let output = output.map_err(Into::into)?;
+                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+                }
+            }
+
+            Box::new(Helper {
+                f: Arc::new(self),
+                _marker: Default::default(),
+            })
+        }
+    }
+}
+
+/// Handles invoking an RPC route's methods.
+#[async_trait]
+pub trait RpcMethodHandler {
+    async fn call_method(method: &str, state: RpcContext, params: Value) -> RpcResult;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonrpc::test_utils::{assert_jsonrpc_conformance, serve_and_query, spawn_server};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    mod specification_tests {
+        //! Test cases lifted directly from the [RPC specification](https://www.jsonrpc.org/specification).
+        use super::*;
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+        use serde_json::json;
+
+        fn spec_router() -> RpcRouter {
+            crate::error::generate_rpc_error_subset!(ExampleError:);
+
+            #[derive(Debug, Deserialize, Serialize)]
+            struct SubtractInput {
+                minuend: i32,
+                subtrahend: i32,
+            }
+            async fn subtract(input: SubtractInput) -> Result<Value, ExampleError> {
+                Ok(Value::Number((input.minuend - input.subtrahend).into()))
+            }
+
+            #[derive(Debug, Deserialize, Serialize)]
+            struct SumInput(Vec<i32>);
+            async fn sum(input: SumInput) -> Result<Value, ExampleError> {
+                Ok(Value::Number((input.0.iter().sum::<i32>()).into()))
+            }
+
+            #[derive(Debug, Deserialize, Serialize)]
+            struct GetDataInput;
+            #[derive(Debug, Deserialize, Serialize)]
+            struct GetDataOutput(Vec<Value>);
+            async fn get_data() -> Result<GetDataOutput, ExampleError> {
+                Ok(GetDataOutput(vec![
+                    Value::String("hello".to_owned()),
+                    Value::Number(5.into()),
+                ]))
+            }
+
+            RpcRouter::builder("vTEST")
+                .register("subtract", subtract)
+                .register("sum", sum)
+                .register("get_data", get_data)
+                .build(RpcContext::for_tests())
+        }
+
+        #[rstest]
+        #[case::with_positional_params(
+            json!({"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": 2}),
+            json!({"jsonrpc": "2.0", "result": 19, "id": 2}),
+        )]
+        #[case::with_positional_params_switched(
+            json!({"jsonrpc": "2.0", "method": "subtract", "params": [23, 42], "id": 2}),
+            json!({"jsonrpc": "2.0", "result": -19, "id": 2}),
+        )]
+        #[case::with_named_params(
+            json!({"jsonrpc": "2.0", "method": "subtract", "params": {"subtrahend": 23, "minuend": 42}, "id": 3}),
+            json!({"jsonrpc": "2.0", "result": 19, "id": 3}),
+        )]
+        #[case::with_named_params_switched(
+            json!({"jsonrpc": "2.0", "method": "subtract", "params": {"minuend": 42, "subtrahend": 23}, "id": 4}),
+            json!({"jsonrpc": "2.0", "result": 19, "id": 4}),
+        )]
+        #[case::non_existent_method(
+            json!({"jsonrpc": "2.0", "method": "foobar", "id": "1"}),
+            json!({"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": "1"}),
+        )]
+        #[case::invalid_request_object(
+            json!({"jsonrpc": "2.0", "method": 1, "params": "bar"}),
+            json!({"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null}),
+        )]
+        #[case::empty_method_name(
+            json!({"jsonrpc": "2.0", "method": "", "id": "1"}),
+            json!({"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null}),
+        )]
+        #[case::empty_batch(
+            json!([]),
+            json!({"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null}),
+        )]
+        #[case::invalid_batch_single(
+            json!([1]),
+            json!([{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null}]),
+        )]
+        #[case::invalid_batch_multiple(
+            json!([1, 2, 3]),
+            json!([
+                {"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null},
+                {"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null},
+                {"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null}
+            ]),
+        )]
+        #[case::batch(
+            json!([
+                {"jsonrpc": "2.0", "method": "sum", "params": [1,2,4], "id": "1"},
+                {"jsonrpc": "2.0", "method": "notify_hello", "params": [7]},
+                {"jsonrpc": "2.0", "method": "subtract", "params": [42,23], "id": "2"},
+                {"foo": "boo"},
+                {"jsonrpc": "2.0", "method": "foo.get", "params": {"name": "myself"}, "id": "5"},
+                {"jsonrpc": "2.0", "method": "get_data", "id": "9"}
+            ]),
+            json!([
+                {"jsonrpc": "2.0", "result": 7, "id": "1"},
+                {"jsonrpc": "2.0", "result": 19, "id": "2"},
+                {"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null},
+                {"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": "5"},
+                {"jsonrpc": "2.0", "result": ["hello", 5], "id": "9"}
+            ]),
+        )]
+        #[tokio::test]
+        async fn specification_test(#[case] request: Value, #[case] expected: Value) {
+            let response = serve_and_query(spec_router(), request).await;
+
+            assert_eq!(response, expected);
+        }
+
+        #[tokio::test]
+        async fn conformance() {
+            assert_jsonrpc_conformance(spec_router()).await;
+        }
+
+        #[rstest]
+        #[case::with_params(json!({"jsonrpc": "2.0", "method": "update", "params": [1,2,3,4,5]}))]
+        #[case::without_params(json!({"jsonrpc": "2.0", "method": "foobar"}))]
+        #[case::batch(json!([
+            {"jsonrpc": "2.0", "method": "notify_sum", "params": [1,2,4]},
+            {"jsonrpc": "2.0", "method": "notify_hello", "params": [7]}
+        ]))]
+        #[tokio::test]
+        async fn notifications(#[case] request: Value) {
+            let url = spawn_server(spec_router()).await;
+
+            let client = reqwest::Client::new();
+            let res = client
+                .post(url.clone())
+                .json(&request)
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(res.content_length(), Some(0));
+        }
+
+        #[rstest]
+        #[case::single(r#"{"jsonrpc": "2.0", "method": "foobar, "params": "bar", "baz]"#)]
+        #[case::batch(
+            r#"[
+            {"jsonrpc": "2.0", "method": "sum", "params": [1,2,4], "id": "1"},
+            {"jsonrpc": "2.0", "method"
+         ]"#
+        )]
+        #[tokio::test]
+        async fn invalid_json(#[case] request: &'static str) {
+            let url = spawn_server(spec_router()).await;
+
+            let client = reqwest::Client::new();
+            let res = client
+                .post(url.clone())
+                .body(request)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .await
+                .unwrap()
+                .json::<Value>()
+                .await
+                .unwrap();
+
+            let expected = serde_json::json!({"jsonrpc": "2.0", "error": {"code": -32700, "message": "Parse error"}, "id": null});
+            assert_eq!(res, expected);
+        }
+    }
+
+    mod panic_handling {
+        use super::*;
+
+        fn panic_router() -> RpcRouter {
+            fn always_panic() -> &'static str {
+                panic!("Oh no!");
+            }
+
+            fn always_success() -> &'static str {
+                "Success"
+            }
+
+            RpcRouter::builder("vTest")
+                .register("panic", always_panic)
+                .register("success", always_success)
+                .build(RpcContext::for_tests())
+        }
+
+        #[tokio::test]
+        async fn panic_is_internal_error() {
+            let response = serve_and_query(
+                panic_router(),
+                json!(
+                    {"jsonrpc": "2.0", "method": "panic", "id": 1}
+                ),
+            )
+            .await;
+
+            let expected = serde_json::json!({"jsonrpc": "2.0", "error": {"code": -32603, "message": "Internal error"}, "id": 1});
+            assert_eq!(response, expected);
+        }
+
+        #[tokio::test]
+        async fn panic_in_batch_is_isolated() {
+            let response = serve_and_query(
+                panic_router(),
+                json!([
+                    {"jsonrpc": "2.0", "method": "panic", "id": 1},
+                    {"jsonrpc": "2.0", "method": "success", "id": 2},
+                ]),
+            )
+            .await;
+
+            let expected = serde_json::json!([
+                {"jsonrpc": "2.0", "error": {"code": -32603, "message": "Internal error"}, "id": 1},
+                {"jsonrpc": "2.0", "result": "Success", "id": 2},
+            ]);
+            assert_eq!(response, expected);
+        }
+    }
+
+    mod sync_methods {
+        use super::*;
+
+        fn sync_router() -> RpcRouter {
+            #[derive(Debug, Deserialize)]
+            struct SumInput(Vec<i32>);
+
+            fn sum(input: SumInput) -> Result<Value, RpcError> {
+                Ok(json!(input.0.iter().sum::<i32>()))
+            }
+
+            fn sum_with_context(_ctx: RpcContext, input: SumInput) -> Result<Value, RpcError> {
+                Ok(json!(input.0.iter().sum::<i32>()))
+            }
+
+            fn context_only(_ctx: RpcContext) -> Result<Value, RpcError> {
+                Ok(json!("context"))
+            }
+
+            fn no_input() -> Result<Value, RpcError> {
+                Ok(json!("none"))
+            }
+
+            fn fallible() -> Result<Value, RpcError> {
+                Err(RpcError::ApplicationError {
+                    code: 1234,
+                    message: "Failed".to_owned(),
+                    data: None,
+                })
+            }
+
+            fn panics() -> Result<Value, RpcError> {
+                panic!("Oh no!");
+            }
+
+            RpcRouter::builder("vTEST")
+                .register("sum", sum)
+                .register("sum_with_context", sum_with_context)
+                .register("context_only", context_only)
+                .register("no_input", no_input)
+                .register("fallible", fallible)
+                .register("panics", panics)
+                .build(RpcContext::for_tests())
+        }
+
+        #[rstest::rstest]
+        #[case::input_only(
+            json!({"jsonrpc": "2.0", "method": "sum", "params": [1, 2, 3], "id": 1}),
+            json!({"jsonrpc": "2.0", "result": 6, "id": 1}),
+        )]
+        #[case::input_and_context(
+            json!({"jsonrpc": "2.0", "method": "sum_with_context", "params": [1, 2, 3], "id": 1}),
+            json!({"jsonrpc": "2.0", "result": 6, "id": 1}),
+        )]
+        #[case::context_only(
+            json!({"jsonrpc": "2.0", "method": "context_only", "id": 1}),
+            json!({"jsonrpc": "2.0", "result": "context", "id": 1}),
+        )]
+        #[case::no_input(
+            json!({"jsonrpc": "2.0", "method": "no_input", "id": 1}),
+            json!({"jsonrpc": "2.0", "result": "none", "id": 1}),
+        )]
+        #[case::invalid_params(
+            json!({"jsonrpc": "2.0", "method": "sum", "params": {"a": 1}, "id": 1}),
+            json!({"jsonrpc": "2.0", "error": {"code": -32602, "message": "Invalid params"}, "id": 1}),
+        )]
+        #[case::error(
+            json!({"jsonrpc": "2.0", "method": "fallible", "id": 1}),
+            json!({"jsonrpc": "2.0", "error": {"code": 1234, "message": "Failed"}, "id": 1}),
+        )]
+        #[case::panic(
+            json!({"jsonrpc": "2.0", "method": "panics", "id": 1}),
+            json!({"jsonrpc": "2.0", "error": {"code": -32603, "message": "Internal error"}, "id": 1}),
+        )]
+        #[tokio::test]
+        async fn sync_method(#[case] request: Value, #[case] expected: Value) {
+            let response = serve_and_query(sync_router(), request).await;
+
+            assert_eq!(response, expected);
+        }
+
+        #[tokio::test(flavor = "current_thread")]
+        async fn does_not_block_the_runtime() {
+            use std::time::Duration;
+
+            fn blocking() -> Result<Value, RpcError> {
+                std::thread::sleep(Duration::from_millis(500));
+                Ok(json!("done"))
+            }
+
+            let router = RpcRouter::builder("vTEST")
+                .register("blocking", blocking)
+                .build(RpcContext::for_tests());
+            let query = tokio::spawn(serve_and_query(
+                router,
+                json!({"jsonrpc": "2.0", "method": "blocking", "id": 1}),
+            ));
+
+            // The single runtime thread remains free to run other tasks meanwhile.
+            let ticker = async {
+                for _ in 0..10 {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            };
+            tokio::select! {
+                biased;
+                _ = tokio::time::sleep(Duration::from_millis(400)) => panic!("Runtime was blocked"),
+                _ = ticker => {}
+            }
+
+            assert_eq!(query.await.unwrap()["result"], json!("done"));
+        }
+    }
+
+    mod batch_response_size {
+        use super::*;
+
+        fn router(max_batch_response_size: usize) -> RpcRouter {
+            crate::error::generate_rpc_error_subset!(LargeError:);
+
+            async fn large() -> Result<String, LargeError> {
+                Ok("x".repeat(100))
+            }
+
+            RpcRouter::builder("vTEST")
+                .register("large", large)
+                .build(RpcContext::for_tests())
+                .with_max_batch_response_size(max_batch_response_size)
+        }
+
+        fn batch(count: usize) -> Value {
+            (0..count)
+                .map(|id| json!({"jsonrpc": "2.0", "method": "large", "id": id}))
+                .collect()
+        }
+
+        #[tokio::test]
+        async fn exceeding_limit_is_rejected() {
+            // Each response fits within the limit, but not all of them together.
+            let response = serve_and_query(router(500), batch(10)).await;
+
+            assert_eq!(
+                response,
+                json!({
+                    "jsonrpc": "2.0",
+                    "error": {"code": -32002, "message": "Batch response too large", "data": {"limit": 500}},
+                    "id": null
+                })
+            );
+        }
+
+        #[tokio::test]
+        async fn within_limit_is_served() {
+            let response = serve_and_query(router(5000), batch(10)).await;
+
+            assert_eq!(response.as_array().unwrap().len(), 10);
+        }
+
+        #[tokio::test]
+        async fn single_request_is_not_limited() {
+            let response = serve_and_query(
+                router(10),
+                json!({"jsonrpc": "2.0", "method": "large", "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "result": "x".repeat(100), "id": 1})
+            );
+        }
+    }
+
+    mod batch_params_size {
+        use super::*;
+
+        fn router(max_batch_params_size: usize) -> RpcRouter {
+            crate::error::generate_rpc_error_subset!(DeclareError:);
+
+            #[derive(Deserialize)]
+            struct Input {
+                #[allow(dead_code)]
+                class: String,
+            }
+
+            async fn declare(_input: Input) -> Result<(), DeclareError> {
+                Ok(())
+            }
+
+            RpcRouter::builder("vTEST")
+                .register("declare", declare)
+                .build(RpcContext::for_tests())
+                .with_max_batch_params_size(max_batch_params_size)
+        }
+
+        /// Each request's params are a little over 100 bytes.
+        fn batch(count: usize) -> Value {
+            (0..count)
+                .map(|id| json!({"jsonrpc": "2.0", "method": "declare", "params": {"class": "x".repeat(100)}, "id": id}))
+                .collect()
+        }
+
+        #[tokio::test]
+        async fn exceeding_limit_is_rejected() {
+            // Only a few requests, but their params are too large together.
+            let response = serve_and_query(router(500), batch(5)).await;
+
+            assert_eq!(
+                response,
+                json!({
+                    "jsonrpc": "2.0",
+                    "error": {"code": -32004, "message": "Batch params too large", "data": {"limit": 500}},
+                    "id": null
+                })
+            );
+        }
+
+        #[tokio::test]
+        async fn within_limit_is_served() {
+            let response = serve_and_query(router(5000), batch(5)).await;
+
+            assert_eq!(response.as_array().unwrap().len(), 5);
+        }
+
+        #[tokio::test]
+        async fn single_request_is_not_limited() {
+            let response = serve_and_query(
+                router(10),
+                json!({"jsonrpc": "2.0", "method": "declare", "params": {"class": "x"}, "id": 1}),
+            )
+            .await;
+
+            assert_eq!(response, json!({"jsonrpc": "2.0", "result": null, "id": 1}));
+        }
+    }
+
+    mod single_flight {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Deserialize)]
+        struct Input {
+            value: u32,
+        }
+
+        static COALESCED_CALLS: AtomicUsize = AtomicUsize::new(0);
+        static SEQUENTIAL_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        async fn coalesced(input: Input) -> Result<u32, RpcError> {
+            COALESCED_CALLS.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Ok(input.value)
+        }
+
+        async fn sequential(input: Input) -> Result<u32, RpcError> {
+            SEQUENTIAL_CALLS.fetch_add(1, Ordering::Relaxed);
+            Ok(input.value)
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("coalesced", coalesced)
+                .register("sequential", sequential)
+                .build(RpcContext::for_tests())
+                .with_single_flight(HashSet::from([
+                    "coalesced".to_owned(),
+                    "sequential".to_owned(),
+                ]))
+        }
+
+        fn request(method: &str, value: u32, id: usize) -> String {
+            json!({"jsonrpc": "2.0", "method": method, "params": {"value": value}, "id": id})
+                .to_string()
+        }
+
+        #[tokio::test]
+        async fn concurrent_identical_calls_are_coalesced() {
+            let router = router();
+            let requests = (0..10)
+                .map(|id| request("coalesced", 1, id))
+                .chain([request("coalesced", 2, 10)])
+                .collect::<Vec<_>>();
+
+            let responses = futures::future::join_all(
+                requests.iter().map(|request| router.run_request(request)),
+            )
+            .await;
+
+            // One invocation for the ten identical calls, and one for the distinct params.
+            assert_eq!(COALESCED_CALLS.load(Ordering::Relaxed), 2);
+            for (id, response) in responses.into_iter().enumerate() {
+                let response = response.unwrap();
+                assert_eq!(response.id, RequestId::Number(id as i64));
+                let expected = if id == 10 { 2 } else { 1 };
+                assert_eq!(response.output, Ok(json!(expected)));
+            }
+        }
+
+        #[tokio::test]
+        async fn results_are_not_cached() {
+            let router = router();
+
+            for id in 0..3 {
+                router
+                    .run_request(&request("sequential", 1, id))
+                    .await
+                    .unwrap();
+            }
+
+            assert_eq!(SEQUENTIAL_CALLS.load(Ordering::Relaxed), 3);
+        }
+
+        #[tokio::test]
+        async fn callers_principal_is_not_shared() {
+            async fn whoami(context: RpcContext) -> Result<Value, RpcError> {
+                Ok(json!(context.principal.name))
+            }
+
+            let mut router = RpcRouter::builder("vTEST")
+                .register("whoami", whoami)
+                .build(RpcContext::for_tests())
+                .with_single_flight(HashSet::from(["whoami".to_owned()]));
+            router.context = router.context.with_principal(crate::jsonrpc::Principal {
+                name: Some("operator".to_owned()),
+                scopes: Default::default(),
+            });
+
+            let request = json!({"jsonrpc": "2.0", "method": "whoami", "id": 1}).to_string();
+            let response = router.run_request(&request).await.unwrap();
+
+            // Joiners would otherwise see the principal of whichever caller came first.
+            assert_eq!(response.output, Ok(json!(null)));
+        }
+    }
+
+    mod head_cache {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CACHED_CALLS: AtomicUsize = AtomicUsize::new(0);
+        static INVALIDATED_CALLS: AtomicUsize = AtomicUsize::new(0);
+        static FAILING_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        /// Fields are out of order, so that a cached result which is serialized differently
+        /// would show.
+        #[derive(serde::Serialize)]
+        struct Head {
+            number: u64,
+            hash: &'static str,
+            attributes: Vec<Option<u64>>,
+        }
+
+        async fn cached() -> Result<Head, RpcError> {
+            CACHED_CALLS.fetch_add(1, Ordering::Relaxed);
+            Ok(Head {
+                number: 10,
+                hash: "0x1234",
+                attributes: vec![Some(1), None],
+            })
+        }
+
+        async fn invalidated() -> Result<usize, RpcError> {
+            Ok(INVALIDATED_CALLS.fetch_add(1, Ordering::Relaxed))
+        }
+
+        async fn failing() -> Result<Value, RpcError> {
+            FAILING_CALLS.fetch_add(1, Ordering::Relaxed);
+            Err(RpcError::InternalError(anyhow::anyhow!("Failed")))
+        }
+
+        fn router(context: RpcContext) -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("cached", cached)
+                .register("invalidated", invalidated)
+                .register("failing", failing)
+                .build(context)
+                .with_head_cache(HashSet::from([
+                    "cached".to_owned(),
+                    "invalidated".to_owned(),
+                    "failing".to_owned(),
+                ]))
+        }
+
+        fn request(method: &str, id: usize) -> String {
+            json!({"jsonrpc": "2.0", "method": method, "id": id}).to_string()
+        }
+
+        #[tokio::test]
+        async fn cached_and_uncached_responses_are_identical() {
+            let url = spawn_server(router(RpcContext::for_tests())).await;
+            let client = reqwest::Client::new();
+
+            let mut bodies = Vec::new();
+            for _ in 0..2 {
+                let body = client
+                    .post(url.clone())
+                    .body(request("cached", 1))
+                    .header("Content-Type", "application/json")
+                    .send()
+                    .await
+                    .unwrap()
+                    .bytes()
+                    .await
+                    .unwrap();
+                bodies.push(body);
+            }
+
+            assert_eq!(CACHED_CALLS.load(Ordering::Relaxed), 1);
+            assert_eq!(bodies[0], bodies[1]);
+        }
+
+        #[tokio::test]
+        async fn invalidated_by_new_head() {
+            let context = RpcContext::for_tests();
+            let router = router(context.clone());
+
+            let first = router.run_request(&request("invalidated", 1)).await;
+            let second = router.run_request(&request("invalidated", 2)).await;
+            context.sync_status.head_changed();
+            let third = router.run_request(&request("invalidated", 3)).await;
+
+            assert_eq!(first.unwrap().output, Ok(json!(0)));
+            assert_eq!(second.unwrap().output, Ok(json!(0)));
+            assert_eq!(third.unwrap().output, Ok(json!(1)));
+        }
+
+        #[tokio::test]
+        async fn errors_are_not_cached() {
+            let router = router(RpcContext::for_tests());
+
+            for id in 0..2 {
+                router.run_request(&request("failing", id)).await.unwrap();
+            }
+
+            assert_eq!(FAILING_CALLS.load(Ordering::Relaxed), 2);
+        }
+
+        #[tokio::test]
+        async fn calls_with_params_are_not_cached() {
+            async fn echo(input: Value) -> Result<Value, RpcError> {
+                Ok(input)
+            }
+
+            let router = RpcRouter::builder("vTEST")
+                .register("echo", echo)
+                .build(RpcContext::for_tests())
+                .with_head_cache(HashSet::from(["echo".to_owned()]));
+
+            for value in [1, 2] {
+                let request =
+                    json!({"jsonrpc": "2.0", "method": "echo", "params": [value], "id": 1});
+                let response = router.run_request(&request.to_string()).await.unwrap();
+                assert_eq!(response.output, Ok(json!([value])));
+            }
+        }
+    }
+
+    mod batch_timeout {
+        use super::*;
+
+        const TIMEOUT: Duration = Duration::from_millis(200);
+
+        async fn fast() -> Result<Value, RpcError> {
+            Ok(json!("fast"))
+        }
+
+        async fn slow() -> Result<Value, RpcError> {
+            tokio::time::sleep(TIMEOUT * 50).await;
+            Ok(json!("slow"))
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("fast", fast)
+                .register("slow", slow)
+                .build(RpcContext::for_tests())
+                .with_batch_timeout(TIMEOUT)
+        }
+
+        const TIMED_OUT: i32 = -32003;
+
+        #[tokio::test]
+        async fn completed_responses_are_returned() {
+            let response = serve_and_query(
+                router(),
+                json!([
+                    {"jsonrpc": "2.0", "method": "fast", "id": 1},
+                    {"jsonrpc": "2.0", "method": "slow", "id": 2},
+                    {"jsonrpc": "2.0", "method": "slow"},
+                    {"jsonrpc": "2.0", "method": "fast", "id": "3"},
+                ]),
+            )
+            .await;
+
+            // The last request is not executed, as the deadline has elapsed by then.
+            assert_eq!(
+                response,
+                json!([
+                    {"jsonrpc": "2.0", "result": "fast", "id": 1},
+                    {"jsonrpc": "2.0", "error": {"code": TIMED_OUT, "message": "Request timed out"}, "id": 2},
+                    {"jsonrpc": "2.0", "error": {"code": TIMED_OUT, "message": "Request timed out"}, "id": "3"},
+                ])
+            );
+        }
+
+        #[tokio::test]
+        async fn single_requests_are_not_limited() {
+            let router = RpcRouter::builder("vTEST")
+                .register("fast", fast)
+                .build(RpcContext::for_tests())
+                .with_batch_timeout(Duration::ZERO);
+
+            let response =
+                serve_and_query(router, json!({"jsonrpc": "2.0", "method": "fast", "id": 1})).await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "result": "fast", "id": 1})
+            );
+        }
+    }
+
+    mod request_timeout {
+        use super::*;
+        use starknet_gateway_client::GatewayApi;
+        use tokio::io::AsyncReadExt;
+
+        #[tokio::test]
+        async fn aborts_gateway_requests() {
+            // Reads the gateway request, but never responds.
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let gateway = tokio::spawn(async move {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 1024];
+                while matches!(socket.read(&mut buf).await, Ok(n) if n > 0) {}
+            });
+
+            async fn addresses(context: RpcContext) -> Result<Value, RpcError> {
+                context
+                    .sequencer
+                    .eth_contract_addresses()
+                    .await
+                    .map(|_| json!("addresses"))
+                    .map_err(|e| RpcError::InternalError(e.into()))
+            }
+
+            let sequencer = starknet_gateway_client::Client::with_base_url(
+                format!("http://{addr}").parse().unwrap(),
+            )
+            .unwrap()
+            .disable_retry_for_tests();
+            let context = RpcContext {
+                sequencer,
+                ..RpcContext::for_tests()
+            };
+            let router = RpcRouter::builder("vTEST")
+                .register("addresses", addresses)
+                .build(context)
+                .with_request_timeout(Duration::from_millis(100));
+
+            let response = serve_and_query(
+                router,
+                json!({"jsonrpc": "2.0", "method": "addresses", "id": 1}),
+            )
+            .await;
+            assert_eq!(response["error"]["code"], json!(-32603));
+
+            // The gateway connection is closed, instead of being left to run in the background.
+            tokio::time::timeout(Duration::from_secs(5), gateway)
+                .await
+                .expect("Gateway request was aborted")
+                .unwrap();
+        }
+    }
+
+    mod scopes {
+        use super::*;
+        use rstest::rstest;
+
+        fn scoped_router() -> RpcRouter {
+            fn always_success() -> &'static str {
+                "Success"
+            }
+
+            RpcRouter::builder("vTEST")
+                .register("public", always_success)
+                .register_with(
+                    "write",
+                    always_success,
+                    MethodOptions::default().scope(Scope::Write),
+                )
+                .build(RpcContext::for_tests())
+                .with_api_keys(Arc::new(
+                    ApiKeys::default()
+                        .with_key("writer", [Scope::Write])
+                        .with_key("reader", [Scope::Public]),
+                ))
+        }
+
+        async fn call(method: &str, api_key: Option<&str>) -> Value {
+            let url = spawn_server(scoped_router()).await;
+
+            let request = reqwest::Client::new()
+                .post(url)
+                .json(&json!({"jsonrpc": "2.0", "method": method, "id": 1}));
+            let request = match api_key {
+                Some(key) => request.bearer_auth(key),
+                None => request,
+            };
+
+            request.send().await.unwrap().json().await.unwrap()
+        }
+
+        #[rstest]
+        #[case::public_without_key("public", None)]
+        #[case::public_with_key("public", Some("reader"))]
+        #[case::scoped_with_granting_key("write", Some("writer"))]
+        #[tokio::test]
+        async fn allowed(#[case] method: &str, #[case] api_key: Option<&str>) {
+            let response = call(method, api_key).await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "result": "Success", "id": 1})
+            );
+        }
+
+        #[rstest]
+        #[case::without_key(None)]
+        #[case::with_key_lacking_scope(Some("reader"))]
+        #[case::with_unknown_key(Some("unknown"))]
+        #[tokio::test]
+        async fn denied(#[case] api_key: Option<&str>) {
+            let response = call("write", api_key).await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32001, "message": "Unauthorized"}, "id": 1})
+            );
+        }
+
+        #[tokio::test]
+        async fn denied_without_api_keys() {
+            fn always_success() -> &'static str {
+                "Success"
+            }
+
+            let router = RpcRouter::builder("vTEST")
+                .register_with(
+                    "write",
+                    always_success,
+                    MethodOptions::default().scope(Scope::Write),
+                )
+                .build(RpcContext::for_tests());
+
+            let response = serve_and_query(
+                router,
+                json!({"jsonrpc": "2.0", "method": "write", "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32001, "message": "Unauthorized"}, "id": 1})
+            );
+        }
+    }
+
+    mod authenticator {
+        use super::*;
+        use crate::jsonrpc::{Principal, Unauthenticated};
+
+        /// Accepts requests carrying the `x-token` header, and rejects all others.
+        struct TokenAuth;
+
+        impl Authenticator for TokenAuth {
+            fn authenticate(
+                &self,
+                request: &axum::http::Request<axum::body::Body>,
+            ) -> Result<Principal, Unauthenticated> {
+                match request.headers().get("x-token") {
+                    Some(token) if token == "secret" => Ok(Principal {
+                        name: Some("operator".to_owned()),
+                        scopes: [Scope::Write].into(),
+                    }),
+                    _ => Err(Unauthenticated),
+                }
+            }
+        }
+
+        async fn whoami(context: RpcContext) -> Result<Value, RpcError> {
+            Ok(json!(context.principal.name))
+        }
+
+        async fn call(token: Option<&str>) -> reqwest::Response {
+            let router = RpcRouter::builder("vTEST")
+                .register_with(
+                    "whoami",
+                    whoami,
+                    MethodOptions::default().scope(Scope::Write),
+                )
+                .build(RpcContext::for_tests())
+                .with_authenticator(Arc::new(TokenAuth));
+            let url = spawn_server(router).await;
+
+            let request = reqwest::Client::new()
+                .post(url)
+                .json(&json!({"jsonrpc": "2.0", "method": "whoami", "id": 1}));
+            let request = match token {
+                Some(token) => request.header("x-token", token),
+                None => request,
+            };
+
+            request.send().await.unwrap()
+        }
+
+        #[tokio::test]
+        async fn accepted() {
+            let response = call(Some("secret")).await;
+
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.json::<Value>().await.unwrap(),
+                json!({"jsonrpc": "2.0", "result": "operator", "id": 1})
+            );
+        }
+
+        #[rstest::rstest]
+        #[case::without_token(None)]
+        #[case::with_wrong_token(Some("guess"))]
+        #[tokio::test]
+        async fn rejected(#[case] token: Option<&str>) {
+            let response = call(token).await;
+
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    mod notification_methods {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Separate counters, since tests run concurrently.
+        static NOTIFIED: AtomicUsize = AtomicUsize::new(0);
+        static CALLED_WITH_ID: AtomicUsize = AtomicUsize::new(0);
+
+        async fn notify(_ctx: RpcContext) -> Result<(), RpcError> {
+            NOTIFIED.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        async fn call_with_id(_ctx: RpcContext) -> Result<(), RpcError> {
+            CALLED_WITH_ID.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        async fn fail(_ctx: RpcContext) -> Result<(), RpcError> {
+            Err(RpcError::InternalError(anyhow::anyhow!("Failure")))
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register_with("notify", notify, MethodOptions::default().notification())
+                .register_with(
+                    "call_with_id",
+                    call_with_id,
+                    MethodOptions::default().notification(),
+                )
+                .register_with("fail", fail, MethodOptions::default().notification())
+                .build(RpcContext::for_tests())
+        }
+
+        #[tokio::test]
+        async fn is_executed_without_response() {
+            let url = spawn_server(router()).await;
+
+            let res = reqwest::Client::new()
+                .post(url.clone())
+                .json(&json!([
+                    {"jsonrpc": "2.0", "method": "notify"},
+                    {"jsonrpc": "2.0", "method": "fail"},
+                ]))
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(res.content_length(), Some(0));
+            assert_eq!(NOTIFIED.load(Ordering::Relaxed), 1);
+        }
+
+        #[tokio::test]
+        async fn responds_when_called_with_id() {
+            let response = serve_and_query(
+                router(),
+                json!({"jsonrpc": "2.0", "method": "call_with_id", "id": 1}),
+            )
+            .await;
+
+            let expected = json!({"jsonrpc": "2.0", "result": null, "id": 1});
+            assert_eq!(response, expected);
+            assert_eq!(CALLED_WITH_ID.load(Ordering::Relaxed), 1);
+        }
+    }
+
+    mod registration {
+        use super::*;
+
+        async fn method() -> Result<(), RpcError> {
+            Ok(())
+        }
+
+        #[test]
+        #[should_panic(expected = "Method duplicate is registered more than once")]
+        fn duplicate_method() {
+            let _ = RpcRouter::builder("vTEST")
+                .register("duplicate", method)
+                .register("other", method)
+                .register("duplicate", method);
+        }
+
+        #[test]
+        #[should_panic(expected = "Method duplicate is registered more than once")]
+        fn duplicate_notification() {
+            let _ = RpcRouter::builder("vTEST")
+                .register("duplicate", method)
+                .register_with("duplicate", method, MethodOptions::default().notification());
+        }
+    }
+
+    mod ndjson {
+        use super::*;
+
+        async fn echo(input: Value) -> Result<Value, RpcError> {
+            Ok(input)
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("echo", echo)
+                .build(RpcContext::for_tests())
+        }
+
+        async fn query(router: RpcRouter, request: Value) -> reqwest::Response {
+            let url = spawn_server(router).await;
+            reqwest::Client::new()
+                .post(url)
+                .header(reqwest::header::ACCEPT, NDJSON)
+                .json(&request)
+                .send()
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn streams_batch_responses_as_lines() {
+            let res = query(
+                router(),
+                json!([
+                    {"jsonrpc": "2.0", "method": "echo", "params": [1], "id": 1},
+                    {"jsonrpc": "2.0", "method": "echo", "params": [2]},
+                    {"jsonrpc": "2.0", "method": "unknown", "id": 3},
+                    {"jsonrpc": "2.0", "method": "echo", "params": [4], "id": 4},
+                ]),
+            )
+            .await;
+
+            assert_eq!(res.headers()[reqwest::header::CONTENT_TYPE], NDJSON);
+
+            let body = res.bytes().await.unwrap();
+
+            let responses = std::str::from_utf8(&body)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<Value>(line).unwrap())
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                responses,
+                vec![
+                    json!({"jsonrpc": "2.0", "result": [1], "id": 1}),
+                    json!({"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": 3}),
+                    json!({"jsonrpc": "2.0", "result": [4], "id": 4}),
+                ]
+            );
+            assert!(body.ends_with(b"\n"));
+        }
+
+        #[tokio::test]
+        async fn ends_once_size_limit_is_exceeded() {
+            let res = query(
+                router().with_max_batch_response_size(60),
+                json!([
+                    {"jsonrpc": "2.0", "method": "echo", "params": [1], "id": 1},
+                    {"jsonrpc": "2.0", "method": "echo", "params": [2], "id": 2},
+                    {"jsonrpc": "2.0", "method": "echo", "params": [3], "id": 3},
+                ]),
+            )
+            .await;
+
+            let body = res.text().await.unwrap();
+            let responses = body
+                .lines()
+                .map(|line| serde_json::from_str::<Value>(line).unwrap())
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                responses,
+                vec![
+                    json!({"jsonrpc": "2.0", "result": [1], "id": 1}),
+                    json!({"jsonrpc": "2.0", "error": {"code": -32002, "message": "Batch response too large", "data": {"limit": 60}}, "id": null}),
+                ]
+            );
+        }
+
+        #[tokio::test]
+        async fn request_timeout_bounds_stream() {
+            async fn slow() -> Result<Value, RpcError> {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                Ok(json!("slow"))
+            }
+
+            let router = RpcRouter::builder("vTEST")
+                .register("echo", echo)
+                .register("slow", slow)
+                .build(RpcContext::for_tests())
+                .with_request_timeout(Duration::from_millis(200));
+
+            let res = query(
+                router,
+                json!([
+                    {"jsonrpc": "2.0", "method": "slow", "id": 1},
+                    {"jsonrpc": "2.0", "method": "echo", "params": [2], "id": 2},
+                ]),
+            )
+            .await;
+
+            let body = res.text().await.unwrap();
+            let responses = body
+                .lines()
+                .map(|line| serde_json::from_str::<Value>(line).unwrap())
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                responses,
+                vec![
+                    json!({"jsonrpc": "2.0", "error": {"code": -32003, "message": "Request timed out"}, "id": 1}),
+                    json!({"jsonrpc": "2.0", "error": {"code": -32003, "message": "Request timed out"}, "id": 2}),
+                ]
+            );
+        }
+
+        #[tokio::test]
+        async fn only_notifications_produce_nothing() {
+            let res = query(
+                router(),
+                json!([{"jsonrpc": "2.0", "method": "echo", "params": [1]}]),
+            )
+            .await;
+
+            assert_eq!(res.text().await.unwrap(), "");
+        }
+
+        #[tokio::test]
+        async fn single_requests_are_not_streamed() {
+            let res = query(
+                router(),
+                json!({"jsonrpc": "2.0", "method": "echo", "params": [1], "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                res.json::<Value>().await.unwrap(),
+                json!({"jsonrpc": "2.0", "result": [1], "id": 1})
+            );
+        }
+    }
+
+    mod params_limits {
+        use super::*;
+
+        async fn echo(input: Value) -> Result<Value, RpcError> {
+            Ok(input)
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("echo", echo)
+                .build(RpcContext::for_tests())
+                .with_params_limits(ParamsLimits {
+                    max_depth: 4,
+                    max_elements: 10,
+                })
+        }
+
+        #[tokio::test]
+        async fn within_limits() {
+            let params = json!({"filter": {"keys": [["0x1", "0x2"]], "chunk_size": 10}});
+            let response = serve_and_query(
+                router(),
+                json!({"jsonrpc": "2.0", "method": "echo", "params": params, "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "result": params, "id": 1})
+            );
+        }
+
+        #[rstest::rstest]
+        #[case::too_deep(json!({"filter": {"keys": [[["0x1"]]]}}))]
+        #[case::too_many_elements(json!({"keys": (0..11).collect::<Vec<_>>()}))]
+        #[tokio::test]
+        async fn exceeded(#[case] params: Value) {
+            let response = serve_and_query(
+                router(),
+                json!({"jsonrpc": "2.0", "method": "echo", "params": params, "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32602, "message": "Invalid params"}, "id": 1})
+            );
+        }
+    }
+
+    mod reserved_methods {
+        use super::*;
+
+        async fn internal() -> Result<Value, RpcError> {
+            Ok(json!("internal"))
+        }
+
+        #[rstest::rstest]
+        #[case::specification("rpc.methods")]
+        #[case::subscribe("pathfinder_subscribe")]
+        #[case::unsubscribe("pathfinder_unsubscribe")]
+        #[tokio::test]
+        async fn are_not_found_even_if_registered(#[case] method: &'static str) {
+            let router = RpcRouter::builder("vTEST")
+                .register(method, internal)
+                .build(RpcContext::for_tests());
+
+            let response =
+                serve_and_query(router, json!({"jsonrpc": "2.0", "method": method, "id": 1})).await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": 1})
+            );
+        }
+    }
+
+    mod method_options {
+        use super::*;
+
+        #[test]
+        fn combine() {
+            let options = MethodOptions::default()
+                .strict()
+                .scope(Scope::Write)
+                .unlogged();
+
+            assert_eq!(options.unknown_fields, UnknownFields::Reject);
+            assert_eq!(options.scope, Scope::Write);
+            assert!(!options.access_log);
+            assert!(!options.notification);
+        }
+    }
+
+    mod unknown_fields {
+        use super::*;
+
+        /// Ignores unknown fields unless the method is registered as strict.
+        #[derive(Deserialize)]
+        struct Input {
+            value: u32,
+        }
+
+        async fn submit(input: Input) -> Result<u32, RpcError> {
+            Ok(input.value)
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register_with("strict", submit, MethodOptions::default().strict())
+                .register("lenient", submit)
+                .build(RpcContext::for_tests())
+        }
+
+        #[tokio::test]
+        async fn strict_method_rejects_extra_field() {
+            let response = serve_and_query(
+                router(),
+                json!({"jsonrpc": "2.0", "method": "strict", "params": {"value": 1, "extra": 2}, "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32602, "message": "Invalid params"}, "id": 1})
+            );
+        }
+
+        #[tokio::test]
+        async fn lenient_method_ignores_extra_field() {
+            let response = serve_and_query(
+                router(),
+                json!({"jsonrpc": "2.0", "method": "lenient", "params": {"value": 1, "extra": 2}, "id": 1}),
+            )
+            .await;
+
+            assert_eq!(response, json!({"jsonrpc": "2.0", "result": 1, "id": 1}));
+        }
+
+        #[rstest::rstest]
+        #[case::named(json!({"value": 1}))]
+        #[case::positional(json!([1]))]
+        #[tokio::test]
+        async fn strict_method_accepts_known_fields(#[case] params: Value) {
+            let response = serve_and_query(
+                router(),
+                json!({"jsonrpc": "2.0", "method": "strict", "params": params, "id": 1}),
+            )
+            .await;
+
+            assert_eq!(response, json!({"jsonrpc": "2.0", "result": 1, "id": 1}));
+        }
+    }
+
+    mod unstructured_params {
+        use super::*;
+
+        async fn echo(input: Value) -> Result<Value, RpcError> {
+            Ok(input)
+        }
+
+        async fn no_input() -> Result<Value, RpcError> {
+            Ok(json!("success"))
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("echo", echo)
+                .register("no_input", no_input)
+                .build(RpcContext::for_tests())
+        }
+
+        #[rstest::rstest]
+        #[case::number(json!(5))]
+        #[case::string(json!("string"))]
+        #[case::boolean(json!(true))]
+        #[tokio::test]
+        async fn are_rejected(#[case] params: Value, #[values("echo", "no_input")] method: &str) {
+            let response = serve_and_query(
+                router(),
+                json!({"jsonrpc": "2.0", "method": method, "params": params, "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32602, "message": "Invalid params"}, "id": 1})
+            );
+        }
+
+        #[tokio::test]
+        async fn null_is_omitted() {
+            let response = serve_and_query(
+                router(),
+                json!({"jsonrpc": "2.0", "method": "no_input", "params": null, "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "result": "success", "id": 1})
+            );
+        }
+    }
+
+    mod debug_notifications {
+        use super::*;
+
+        async fn echo(input: Value) -> Result<Value, RpcError> {
+            Ok(input)
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("echo", echo)
+                .build(RpcContext::for_tests())
+        }
+
+        #[tokio::test]
+        async fn disabled_by_default() {
+            let url = spawn_server(router()).await;
+
+            let res = reqwest::Client::new()
+                .post(url)
+                .json(&json!({"jsonrpc": "2.0", "method": "echo", "params": {"a": 1}}))
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(res.content_length(), Some(0));
+        }
+
+        #[tokio::test]
+        async fn responds_with_synthetic_id() {
+            let response = serve_and_query(
+                router().with_debug_notifications(),
+                json!([
+                    {"jsonrpc": "2.0", "method": "echo", "params": {"a": 1}},
+                    {"jsonrpc": "2.0", "method": "unknown"},
+                    {"jsonrpc": "2.0", "method": "echo", "params": {"b": 2}, "id": 1},
+                ]),
+            )
+            .await;
+
+            let expected = json!([
+                {"jsonrpc": "2.0", "result": {"a": 1}, "id": "debug-notification"},
+                {"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": "debug-notification"},
+                {"jsonrpc": "2.0", "result": {"b": 2}, "id": 1},
+            ]);
+            assert_eq!(response, expected);
+        }
+    }
+
+    mod strict_requests {
+        use super::*;
+
+        async fn first() -> Result<Value, RpcError> {
+            Ok(json!("first"))
+        }
+
+        async fn second() -> Result<Value, RpcError> {
+            Ok(json!("second"))
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("first", first)
+                .register("second", second)
+                .build(RpcContext::for_tests())
+        }
+
+        /// Written by hand, as serializing a [Value] cannot produce duplicate keys.
+        const REQUESTS: &str = r#"[
+            {"jsonrpc": "2.0", "method": "first", "id": 1, "id": 2},
+            {"jsonrpc": "2.0", "method": "first", "method": "second", "id": 3},
+            {"jsonrpc": "2.0", "method": "first", "id": 4}
+        ]"#;
+
+        async fn query(router: RpcRouter) -> Value {
+            let url = spawn_server(router).await;
+
+            reqwest::Client::new()
+                .post(url)
+                .body(REQUESTS)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .await
+                .unwrap()
+                .json::<Value>()
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn last_value_wins_by_default() {
+            let response = query(router()).await;
+
+            let expected = json!([
+                {"jsonrpc": "2.0", "result": "first", "id": 2},
+                {"jsonrpc": "2.0", "result": "second", "id": 3},
+                {"jsonrpc": "2.0", "result": "first", "id": 4},
+            ]);
+            assert_eq!(response, expected);
+        }
+
+        #[tokio::test]
+        async fn rejects_duplicate_keys() {
+            let response = query(router().with_strict_requests()).await;
+
+            let invalid_request = json!({"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null});
+            let expected = json!([
+                invalid_request,
+                invalid_request,
+                {"jsonrpc": "2.0", "result": "first", "id": 4},
+            ]);
+            assert_eq!(response, expected);
+        }
+    }
+
+    mod omit_null_fields {
+        use super::*;
+
+        #[derive(Serialize)]
+        struct Output {
+            status: &'static str,
+            block_hash: Option<&'static str>,
+            receipts: Vec<Receipt>,
+            parents: Vec<Option<&'static str>>,
+        }
+
+        #[derive(Serialize)]
+        struct Receipt {
+            transaction_hash: &'static str,
+            revert_reason: Option<&'static str>,
+        }
+
+        async fn pending_block() -> Result<Output, RpcError> {
+            Ok(Output {
+                status: "PENDING",
+                block_hash: None,
+                receipts: vec![Receipt {
+                    transaction_hash: "0x1",
+                    revert_reason: None,
+                }],
+                parents: vec![None],
+            })
+        }
+
+        async fn query(router: RpcRouter) -> Value {
+            serve_and_query(
+                router,
+                json!({"jsonrpc": "2.0", "method": "pending_block", "id": 1}),
+            )
+            .await
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("pending_block", pending_block)
+                .build(RpcContext::for_tests())
+        }
+
+        #[tokio::test]
+        async fn serialized_as_null_by_default() {
+            let response = query(router()).await;
+
+            assert_eq!(
+                response["result"],
+                json!({
+                    "status": "PENDING",
+                    "block_hash": null,
+                    "receipts": [{"transaction_hash": "0x1", "revert_reason": null}],
+                    "parents": [null],
+                })
+            );
+        }
+
+        #[tokio::test]
+        async fn omitted() {
+            let response = query(router().with_omitted_null_fields()).await;
+
+            assert_eq!(
+                response["result"],
+                json!({
+                    "status": "PENDING",
+                    "receipts": [{"transaction_hash": "0x1"}],
+                    "parents": [null],
+                })
+            );
+        }
+    }
+
+    mod method_in_errors {
+        use super::*;
+
+        async fn echo(input: Value) -> Result<Value, RpcError> {
+            Ok(input)
+        }
+
+        /// Fails with the given `data`.
+        async fn fails(input: (Value,)) -> Result<Value, RpcError> {
+            Err(RpcError::ApplicationError {
+                code: 1,
+                message: "Failed".to_owned(),
+                data: Some(input.0),
+            })
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("echo", echo)
+                .register("fails", fails)
+                .build(RpcContext::for_tests())
+        }
+
+        #[tokio::test]
+        async fn disabled_by_default() {
+            let response = serve_and_query(
+                router(),
+                json!({"jsonrpc": "2.0", "method": "echo", "params": 5, "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32602, "message": "Invalid params"}, "id": 1})
+            );
+        }
+
+        #[tokio::test]
+        async fn method_is_added() {
+            let response = serve_and_query(
+                router().with_method_in_errors(),
+                json!({"jsonrpc": "2.0", "method": "echo", "params": 5, "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32602, "message": "Invalid params", "data": {"method": "echo"}}, "id": 1})
+            );
+        }
+
+        #[rstest::rstest]
+        #[case::object(json!({"reason": "x"}), json!({"reason": "x", "method": "fails"}))]
+        #[case::existing_method_field(json!({"method": "other"}), json!({"method": "other"}))]
+        #[case::not_an_object(json!(["x"]), json!(["x"]))]
+        #[tokio::test]
+        async fn method_is_merged_into_data(#[case] data: Value, #[case] expected: Value) {
+            let response = serve_and_query(
+                router().with_method_in_errors(),
+                json!({"jsonrpc": "2.0", "method": "fails", "params": [data], "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": 1, "message": "Failed", "data": expected}, "id": 1})
+            );
+        }
+
+        #[tokio::test]
+        async fn unknown_method_is_not_named() {
+            let response = serve_and_query(
+                router().with_method_in_errors(),
+                json!({"jsonrpc": "2.0", "method": "unknown", "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": 1})
+            );
+        }
+    }
+
+    mod localization {
+        use super::*;
+        use crate::jsonrpc::{Localization, MessageTable};
+
+        async fn query(accept_language: Option<&str>, body: &str) -> Value {
+            let router = RpcRouter::builder("vTEST")
+                .build(RpcContext::for_tests())
+                .with_localization(
+                    Localization::default().with_language(
+                        "de",
+                        MessageTable::default()
+                            .with_message(-32601, "Methode nicht gefunden")
+                            .with_message(-32700, "Syntaxfehler"),
+                    ),
+                );
+            let url = spawn_server(router).await;
+
+            let request = reqwest::Client::new()
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_owned());
+            let request = match accept_language {
+                Some(language) => request.header(reqwest::header::ACCEPT_LANGUAGE, language),
+                None => request,
+            };
+
+            request.send().await.unwrap().json().await.unwrap()
+        }
+
+        const UNKNOWN_METHOD: &str = r#"{"jsonrpc": "2.0", "method": "unknown", "id": 1}"#;
+
+        #[tokio::test]
+        async fn localized_message() {
+            let response = query(Some("de-CH, en;q=0.5"), UNKNOWN_METHOD).await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32601, "message": "Methode nicht gefunden"}, "id": 1})
+            );
+        }
+
+        #[rstest::rstest]
+        #[case::unsupported_language(Some("fr"))]
+        #[case::english_preferred(Some("en, de;q=0.5"))]
+        #[case::no_header(None)]
+        #[tokio::test]
+        async fn falls_back_to_english(#[case] accept_language: Option<&str>) {
+            let response = query(accept_language, UNKNOWN_METHOD).await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": 1})
+            );
+        }
+
+        #[tokio::test]
+        async fn batch_and_parse_errors_are_localized() {
+            let batch = format!("[{UNKNOWN_METHOD}]");
+            let response = query(Some("de"), &batch).await;
+            assert_eq!(
+                response,
+                json!([{"jsonrpc": "2.0", "error": {"code": -32601, "message": "Methode nicht gefunden"}, "id": 1}])
+            );
+
+            let response = query(Some("de"), "{").await;
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "error": {"code": -32700, "message": "Syntaxfehler"}, "id": null})
+            );
+        }
+    }
+
+    mod server_timing {
+        use super::*;
+
+        async fn query(router: RpcRouter, body: Value) -> reqwest::Response {
+            let url = spawn_server(router).await;
+
+            reqwest::Client::new()
+                .post(url)
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+        }
+
+        async fn echo(input: Value) -> Result<Value, RpcError> {
+            Ok(input)
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("echo", echo)
+                .build(RpcContext::for_tests())
+        }
+
+        fn phases(response: &reqwest::Response) -> Vec<String> {
+            response.headers()["server-timing"]
+                .to_str()
+                .unwrap()
+                .split(", ")
+                .map(|metric| metric.split_once(";dur=").unwrap().0.to_owned())
+                .collect()
+        }
+
+        #[tokio::test]
+        async fn disabled_by_default() {
+            let request = json!({"jsonrpc": "2.0", "method": "echo", "params": [1], "id": 1});
+            let response = query(router(), request).await;
+
+            assert_eq!(response.headers().get("server-timing"), None);
+        }
+
+        #[tokio::test]
+        async fn single_request() {
+            let request = json!({"jsonrpc": "2.0", "method": "echo", "params": [1], "id": 1});
+            let response = query(router().with_server_timing(), request).await;
+
+            assert_eq!(
+                phases(&response),
+                ["read", "parse", "dispatch", "serialize"]
+            );
+            // The body remains a regular JSON-RPC response.
+            let body = response.json::<Value>().await.unwrap();
+            assert_eq!(body, json!({"jsonrpc": "2.0", "result": [1], "id": 1}));
+        }
+
+        #[tokio::test]
+        async fn batch_request() {
+            let request = json!([
+                {"jsonrpc": "2.0", "method": "echo", "params": [1], "id": 1},
+                {"jsonrpc": "2.0", "method": "echo", "params": [2], "id": 2},
+            ]);
+            let response = query(router().with_server_timing(), request).await;
+
+            assert_eq!(
+                phases(&response),
+                ["read", "parse", "dispatch", "serialize"]
+            );
+        }
+
+        #[tokio::test]
+        async fn parse_error() {
+            let url = spawn_server(router().with_server_timing()).await;
+            let response = reqwest::Client::new()
+                .post(url)
+                .header("content-type", "application/json")
+                .body("{")
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(phases(&response), ["read"]);
+        }
+    }
+
+    mod version_header {
+        use super::*;
+
+        async fn query(router: RpcRouter) -> reqwest::Response {
+            let url = spawn_server(router).await;
+
+            reqwest::Client::new()
+                .post(url)
+                .json(&json!({"jsonrpc": "2.0", "method": "unknown", "id": 1}))
+                .send()
+                .await
+                .unwrap()
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST").build(RpcContext::for_tests())
+        }
+
+        #[tokio::test]
+        async fn disabled_by_default() {
+            let response = query(router()).await;
+
+            assert_eq!(response.headers().get(VERSION_HEADER), None);
+        }
+
+        #[tokio::test]
+        async fn reports_router_version() {
+            let response = query(router().with_version_header()).await;
+
+            assert_eq!(response.headers()[VERSION_HEADER], "vTEST");
+            // The body remains a regular JSON-RPC response.
+            let body = response.json::<Value>().await.unwrap();
+            assert_eq!(
+                body,
+                json!({"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": 1})
+            );
+        }
+    }
+
+    mod default_block_tag {
+        use super::*;
+        use pathfinder_common::BlockId;
+
+        #[derive(serde::Deserialize)]
+        struct BlockInput {
+            #[allow(dead_code)]
+            value: u32,
+            block_id: BlockId,
+        }
+
+        async fn block(input: BlockInput) -> Result<String, RpcError> {
+            Ok(format!("{:?}", input.block_id))
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register_with("block", block, MethodOptions::default().default_block_id(1))
+                .register("block_strict", block)
+                .build(RpcContext::for_tests())
+        }
+
+        fn requests() -> Value {
+            json!([
+                {"jsonrpc": "2.0", "method": "block", "params": {"value": 1}, "id": 0},
+                {"jsonrpc": "2.0", "method": "block", "params": [1], "id": 1},
+                {"jsonrpc": "2.0", "method": "block", "params": [1, "latest"], "id": 2},
+                {"jsonrpc": "2.0", "method": "block", "params": {"value": 1, "block_id": {"block_number": 3}}, "id": 3},
+                {"jsonrpc": "2.0", "method": "block_strict", "params": [1], "id": 4},
+            ])
+        }
+
+        #[tokio::test]
+        async fn strict_by_default() {
+            let response = serve_and_query(router(), requests()).await;
+
+            let invalid_params = json!({"code": -32602, "message": "Invalid params"});
+            let expected = json!([
+                {"jsonrpc": "2.0", "error": invalid_params, "id": 0},
+                {"jsonrpc": "2.0", "error": invalid_params, "id": 1},
+                {"jsonrpc": "2.0", "result": "Latest", "id": 2},
+                {"jsonrpc": "2.0", "result": "Number(BlockNumber(3))", "id": 3},
+                {"jsonrpc": "2.0", "error": invalid_params, "id": 4},
+            ]);
+            assert_eq!(response, expected);
+        }
+
+        #[tokio::test]
+        async fn supplies_omitted_block_id() {
+            let router = router().with_default_block_tag(BlockTag::Pending);
+            let response = serve_and_query(router, requests()).await;
+
+            let expected = json!([
+                {"jsonrpc": "2.0", "result": "Pending", "id": 0},
+                {"jsonrpc": "2.0", "result": "Pending", "id": 1},
+                {"jsonrpc": "2.0", "result": "Latest", "id": 2},
+                {"jsonrpc": "2.0", "result": "Number(BlockNumber(3))", "id": 3},
+                {"jsonrpc": "2.0", "error": {"code": -32602, "message": "Invalid params"}, "id": 4},
+            ]);
+            assert_eq!(response, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn version_is_recorded_in_span() {
+        use std::sync::Mutex;
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+
+        /// Captures the `version` field of all new spans.
+        #[derive(Clone, Default)]
+        struct VersionCapture(Arc<Mutex<Vec<String>>>);
+
+        impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for VersionCapture {
+            fn on_new_span(
+                &self,
+                attrs: &tracing::span::Attributes<'_>,
+                _id: &tracing::span::Id,
+                _ctx: Context<'_, S>,
+            ) {
+                attrs.record(
+                    &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+                        if field.name() == "version" {
+                            self.0.lock().unwrap().push(format!("{value:?}"));
+                        }
+                    },
+                );
+            }
+        }
+
+        fn always_success() -> &'static str {
+            "Success"
+        }
+
+        let capture = VersionCapture::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+        let router = RpcRouter::builder("vTEST")
+            .register("success", always_success)
+            .build(RpcContext::for_tests());
+        serve_and_query(
+            router,
+            json!({"jsonrpc": "2.0", "method": "success", "id": 1}),
+        )
+        .await;
+
+        assert_eq!(*capture.0.lock().unwrap(), vec!["\"vTEST\"".to_owned()]);
+    }
+
+    mod get_handler {
+        use super::*;
+
+        #[tokio::test]
+        async fn response_headers_match_post() {
+            fn chain_id() -> &'static str {
+                "0x1"
+            }
+
+            let router = RpcRouter::builder("vTEST")
+                .register("chain_id", chain_id)
+                .build(RpcContext::for_tests())
+                .with_version_header()
+                .with_server_timing();
+
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async {
+                let router = axum::Router::new()
+                    .route("/", axum::routing::get(rpc_get_handler))
+                    .with_state(router);
+                axum::Server::from_tcp(listener)
+                    .unwrap()
+                    .serve(router.into_make_service())
+                    .await
+            });
+
+            let response = reqwest::Client::new()
+                .get(format!("http://{addr}/?method=chain_id"))
+                .header(REQUEST_ID_HEADER, "abc-123")
+                .send()
+                .await
+                .unwrap();
+
+            assert_eq!(response.headers()[REQUEST_ID_HEADER], "abc-123");
+            assert_eq!(response.headers()[VERSION_HEADER], "vTEST");
+            assert!(response.headers().contains_key("server-timing"));
+            assert_eq!(
+                response.json::<Value>().await.unwrap(),
+                json!({"jsonrpc": "2.0", "result": "0x1", "id": 0})
+            );
+        }
+    }
+
+    mod request_id {
+        use super::*;
+        use std::sync::Mutex;
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+
+        async fn query(request_id: Option<&str>) -> reqwest::Response {
+            let url =
+                spawn_server(RpcRouter::builder("vTEST").build(RpcContext::for_tests())).await;
+
+            let request = reqwest::Client::new()
+                .post(url)
+                .json(&json!({"jsonrpc": "2.0", "method": "unknown", "id": 1}));
+            let request = match request_id {
+                Some(request_id) => request.header(REQUEST_ID_HEADER, request_id),
+                None => request,
+            };
+            request.send().await.unwrap()
+        }
+
+        #[tokio::test]
+        async fn is_echoed() {
+            let response = query(Some("abc-123")).await;
+
+            assert_eq!(response.headers()[REQUEST_ID_HEADER], "abc-123");
+        }
+
+        #[tokio::test]
+        async fn is_generated_if_missing() {
+            let response = query(None).await;
+
+            let request_id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+            uuid::Uuid::parse_str(request_id).unwrap();
+        }
+
+        #[tokio::test]
+        async fn oversized_is_replaced() {
+            let oversized = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+            let response = query(Some(&oversized)).await;
+
+            let request_id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+            uuid::Uuid::parse_str(request_id).unwrap();
+        }
+
+        #[tokio::test]
+        async fn is_recorded_in_span() {
+            /// Captures the `request_id` field of all new spans.
+            #[derive(Clone, Default)]
+            struct RequestIdCapture(Arc<Mutex<Vec<String>>>);
+
+            impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for RequestIdCapture {
+                fn on_new_span(
+                    &self,
+                    attrs: &tracing::span::Attributes<'_>,
+                    _id: &tracing::span::Id,
+                    _ctx: Context<'_, S>,
+                ) {
+                    attrs.record(
+                        &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+                            if field.name() == "request_id" {
+                                self.0.lock().unwrap().push(format!("{value:?}"));
+                            }
+                        },
+                    );
+                }
+            }
+
+            let capture = RequestIdCapture::default();
+            let _guard = tracing::subscriber::set_default(
+                tracing_subscriber::registry().with(capture.clone()),
+            );
+
+            query(Some("abc-123")).await;
+
+            assert_eq!(*capture.0.lock().unwrap(), vec!["abc-123".to_owned()]);
+        }
+    }
+
+    mod body_size {
+        use super::*;
+
+        #[tokio::test]
+        async fn oversized_content_length_is_rejected_before_reading_body() {
+            use std::io::{Read, Write};
+
+            let router = RpcRouter::builder("vTEST")
+                .build(RpcContext::for_tests())
+                .with_max_body_size(1024);
+            let url = spawn_server(router).await;
+            let addr = url.trim_start_matches("http://").to_owned();
+
+            // Only the headers are sent, so the request would stall if the server attempted to
+            // read the body.
+            let response = tokio::task::spawn_blocking(move || {
+                let mut stream = std::net::TcpStream::connect(&addr).unwrap();
+                stream
+                    .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+                    .unwrap();
+                write!(
+                    stream,
+                    "POST / HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: 1000000\r\n\r\n"
+                )
+                .unwrap();
+
+                let mut response = vec![0; 64];
+                let read = stream
+                    .read(&mut response)
+                    .expect("Response should not wait for the body");
+                String::from_utf8_lossy(&response[..read]).into_owned()
+            })
+            .await
+            .unwrap();
+
+            assert!(
+                response.starts_with("HTTP/1.1 413"),
+                "Unexpected response: {response}"
+            );
+        }
+
+        #[tokio::test]
+        async fn content_length_within_limit_is_served() {
+            fn always_success() -> &'static str {
+                "Success"
+            }
+
+            let router = RpcRouter::builder("vTEST")
+                .register("success", always_success)
+                .build(RpcContext::for_tests())
+                .with_max_body_size(1024);
+
+            let response = serve_and_query(
+                router,
+                json!({"jsonrpc": "2.0", "method": "success", "id": 1}),
+            )
+            .await;
+
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "result": "Success", "id": 1})
+            );
+        }
+    }
+
+    mod body_read_timeout {
+        use super::*;
+
+        #[tokio::test]
+        async fn stalled_body_is_aborted() {
+            use std::io::{Read, Write};
+
+            let router = RpcRouter::builder("vTEST")
+                .build(RpcContext::for_tests())
+                .with_body_read_timeout(Duration::from_millis(100));
+            let url = spawn_server(router).await;
+            let addr = url.trim_start_matches("http://").to_owned();
+
+            // Only part of the body is sent, after which the client stalls.
+            let response = tokio::task::spawn_blocking(move || {
+                let mut stream = std::net::TcpStream::connect(&addr).unwrap();
+                stream
+                    .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+                    .unwrap();
+                write!(
+                    stream,
+                    "POST / HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: 100\r\n\r\n{{\"jsonrpc\""
+                )
+                .unwrap();
+
+                let mut response = vec![0; 256];
+                let read = stream
+                    .read(&mut response)
+                    .expect("Response should not wait for the rest of the body");
+                String::from_utf8_lossy(&response[..read]).into_owned()
+            })
+            .await
+            .unwrap();
+
+            assert!(
+                response.starts_with("HTTP/1.1 400"),
+                "Unexpected response: {response}"
+            );
+            assert!(
+                response.contains("Timed out reading the request body"),
+                "Unexpected response: {response}"
+            );
+        }
+    }
+
+    mod invalid_utf8 {
+        use super::*;
+
+        #[tokio::test]
+        async fn is_reported_as_encoding_problem() {
+            let url =
+                spawn_server(RpcRouter::builder("vTEST").build(RpcContext::for_tests())).await;
+
+            // A latin-1 encoded `é`, which is not valid UTF-8.
+            let body = b"{\"jsonrpc\": \"2.0\", \"method\": \"caf\xe9\", \"id\": 1}".to_vec();
+            let response = reqwest::Client::new()
+                .post(url)
+                .body(body)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .await
+                .unwrap()
+                .json::<Value>()
+                .await
+                .unwrap();
+
+            assert_eq!(
+                response,
+                json!({
+                    "jsonrpc": "2.0",
+                    "error": {
+                        "code": -32700,
+                        "message": "Parse error",
+                        "data": {"reason": "INVALID_UTF8", "valid_up_to": 33},
+                    },
+                    "id": null,
+                })
+            );
+        }
+    }
+
+    mod client_disconnect {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        const CALL: Duration = Duration::from_millis(50);
+        const BATCH_SIZE: usize = 20;
+
+        static EXECUTED: AtomicUsize = AtomicUsize::new(0);
+
+        async fn counted(_ctx: RpcContext) -> RpcResult {
+            EXECUTED.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(CALL).await;
+            Ok(json!("counted"))
+        }
+
+        #[tokio::test]
+        async fn remaining_batch_requests_are_not_executed() {
+            use std::io::Write;
+
+            let router = RpcRouter::builder("vTEST")
+                .register("counted", counted)
+                .build(RpcContext::for_tests());
+            let url = spawn_server(router).await;
+            let addr = url.trim_start_matches("http://").to_owned();
+
+            let batch = (0..BATCH_SIZE)
+                .map(|id| json!({"jsonrpc": "2.0", "method": "counted", "id": id}))
+                .collect::<Vec<_>>();
+            let body = serde_json::to_string(&batch).unwrap();
+
+            tokio::task::spawn_blocking(move || {
+                let mut stream = std::net::TcpStream::connect(&addr).unwrap();
+                write!(
+                    stream,
+                    "POST / HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+
+                // Disconnect while the batch is executing.
+                std::thread::sleep(CALL * 3);
+                stream.shutdown(std::net::Shutdown::Both).unwrap();
+            })
+            .await
+            .unwrap();
+
+            let at_disconnect = EXECUTED.load(Ordering::SeqCst);
+            assert!(at_disconnect > 0, "Batch should be executing");
+
+            // Long enough for the whole batch to have executed.
+            tokio::time::sleep(CALL * BATCH_SIZE as u32).await;
+
+            let executed = EXECUTED.load(Ordering::SeqCst);
+            assert!(
+                executed <= at_disconnect + 1,
+                "{executed} requests executed, {at_disconnect} at disconnect"
+            );
+            assert!(executed < BATCH_SIZE);
+        }
+    }
+
+    mod method_schema {
+        use super::*;
+
+        #[derive(Deserialize, schemars::JsonSchema)]
+        struct Input {
+            #[allow(dead_code)]
+            value: u32,
+        }
+
+        async fn documented(_input: Input) -> Result<Value, RpcError> {
+            Ok(json!("documented"))
+        }
+
+        async fn undocumented(_input: Input) -> Result<Value, RpcError> {
+            Ok(json!("undocumented"))
+        }
+
+        async fn schema_of(method: &str) -> Value {
+            let router = RpcRouter::builder("vTEST")
+                .register_with(
+                    "documented",
+                    documented,
+                    MethodOptions::default().schema::<Input>(),
+                )
+                .register("undocumented", undocumented)
+                .register_method_schema("schema")
+                .build(RpcContext::for_tests());
+
+            serve_and_query(
+                router,
+                json!({"jsonrpc": "2.0", "method": "schema", "params": {"method": method}, "id": 1}),
+            )
+            .await
+        }
+
+        #[tokio::test]
+        async fn available() {
+            let response = schema_of("documented").await;
+
+            let schema = &response["result"];
+            assert_eq!(schema["type"], json!("object"));
+            assert_eq!(schema["required"], json!(["value"]));
+            assert_eq!(schema["properties"]["value"]["type"], json!("integer"));
+        }
+
+        #[tokio::test]
+        async fn unavailable() {
+            let response = schema_of("undocumented").await;
+
+            assert_eq!(response["result"], Value::Null);
+            assert!(response.get("error").is_none());
+        }
+
+        #[tokio::test]
+        async fn own_schema() {
+            let response = schema_of("schema").await;
+
+            assert_eq!(response["result"]["required"], json!(["method"]));
+        }
+
+        #[tokio::test]
+        async fn unknown_method() {
+            let response = schema_of("unknown").await;
+
+            assert_eq!(
+                response["error"],
+                json!({"code": -32602, "message": "Invalid params"})
+            );
+        }
+    }
+
+    mod slow_requests {
+        use super::*;
+        use std::sync::Mutex;
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+
+        const THRESHOLD: Duration = Duration::from_millis(50);
+
+        /// Captures the `method` and `params` fields of all slow request warnings.
+        #[derive(Clone, Default)]
+        struct SlowCapture(Arc<Mutex<Vec<(String, String)>>>);
+
+        impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for SlowCapture {
+            fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+                let mut message = String::new();
+                let mut method = String::new();
+                let mut params = String::new();
+                event.record(
+                    &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| match field
+                        .name()
+                    {
+                        "message" => message = format!("{value:?}"),
+                        "method" => method = format!("{value:?}"),
+                        "params" => params = format!("{value:?}"),
+                        _ => {}
+                    },
+                );
+                if message == "Slow RPC request" {
+                    self.0.lock().unwrap().push((method, params));
+                }
+            }
+        }
+
+        #[derive(Deserialize)]
+        struct Input {
+            #[allow(dead_code)]
+            value: u32,
+        }
+
+        async fn slow(_input: Input) -> Result<Value, RpcError> {
+            tokio::time::sleep(THRESHOLD * 2).await;
+            Ok(json!("slow"))
+        }
+
+        async fn fast() -> Result<Value, RpcError> {
+            Ok(json!("fast"))
+        }
+
+        async fn query(router: RpcRouter) -> Vec<(String, String)> {
+            let capture = SlowCapture::default();
+            let _guard = tracing::subscriber::set_default(
+                tracing_subscriber::registry().with(capture.clone()),
+            );
+
+            serve_and_query(
+                router.clone(),
+                json!({"jsonrpc": "2.0", "method": "slow", "params": {"value": 1}, "id": 1}),
+            )
+            .await;
+            serve_and_query(router, json!({"jsonrpc": "2.0", "method": "fast", "id": 2})).await;
+
+            let captured = capture.0.lock().unwrap().clone();
+            captured
+        }
+
+        fn router() -> RpcRouter {
+            RpcRouter::builder("vTEST")
+                .register("slow", slow)
+                .register("fast", fast)
+                .build(RpcContext::for_tests())
+        }
+
+        #[tokio::test]
+        async fn only_slow_requests_are_logged() {
+            let logged = query(router().with_slow_request_threshold(THRESHOLD)).await;
+
+            assert_eq!(
+                logged,
+                vec![("slow".to_owned(), r#"{"value":1}"#.to_owned())]
+            );
+        }
+
+        #[tokio::test]
+        async fn disabled_by_default() {
+            let logged = query(router()).await;
+
+            assert!(logged.is_empty());
+        }
+
+        #[test]
+        fn params_summary_is_truncated() {
+            let raw = RawValue::from_string(r#"["ééé"]"#.to_owned()).unwrap();
+            let params = RawParams::from(raw.as_ref());
+
+            assert_eq!(params.summary(3), r#"["é..."#);
+            assert_eq!(params.summary(7), r#"["ééé"]"#);
+            assert_eq!(RawParams::default().summary(3), "");
+        }
+    }
 
-            #[axum::async_trait]
-            impl<F> RpcMethod for Helper<F>
-            where
-                F: Fn() -> &'static str + Sync + Send,
-            {
-                async fn invoke<'a>(&self, _state: RpcContext, input: RawParams<'a>) -> RpcResult {
-                    if !input.is_empty() {
-                        return Err(RpcError::InvalidParams);
-                    }
-                    let output = (self.f)();
-                    serde_json::to_value(output).map_err(|e| RpcError::InternalError(e.into()))
+    mod access_log {
+        use super::*;
+        use std::sync::Mutex;
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+
+        /// Captures the `method` field of all access log lines.
+        #[derive(Clone, Default)]
+        struct AccessCapture(Arc<Mutex<Vec<String>>>);
+
+        impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for AccessCapture {
+            fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+                let mut message = String::new();
+                let mut method = String::new();
+                event.record(
+                    &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| match field
+                        .name()
+                    {
+                        "message" => message = format!("{value:?}"),
+                        "method" => method = format!("{value:?}"),
+                        _ => {}
+                    },
+                );
+                if message == "RPC call" {
+                    self.0.lock().unwrap().push(method);
                 }
             }
-            Box::new(Helper { f: self })
         }
-    }
-}
-
-/// Handles invoking an RPC route's methods.
-#[async_trait]
-pub trait RpcMethodHandler {
-    async fn call_method(method: &str, state: RpcContext, params: Value) -> RpcResult;
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde::Deserialize;
-    use serde_json::json;
+        async fn block_number() -> Result<Value, RpcError> {
+            Ok(json!(1))
+        }
 
-    async fn spawn_server(router: RpcRouter) -> String {
-        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
-        let addr = listener.local_addr().unwrap();
-        let url = format!("http://127.0.0.1:{}", addr.port());
+        async fn add_transaction() -> Result<Value, RpcError> {
+            Ok(json!("0x1"))
+        }
 
-        tokio::spawn(async {
-            let router = axum::Router::new()
-                .route("/", axum::routing::post(rpc_handler))
-                .with_state(router);
-            axum::Server::from_tcp(listener)
-                .unwrap()
-                .serve(router.into_make_service())
-                .await
-        });
+        #[tokio::test]
+        async fn unlogged_methods_are_omitted() {
+            let router = RpcRouter::builder("vTEST")
+                .register_with(
+                    "block_number",
+                    block_number,
+                    MethodOptions::default().unlogged(),
+                )
+                .register("add_transaction", add_transaction)
+                .build(RpcContext::for_tests());
 
-        url
-    }
+            let capture = AccessCapture::default();
+            let _guard = tracing::subscriber::set_default(
+                tracing_subscriber::registry().with(capture.clone()),
+            );
 
-    /// Spawns an RPC server with the given router and queries it with the given request.
-    async fn serve_and_query(router: RpcRouter, request: Value) -> Value {
-        let url = spawn_server(router).await;
+            serve_and_query(
+                router.clone(),
+                json!({"jsonrpc": "2.0", "method": "block_number", "id": 1}),
+            )
+            .await;
+            serve_and_query(
+                router,
+                json!({"jsonrpc": "2.0", "method": "add_transaction", "id": 2}),
+            )
+            .await;
 
-        let client = reqwest::Client::new();
-        client
-            .post(url.clone())
-            .json(&request)
-            .send()
-            .await
-            .unwrap()
-            .json::<Value>()
-            .await
-            .unwrap()
+            let logged = capture.0.lock().unwrap().clone();
+            assert_eq!(logged, vec!["add_transaction".to_owned()]);
+        }
     }
 
-    mod specification_tests {
-        //! Test cases lifted directly from the [RPC specification](https://www.jsonrpc.org/specification).
+    mod large_responses {
         use super::*;
-        use pretty_assertions::assert_eq;
-        use rstest::rstest;
-        use serde_json::json;
+        use std::sync::Mutex;
+        use tracing_subscriber::layer::{Context, SubscriberExt};
 
-        fn spec_router() -> RpcRouter {
-            crate::error::generate_rpc_error_subset!(ExampleError:);
+        const THRESHOLD: usize = 1024;
 
-            #[derive(Debug, Deserialize, Serialize)]
-            struct SubtractInput {
-                minuend: i32,
-                subtrahend: i32,
-            }
-            async fn subtract(input: SubtractInput) -> Result<Value, ExampleError> {
-                Ok(Value::Number((input.minuend - input.subtrahend).into()))
-            }
+        /// Captures the `method` and `size` fields of all large response warnings.
+        #[derive(Clone, Default)]
+        struct LargeCapture(Arc<Mutex<Vec<(String, String)>>>);
 
-            #[derive(Debug, Deserialize, Serialize)]
-            struct SumInput(Vec<i32>);
-            async fn sum(input: SumInput) -> Result<Value, ExampleError> {
-                Ok(Value::Number((input.0.iter().sum::<i32>()).into()))
+        impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for LargeCapture {
+            fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+                let mut message = String::new();
+                let mut method = String::new();
+                let mut size = String::new();
+                event.record(
+                    &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| match field
+                        .name()
+                    {
+                        "message" => message = format!("{value:?}"),
+                        "method" => method = format!("{value:?}"),
+                        "size" => size = format!("{value:?}"),
+                        _ => {}
+                    },
+                );
+                if message == "Large RPC response" {
+                    self.0.lock().unwrap().push((method, size));
+                }
             }
+        }
 
-            #[derive(Debug, Deserialize, Serialize)]
-            struct GetDataInput;
-            #[derive(Debug, Deserialize, Serialize)]
-            struct GetDataOutput(Vec<Value>);
-            async fn get_data() -> Result<GetDataOutput, ExampleError> {
-                Ok(GetDataOutput(vec![
-                    Value::String("hello".to_owned()),
-                    Value::Number(5.into()),
-                ]))
-            }
+        async fn large() -> Result<Value, RpcError> {
+            Ok(json!("x".repeat(THRESHOLD * 2)))
+        }
+
+        async fn small() -> Result<Value, RpcError> {
+            Ok(json!("small"))
+        }
+
+        async fn query(router: RpcRouter) -> Vec<(String, String)> {
+            let capture = LargeCapture::default();
+            let _guard = tracing::subscriber::set_default(
+                tracing_subscriber::registry().with(capture.clone()),
+            );
+
+            serve_and_query(
+                router.clone(),
+                json!({"jsonrpc": "2.0", "method": "large", "id": 1}),
+            )
+            .await;
+            serve_and_query(
+                router,
+                json!({"jsonrpc": "2.0", "method": "small", "id": 2}),
+            )
+            .await;
+
+            let captured = capture.0.lock().unwrap().clone();
+            captured
+        }
 
+        fn router() -> RpcRouter {
             RpcRouter::builder("vTEST")
-                .register("subtract", subtract)
-                .register("sum", sum)
-                .register("get_data", get_data)
+                .register("large", large)
+                .register("small", small)
                 .build(RpcContext::for_tests())
         }
 
-        #[rstest]
-        #[case::with_positional_params(
-            json!({"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": 2}),
-            json!({"jsonrpc": "2.0", "result": 19, "id": 2}),
-        )]
-        #[case::with_positional_params_switched(
-            json!({"jsonrpc": "2.0", "method": "subtract", "params": [23, 42], "id": 2}),
-            json!({"jsonrpc": "2.0", "result": -19, "id": 2}),
-        )]
-        #[case::with_named_params(
-            json!({"jsonrpc": "2.0", "method": "subtract", "params": {"subtrahend": 23, "minuend": 42}, "id": 3}),
-            json!({"jsonrpc": "2.0", "result": 19, "id": 3}),
-        )]
-        #[case::with_named_params_switched(
-            json!({"jsonrpc": "2.0", "method": "subtract", "params": {"minuend": 42, "subtrahend": 23}, "id": 4}),
-            json!({"jsonrpc": "2.0", "result": 19, "id": 4}),
-        )]
-        #[case::non_existent_method(
-            json!({"jsonrpc": "2.0", "method": "foobar", "id": "1"}),
-            json!({"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": "1"}),
-        )]
-        #[case::invalid_request_object(
-            json!({"jsonrpc": "2.0", "method": 1, "params": "bar"}),
-            json!({"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null}),
-        )]
-        #[case::empty_batch(
-            json!([]),
-            json!({"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null}),
-        )]
-        #[case::invalid_batch_single(
-            json!([1]),
-            json!([{"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null}]),
-        )]
-        #[case::invalid_batch_multiple(
-            json!([1, 2, 3]),
-            json!([
-                {"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null},
-                {"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null},
-                {"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null}
-            ]),
-        )]
-        #[case::batch(
-            json!([
-                {"jsonrpc": "2.0", "method": "sum", "params": [1,2,4], "id": "1"},
-                {"jsonrpc": "2.0", "method": "notify_hello", "params": [7]},
-                {"jsonrpc": "2.0", "method": "subtract", "params": [42,23], "id": "2"},
-                {"foo": "boo"},
-                {"jsonrpc": "2.0", "method": "foo.get", "params": {"name": "myself"}, "id": "5"},
-                {"jsonrpc": "2.0", "method": "get_data", "id": "9"}
-            ]),
-            json!([
-                {"jsonrpc": "2.0", "result": 7, "id": "1"},
-                {"jsonrpc": "2.0", "result": 19, "id": "2"},
-                {"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null},
-                {"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": "5"},
-                {"jsonrpc": "2.0", "result": ["hello", 5], "id": "9"}
-            ]),
-        )]
         #[tokio::test]
-        async fn specification_test(#[case] request: Value, #[case] expected: Value) {
-            let response = serve_and_query(spec_router(), request).await;
+        async fn only_large_responses_are_logged() {
+            let logged = query(router().with_large_response_threshold(THRESHOLD)).await;
 
-            assert_eq!(response, expected);
+            // The result is a string, so the size includes its quotes.
+            let size = THRESHOLD * 2 + 2;
+            assert_eq!(logged, vec![("large".to_owned(), size.to_string())]);
         }
 
-        #[rstest]
-        #[case::with_params(json!({"jsonrpc": "2.0", "method": "update", "params": [1,2,3,4,5]}))]
-        #[case::without_params(json!({"jsonrpc": "2.0", "method": "foobar"}))]
-        #[case::batch(json!([
-            {"jsonrpc": "2.0", "method": "notify_sum", "params": [1,2,4]},
-            {"jsonrpc": "2.0", "method": "notify_hello", "params": [7]}
-        ]))]
         #[tokio::test]
-        async fn notifications(#[case] request: Value) {
-            let url = spawn_server(spec_router()).await;
+        async fn disabled_by_default() {
+            let logged = query(router()).await;
 
-            let client = reqwest::Client::new();
-            let res = client
-                .post(url.clone())
-                .json(&request)
-                .send()
-                .await
-                .unwrap();
+            assert!(logged.is_empty());
+        }
+    }
 
-            assert_eq!(res.content_length(), Some(0));
+    mod call_limiter {
+        use super::*;
+        use std::time::{Duration, Instant};
+
+        const SLOW_CALL: Duration = Duration::from_millis(50);
+
+        async fn slow(_ctx: RpcContext) -> RpcResult {
+            tokio::time::sleep(SLOW_CALL).await;
+            Ok(json!("slow"))
+        }
+
+        fn fast() -> &'static str {
+            "fast"
         }
 
-        #[rstest]
-        #[case::single(r#"{"jsonrpc": "2.0", "method": "foobar, "params": "bar", "baz]"#)]
-        #[case::batch(
-            r#"[
-            {"jsonrpc": "2.0", "method": "sum", "params": [1,2,4], "id": "1"},
-            {"jsonrpc": "2.0", "method"
-         ]"#
-        )]
         #[tokio::test]
-        async fn invalid_json(#[case] request: &'static str) {
-            let url = spawn_server(spec_router()).await;
+        async fn single_request_is_not_blocked_by_batch() {
+            const BATCH_SIZE: u32 = 40;
 
-            let client = reqwest::Client::new();
-            let res = client
-                .post(url.clone())
-                .body(request)
-                .header(reqwest::header::CONTENT_TYPE, "application/json")
+            let router = RpcRouter::builder("vTEST")
+                .register("slow", slow)
+                .register("fast", fast)
+                .build(RpcContext::for_tests())
+                .with_call_limiter(Arc::new(tokio::sync::Semaphore::new(1)));
+            let url = spawn_server(router).await;
+
+            let batch = (0..BATCH_SIZE)
+                .map(|id| json!({"jsonrpc": "2.0", "method": "slow", "id": id}))
+                .collect::<Vec<_>>();
+            let batch_url = url.clone();
+            let batch = tokio::spawn(async move {
+                reqwest::Client::new()
+                    .post(batch_url)
+                    .json(&batch)
+                    .send()
+                    .await
+                    .unwrap()
+                    .json::<Vec<Value>>()
+                    .await
+                    .unwrap()
+            });
+
+            // Give the batch a head start so that it is executing once the single request arrives.
+            tokio::time::sleep(SLOW_CALL * 2).await;
+
+            let start = Instant::now();
+            let response = reqwest::Client::new()
+                .post(url)
+                .json(&json!({"jsonrpc": "2.0", "method": "fast", "id": 1}))
                 .send()
                 .await
                 .unwrap()
                 .json::<Value>()
                 .await
                 .unwrap();
+            let elapsed = start.elapsed();
 
-            let expected = serde_json::json!({"jsonrpc": "2.0", "error": {"code": -32700, "message": "Parse error"}, "id": null});
-            assert_eq!(res, expected);
+            assert_eq!(
+                response,
+                json!({"jsonrpc": "2.0", "result": "fast", "id": 1})
+            );
+            assert!(!batch.is_finished(), "Batch should still be executing");
+            assert!(
+                elapsed < SLOW_CALL * (BATCH_SIZE / 2),
+                "Single request took {elapsed:?}"
+            );
+
+            let responses = batch.await.unwrap();
+            assert_eq!(responses.len(), BATCH_SIZE as usize);
         }
     }
 
-    mod panic_handling {
-        use super::*;
+    #[rstest::rstest]
+    #[case::single(json!({"jsonrpc": "2.0", "method": "success", "id": 1}))]
+    #[case::batch(json!([
+        {"jsonrpc": "2.0", "method": "success", "id": 1},
+        {"jsonrpc": "2.0", "method": "success", "id": 2},
+    ]))]
+    #[tokio::test]
+    async fn pretty_response(#[case] request: Value) {
+        fn always_success() -> &'static str {
+            "Success"
+        }
 
-        fn panic_router() -> RpcRouter {
-            fn always_panic() -> &'static str {
-                panic!("Oh no!");
-            }
+        let router = RpcRouter::builder("vTEST")
+            .register("success", always_success)
+            .build(RpcContext::for_tests());
+        let url = spawn_server(router).await;
 
-            fn always_success() -> &'static str {
-                "Success"
+        let client = reqwest::Client::new();
+        let query = |url: String| {
+            let request = request.clone();
+            let client = client.clone();
+            async move {
+                client
+                    .post(url)
+                    .json(&request)
+                    .send()
+                    .await
+                    .unwrap()
+                    .text()
+                    .await
+                    .unwrap()
             }
+        };
 
-            RpcRouter::builder("vTest")
-                .register("panic", always_panic)
-                .register("success", always_success)
-                .build(RpcContext::for_tests())
-        }
-
-        #[tokio::test]
-        async fn panic_is_internal_error() {
-            let response = serve_and_query(
-                panic_router(),
-                json!(
-                    {"jsonrpc": "2.0", "method": "panic", "id": 1}
-                ),
-            )
-            .await;
-
-            let expected = serde_json::json!({"jsonrpc": "2.0", "error": {"code": -32603, "message": "Internal error"}, "id": 1});
-            assert_eq!(response, expected);
-        }
+        let compact = query(url.clone()).await;
+        let pretty = query(format!("{url}?pretty=1")).await;
 
-        #[tokio::test]
-        async fn panic_in_batch_is_isolated() {
-            let response = serve_and_query(
-                panic_router(),
-                json!([
-                    {"jsonrpc": "2.0", "method": "panic", "id": 1},
-                    {"jsonrpc": "2.0", "method": "success", "id": 2},
-                ]),
-            )
-            .await;
+        assert!(!compact.contains('\n'));
+        assert!(pretty.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<Value>(&pretty).unwrap(),
+            serde_json::from_str::<Value>(&compact).unwrap()
+        );
+    }
 
-            let expected = serde_json::json!([
-                {"jsonrpc": "2.0", "error": {"code": -32603, "message": "Internal error"}, "id": 1},
-                {"jsonrpc": "2.0", "result": "Success", "id": 2},
-            ]);
-            assert_eq!(response, expected);
-        }
+    #[rstest::rstest]
+    #[case::absent(None, false)]
+    #[case::flag(Some("pretty"), true)]
+    #[case::one(Some("pretty=1"), true)]
+    #[case::true_(Some("pretty=true"), true)]
+    #[case::among_others(Some("a=b&pretty=1"), true)]
+    #[case::zero(Some("pretty=0"), false)]
+    #[case::other(Some("prettyish=1"), false)]
+    fn is_pretty_query(#[case] query: Option<&str>, #[case] expected: bool) {
+        assert_eq!(is_pretty(query), expected);
     }
 
     #[tokio::test]
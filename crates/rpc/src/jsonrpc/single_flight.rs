@@ -0,0 +1,91 @@
+//! Coalesces concurrent identical calls of a method into a single invocation.
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use futures::future::{BoxFuture, Shared};
+use futures::FutureExt;
+use serde_json::value::RawValue;
+
+use crate::context::RpcContext;
+use crate::jsonrpc::request::RawParams;
+use crate::jsonrpc::response::RpcResult;
+use crate::jsonrpc::router::RpcMethod;
+
+/// The method name and its params, as received.
+type Key = (&'static str, String);
+
+/// The output of a method, or [None] if it panicked.
+type Output = Arc<Option<RpcResult>>;
+
+/// Lets concurrent calls of the same method with identical params share a single invocation,
+/// and its result.
+///
+/// Unlike a cache, results are not kept once the invocation completes. Calls made after
+/// that invoke the method again.
+///
+/// Methods are invoked with a context which carries no caller-specific state, such as the
+/// principal, deadline or request id, as the invocation is shared by all of its callers. It is
+/// cancelled once all of its callers have given up on it.
+pub struct SingleFlight {
+    /// Methods whose calls are coalesced.
+    methods: HashSet<String>,
+    /// The context of the router, before any request-specific state is added.
+    context: RpcContext,
+    in_flight: Arc<Mutex<HashMap<Key, Shared<BoxFuture<'static, Output>>>>>,
+}
+
+impl SingleFlight {
+    pub fn new(methods: HashSet<String>, context: RpcContext) -> Self {
+        Self {
+            methods,
+            context,
+            in_flight: Default::default(),
+        }
+    }
+
+    pub fn applies_to(&self, method_name: &str) -> bool {
+        self.methods.contains(method_name)
+    }
+
+    /// Invokes `method`, or joins the identical call which is already in flight.
+    ///
+    /// Returns [None] if the method panicked.
+    pub async fn invoke(
+        &self,
+        method_name: &'static str,
+        method: &'static dyn RpcMethod,
+        params: RawParams<'_>,
+    ) -> Option<RpcResult> {
+        let key = (
+            method_name,
+            params.raw().map_or("", RawValue::get).to_owned(),
+        );
+
+        let call = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_insert_with(|| {
+                let params = params.raw().map(ToOwned::to_owned);
+                let in_flight = self.in_flight.clone();
+                let context = self.context.clone();
+                async move {
+                    let params = params.as_deref().map(RawParams::from).unwrap_or_default();
+                    let output = std::panic::AssertUnwindSafe(method.invoke(context, params))
+                        .catch_unwind()
+                        .await
+                        .ok();
+
+                    // Later calls invoke the method again.
+                    in_flight.lock().unwrap().remove(&key);
+                    Arc::new(output)
+                }
+                .boxed()
+                .shared()
+            })
+            .clone();
+
+        (*call.await).clone()
+    }
+}
@@ -0,0 +1,160 @@
+//! Helpers for testing [RpcRouter]s over HTTP.
+use serde_json::{json, Value};
+
+use crate::jsonrpc::{rpc_handler, RpcRouter};
+
+/// A method name which no router is expected to register.
+const UNKNOWN_METHOD: &str = "jsonrpc_conformance_unknown_method";
+
+/// Spawns an HTTP server serving the given router, and returns its url.
+pub async fn spawn_server(router: RpcRouter) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let url = format!("http://127.0.0.1:{}", addr.port());
+
+    tokio::spawn(async {
+        let router = axum::Router::new()
+            .route("/", axum::routing::post(rpc_handler))
+            .with_state(router);
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router.into_make_service())
+            .await
+    });
+
+    url
+}
+
+/// Spawns an RPC server with the given router and queries it with the given request.
+pub async fn serve_and_query(router: RpcRouter, request: Value) -> Value {
+    let url = spawn_server(router).await;
+
+    let client = reqwest::Client::new();
+    client
+        .post(url.clone())
+        .json(&request)
+        .send()
+        .await
+        .unwrap()
+        .json::<Value>()
+        .await
+        .unwrap()
+}
+
+/// Asserts that the router handles the cases of the [JSON-RPC specification](https://www.jsonrpc.org/specification)
+/// which are independent of the methods it registers.
+///
+/// This covers invalid requests, empty and invalid batches, parse errors, notifications and
+/// unknown methods.
+pub async fn assert_jsonrpc_conformance(router: RpcRouter) {
+    let url = spawn_server(router).await;
+    let client = reqwest::Client::new();
+
+    let query = |body: String| {
+        let request = client
+            .post(url.clone())
+            .body(body)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        async move { request.send().await.unwrap() }
+    };
+
+    let invalid_request = json!({"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null});
+    let parse_error =
+        json!({"jsonrpc": "2.0", "error": {"code": -32700, "message": "Parse error"}, "id": null});
+
+    let cases = [
+        (
+            "invalid request",
+            json!({"jsonrpc": "2.0", "method": 1, "params": "bar"}).to_string(),
+            invalid_request.clone(),
+        ),
+        (
+            "empty batch",
+            json!([]).to_string(),
+            invalid_request.clone(),
+        ),
+        (
+            "invalid batch",
+            json!([1, 2]).to_string(),
+            json!([invalid_request, invalid_request]),
+        ),
+        (
+            "parse error",
+            r#"{"jsonrpc": "2.0", "method": "foobar, "params": "bar", "baz]"#.to_owned(),
+            parse_error.clone(),
+        ),
+        (
+            "batch parse error",
+            r#"[{"jsonrpc": "2.0", "method": "foobar", "id": "1"}, {"jsonrpc": "2.0", "method"]"#
+                .to_owned(),
+            parse_error,
+        ),
+        (
+            "method not found",
+            json!({"jsonrpc": "2.0", "method": UNKNOWN_METHOD, "id": "1"}).to_string(),
+            json!({"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": "1"}),
+        ),
+    ];
+
+    for (name, request, expected) in cases {
+        let response = query(request).await.json::<Value>().await.unwrap();
+        pretty_assertions::assert_eq!(response, expected, "{name}");
+    }
+
+    let notifications = [
+        (
+            "notification",
+            json!({"jsonrpc": "2.0", "method": UNKNOWN_METHOD, "params": [1, 2]}),
+        ),
+        (
+            "notification batch",
+            json!([
+                {"jsonrpc": "2.0", "method": UNKNOWN_METHOD, "params": [1, 2]},
+                {"jsonrpc": "2.0", "method": UNKNOWN_METHOD}
+            ]),
+        ),
+    ];
+
+    for (name, request) in notifications {
+        let response = query(request.to_string()).await;
+        assert_eq!(response.content_length(), Some(0), "{name}");
+    }
+}
+
+/// Asserts that every method of the router rejects malformed params with an invalid params
+/// error, rather than panicking or failing otherwise.
+///
+/// The params are chosen so that no method input can deserialize them: named params with an
+/// unknown key, and more positional params than any method accepts.
+pub async fn assert_methods_reject_invalid_params(router: RpcRouter) {
+    let mut methods = router.method_names().collect::<Vec<_>>();
+    methods.sort_unstable();
+
+    let invalid_params = [
+        json!({"jsonrpc_invalid_param": "0xZZ"}),
+        json!(vec![json!({"jsonrpc_invalid_param": true}); 16]),
+    ];
+
+    let requests = methods
+        .iter()
+        .flat_map(|method| {
+            invalid_params.iter().map(move |params| {
+                json!({"jsonrpc": "2.0", "method": method, "params": params, "id": method})
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let expected_responses = requests.len();
+    let responses = serve_and_query(router, Value::Array(requests)).await;
+    let responses = responses.as_array().expect("Batch response");
+    assert_eq!(responses.len(), expected_responses);
+
+    for response in responses {
+        assert_eq!(
+            response["error"],
+            json!({"code": -32602, "message": "Invalid params"}),
+            "{}",
+            response["id"]
+        );
+    }
+}
@@ -2,11 +2,14 @@
 pub mod context;
 mod error;
 mod executor;
+mod fee;
 mod felt;
 pub mod gas_price;
 mod jsonrpc;
 pub mod middleware;
 mod pathfinder;
+mod recent_submissions;
+mod sync_rate;
 #[cfg(test)]
 mod test_setup;
 pub mod v02;
@@ -15,9 +18,14 @@ pub mod v04;
 pub mod v05;
 
 pub use executor::compose_executor_transaction;
+pub use sync_rate::SyncRate;
 
-use crate::jsonrpc::rpc_handler;
 pub use crate::jsonrpc::websocket::{BlockHeader, WebsocketSenders};
+use crate::jsonrpc::{rpc_handler, versioned_rpc_handler};
+pub use crate::jsonrpc::{
+    ApiKeys, Authenticator, BlockTag, Localization, MessageTable, MethodFilter, NoAuth,
+    ParamsLimits, Principal, Scope, Unauthenticated,
+};
 use crate::v02::types::syncing::Syncing;
 use anyhow::Context;
 use axum::error_handling::HandleErrorLayer;
@@ -28,6 +36,7 @@ use context::RpcContext;
 use http::Request;
 use hyper::Body;
 use pathfinder_common::AllowedOrigins;
+use std::collections::{HashMap, HashSet};
 use std::num::NonZeroUsize;
 use std::{net::SocketAddr, result::Result};
 use tokio::sync::RwLock;
@@ -35,6 +44,22 @@ use tokio::task::JoinHandle;
 use tower_http::cors::CorsLayer;
 
 const DEFAULT_MAX_CONNECTIONS: usize = 1024;
+const DEFAULT_MAX_REQUEST_BODY_SIZE: usize = 10 * 1024 * 1024;
+/// Linux clamps this to `net.core.somaxconn`, which itself defaults to 4096 on
+/// modern kernels. The standard library uses a much smaller value of 128.
+const DEFAULT_LISTEN_BACKLOG: u32 = 1024;
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+const DEFAULT_WRITE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+const DEFAULT_BODY_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+/// Paths served by a single router, which can therefore be configured individually e.g. using
+/// [RpcServer::with_method_filter].
+const ROUTER_PATHS: [&str; 5] = [
+    "/",
+    "/rpc/v0.3",
+    "/rpc/v0.4",
+    "/rpc/v0.5",
+    "/rpc/pathfinder/v0.1",
+];
 
 pub enum DefaultVersion {
     V03,
@@ -46,9 +71,40 @@ pub struct RpcServer {
     addr: SocketAddr,
     context: RpcContext,
     max_connections: usize,
+    max_open_connections: Option<NonZeroUsize>,
+    max_concurrent_calls: Option<NonZeroUsize>,
+    max_concurrent_requests_per_ip: Option<NonZeroUsize>,
+    max_request_body_size: usize,
+    max_batch_response_size: Option<NonZeroUsize>,
+    max_batch_params_size: Option<NonZeroUsize>,
+    params_limits: ParamsLimits,
+    keep_alive_timeout: Option<std::time::Duration>,
+    read_timeout: std::time::Duration,
+    write_timeout: std::time::Duration,
+    body_read_timeout: std::time::Duration,
+    max_requests_per_connection: Option<NonZeroUsize>,
+    default_block_tag: Option<BlockTag>,
+    strict_requests: bool,
+    version_header: bool,
+    server_timing: bool,
+    get_requests: bool,
+    omit_null_fields: bool,
+    localization: Option<Localization>,
+    method_in_errors: bool,
+    slow_request_threshold: Option<std::time::Duration>,
+    large_response_threshold: Option<NonZeroUsize>,
+    batch_timeout: Option<std::time::Duration>,
+    single_flight_methods: Option<HashSet<String>>,
+    head_cached_methods: Option<HashSet<String>>,
+    listen_backlog: u32,
+    tcp_nodelay: bool,
+    reuse_address: bool,
     cors: Option<CorsLayer>,
     ws_senders: Option<WebsocketSenders>,
     default_version: DefaultVersion,
+    merged_path: Option<String>,
+    method_filters: HashMap<String, MethodFilter>,
+    authenticator: Option<std::sync::Arc<dyn Authenticator>>,
 }
 
 impl RpcServer {
@@ -57,9 +113,40 @@ impl RpcServer {
             addr,
             context,
             max_connections: DEFAULT_MAX_CONNECTIONS,
+            max_open_connections: None,
+            max_concurrent_calls: None,
+            max_concurrent_requests_per_ip: None,
+            max_request_body_size: DEFAULT_MAX_REQUEST_BODY_SIZE,
+            max_batch_response_size: None,
+            max_batch_params_size: None,
+            params_limits: ParamsLimits::default(),
+            keep_alive_timeout: None,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            write_timeout: DEFAULT_WRITE_TIMEOUT,
+            body_read_timeout: DEFAULT_BODY_READ_TIMEOUT,
+            max_requests_per_connection: None,
+            default_block_tag: None,
+            strict_requests: false,
+            version_header: false,
+            server_timing: false,
+            get_requests: false,
+            omit_null_fields: false,
+            localization: None,
+            method_in_errors: false,
+            slow_request_threshold: None,
+            large_response_threshold: None,
+            batch_timeout: None,
+            single_flight_methods: None,
+            head_cached_methods: None,
+            listen_backlog: DEFAULT_LISTEN_BACKLOG,
+            tcp_nodelay: true,
+            reuse_address: true,
             cors: None,
             ws_senders: None,
             default_version,
+            merged_path: None,
+            method_filters: Default::default(),
+            authenticator: None,
         }
     }
 
@@ -75,6 +162,264 @@ impl RpcServer {
         self
     }
 
+    /// Limits the number of simultaneously open connections. Connections beyond the limit are
+    /// closed as soon as they are accepted. Unlimited by default.
+    ///
+    /// Unlike [with_max_connections](Self::with_max_connections), this also counts idle
+    /// connections which are kept alive, and protects against running out of file descriptors.
+    pub fn with_max_open_connections(mut self, max_open_connections: NonZeroUsize) -> Self {
+        self.max_open_connections = Some(max_open_connections);
+        self
+    }
+
+    /// Limits the number of RPC method calls executing concurrently across all paths.
+    ///
+    /// Unlike [with_max_connections](Self::with_max_connections), this counts the individual
+    /// calls of a batch, which share the limit fairly with single requests. This prevents a
+    /// large batch from starving concurrent single requests. Unlimited by default.
+    pub fn with_max_concurrent_calls(mut self, max_concurrent_calls: NonZeroUsize) -> Self {
+        self.max_concurrent_calls = Some(max_concurrent_calls);
+        self
+    }
+
+    /// Limits the number of requests each IP address may have in flight at once, over all of
+    /// its connections. Requests beyond the limit are answered with `429 Too Many Requests`.
+    /// Unlimited by default.
+    pub fn with_max_concurrent_requests_per_ip(mut self, limit: NonZeroUsize) -> Self {
+        self.max_concurrent_requests_per_ip = Some(limit);
+        self
+    }
+
+    /// Sets the maximum size in bytes of a request body. Defaults to 10 MiB.
+    ///
+    /// Requests which declare a larger `Content-Length` are rejected before their body is read.
+    pub fn with_max_request_body_size(mut self, max_request_body_size: usize) -> Self {
+        self.max_request_body_size = max_request_body_size;
+        self
+    }
+
+    /// Limits the combined size in bytes of the responses to a batch request. Batches exceeding
+    /// the limit are answered with a single error instead. Unlimited by default.
+    pub fn with_max_batch_response_size(mut self, max_batch_response_size: NonZeroUsize) -> Self {
+        self.max_batch_response_size = Some(max_batch_response_size);
+        self
+    }
+
+    /// Limits the combined size in bytes of the params of a batch request. Batches exceeding
+    /// the limit are answered with a single error instead, without executing any of their
+    /// requests. Unlimited by default.
+    pub fn with_max_batch_params_size(mut self, max_batch_params_size: NonZeroUsize) -> Self {
+        self.max_batch_params_size = Some(max_batch_params_size);
+        self
+    }
+
+    /// Limits the nesting depth and number of elements of request params. Calls exceeding the
+    /// limits are rejected with an invalid params error before their params are deserialized.
+    ///
+    /// Defaults to [ParamsLimits::default], i.e. a depth of 64 and one million elements.
+    pub fn with_params_limits(mut self, params_limits: ParamsLimits) -> Self {
+        self.params_limits = params_limits;
+        self
+    }
+
+    /// Closes HTTP/1.1 keep-alive connections which have been idle for longer than
+    /// `keep_alive_timeout`. By default idle connections are kept open until the client
+    /// closes them.
+    pub fn with_keep_alive_timeout(mut self, keep_alive_timeout: std::time::Duration) -> Self {
+        self.keep_alive_timeout = Some(keep_alive_timeout);
+        self
+    }
+
+    /// Closes connections once they have served `max_requests_per_connection` requests, by
+    /// sending `Connection: close` with the last response. Unlimited by default.
+    ///
+    /// This forces long-lived clients to reconnect, which spreads them across instances
+    /// behind a load balancer.
+    pub fn with_max_requests_per_connection(
+        mut self,
+        max_requests_per_connection: NonZeroUsize,
+    ) -> Self {
+        self.max_requests_per_connection = Some(max_requests_per_connection);
+        self
+    }
+
+    /// Supplies `tag` as the block id of `starknet_call` and `starknet_getStorageAt` calls
+    /// which omit it. By default an explicit block id is required, as per the specification.
+    pub fn with_default_block_tag(mut self, tag: BlockTag) -> Self {
+        self.default_block_tag = Some(tag);
+        self
+    }
+
+    /// Rejects requests which repeat a top-level key, such as `method` or `id`, with an invalid
+    /// request error. By default the last value of a repeated key is used.
+    pub fn with_strict_requests(mut self) -> Self {
+        self.strict_requests = true;
+        self
+    }
+
+    /// Reports the version of the router which handled a request, e.g. `v0.4`, in the
+    /// `x-pathfinder-version` response header. Intended for debugging multi-version setups.
+    pub fn with_version_header(mut self) -> Self {
+        self.version_header = true;
+        self
+    }
+
+    /// Reports the time spent reading, parsing, dispatching and serializing each request in
+    /// the W3C `Server-Timing` response header. Intended for debugging latency.
+    pub fn with_server_timing(mut self) -> Self {
+        self.server_timing = true;
+        self
+    }
+
+    /// Additionally serves GET requests which name a method in their query, e.g.
+    /// `GET /rpc/v0.5?method=starknet_blockNumber`, for monitoring tools which cannot POST.
+    /// The method is invoked without params, see [jsonrpc::rpc_get_handler].
+    pub fn with_get_requests(mut self) -> Self {
+        self.get_requests = true;
+        self
+    }
+
+    /// Omits the `null` fields of results, instead of serializing them. By default this
+    /// depends on the method and API version.
+    pub fn with_omitted_null_fields(mut self) -> Self {
+        self.omit_null_fields = true;
+        self
+    }
+
+    /// Adds the name of the failed method to the `data` of its errors, e.g.
+    /// `{"method": "starknet_call"}`. Intended for debugging, as this is not part of the
+    /// specification.
+    pub fn with_method_in_errors(mut self) -> Self {
+        self.method_in_errors = true;
+        self
+    }
+
+    /// Localizes the messages of error responses according to each request's `Accept-Language`
+    /// header. Messages remain in English for other languages.
+    pub fn with_localization(mut self, localization: Localization) -> Self {
+        self.localization = Some(localization);
+        self
+    }
+
+    /// Closes connections which take longer than `read_timeout` to send the headers of a
+    /// request, which guards against clients trickling bytes to keep connections open.
+    ///
+    /// Defaults to 30 seconds. Reading the body is limited by the body read timeout instead, see
+    /// [with_body_read_timeout](Self::with_body_read_timeout).
+    pub fn with_read_timeout(mut self, read_timeout: std::time::Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    /// Closes connections whose writes make no progress for `write_timeout`, i.e. clients
+    /// which are too slow to receive their response.
+    ///
+    /// Defaults to 30 seconds. This does not limit the total time taken to send a response, so
+    /// large responses are not cut off as long as the client keeps receiving them.
+    pub fn with_write_timeout(mut self, write_timeout: std::time::Duration) -> Self {
+        self.write_timeout = write_timeout;
+        self
+    }
+
+    /// Aborts requests whose body makes no progress for `body_read_timeout`, i.e. clients which
+    /// stall part way through sending the body, with a bad request response.
+    ///
+    /// Defaults to 30 seconds. Slow bodies are not cut off as long as they keep arriving.
+    pub fn with_body_read_timeout(mut self, body_read_timeout: std::time::Duration) -> Self {
+        self.body_read_timeout = body_read_timeout;
+        self
+    }
+
+    /// Logs a warning, including the method and its params, for each call which takes longer
+    /// than `threshold` to execute.
+    pub fn with_slow_request_threshold(mut self, threshold: std::time::Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    /// Logs a warning, including the method and the size, for each response whose result is
+    /// larger than `threshold` bytes.
+    pub fn with_large_response_threshold(mut self, threshold: NonZeroUsize) -> Self {
+        self.large_response_threshold = Some(threshold);
+        self
+    }
+
+    /// Limits the time spent executing a batch. Requests which have not completed by then are
+    /// answered with a timeout error, while the completed responses are still returned.
+    pub fn with_batch_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.batch_timeout = Some(timeout);
+        self
+    }
+
+    /// Lets concurrent calls of the given methods with identical params share a single
+    /// invocation, and its result.
+    pub fn with_single_flight(mut self, methods: HashSet<String>) -> Self {
+        self.single_flight_methods = Some(methods);
+        self
+    }
+
+    /// Serves the results of the given methods from a cache while the chain head is unchanged,
+    /// for calls without params. Intended for cheap, frequently called methods such as
+    /// `starknet_blockNumber`.
+    pub fn with_head_cache(mut self, methods: HashSet<String>) -> Self {
+        self.head_cached_methods = Some(methods);
+        self
+    }
+
+    /// Sets the maximum number of pending connections queued by the OS before `accept`.
+    ///
+    /// Defaults to 1024. Note that the OS may silently clamp this value.
+    pub fn with_listen_backlog(mut self, listen_backlog: u32) -> Self {
+        self.listen_backlog = listen_backlog;
+        self
+    }
+
+    /// Sets `TCP_NODELAY` on accepted connections. Defaults to `true` since RPC responses
+    /// are latency sensitive.
+    pub fn with_tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    /// Sets `SO_REUSEADDR` on the listening socket. Defaults to `true` which allows
+    /// restarting the node without waiting for lingering `TIME_WAIT` connections.
+    pub fn with_reuse_address(mut self, reuse_address: bool) -> Self {
+        self.reuse_address = reuse_address;
+        self
+    }
+
+    /// Restricts the methods served on the given path, e.g. `/rpc/v0.4`.
+    ///
+    /// Requests for filtered methods are answered with a method-not-found error. Starting the
+    /// server fails if the path is not served by a single version, such as `/` or `/rpc/v0.4`.
+    pub fn with_method_filter(mut self, path: impl Into<String>, filter: MethodFilter) -> Self {
+        self.method_filters.insert(path.into(), filter);
+        self
+    }
+
+    /// Additionally serves the `v0.3`, `v0.4` and `v0.5` APIs on `path`, e.g. `/rpc`, selecting
+    /// the version of each request by its `x-starknet-rpc-version` header. Requests without the
+    /// header, or for another version, are served by the default version.
+    ///
+    /// Each version is configured as on its own path, e.g. `/rpc/v0.4`. The path must not
+    /// already be served.
+    pub fn with_merged_path(mut self, path: impl Into<String>) -> Self {
+        self.merged_path = Some(path.into());
+        self
+    }
+
+    /// Identifies the caller of each request on all paths, which determines the scoped methods
+    /// it may call. By default all callers are anonymous, see [NoAuth].
+    pub fn with_authenticator(mut self, authenticator: impl Authenticator + 'static) -> Self {
+        self.authenticator = Some(std::sync::Arc::new(authenticator));
+        self
+    }
+
+    /// Sets the API keys which grant callers access to scoped methods on all paths.
+    pub fn with_api_keys(self, api_keys: ApiKeys) -> Self {
+        self.with_authenticator(api_keys)
+    }
+
     pub fn with_cors(self, allowed_origins: AllowedOrigins) -> Self {
         Self {
             cors: Some(middleware::cors::with_allowed_origins(allowed_origins)),
@@ -84,14 +429,8 @@ impl RpcServer {
 
     /// Starts the HTTP-RPC server.
     pub fn spawn(self) -> Result<(JoinHandle<anyhow::Result<()>>, SocketAddr), anyhow::Error> {
-        use axum::routing::{get, post};
-
-        // TODO: make this configurable
-        const REQUEST_MAX_SIZE: usize = 10 * 1024 * 1024;
-        // TODO: make this configurable
-        const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
-
-        let listener = match std::net::TcpListener::bind(self.addr) {
+        self.validate()?;
+        let listener = match bind_listener(self.addr, self.listen_backlog, self.reuse_address) {
             Ok(listener) => listener,
             Err(e) => return Err(e).context(format!("RPC address {} is already in use.
     
@@ -102,7 +441,102 @@ impl RpcServer {
         let addr = listener
             .local_addr()
             .context("Getting local address from listener")?;
-        let server = axum::Server::from_tcp(listener).context("Binding server to tcp listener")?;
+        let listener =
+            tokio::net::TcpListener::from_std(listener).context("Registering tcp listener")?;
+        let mut incoming = hyper::server::conn::AddrIncoming::from_listener(listener)
+            .context("Binding server to tcp listener")?;
+        incoming.set_nodelay(self.tcp_nodelay);
+        let incoming =
+            middleware::write_timeout::WriteTimeoutIncoming::new(incoming, self.write_timeout);
+        let server = axum::Server::builder(incoming)
+            .http1_half_close(false)
+            .http1_header_read_timeout(self.read_timeout);
+
+        let prune_caches = self.prune_caches();
+        let make_service = self.make_service();
+        let server_handle = tokio::spawn(async move {
+            with_pruning(server.serve(make_service), prune_caches)
+                .await
+                .map_err(Into::into)
+        });
+
+        Ok((server_handle, addr))
+    }
+
+    /// Starts the HTTP-RPC server on the Unix domain socket at `path`, instead of the TCP
+    /// address. This serves the same paths and versions as [spawn](Self::spawn).
+    ///
+    /// The socket file must not exist yet, and is not removed once the server stops.
+    pub fn spawn_unix(
+        self,
+        path: &std::path::Path,
+    ) -> Result<JoinHandle<anyhow::Result<()>>, anyhow::Error> {
+        self.validate()?;
+        let listener = tokio::net::UnixListener::bind(path)
+            .with_context(|| format!("Binding RPC unix socket {}", path.display()))?;
+        let incoming = hyper::server::accept::poll_fn(move |cx| {
+            listener
+                .poll_accept(cx)
+                .map(|result| Some(result.map(|(stream, _)| stream)))
+        });
+        let incoming =
+            middleware::write_timeout::WriteTimeoutIncoming::new(incoming, self.write_timeout);
+        let server = axum::Server::builder(incoming)
+            .http1_half_close(false)
+            .http1_header_read_timeout(self.read_timeout);
+
+        let prune_caches = self.prune_caches();
+        let make_service = self.make_service();
+        let server_handle = tokio::spawn(async move {
+            with_pruning(server.serve(make_service), prune_caches)
+                .await
+                .map_err(Into::into)
+        });
+
+        Ok(server_handle)
+    }
+
+    /// Rejects configuration which would otherwise be silently ignored, before anything is
+    /// bound.
+    fn validate(&self) -> anyhow::Result<()> {
+        for path in self.method_filters.keys() {
+            anyhow::ensure!(
+                ROUTER_PATHS.contains(&path.as_str()),
+                "Method filter configured for {path}, which is not one of the served paths {}",
+                ROUTER_PATHS.join(", ")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Periodically evicts the expired entries of the caches kept across requests.
+    fn prune_caches(&self) -> impl std::future::Future<Output = std::convert::Infallible> {
+        let submissions = self.context.recent_submissions.clone();
+        let ttl = self.context.config.resubmission_ttl;
+        async move {
+            match ttl {
+                Some(ttl) => recent_submissions::prune_periodically(submissions, ttl).await,
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    /// Builds the service which serves each accepted connection, independently of the
+    /// transport the connections are accepted on.
+    ///
+    /// Both transports disable HTTP/1 half-closes, so that a connection is closed as soon as
+    /// the client disconnects. This drops the handler of the in-flight request, and with it
+    /// the batch requests which have yet to execute.
+    fn make_service(
+        self,
+    ) -> middleware::peer_limit::PeerLimit<
+        middleware::connection_limit::ConnectionLimit<axum::routing::IntoMakeService<axum::Router>>,
+    > {
+        use axum::routing::{get, post};
+
+        // TODO: make this configurable
+        const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
 
         async fn handle_middleware_errors(err: axum::BoxError) -> (http::StatusCode, String) {
             use http::StatusCode;
@@ -127,8 +561,10 @@ impl RpcServer {
             // This is required by axum -- axum doesn't deal with Result, errors
             // must be responses as well.
             .layer(HandleErrorLayer::new(handle_middleware_errors))
-            .concurrency_limit(self.max_connections)
-            .layer(DefaultBodyLimit::max(REQUEST_MAX_SIZE))
+            .layer(middleware::concurrency_limit::ConcurrencyLimitLayer::new(
+                self.max_connections,
+            ))
+            .layer(DefaultBodyLimit::max(self.max_request_body_size))
             .timeout(REQUEST_TIMEOUT)
             .layer(tower_http::trace::TraceLayer::new_for_http())
             .option_layer(self.cors);
@@ -155,30 +591,149 @@ impl RpcServer {
             DefaultVersion::V05 => v05_routes.clone(),
         };
 
-        let router = axum::Router::new()
+        let call_limiter = self
+            .max_concurrent_calls
+            .map(|limit| std::sync::Arc::new(tokio::sync::Semaphore::new(limit.get())));
+
+        let configured = |path: &str, router: jsonrpc::RpcRouter| {
+            let router = match self.method_filters.get(path) {
+                Some(filter) => router.with_method_filter(filter.clone()),
+                None => router,
+            };
+            let router = router
+                .with_max_body_size(self.max_request_body_size as u64)
+                .with_body_read_timeout(self.body_read_timeout)
+                .with_params_limits(self.params_limits);
+            let router = match self.max_batch_response_size {
+                Some(max) => router.with_max_batch_response_size(max.get()),
+                None => router,
+            };
+            let router = match self.max_batch_params_size {
+                Some(max) => router.with_max_batch_params_size(max.get()),
+                None => router,
+            };
+            let router = match self.default_block_tag {
+                Some(tag) => router.with_default_block_tag(tag),
+                None => router,
+            };
+            let router = match self.strict_requests {
+                true => router.with_strict_requests(),
+                false => router,
+            };
+            let router = match self.version_header {
+                true => router.with_version_header(),
+                false => router,
+            };
+            let router = match self.server_timing {
+                true => router.with_server_timing(),
+                false => router,
+            };
+            let router = match self.omit_null_fields {
+                true => router.with_omitted_null_fields(),
+                false => router,
+            };
+            let router = match self.method_in_errors {
+                true => router.with_method_in_errors(),
+                false => router,
+            };
+            let router = match &self.localization {
+                Some(localization) => router.with_localization(localization.clone()),
+                None => router,
+            };
+            let router = match self.slow_request_threshold {
+                Some(threshold) => router.with_slow_request_threshold(threshold),
+                None => router,
+            };
+            let router = match self.large_response_threshold {
+                Some(threshold) => router.with_large_response_threshold(threshold.get()),
+                None => router,
+            };
+            let router = match self.batch_timeout {
+                Some(timeout) => router.with_batch_timeout(timeout),
+                None => router,
+            };
+            let router = router.with_request_timeout(REQUEST_TIMEOUT);
+            let router = match &self.single_flight_methods {
+                Some(methods) => router.with_single_flight(methods.clone()),
+                None => router,
+            };
+            let router = match &self.head_cached_methods {
+                Some(methods) => router.with_head_cache(methods.clone()),
+                None => router,
+            };
+            let router = match &self.authenticator {
+                Some(authenticator) => router.with_authenticator(authenticator.clone()),
+                None => router,
+            };
+            match &call_limiter {
+                Some(limiter) => router.with_call_limiter(limiter.clone()),
+                None => router,
+            }
+        };
+        let [root_path, v03_path, v04_path, v05_path, pathfinder_path] = ROUTER_PATHS;
+        let default_router = configured(root_path, default_router);
+        let v03_routes = configured(v03_path, v03_routes);
+        let v04_routes = configured(v04_path, v04_routes);
+        let v05_routes = configured(v05_path, v05_routes);
+        let pathfinder_routes = configured(pathfinder_path, pathfinder_routes);
+
+        let merged_routes = self.merged_path.map(|path| {
+            let default = match self.default_version {
+                DefaultVersion::V03 => v03_routes.clone(),
+                DefaultVersion::V04 => v04_routes.clone(),
+                DefaultVersion::V05 => v05_routes.clone(),
+            };
+            let versions = vec![v03_routes.clone(), v04_routes.clone(), v05_routes.clone()];
+            (path, jsonrpc::VersionedRouter::new(versions, default))
+        });
+
+        let get_requests = self.get_requests;
+        let versioned_route = || match get_requests {
+            true => get(jsonrpc::rpc_get_handler).post(rpc_handler),
+            false => post(rpc_handler),
+        };
+
+        let router: axum::Router = axum::Router::new()
             // Also return success for get's with an empty body. These are often
             // used by monitoring bots to check service health.
-            .route("/", get(empty_body).post(rpc_handler))
+            .route(root_path, get(empty_body).post(rpc_handler))
             .with_state(default_router)
-            .route("/rpc/v0.3", post(rpc_handler))
+            .route(v03_path, versioned_route())
             .with_state(v03_routes)
-            .route("/rpc/v0.4", post(rpc_handler))
+            .route(v04_path, versioned_route())
             .with_state(v04_routes)
-            .route("/rpc/v0.5", post(rpc_handler))
+            .route(v05_path, versioned_route())
             .with_state(v05_routes)
-            .route("/rpc/pathfinder/v0.1", post(rpc_handler))
-            .with_state(pathfinder_routes)
-            .layer(middleware);
+            .route(pathfinder_path, versioned_route())
+            .with_state(pathfinder_routes);
+        let router = match merged_routes {
+            Some((path, routes)) => router.merge(
+                axum::Router::new()
+                    .route(&path, post(versioned_rpc_handler))
+                    .with_state(routes),
+            ),
+            None => router,
+        };
+        let router = router.layer(middleware);
         // TODO: websockets
 
-        let server_handle = tokio::spawn(async move {
-            server
-                .serve(router.into_make_service())
-                .await
-                .map_err(Into::into)
-        });
+        let make_service = middleware::connection_limit::ConnectionLimit::new(
+            router.into_make_service(),
+            self.max_open_connections.map(NonZeroUsize::get),
+        );
+        let make_service = match self.max_requests_per_connection {
+            Some(max) => make_service.with_max_requests(max.get()),
+            None => make_service,
+        };
+        let make_service = match self.keep_alive_timeout {
+            Some(timeout) => make_service.with_keep_alive_timeout(timeout),
+            None => make_service,
+        };
 
-        Ok((server_handle, addr))
+        middleware::peer_limit::PeerLimit::new(
+            make_service,
+            self.max_concurrent_requests_per_ip.map(NonZeroUsize::get),
+        )
     }
 
     pub fn get_ws_senders(&self) -> WebsocketSenders {
@@ -192,18 +747,69 @@ impl RpcServer {
     }
 }
 
+/// Runs `serve` alongside `prune_caches`, which therefore stops along with the server.
+async fn with_pruning<E>(
+    serve: impl std::future::Future<Output = Result<(), E>>,
+    prune_caches: impl std::future::Future<Output = std::convert::Infallible>,
+) -> Result<(), E> {
+    let serve = std::pin::pin!(serve);
+    let prune_caches = std::pin::pin!(prune_caches);
+    match futures::future::select(serve, prune_caches).await {
+        futures::future::Either::Left((result, _)) => result,
+        futures::future::Either::Right((never, _)) => match never {},
+    }
+}
+
+/// Binds a TCP listener, configuring the socket options which cannot be set after `listen`.
+fn bind_listener(
+    addr: SocketAddr,
+    backlog: u32,
+    reuse_address: bool,
+) -> std::io::Result<std::net::TcpListener> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_reuse_address(reuse_address)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(i32::try_from(backlog).unwrap_or(i32::MAX))?;
+
+    Ok(socket.into())
+}
+
 pub struct SyncState {
     pub status: RwLock<Syncing>,
+    /// Import times of recent blocks, used to estimate how long syncing will take.
+    pub rate: std::sync::Mutex<SyncRate>,
+    /// Changes whenever the latest block in storage changes, see [SyncState::head_version].
+    head_version: std::sync::atomic::AtomicU64,
 }
 
 impl Default for SyncState {
     fn default() -> Self {
         Self {
             status: RwLock::new(Syncing::False(false)),
+            rate: Default::default(),
+            head_version: Default::default(),
         }
     }
 }
 
+impl SyncState {
+    /// Identifies the current chain head, i.e. the latest block in storage. Results which only
+    /// depend on the chain head may be kept for as long as this is unchanged.
+    pub fn head_version(&self) -> u64 {
+        self.head_version.load(std::sync::atomic::Ordering::Acquire)
+    }
+
+    /// Signals that the chain head has changed, e.g. because a block was committed or a reorg
+    /// was applied. Must be called after the change is committed to storage.
+    pub fn head_changed(&self) {
+        self.head_version
+            .fetch_add(1, std::sync::atomic::Ordering::AcqRel);
+    }
+}
+
 pub mod test_utils {
     use pathfinder_common::event::Event;
     use pathfinder_common::macro_prelude::*;
@@ -820,6 +1426,307 @@ mod tests {
         assert!(!status.is_success());
     }
 
+    #[tokio::test]
+    async fn get_requests() {
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let (_jh, addr) = RpcServer::new(addr, context, DefaultVersion::V04)
+            .with_get_requests()
+            .spawn()
+            .unwrap();
+
+        let query = |method: &str| {
+            let url = format!("http://{addr}/rpc/v0.4?method={method}");
+            async move {
+                reqwest::get(url)
+                    .await
+                    .unwrap()
+                    .json::<serde_json::Value>()
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let response = query("starknet_chainId").await;
+        assert!(response.get("result").is_some(), "{response}");
+        assert_eq!(response["id"], 0);
+
+        // Only methods without params are eligible.
+        let response = query("starknet_getBlockWithTxHashes").await;
+        assert_eq!(
+            response["error"],
+            json!({"code": -32602, "message": "Invalid params"})
+        );
+    }
+
+    #[tokio::test]
+    async fn get_requests_are_disabled_by_default() {
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let (_jh, addr) = RpcServer::new(addr, context, DefaultVersion::V04)
+            .spawn()
+            .unwrap();
+
+        let status = reqwest::get(format!("http://{addr}/rpc/v0.4?method=starknet_chainId"))
+            .await
+            .unwrap()
+            .status();
+        assert_eq!(status, reqwest::StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn custom_socket_options() {
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let (_jh, addr) = RpcServer::new(addr, context, DefaultVersion::V04)
+            .with_listen_backlog(1)
+            .with_tcp_nodelay(false)
+            .with_reuse_address(false)
+            .spawn()
+            .unwrap();
+
+        let status = reqwest::get(format!("http://{addr}/"))
+            .await
+            .unwrap()
+            .status();
+        assert!(status.is_success());
+    }
+
+    #[tokio::test]
+    async fn unix_socket() {
+        use std::io::{Read, Write};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rpc.sock");
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let _jh = RpcServer::new(addr, context, DefaultVersion::V04)
+            .spawn_unix(&path)
+            .unwrap();
+
+        let response = tokio::task::spawn_blocking(move || {
+            let body = json!({"jsonrpc": "2.0", "method": "starknet_chainId", "id": 1}).to_string();
+            let request = format!(
+                "POST /rpc/v0.4 HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+
+            let mut stream = std::os::unix::net::UnixStream::connect(path).unwrap();
+            stream.write_all(request.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        })
+        .await
+        .unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(body).unwrap(),
+            json!({"jsonrpc": "2.0", "result": "0x534e5f474f45524c49", "id": 1})
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::get_block_with_tx_hashes("starknet_getBlockWithTxHashes", json!({}))]
+    #[case::get_block_with_txs("starknet_getBlockWithTxs", json!({}))]
+    #[case::get_block_transaction_count("starknet_getBlockTransactionCount", json!({}))]
+    #[case::get_state_update("starknet_getStateUpdate", json!({}))]
+    #[case::get_nonce("starknet_getNonce", json!({"contract_address": "0x1"}))]
+    #[case::get_class_hash_at("starknet_getClassHashAt", json!({"contract_address": "0x1"}))]
+    #[case::get_proof("pathfinder_getProof", json!({"contract_address": "0x1", "keys": []}))]
+    #[tokio::test]
+    async fn malformed_block_id_is_invalid_params(
+        #[case] method: &'static str,
+        #[case] params: serde_json::Value,
+    ) {
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let (_jh, addr) = RpcServer::new(addr, context, DefaultVersion::V04)
+            .spawn()
+            .unwrap();
+
+        let url = format!("http://{addr}/rpc/v0.4");
+        let client = reqwest::Client::new();
+
+        for block_id in [
+            json!("earliest"),
+            json!({"block_hash": "0xzz"}),
+            json!({"block_number": "one"}),
+            json!({"block_number": 1, "block_hash": "0x1"}),
+        ] {
+            let mut params = params.clone();
+            params["block_id"] = block_id.clone();
+
+            let request = json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": params,
+                "id": 0,
+            });
+
+            let res: serde_json::Value = client
+                .post(url.clone())
+                .json(&request)
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+
+            assert_eq!(res["error"]["code"], json!(-32602), "block_id: {block_id}");
+        }
+    }
+
+    /// Guards against methods which panic on malformed input, which would otherwise only be
+    /// caught by the router as an internal error.
+    #[rstest::rstest]
+    #[case::v03(v03::register_routes())]
+    #[case::v04(v04::register_routes())]
+    #[case::v05(v05::register_routes())]
+    #[case::pathfinder(pathfinder::register_routes())]
+    #[tokio::test]
+    async fn methods_reject_invalid_params(#[case] routes: jsonrpc::RpcRouterBuilder) {
+        let router = routes.build(RpcContext::for_tests());
+        jsonrpc::test_utils::assert_methods_reject_invalid_params(router).await;
+    }
+
+    #[rstest::rstest]
+    #[case::v03(v03::register_routes())]
+    #[case::v04(v04::register_routes())]
+    #[tokio::test]
+    async fn shared_methods_are_registered(#[case] routes: jsonrpc::RpcRouterBuilder) {
+        let shared = v03::register_shared_routes(jsonrpc::RpcRouter::builder("shared"))
+            .build(RpcContext::for_tests());
+        let router = routes.build(RpcContext::for_tests());
+
+        let methods = router.method_names().collect::<HashSet<_>>();
+        for method in shared.method_names() {
+            assert!(methods.contains(method), "{method} is not registered");
+        }
+    }
+
+    #[tokio::test]
+    async fn closes_connections_trickling_headers() {
+        use std::io::{Read, Write};
+        use std::time::Duration;
+
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let (_jh, addr) = RpcServer::new(addr, context, DefaultVersion::V04)
+            .with_read_timeout(Duration::from_millis(100))
+            .spawn()
+            .unwrap();
+
+        tokio::task::spawn_blocking(move || {
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+
+            // The headers are never completed.
+            stream.write_all(b"POST / HTTP/1.1\r\nHost: localhost\r\n").unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            stream.write_all(b"Content-Type: application/json\r\n").unwrap();
+
+            let result = stream.read_to_end(&mut Vec::new());
+            assert!(
+                !matches!(&result, Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut)),
+                "Connection should be closed by the server"
+            );
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn method_filter_is_applied_per_path() {
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let filter = MethodFilter::Deny(["starknet_chainId".to_owned()].into());
+        let (_jh, addr) = RpcServer::new(addr, context, DefaultVersion::V04)
+            .with_method_filter("/rpc/v0.4", filter)
+            .spawn()
+            .unwrap();
+
+        let client = reqwest::Client::new();
+        let query = |path: &'static str, method: &'static str| {
+            let client = client.clone();
+            async move {
+                client
+                    .post(format!("http://{addr}{path}"))
+                    .json(&json!({"jsonrpc": "2.0", "method": method, "id": 0}))
+                    .send()
+                    .await
+                    .unwrap()
+                    .json::<serde_json::Value>()
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let method_not_found = json!(-32601);
+
+        let denied = query("/rpc/v0.4", "starknet_chainId").await;
+        assert_eq!(denied["error"]["code"], method_not_found);
+
+        let allowed = query("/rpc/v0.4", "starknet_blockNumber").await;
+        assert!(allowed.get("result").is_some(), "{allowed}");
+
+        // Other paths are unaffected.
+        let other_path = query("/rpc/v0.3", "starknet_chainId").await;
+        assert!(other_path.get("result").is_some(), "{other_path}");
+    }
+
+    #[rstest::rstest]
+    #[case::unknown_version("/rpc/v04")]
+    #[case::merged_path("/rpc")]
+    #[tokio::test]
+    async fn method_filter_for_unserved_path_is_rejected(#[case] path: &str) {
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let filter = MethodFilter::Deny(["starknet_chainId".to_owned()].into());
+        let error = RpcServer::new(addr, context, DefaultVersion::V04)
+            .with_merged_path("/rpc")
+            .with_method_filter(path, filter)
+            .spawn()
+            .unwrap_err();
+
+        assert!(error.to_string().contains(path), "{error}");
+    }
+
+    #[rstest::rstest]
+    #[case::selected(Some("v0.5"), "v0.5")]
+    #[case::without_prefix(Some("0.3"), "v0.3")]
+    #[case::absent(None, "v0.4")]
+    #[case::unknown(Some("v9.9"), "v0.4")]
+    #[tokio::test]
+    async fn merged_path_selects_version_by_header(
+        #[case] requested: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let (_jh, addr) = RpcServer::new(addr, context, DefaultVersion::V04)
+            .with_merged_path("/rpc")
+            .with_version_header()
+            .spawn()
+            .unwrap();
+
+        let request = reqwest::Client::new()
+            .post(format!("http://{addr}/rpc"))
+            .json(&json!({"jsonrpc": "2.0", "method": "starknet_chainId", "id": 0}));
+        let request = match requested {
+            Some(version) => request.header("x-starknet-rpc-version", version),
+            None => request,
+        };
+        let response = request.send().await.unwrap();
+
+        assert_eq!(response.headers()["x-pathfinder-version"], expected);
+    }
+
     #[rustfmt::skip]
     #[rstest::rstest]
     // Ensure that a missing method would actually fail this test.
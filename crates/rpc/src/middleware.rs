@@ -1 +1,7 @@
+pub mod concurrency_limit;
+pub mod connection_limit;
 pub mod cors;
+pub mod guarded_body;
+pub mod peer_limit;
+pub mod read_timeout;
+pub mod write_timeout;
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use http::{Request, Response};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower::{Layer, Service};
+
+use crate::middleware::guarded_body::GuardedBody;
+
+/// Limits the number of requests in flight at once, shared by all connections. Requests beyond
+/// the limit wait for a slot.
+///
+/// Unlike [tower::limit::ConcurrencyLimitLayer], a request holds its slot until its response
+/// body has been sent rather than until its response future completes, so that streamed
+/// responses also count towards the limit.
+#[derive(Clone)]
+pub struct ConcurrencyLimitLayer {
+    semaphore: Arc<Semaphore>,
+}
+
+impl ConcurrencyLimitLayer {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(limit)),
+        }
+    }
+}
+
+impl<S> Layer<S> for ConcurrencyLimitLayer {
+    type Service = ConcurrencyLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConcurrencyLimit {
+            inner,
+            semaphore: self.semaphore.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConcurrencyLimit<S> {
+    inner: S,
+    semaphore: Arc<Semaphore>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for ConcurrencyLimit<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: Send + 'static,
+{
+    type Response = Response<GuardedBody<ResBody, OwnedSemaphorePermit>>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        // The service which was polled for readiness is the one which must be called.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let semaphore = self.semaphore.clone();
+
+        async move {
+            let permit = semaphore
+                .acquire_owned()
+                .await
+                .expect("Semaphore is never closed");
+            let response = inner.call(request).await?;
+            Ok(response.map(|body| GuardedBody::new(body, permit)))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn slot_is_held_until_body_is_dropped() {
+        let service = tower::service_fn(|_: Request<Body>| async {
+            Ok::<_, Infallible>(Response::new(Body::from("response")))
+        });
+        let layer = ConcurrencyLimitLayer::new(1);
+        let service = layer.layer(service);
+
+        let response = service.clone().oneshot(Request::new(Body::empty())).await;
+        assert_eq!(layer.semaphore.available_permits(), 0);
+
+        drop(response);
+        assert_eq!(layer.semaphore.available_permits(), 1);
+    }
+}
@@ -0,0 +1,274 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use http::{HeaderValue, Request, Response};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Sleep;
+use tower::{BoxError, Service};
+
+/// Wraps a make-service, i.e. the service which creates a service for each accepted connection,
+/// and tracks the number of open connections using the `rpc_active_connections` gauge.
+///
+/// If a limit is set, connections accepted while the limit is reached are closed immediately.
+/// This is independent of any per-request limits, and protects against running out of file
+/// descriptors.
+///
+/// Connections may also be closed once they have served a number of requests, or once they
+/// have been idle for the keep-alive timeout.
+#[derive(Clone)]
+pub struct ConnectionLimit<M> {
+    inner: M,
+    semaphore: Option<Arc<Semaphore>>,
+    max_requests: Option<usize>,
+    keep_alive_timeout: Option<Duration>,
+}
+
+impl<M> ConnectionLimit<M> {
+    pub fn new(inner: M, limit: Option<usize>) -> Self {
+        Self {
+            inner,
+            semaphore: limit.map(|limit| Arc::new(Semaphore::new(limit))),
+            max_requests: None,
+            keep_alive_timeout: None,
+        }
+    }
+
+    /// Closes connections once they have served `max_requests` requests.
+    pub fn with_max_requests(self, max_requests: usize) -> Self {
+        Self {
+            max_requests: Some(max_requests),
+            ..self
+        }
+    }
+
+    /// Closes connections which have been waiting for their next request for longer than
+    /// `keep_alive_timeout`.
+    pub fn with_keep_alive_timeout(self, keep_alive_timeout: Duration) -> Self {
+        Self {
+            keep_alive_timeout: Some(keep_alive_timeout),
+            ..self
+        }
+    }
+}
+
+impl<M, T> Service<T> for ConnectionLimit<M>
+where
+    M: Service<T>,
+    M::Future: Send + 'static,
+    M::Error: Into<BoxError>,
+{
+    type Response = Connection<M::Response>;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, target: T) -> Self::Future {
+        let permit = match &self.semaphore {
+            Some(semaphore) => match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    tracing::debug!("Connection limit reached, closing connection");
+                    return futures::future::ready(Err("Connection limit reached".into())).boxed();
+                }
+            },
+            None => None,
+        };
+        let guard = ConnectionGuard::new(permit);
+        let max_requests = self.max_requests;
+        let keep_alive_timeout = self.keep_alive_timeout;
+
+        let service = self.inner.call(target);
+        async move {
+            let service = service.await.map_err(Into::into)?;
+            Ok(Connection {
+                inner: service,
+                requests: 0,
+                max_requests,
+                keep_alive_timeout,
+                idle: None,
+                _guard: guard,
+            })
+        }
+        .boxed()
+    }
+}
+
+/// The service of a single connection, which releases its slot once the connection is closed.
+pub struct Connection<S> {
+    inner: S,
+    requests: usize,
+    max_requests: Option<usize>,
+    keep_alive_timeout: Option<Duration>,
+    /// Expires once the connection has been idle for the keep-alive timeout.
+    idle: Option<Pin<Box<Sleep>>>,
+    _guard: ConnectionGuard,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for Connection<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // The server only polls for readiness while no request is in flight, so the timer
+        // measures the time spent waiting for the next request. An error closes the connection.
+        if let Some(timeout) = self.keep_alive_timeout {
+            let idle = self
+                .idle
+                .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+            if idle.as_mut().poll(cx).is_ready() {
+                tracing::trace!("Keep-alive timeout elapsed, closing connection");
+                return Poll::Ready(Err("Keep-alive timeout elapsed".into()));
+            }
+        }
+
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        self.idle = None;
+        self.requests += 1;
+        let close = self.max_requests.is_some_and(|max| self.requests >= max);
+
+        let response = self.inner.call(request);
+        async move {
+            let mut response = response.await.map_err(Into::into)?;
+            if close {
+                // Instructs the server to close the connection once the response has been sent.
+                response
+                    .headers_mut()
+                    .insert(http::header::CONNECTION, HeaderValue::from_static("close"));
+            }
+            Ok(response)
+        }
+        .boxed()
+    }
+}
+
+struct ConnectionGuard {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl ConnectionGuard {
+    fn new(permit: Option<OwnedSemaphorePermit>) -> Self {
+        metrics::increment_gauge!("rpc_active_connections", 1.0);
+        Self { _permit: permit }
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        metrics::decrement_gauge!("rpc_active_connections", 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::num::NonZeroUsize;
+    use std::time::Duration;
+
+    use crate::{context::RpcContext, DefaultVersion, RpcServer};
+
+    /// Sends a request on the connection and returns the number of bytes received in response.
+    fn query(stream: &mut TcpStream) -> usize {
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        // A failed write or read means the connection was closed by the server.
+        let request = "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        if stream.write_all(request.as_bytes()).is_err() {
+            return 0;
+        }
+        let mut response = [0; 64];
+        stream.read(&mut response).unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn rejects_connections_beyond_limit() {
+        let addr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let (_jh, addr) = RpcServer::new(addr, context, DefaultVersion::V04)
+            .with_max_open_connections(NonZeroUsize::new(1).unwrap())
+            .spawn()
+            .unwrap();
+
+        tokio::task::spawn_blocking(move || {
+            let mut first = TcpStream::connect(addr).unwrap();
+            assert!(query(&mut first) > 0);
+
+            let mut second = TcpStream::connect(addr).unwrap();
+            assert_eq!(query(&mut second), 0);
+
+            // Closing the first connection frees up its slot, which happens asynchronously.
+            drop(first);
+            let served = (0..50).any(|_| {
+                std::thread::sleep(Duration::from_millis(20));
+                let mut third = TcpStream::connect(addr).unwrap();
+                query(&mut third) > 0
+            });
+            assert!(served);
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn closes_connections_after_max_requests() {
+        let addr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let (_jh, addr) = RpcServer::new(addr, context, DefaultVersion::V04)
+            .with_max_requests_per_connection(NonZeroUsize::new(2).unwrap())
+            .spawn()
+            .unwrap();
+
+        tokio::task::spawn_blocking(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            assert!(query(&mut stream) > 0);
+
+            // The second request is the last one served, after which the server hangs up.
+            let request = "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+            stream.write_all(request.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            assert!(response.to_lowercase().contains("connection: close"));
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn closes_idle_connections() {
+        let addr = "127.0.0.1:0".parse().unwrap();
+        let context = RpcContext::for_tests();
+        let (_jh, addr) = RpcServer::new(addr, context, DefaultVersion::V04)
+            .with_keep_alive_timeout(Duration::from_millis(100))
+            .spawn()
+            .unwrap();
+
+        tokio::task::spawn_blocking(move || {
+            let mut active = TcpStream::connect(addr).unwrap();
+            assert!(query(&mut active) > 0);
+
+            let mut idle = TcpStream::connect(addr).unwrap();
+            std::thread::sleep(Duration::from_millis(500));
+            assert_eq!(query(&mut idle), 0);
+        })
+        .await
+        .unwrap();
+    }
+}
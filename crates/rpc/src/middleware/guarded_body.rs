@@ -0,0 +1,54 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use hyper::body::HttpBody;
+
+/// A response body which keeps `guard` alive until the body is dropped, i.e. until it has been
+/// sent or the client has disconnected.
+///
+/// Middleware which releases its resources once the response future completes would otherwise
+/// release them while streamed bodies, such as streamed batch responses, are still executing.
+pub struct GuardedBody<B, G> {
+    inner: B,
+    _guard: G,
+}
+
+impl<B, G> GuardedBody<B, G> {
+    pub fn new(inner: B, guard: G) -> Self {
+        Self {
+            inner,
+            _guard: guard,
+        }
+    }
+}
+
+impl<B, G> HttpBody for GuardedBody<B, G>
+where
+    B: HttpBody + Unpin,
+    G: Unpin,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_data(cx)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> hyper::body::SizeHint {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use http::{Request, StatusCode};
+use hyper::server::conn::AddrStream;
+use tokio::sync::Semaphore;
+use tower::{BoxError, Service};
+
+use crate::middleware::guarded_body::GuardedBody;
+use crate::middleware::write_timeout::WriteTimeout;
+
+/// A connection whose peer may be identified by its IP address.
+pub trait PeerIp {
+    fn peer_ip(&self) -> Option<IpAddr>;
+}
+
+impl PeerIp for AddrStream {
+    fn peer_ip(&self) -> Option<IpAddr> {
+        Some(self.remote_addr().ip())
+    }
+}
+
+impl PeerIp for tokio::net::UnixStream {
+    fn peer_ip(&self) -> Option<IpAddr> {
+        None
+    }
+}
+
+impl<S: PeerIp> PeerIp for WriteTimeout<S> {
+    fn peer_ip(&self) -> Option<IpAddr> {
+        self.get_ref().peer_ip()
+    }
+}
+
+/// The in-flight requests of all open connections from an IP address.
+struct Peer {
+    semaphore: Arc<Semaphore>,
+    connections: usize,
+}
+
+type Peers = Arc<Mutex<HashMap<IpAddr, Peer>>>;
+
+/// Wraps a make-service, and limits the number of requests each IP address may have in flight
+/// at once, summed over all of its connections.
+///
+/// Requests beyond the limit are answered with `429 Too Many Requests` immediately. A request
+/// is in flight until its response body has been sent. Unlike a rate limit this does not
+/// restrict how many requests a client sends over time, but prevents a single client from
+/// occupying all workers with slow requests.
+///
+/// An IP address is forgotten once its last connection is closed. Connections without an IP
+/// address, such as those accepted on a Unix domain socket, are not limited.
+#[derive(Clone)]
+pub struct PeerLimit<M> {
+    inner: M,
+    limit: Option<usize>,
+    peers: Peers,
+}
+
+impl<M> PeerLimit<M> {
+    pub fn new(inner: M, limit: Option<usize>) -> Self {
+        Self {
+            inner,
+            limit,
+            peers: Default::default(),
+        }
+    }
+}
+
+impl<'a, M, C> Service<&'a C> for PeerLimit<M>
+where
+    C: PeerIp,
+    M: Service<&'a C>,
+    M::Future: Send + 'static,
+    M::Error: Into<BoxError>,
+{
+    type Response = PeerConnection<M::Response>;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, target: &'a C) -> Self::Future {
+        let slot = match (self.limit, target.peer_ip()) {
+            (Some(limit), Some(ip)) => Some(PeerSlot::new(self.peers.clone(), ip, limit)),
+            _ => None,
+        };
+
+        let service = self.inner.call(target);
+        async move {
+            let service = service.await.map_err(Into::into)?;
+            Ok(PeerConnection {
+                inner: service,
+                slot,
+            })
+        }
+        .boxed()
+    }
+}
+
+/// The service of a single connection, which shares the in-flight limit of its peer.
+pub struct PeerConnection<S> {
+    inner: S,
+    slot: Option<PeerSlot>,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for PeerConnection<S>
+where
+    S: Service<Request<ReqBody>, Response = Response>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let permit = match &self.slot {
+            Some(slot) => match slot.semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    tracing::debug!(ip=%slot.ip, "Concurrent request limit of peer reached, rejecting request");
+                    metrics::increment_counter!("rpc_peer_limited_requests_total");
+                    let response = (
+                        StatusCode::TOO_MANY_REQUESTS,
+                        "Too many concurrent requests",
+                    )
+                        .into_response();
+                    return futures::future::ready(Ok(response)).boxed();
+                }
+            },
+            None => None,
+        };
+
+        let response = self.inner.call(request);
+        async move {
+            let response = response.await.map_err(Into::into)?;
+            // Held until the body has been sent, as streamed responses are still executing.
+            Ok(response.map(|body| axum::body::boxed(GuardedBody::new(body, permit))))
+        }
+        .boxed()
+    }
+}
+
+/// Registers a connection with its peer, which is forgotten once its last connection is closed.
+struct PeerSlot {
+    ip: IpAddr,
+    semaphore: Arc<Semaphore>,
+    peers: Peers,
+}
+
+impl PeerSlot {
+    fn new(peers: Peers, ip: IpAddr, limit: usize) -> Self {
+        let semaphore = {
+            let mut guard = peers.lock().unwrap();
+            let peer = guard.entry(ip).or_insert_with(|| Peer {
+                semaphore: Arc::new(Semaphore::new(limit)),
+                connections: 0,
+            });
+            peer.connections += 1;
+            peer.semaphore.clone()
+        };
+
+        Self {
+            ip,
+            semaphore,
+            peers,
+        }
+    }
+}
+
+impl Drop for PeerSlot {
+    fn drop(&mut self) {
+        let mut peers = self.peers.lock().unwrap();
+        if let Some(peer) = peers.get_mut(&self.ip) {
+            peer.connections -= 1;
+            if peer.connections == 0 {
+                peers.remove(&self.ip);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use axum::body::Body;
+    use futures::future::Ready;
+    use tower::util::BoxService;
+
+    use super::*;
+
+    struct Connection(IpAddr);
+
+    impl PeerIp for Connection {
+        fn peer_ip(&self) -> Option<IpAddr> {
+            Some(self.0)
+        }
+    }
+
+    const FIRST: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 1));
+    const SECOND: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 2));
+
+    type ConnectionService = BoxService<Request<Body>, Response, Infallible>;
+
+    /// Limits each peer to two requests, which are answered successfully.
+    fn make_service() -> PeerLimit<
+        impl for<'a> Service<
+            &'a Connection,
+            Response = ConnectionService,
+            Error = Infallible,
+            Future = Ready<Result<ConnectionService, Infallible>>,
+        >,
+    > {
+        let make_service = tower::service_fn(|_: &Connection| {
+            let service = tower::service_fn(|_: Request<Body>| async {
+                Ok::<_, Infallible>(StatusCode::OK.into_response())
+            });
+            futures::future::ok(BoxService::new(service))
+        });
+        PeerLimit::new(make_service, Some(2))
+    }
+
+    fn request() -> Request<Body> {
+        Request::new(Body::empty())
+    }
+
+    #[tokio::test]
+    async fn rejects_requests_beyond_limit() {
+        let mut make_service = make_service();
+        let mut first = make_service.call(&Connection(FIRST)).await.unwrap();
+        let mut second = make_service.call(&Connection(FIRST)).await.unwrap();
+        let mut other = make_service.call(&Connection(SECOND)).await.unwrap();
+
+        // Requests are in flight until their response future completes, and the limit is
+        // shared by all connections of the peer.
+        let in_flight = [first.call(request()), second.call(request())];
+
+        let response = first.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let response = second.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Other peers are unaffected.
+        let response = other.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Completing an in-flight request frees up its slot.
+        let [completed, _] = in_flight;
+        completed.await.unwrap();
+        let response = first.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn peers_are_forgotten_once_disconnected() {
+        let mut make_service = make_service();
+        let first = make_service.call(&Connection(FIRST)).await.unwrap();
+        let second = make_service.call(&Connection(FIRST)).await.unwrap();
+        let other = make_service.call(&Connection(SECOND)).await.unwrap();
+
+        drop(first);
+        assert_eq!(make_service.peers.lock().unwrap().len(), 2);
+
+        drop(second);
+        drop(other);
+        assert!(make_service.peers.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use axum::body::Bytes;
+use hyper::body::HttpBody;
+use tokio::time::Sleep;
+
+/// A request body whose reads fail once they have been stalled for longer than `timeout`.
+///
+/// This aborts requests whose clients stop sending their body part way through, without
+/// limiting the total time taken to receive large bodies.
+pub struct ReadTimeoutBody<B> {
+    inner: B,
+    timeout: Duration,
+    /// Expires once the pending read has been stalled for the timeout.
+    stalled: Option<Pin<Box<Sleep>>>,
+}
+
+/// The error of a [ReadTimeoutBody] whose read stalled.
+#[derive(Debug, thiserror::Error)]
+#[error("Timed out reading the request body")]
+pub struct BodyReadTimedOut;
+
+impl<B> ReadTimeoutBody<B> {
+    pub fn new(inner: B, timeout: Duration) -> Self {
+        Self {
+            inner,
+            timeout,
+            stalled: None,
+        }
+    }
+}
+
+impl<B> HttpBody for ReadTimeoutBody<B>
+where
+    B: HttpBody<Data = Bytes> + Unpin,
+    B::Error: Into<axum::BoxError>,
+{
+    type Data = Bytes;
+    type Error = axum::BoxError;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        if let Poll::Ready(data) = Pin::new(&mut self.inner).poll_data(cx) {
+            self.stalled = None;
+            return Poll::Ready(data.map(|data| data.map_err(Into::into)));
+        }
+
+        let timeout = self.timeout;
+        let stalled = self
+            .stalled
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+        match stalled.as_mut().poll(cx) {
+            Poll::Ready(()) => {
+                tracing::trace!("Body read timeout elapsed, aborting request");
+                Poll::Ready(Some(Err(BodyReadTimedOut.into())))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner)
+            .poll_trailers(cx)
+            .map_err(Into::into)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> hyper::body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[tokio::test(start_paused = true)]
+    async fn stalled_body_is_aborted() {
+        let (mut sender, body) = hyper::Body::channel();
+        let mut body = ReadTimeoutBody::new(body, TIMEOUT);
+
+        // Part of the body is sent, after which the client stalls.
+        sender
+            .send_data(Bytes::from_static(b"{\"jsonrpc\""))
+            .await
+            .unwrap();
+        assert_eq!(
+            body.data().await.unwrap().unwrap(),
+            Bytes::from_static(b"{\"jsonrpc\"")
+        );
+
+        let error = body.data().await.unwrap().unwrap_err();
+        assert!(error.is::<BodyReadTimedOut>());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_body_is_not_cut_off() {
+        let (mut sender, body) = hyper::Body::channel();
+        let mut body = ReadTimeoutBody::new(body, TIMEOUT);
+
+        // Each chunk arrives well within the timeout, though the body as a whole takes much
+        // longer than the timeout to receive.
+        tokio::spawn(async move {
+            for _ in 0..10 {
+                tokio::time::sleep(TIMEOUT / 2).await;
+                sender.send_data(Bytes::from_static(b"x")).await.unwrap();
+            }
+        });
+
+        let body = hyper::body::to_bytes(body).await.unwrap();
+        assert_eq!(body.len(), 10);
+    }
+}
@@ -0,0 +1,173 @@
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use hyper::server::accept::Accept;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::time::Sleep;
+
+/// Wraps the connections accepted by `inner` in [WriteTimeout].
+pub struct WriteTimeoutIncoming<A> {
+    inner: A,
+    timeout: Duration,
+}
+
+impl<A> WriteTimeoutIncoming<A> {
+    pub fn new(inner: A, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+}
+
+impl<A> Accept for WriteTimeoutIncoming<A>
+where
+    A: Accept + Unpin,
+{
+    type Conn = WriteTimeout<A::Conn>;
+    type Error = A::Error;
+
+    fn poll_accept(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let timeout = self.timeout;
+        Pin::new(&mut self.inner)
+            .poll_accept(cx)
+            .map_ok(|conn| WriteTimeout::new(conn, timeout))
+    }
+}
+
+/// A connection which fails writes which have made no progress for longer than `timeout`,
+/// which closes the connection.
+///
+/// This disconnects clients which are too slow to receive their response, without limiting
+/// the total time taken to send large responses.
+pub struct WriteTimeout<S> {
+    inner: S,
+    timeout: Duration,
+    /// Expires once the pending write has been stalled for the timeout.
+    stalled: Option<Pin<Box<Sleep>>>,
+}
+
+impl<S> WriteTimeout<S> {
+    pub fn new(inner: S, timeout: Duration) -> Self {
+        Self {
+            inner,
+            timeout,
+            stalled: None,
+        }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    fn poll_progress<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        poll: Poll<io::Result<T>>,
+    ) -> Poll<io::Result<T>> {
+        if poll.is_ready() {
+            self.stalled = None;
+            return poll;
+        }
+
+        let timeout = self.timeout;
+        let stalled = self
+            .stalled
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+        match stalled.as_mut().poll(cx) {
+            Poll::Ready(()) => {
+                tracing::trace!("Write timeout elapsed, closing connection");
+                Poll::Ready(Err(io::ErrorKind::TimedOut.into()))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for WriteTimeout<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for WriteTimeout<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        self.poll_progress(cx, poll)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        self.poll_progress(cx, poll)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let poll = Pin::new(&mut self.inner).poll_flush(cx);
+        self.poll_progress(cx, poll)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let poll = Pin::new(&mut self.inner).poll_shutdown(cx);
+        self.poll_progress(cx, poll)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+    const RESPONSE_SIZE: usize = 1024 * 1024;
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_reader_is_disconnected() {
+        let (server, _client) = tokio::io::duplex(1024);
+        let mut server = WriteTimeout::new(server, TIMEOUT);
+
+        // The client never reads, so the write stalls once the buffer is full.
+        let error = server.write_all(&vec![0; RESPONSE_SIZE]).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn large_response_is_not_cut_off() {
+        let (server, mut client) = tokio::io::duplex(1024);
+        let mut server = WriteTimeout::new(server, TIMEOUT);
+
+        // Each chunk is read well within the timeout, though the response as a whole takes
+        // much longer than the timeout to receive.
+        let reader = tokio::spawn(async move {
+            let mut received = 0;
+            let mut chunk = [0; 1024];
+            while received < RESPONSE_SIZE {
+                tokio::time::sleep(TIMEOUT / 100).await;
+                received += client.read(&mut chunk).await.unwrap();
+            }
+            received
+        });
+
+        server.write_all(&vec![0; RESPONSE_SIZE]).await.unwrap();
+        assert_eq!(reader.await.unwrap(), RESPONSE_SIZE);
+    }
+}
@@ -1,11 +1,59 @@
-use crate::jsonrpc::{RpcRouter, RpcRouterBuilder};
+use crate::jsonrpc::{MethodOptions, RpcRouter, RpcRouterBuilder};
 
 pub(crate) mod methods;
 
 #[rustfmt::skip]
 pub fn register_routes() -> RpcRouterBuilder {
     RpcRouter::builder("v0.1")
-        .register("pathfinder_version",              || { pathfinder_common::consts::VERGEN_GIT_DESCRIBE })
-        .register("pathfinder_getProof",             methods::get_proof)
-        .register("pathfinder_getTransactionStatus", methods::get_transaction_status)
+        .register("pathfinder_version",                 || { pathfinder_common::consts::VERGEN_GIT_DESCRIBE })
+        .register("pathfinder_getProof",                methods::get_proof)
+        .register("pathfinder_getBlockBundle",          methods::get_block_bundle)
+        .register_with("pathfinder_getTransactionStatus", methods::get_transaction_status, MethodOptions::default().schema::<methods::GetGatewayTransactionInput>())
+        .register_with("pathfinder_getTransactionsByHashes", methods::get_transactions_by_hashes, MethodOptions::default().schema::<methods::GetTransactionsByHashesInput>())
+        .register("pathfinder_syncing",                 methods::syncing)
+        .register("pathfinder_status",                  methods::status)
+        .register("pathfinder_errorCodes",              methods::error_codes)
+        .register("pathfinder_pendingSummary",          methods::pending_summary)
+        .register("pathfinder_classesDeclared",         methods::classes_declared)
+        .register("pathfinder_feeTokens",               methods::fee_tokens)
+        .register_with("pathfinder_estimateTransactionFee", methods::estimate_transaction_fee, MethodOptions::default().schema::<methods::EstimateTransactionFeeInput>())
+        .register_method_schema("pathfinder_methodSchema")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::RpcContext;
+    use crate::jsonrpc::test_utils::{assert_jsonrpc_conformance, serve_and_query};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn conformance() {
+        let router = super::register_routes().build(RpcContext::for_tests());
+        assert_jsonrpc_conformance(router).await;
+    }
+
+    #[tokio::test]
+    async fn method_schema() {
+        let router = super::register_routes().build(RpcContext::for_tests());
+
+        let response = serve_and_query(
+            router,
+            json!({
+                "jsonrpc": "2.0",
+                "method": "pathfinder_methodSchema",
+                "params": ["pathfinder_getTransactionsByHashes"],
+                "id": 1
+            }),
+        )
+        .await;
+
+        let schema = &response["result"];
+        assert_eq!(schema["type"], json!("object"));
+        assert_eq!(schema["additionalProperties"], json!(false));
+        assert_eq!(schema["required"], json!(["transaction_hashes"]));
+        assert_eq!(
+            schema["properties"]["transaction_hashes"]["items"],
+            json!({"type": "string"})
+        );
+    }
 }
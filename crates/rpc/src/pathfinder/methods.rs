@@ -1,5 +1,25 @@
+mod classes_declared;
+mod error_codes;
+mod estimate_transaction_fee;
+mod fee_tokens;
+mod get_block_bundle;
 mod get_proof;
 mod get_transaction_status;
+mod get_transactions_by_hashes;
+mod pending_summary;
+mod status;
+mod syncing;
 
+pub(crate) use classes_declared::classes_declared;
+pub(crate) use error_codes::error_codes;
+pub(crate) use estimate_transaction_fee::{estimate_transaction_fee, EstimateTransactionFeeInput};
+pub(crate) use fee_tokens::fee_tokens;
+pub(crate) use get_block_bundle::get_block_bundle;
 pub(crate) use get_proof::get_proof;
-pub(crate) use get_transaction_status::get_transaction_status;
+pub(crate) use get_transaction_status::{get_transaction_status, GetGatewayTransactionInput};
+pub(crate) use get_transactions_by_hashes::{
+    get_transactions_by_hashes, GetTransactionsByHashesInput,
+};
+pub(crate) use pending_summary::pending_summary;
+pub(crate) use status::status;
+pub(crate) use syncing::syncing;
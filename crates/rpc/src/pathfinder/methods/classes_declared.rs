@@ -0,0 +1,189 @@
+use anyhow::Context;
+use pathfinder_common::{BlockId, ClassHash};
+
+use crate::context::RpcContext;
+use crate::v02::method::get_class::is_pending_class;
+
+#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ClassesDeclaredInput {
+    class_hashes: Vec<ClassHash>,
+    block_id: BlockId,
+}
+
+#[derive(Debug)]
+pub enum ClassesDeclaredError {
+    Internal(anyhow::Error),
+    BlockNotFound,
+    ClassLimitExceeded { limit: usize, requested: usize },
+}
+impl From<anyhow::Error> for ClassesDeclaredError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Internal(e)
+    }
+}
+impl From<ClassesDeclaredError> for crate::error::RpcError {
+    fn from(x: ClassesDeclaredError) -> Self {
+        match x {
+            ClassesDeclaredError::ClassLimitExceeded { limit, requested } => {
+                Self::ClassLimitExceeded { limit, requested }
+            }
+            ClassesDeclaredError::BlockNotFound => Self::BlockNotFound,
+            ClassesDeclaredError::Internal(internal) => Self::Internal(internal),
+        }
+    }
+}
+
+/// Reports whether each of the given classes is declared at the given block, in the same order
+/// as requested, without reading their definitions.
+///
+/// Follows `starknet_getClass` in what counts as declared, including for the pending block.
+pub async fn classes_declared(
+    context: RpcContext,
+    input: ClassesDeclaredInput,
+) -> Result<Vec<bool>, ClassesDeclaredError> {
+    let limit = context.config.max_classes_declared;
+    let requested = input.class_hashes.len();
+    if requested > limit {
+        return Err(ClassesDeclaredError::ClassLimitExceeded { limit, requested });
+    }
+
+    let (block_id, pending) = match input.block_id {
+        BlockId::Pending => {
+            let mut pending = Vec::with_capacity(requested);
+            for class_hash in &input.class_hashes {
+                pending.push(is_pending_class(&context.pending_data, *class_hash).await);
+            }
+            (pathfinder_storage::BlockId::Latest, pending)
+        }
+        other => (
+            other.try_into().expect("Only pending cast should fail"),
+            vec![false; requested],
+        ),
+    };
+
+    let span = tracing::Span::current();
+    let jh = tokio::task::spawn_blocking(move || {
+        let _g = span.enter();
+        let mut db = context
+            .storage
+            .connection()
+            .context("Opening database connection")?;
+        let tx = db.transaction().context("Creating database transaction")?;
+
+        if !tx.block_exists(block_id)? {
+            return Err(ClassesDeclaredError::BlockNotFound);
+        }
+
+        let declared = tx
+            .classes_declared_at(block_id, &input.class_hashes)
+            .context("Querying class declarations")?
+            .into_iter()
+            .zip(pending)
+            .map(|(declared, pending)| declared || pending)
+            .collect();
+
+        Ok(declared)
+    });
+
+    jh.await
+        .context("Reading class declarations from database")?
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+    use pathfinder_common::BlockNumber;
+
+    use super::*;
+    use crate::context::RpcConfig;
+
+    fn input(block_id: BlockId) -> ClassesDeclaredInput {
+        ClassesDeclaredInput {
+            class_hashes: vec![
+                class_hash_bytes!(b"class 0 hash"),
+                class_hash_bytes!(b"class 2 hash (sierra)"),
+                class_hash_bytes!(b"pending class 0 hash"),
+                class_hash_bytes!(b"invalid"),
+            ],
+            block_id,
+        }
+    }
+
+    #[test]
+    fn parsing() {
+        let input = serde_json::json!({
+            "class_hashes": ["0x1", "0x2"],
+            "block_id": "latest",
+        });
+        let input = serde_json::from_value::<ClassesDeclaredInput>(input).unwrap();
+
+        assert_eq!(
+            input,
+            ClassesDeclaredInput {
+                class_hashes: vec![class_hash!("0x1"), class_hash!("0x2")],
+                block_id: BlockId::Latest,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn latest() {
+        let context = RpcContext::for_tests_with_pending().await;
+
+        let result = classes_declared(context, input(BlockId::Latest))
+            .await
+            .unwrap();
+        assert_eq!(result, vec![true, true, false, false]);
+    }
+
+    #[tokio::test]
+    async fn pending() {
+        let context = RpcContext::for_tests_with_pending().await;
+
+        let result = classes_declared(context, input(BlockId::Pending))
+            .await
+            .unwrap();
+        assert_eq!(result, vec![true, true, true, false]);
+    }
+
+    #[tokio::test]
+    async fn at_number() {
+        let context = RpcContext::for_tests_with_pending().await;
+
+        // The Sierra class is only declared in block 2.
+        let block_id = BlockId::Number(BlockNumber::new_or_panic(1));
+        let result = classes_declared(context, input(block_id)).await.unwrap();
+        assert_eq!(result, vec![true, false, false, false]);
+    }
+
+    #[tokio::test]
+    async fn block_not_found() {
+        let context = RpcContext::for_tests();
+
+        let block_id = BlockId::Hash(block_hash_bytes!(b"unknown"));
+        let error = classes_declared(context, input(block_id))
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(error, ClassesDeclaredError::BlockNotFound);
+    }
+
+    #[tokio::test]
+    async fn limit_exceeded() {
+        let context = RpcContext::for_tests().with_config(RpcConfig {
+            max_classes_declared: 3,
+            ..Default::default()
+        });
+
+        let error = classes_declared(context, input(BlockId::Latest))
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            ClassesDeclaredError::ClassLimitExceeded {
+                limit: 3,
+                requested: 4
+            }
+        );
+    }
+}
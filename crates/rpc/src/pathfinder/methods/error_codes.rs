@@ -0,0 +1,62 @@
+use serde::Serialize;
+
+use crate::error::RpcError;
+use crate::jsonrpc::RouterError;
+
+crate::error::generate_rpc_error_subset!(ErrorCodesError);
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ErrorCode {
+    code: i32,
+    name: String,
+    message: String,
+}
+
+/// Lists the code, name and message of every error this node may return: those raised by the
+/// router itself, followed by the application errors.
+pub async fn error_codes() -> Result<Vec<ErrorCode>, ErrorCodesError> {
+    let application = RpcError::variants().into_iter().map(|error| ErrorCode {
+        code: error.code(),
+        name: error.name().to_owned(),
+        message: error.to_string(),
+    });
+    let router = RouterError::ALL.into_iter().map(|error| ErrorCode {
+        code: error.code,
+        name: error.name.to_owned(),
+        message: error.message.to_owned(),
+    });
+
+    let codes = router.chain(application).collect();
+
+    Ok(codes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lists_all_variants() {
+        let codes = error_codes().await.unwrap();
+
+        assert_eq!(
+            codes.len(),
+            RpcError::variants().len() + RouterError::ALL.len()
+        );
+        assert!(codes.contains(&ErrorCode {
+            code: 24,
+            name: "BlockNotFound".to_owned(),
+            message: "Block not found".to_owned(),
+        }));
+        assert!(codes.contains(&ErrorCode {
+            code: 10000,
+            name: "ProofLimitExceeded".to_owned(),
+            message: "Too many storage keys requested".to_owned(),
+        }));
+        assert!(codes.contains(&ErrorCode {
+            code: -32001,
+            name: "Unauthorized".to_owned(),
+            message: "Unauthorized".to_owned(),
+        }));
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::Context;
+use pathfinder_common::{BlockId, TransactionHash};
+use primitive_types::U256;
+
+use crate::compose_executor_transaction;
+use crate::context::RpcContext;
+use crate::v03::method::estimate_fee::FeeEstimate;
+
+#[derive(serde::Deserialize, schemars::JsonSchema, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct EstimateTransactionFeeInput {
+    /// Hex encoded hash of the transaction.
+    #[schemars(with = "String")]
+    transaction_hash: TransactionHash,
+}
+
+crate::error::generate_rpc_error_subset!(EstimateTransactionFeeError: TxnHashNotFoundV04);
+
+impl From<pathfinder_executor::CallError> for EstimateTransactionFeeError {
+    fn from(value: pathfinder_executor::CallError) -> Self {
+        use pathfinder_executor::CallError::*;
+        match value {
+            ContractNotFound => Self::Internal(anyhow::anyhow!("Contract not found")),
+            InvalidMessageSelector => Self::Internal(anyhow::anyhow!("Invalid message selector")),
+            Reverted(revert_error) => {
+                Self::Internal(anyhow::anyhow!("Transaction reverted: {}", revert_error))
+            }
+            Internal(e) => Self::Internal(e),
+        }
+    }
+}
+
+impl From<crate::executor::ExecutionStateError> for EstimateTransactionFeeError {
+    fn from(error: crate::executor::ExecutionStateError) -> Self {
+        use crate::executor::ExecutionStateError::*;
+        match error {
+            BlockNotFound => Self::Internal(anyhow::anyhow!("Parent block not found")),
+            Internal(e) => Self::Internal(e),
+        }
+    }
+}
+
+/// Estimates the fee of a transaction which was already included in a block.
+///
+/// The transaction is read from storage and estimated on top of the state of its block's parent,
+/// at its block's gas price. The transactions preceding it in its block are executed first, so
+/// that it is estimated against the state it was actually executed on.
+pub async fn estimate_transaction_fee(
+    context: RpcContext,
+    input: EstimateTransactionFeeInput,
+) -> Result<FeeEstimate, EstimateTransactionFeeError> {
+    let span = tracing::Span::current();
+    let storage = context.storage.clone();
+
+    let (transactions, parent_block_hash, gas_price) = tokio::task::spawn_blocking(move || {
+        let _g = span.enter();
+
+        let mut db = storage.connection()?;
+        let tx = db.transaction()?;
+
+        let block_hash = tx
+            .transaction_block_hash(input.transaction_hash)?
+            .ok_or(EstimateTransactionFeeError::TxnHashNotFoundV04)?;
+
+        let header = tx
+            .block_header(block_hash.into())?
+            .context("Block of stored transaction is missing")?;
+
+        let transactions = tx
+            .transaction_data_for_block(block_hash.into())?
+            .context("Transactions of stored block are missing")?
+            .into_iter()
+            .map(|(transaction, _)| transaction);
+
+        let mut preceding = Vec::new();
+        for transaction in transactions {
+            let hash = transaction.hash();
+            preceding.push(compose_executor_transaction(transaction, &tx)?);
+            if hash == input.transaction_hash {
+                break;
+            }
+        }
+
+        Ok::<_, EstimateTransactionFeeError>((
+            preceding,
+            header.parent_hash,
+            U256::from(header.gas_price.0),
+        ))
+    })
+    .await
+    .context("Fetching transaction")??;
+
+    let execution_state = crate::executor::execution_state(
+        context,
+        BlockId::Hash(parent_block_hash),
+        Some(gas_price),
+    )
+    .await?;
+
+    let span = tracing::Span::current();
+    let estimates = tokio::task::spawn_blocking(move || {
+        let _g = span.enter();
+        pathfinder_executor::estimate(execution_state, transactions)
+    })
+    .await
+    .context("Executing transaction")??;
+
+    let estimate = estimates
+        .into_iter()
+        .last()
+        .context("Estimate of transaction is missing")?;
+
+    Ok(estimate.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fee::FeeWei;
+    use crate::v04::method::setup_multi_tx_trace_test;
+    use pathfinder_common::macro_prelude::*;
+
+    #[tokio::test]
+    async fn known_transaction() {
+        let (context, block_header, traces) = setup_multi_tx_trace_test().await.unwrap();
+
+        let input = EstimateTransactionFeeInput {
+            transaction_hash: traces[2].transaction_hash,
+        };
+        let estimate = estimate_transaction_fee(context, input).await.unwrap();
+
+        assert_eq!(
+            estimate.gas_price,
+            FeeWei(U256::from(block_header.gas_price.0))
+        );
+        assert!(estimate.gas_consumed > U256::zero());
+        assert_eq!(
+            estimate.overall_fee,
+            FeeWei(estimate.gas_consumed * estimate.gas_price.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_transaction() {
+        let context = RpcContext::for_tests();
+        let input = EstimateTransactionFeeInput {
+            transaction_hash: transaction_hash_bytes!(b"unknown"),
+        };
+
+        let error = estimate_transaction_fee(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(error, EstimateTransactionFeeError::TxnHashNotFoundV04);
+    }
+}
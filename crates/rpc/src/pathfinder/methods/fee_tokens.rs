@@ -0,0 +1,56 @@
+use pathfinder_common::ContractAddress;
+use serde::Serialize;
+
+crate::error::generate_rpc_error_subset!(FeeTokensError);
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct FeeToken {
+    address: ContractAddress,
+    symbol: &'static str,
+}
+
+/// Lists the token contracts in which transaction fees are paid.
+///
+/// Fees are currently paid in ETH on every network, whose token is deployed at the same address
+/// on each of them.
+pub async fn fee_tokens() -> Result<Vec<FeeToken>, FeeTokensError> {
+    Ok(vec![FeeToken {
+        address: pathfinder_executor::FEE_TOKEN_ADDRESS,
+        symbol: "ETH",
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn eth() {
+        let tokens = fee_tokens().await.unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![FeeToken {
+                address: contract_address!(
+                    "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"
+                ),
+                symbol: "ETH",
+            }]
+        );
+    }
+
+    #[test]
+    fn serialization() {
+        let token = FeeToken {
+            address: contract_address!("0x1"),
+            symbol: "ETH",
+        };
+
+        assert_eq!(
+            serde_json::to_value(token).unwrap(),
+            serde_json::json!({"address": "0x1", "symbol": "ETH"})
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::Context;
+use pathfinder_common::BlockId;
+use serde::Serialize;
+
+use crate::context::RpcContext;
+use crate::v02::types::reply::BlockStatus;
+use crate::v03::method::get_state_update::types::StateUpdate;
+use crate::v04::method::get_block_with_txs::types::Block;
+use crate::v04::method::get_transaction_receipt::types::{FinalityStatus, TransactionReceipt};
+
+#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct GetBlockBundleInput {
+    block_id: BlockId,
+}
+
+crate::error::generate_rpc_error_subset!(GetBlockBundleError: BlockNotFound);
+
+/// A block along with everything an explorer displays for it, as returned by
+/// `starknet_getBlockWithTxs`, `starknet_getTransactionReceipt` and `starknet_getStateUpdate`.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct BlockBundle {
+    block: Block,
+    /// The receipts of the block's transactions, in the same order.
+    receipts: Vec<TransactionReceipt>,
+    state_update: StateUpdate,
+}
+
+/// Returns a block with its transactions, receipts and state update, which are all read in a
+/// single database transaction.
+///
+/// The pending block is not supported, and reported as not found.
+pub async fn get_block_bundle(
+    context: RpcContext,
+    input: GetBlockBundleInput,
+) -> Result<BlockBundle, GetBlockBundleError> {
+    let block_id = match input.block_id {
+        BlockId::Pending => return Err(GetBlockBundleError::BlockNotFound),
+        other => other.try_into().expect("Only pending cast should fail"),
+    };
+
+    let storage = context.storage.clone();
+    let span = tracing::Span::current();
+
+    tokio::task::spawn_blocking(move || {
+        let _g = span.enter();
+        let mut db = storage
+            .connection()
+            .context("Opening database connection")?;
+
+        let tx = db.transaction().context("Creating database transaction")?;
+
+        let header = tx
+            .block_header(block_id)
+            .context("Reading block header")?
+            .ok_or(GetBlockBundleError::BlockNotFound)?;
+        let block_id = header.number.into();
+
+        let l1_accepted = tx
+            .block_is_l1_accepted(block_id)
+            .context("Querying block status")?;
+        let (block_status, finality_status) = if l1_accepted {
+            (BlockStatus::AcceptedOnL1, FinalityStatus::AcceptedOnL1)
+        } else {
+            (BlockStatus::AcceptedOnL2, FinalityStatus::AcceptedOnL2)
+        };
+
+        let transaction_data = tx
+            .transaction_data_for_block(block_id)
+            .context("Reading transactions")?
+            .context("Transaction data missing for block")?;
+
+        let state_update = tx
+            .state_update(block_id)
+            .context("Reading state update")?
+            .context("State update missing for block")?;
+
+        let mut transactions = Vec::with_capacity(transaction_data.len());
+        let mut receipts = Vec::with_capacity(transaction_data.len());
+        for (transaction, receipt) in transaction_data {
+            transactions.push(transaction.clone().into());
+            receipts.push(TransactionReceipt::with_block_data(
+                receipt,
+                finality_status.clone(),
+                header.hash,
+                header.number,
+                transaction,
+            ));
+        }
+
+        Ok(BlockBundle {
+            block: Block::from_parts(header, block_status, transactions),
+            receipts,
+            state_update: state_update.into(),
+        })
+    })
+    .await
+    .context("Database read panic or shutting down")?
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+    use pathfinder_common::BlockNumber;
+    use serde_json::json;
+
+    use super::*;
+    use crate::v04::method::get_transaction_receipt::types::MaybePendingTransactionReceipt;
+
+    /// Covers both an L1 and an L2 accepted block.
+    #[rstest::rstest]
+    #[case::genesis(json!({"block_number": 0}))]
+    #[case::latest(json!("latest"))]
+    #[tokio::test]
+    async fn matches_separate_calls(#[case] block_id: serde_json::Value) {
+        let context = RpcContext::for_tests();
+        let input = serde_json::from_value(json!({ "block_id": block_id })).unwrap();
+
+        let mut bundle = get_block_bundle(context.clone(), input).await.unwrap();
+
+        let input = serde_json::from_value(json!({ "block_id": block_id })).unwrap();
+        let block =
+            crate::v04::method::get_block_with_txs::get_block_with_txs(context.clone(), input)
+                .await
+                .unwrap();
+
+        let mut receipts = Vec::new();
+        for transaction in &block.transactions {
+            let input = serde_json::from_value(json!({
+                "transaction_hash": transaction.transaction_hash,
+            }))
+            .unwrap();
+            let receipt = crate::v04::method::get_transaction_receipt::get_transaction_receipt(
+                context.clone(),
+                input,
+            )
+            .await
+            .unwrap();
+            let MaybePendingTransactionReceipt::Normal(receipt) = receipt else {
+                panic!("Stored receipt should not be pending");
+            };
+            receipts.push(receipt);
+        }
+
+        let input = serde_json::from_value(json!({ "block_id": block_id })).unwrap();
+        let mut state_update = crate::v03::method::get_state_update(context, input)
+            .await
+            .unwrap();
+
+        // The state diff's order is not deterministic.
+        bundle.state_update.sort();
+        state_update.sort();
+
+        assert!(!receipts.is_empty());
+        assert_eq!(bundle.block, block);
+        assert_eq!(bundle.receipts, receipts);
+        assert_eq!(bundle.state_update, state_update);
+    }
+
+    #[rstest::rstest]
+    #[case::number(BlockId::Number(BlockNumber::new_or_panic(9999)))]
+    #[case::hash(BlockId::Hash(block_hash_bytes!(b"unknown")))]
+    #[case::pending(BlockId::Pending)]
+    #[tokio::test]
+    async fn block_not_found(#[case] block_id: BlockId) {
+        let context = RpcContext::for_tests();
+        let input = GetBlockBundleInput { block_id };
+
+        let error = get_block_bundle(context, input).await.unwrap_err();
+
+        assert_matches::assert_matches!(error, GetBlockBundleError::BlockNotFound);
+    }
+}
@@ -5,12 +5,14 @@ use starknet_gateway_types::reply::transaction::ExecutionStatus;
 
 use crate::context::RpcContext;
 
-#[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+#[derive(serde::Deserialize, schemars::JsonSchema, Debug, PartialEq, Eq)]
 pub struct GetGatewayTransactionInput {
+    /// Hex encoded hash of the transaction.
+    #[schemars(with = "String")]
     transaction_hash: TransactionHash,
 }
 
-crate::error::generate_rpc_error_subset!(GetGatewayTransactionError:);
+crate::error::generate_rpc_error_subset!(GetGatewayTransactionError: TxnHashNotFoundV04);
 
 pub async fn get_transaction_status(
     context: RpcContext,
@@ -63,15 +65,22 @@ pub async fn get_transaction_status(
         return Ok(db_status);
     }
 
-    // Check gateway for rejected transactions.
+    // Only unknown transactions fall through to the gateway. These are typically
+    // rejected transactions, or transactions we have not yet synced.
     use starknet_gateway_client::GatewayApi;
-    context
+    let status = context
         .sequencer
         .transaction(input.transaction_hash)
         .await
-        .context("Fetching transaction from gateway")
-        .map(|tx| tx.status.into())
-        .map_err(GetGatewayTransactionError::Internal)
+        .context("Fetching transaction from gateway")?
+        .status;
+
+    match status {
+        starknet_gateway_types::reply::Status::NotReceived => {
+            Err(GetGatewayTransactionError::TxnHashNotFoundV04)
+        }
+        other => Ok(other.into()),
+    }
 }
 
 async fn pending_status(
@@ -192,6 +201,17 @@ mod tests {
         assert_eq!(status, TransactionStatus::Rejected);
     }
 
+    #[tokio::test]
+    async fn unknown() {
+        let input = GetGatewayTransactionInput {
+            transaction_hash: transaction_hash_bytes!(b"not a real transaction"),
+        };
+        let context = RpcContext::for_tests();
+        let err = get_transaction_status(context, input).await.unwrap_err();
+
+        assert_matches::assert_matches!(err, GetGatewayTransactionError::TxnHashNotFoundV04);
+    }
+
     #[tokio::test]
     async fn reverted() {
         let context = RpcContext::for_tests_with_pending().await;
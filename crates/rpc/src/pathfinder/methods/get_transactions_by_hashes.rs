@@ -0,0 +1,155 @@
+use pathfinder_common::TransactionHash;
+
+use crate::context::RpcContext;
+use crate::v02::method::get_transaction_by_hash::{
+    get_transaction_by_hash_impl, GetTransactionByHashInput,
+};
+use crate::v04::types::TransactionWithHash;
+
+#[derive(serde::Deserialize, schemars::JsonSchema, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct GetTransactionsByHashesInput {
+    /// Hex encoded hashes of the transactions.
+    #[schemars(with = "Vec<String>")]
+    transaction_hashes: Vec<TransactionHash>,
+}
+
+#[derive(Debug)]
+pub enum GetTransactionsByHashesError {
+    Internal(anyhow::Error),
+    TransactionLimitExceeded { limit: usize, requested: usize },
+}
+impl From<anyhow::Error> for GetTransactionsByHashesError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Internal(e)
+    }
+}
+impl From<GetTransactionsByHashesError> for crate::error::RpcError {
+    fn from(x: GetTransactionsByHashesError) -> Self {
+        match x {
+            GetTransactionsByHashesError::TransactionLimitExceeded { limit, requested } => {
+                Self::TransactionLimitExceeded { limit, requested }
+            }
+            GetTransactionsByHashesError::Internal(internal) => Self::Internal(internal),
+        }
+    }
+}
+
+/// Looks up each of the given transactions, returning them in the same order as requested.
+///
+/// Unknown transactions are reported as `null` rather than failing the whole request.
+pub async fn get_transactions_by_hashes(
+    context: RpcContext,
+    input: GetTransactionsByHashesInput,
+) -> Result<Vec<Option<TransactionWithHash>>, GetTransactionsByHashesError> {
+    let limit = context.config.max_transactions_by_hashes;
+    let requested = input.transaction_hashes.len();
+    if requested > limit {
+        return Err(GetTransactionsByHashesError::TransactionLimitExceeded { limit, requested });
+    }
+
+    let lookups = input
+        .transaction_hashes
+        .into_iter()
+        .map(|transaction_hash| {
+            get_transaction_by_hash_impl(
+                context.clone(),
+                GetTransactionByHashInput { transaction_hash },
+            )
+        });
+
+    let transactions = futures::future::try_join_all(lookups)
+        .await?
+        .into_iter()
+        .map(|tx| tx.map(|x| pathfinder_common::transaction::Transaction::from(x).into()))
+        .collect();
+
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+
+    use super::*;
+    use crate::context::RpcConfig;
+
+    #[test]
+    fn parsing() {
+        let input = serde_json::json!({ "transaction_hashes": ["0x1", "0x2"] });
+        let input = serde_json::from_value::<GetTransactionsByHashesInput>(input).unwrap();
+
+        assert_eq!(
+            input,
+            GetTransactionsByHashesInput {
+                transaction_hashes: vec![transaction_hash!("0x1"), transaction_hash!("0x2")],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn known_and_unknown() {
+        let context = RpcContext::for_tests_with_pending().await;
+        let input = GetTransactionsByHashesInput {
+            transaction_hashes: vec![
+                transaction_hash_bytes!(b"txn 0"),
+                transaction_hash_bytes!(b"unknown"),
+                transaction_hash_bytes!(b"pending tx hash 0"),
+                transaction_hash_bytes!(b"txn 1"),
+            ],
+        };
+
+        let result = get_transactions_by_hashes(context, input).await.unwrap();
+        let hashes = result
+            .into_iter()
+            .map(|tx| tx.map(|tx| tx.transaction_hash))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            hashes,
+            vec![
+                Some(transaction_hash_bytes!(b"txn 0")),
+                None,
+                Some(transaction_hash_bytes!(b"pending tx hash 0")),
+                Some(transaction_hash_bytes!(b"txn 1")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn empty() {
+        let context = RpcContext::for_tests();
+        let input = GetTransactionsByHashesInput {
+            transaction_hashes: vec![],
+        };
+
+        let result = get_transactions_by_hashes(context, input).await.unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn limit_exceeded() {
+        let context = RpcContext::for_tests().with_config(RpcConfig {
+            max_transactions_by_hashes: 2,
+            ..Default::default()
+        });
+        let input = GetTransactionsByHashesInput {
+            transaction_hashes: vec![
+                transaction_hash_bytes!(b"txn 0"),
+                transaction_hash_bytes!(b"txn 1"),
+                transaction_hash_bytes!(b"txn 2"),
+            ],
+        };
+
+        let error = get_transactions_by_hashes(context, input)
+            .await
+            .unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            GetTransactionsByHashesError::TransactionLimitExceeded {
+                limit: 2,
+                requested: 3
+            }
+        );
+    }
+}
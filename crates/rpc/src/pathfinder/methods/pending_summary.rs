@@ -0,0 +1,83 @@
+use pathfinder_common::BlockTimestamp;
+use serde::Serialize;
+
+use crate::context::RpcContext;
+
+crate::error::generate_rpc_error_subset!(PendingSummaryError);
+
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct PendingSummary {
+    transaction_count: usize,
+    /// Cairo and Sierra classes declared in the pending block.
+    declared_class_count: usize,
+    /// Timestamp of the pending block, which is when its oldest transaction was added.
+    timestamp: Option<BlockTimestamp>,
+}
+
+/// Summarizes the contents of the pending block, which is cheaper for monitoring than
+/// fetching all pending transactions.
+///
+/// Without a pending block, the summary is empty.
+pub async fn pending_summary(context: RpcContext) -> Result<PendingSummary, PendingSummaryError> {
+    let Some(pending) = context.pending_data else {
+        return Ok(PendingSummary::default());
+    };
+
+    let block = pending.block().await;
+    let state_update = pending.state_update().await;
+
+    let summary = PendingSummary {
+        transaction_count: block.as_ref().map_or(0, |block| block.transactions.len()),
+        declared_class_count: state_update.map_or(0, |state_update| {
+            state_update.declared_cairo_classes.len() + state_update.declared_sierra_classes.len()
+        }),
+        timestamp: block.map(|block| block.timestamp),
+    };
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn pending() {
+        let context = RpcContext::for_tests_with_pending().await;
+
+        let summary = pending_summary(context).await.unwrap();
+
+        assert_eq!(
+            summary,
+            PendingSummary {
+                transaction_count: 3,
+                declared_class_count: 2,
+                timestamp: Some(BlockTimestamp::new_or_panic(1234567)),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn without_pending_data() {
+        let context = RpcContext::for_tests();
+
+        let summary = pending_summary(context).await.unwrap();
+
+        assert_eq!(
+            serde_json::to_value(summary).unwrap(),
+            json!({"transaction_count": 0, "declared_class_count": 0, "timestamp": null})
+        );
+    }
+
+    #[tokio::test]
+    async fn without_pending_block() {
+        let context = RpcContext::for_tests_with_pending().await;
+        context.pending_data.as_ref().unwrap().clear().await;
+
+        let summary = pending_summary(context).await.unwrap();
+
+        assert_eq!(summary, PendingSummary::default());
+    }
+}
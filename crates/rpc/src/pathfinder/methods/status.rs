@@ -0,0 +1,138 @@
+use pathfinder_common::BlockNumber;
+use serde::Serialize;
+
+use crate::context::RpcContext;
+use crate::v02::types::syncing::Syncing;
+
+crate::error::generate_rpc_error_subset!(StatusError);
+
+/// Aggregates the node's health signals, e.g. for dashboards.
+///
+/// Only state which the node already tracks is reported, so this neither queries the gateway
+/// nor the database.
+pub async fn status(context: RpcContext) -> Result<StatusOutput, StatusError> {
+    let sync = match &*context.sync_status.status.read().await {
+        Syncing::False(_) => SyncStatus::default(),
+        Syncing::Status(status) => SyncStatus {
+            current_block_num: Some(status.current.number),
+            highest_block_num: Some(status.highest.number),
+        },
+    };
+
+    let gateway = match context.sequencer.is_circuit_open() {
+        None => GatewayStatus {
+            reachable: None,
+            circuit_breaker: CircuitBreakerState::Disabled,
+        },
+        Some(true) => GatewayStatus {
+            reachable: Some(false),
+            circuit_breaker: CircuitBreakerState::Open,
+        },
+        Some(false) => GatewayStatus {
+            reachable: Some(true),
+            circuit_breaker: CircuitBreakerState::Closed,
+        },
+    };
+
+    Ok(StatusOutput {
+        gateway,
+        sync,
+        execution_enabled: !context.config.execution_disabled,
+    })
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct StatusOutput {
+    gateway: GatewayStatus,
+    sync: SyncStatus,
+    execution_enabled: bool,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct GatewayStatus {
+    /// Derived from the circuit breaker, and unknown if it is disabled.
+    reachable: Option<bool>,
+    circuit_breaker: CircuitBreakerState,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CircuitBreakerState {
+    Disabled,
+    Closed,
+    /// The breaker of at least one gateway method is open, or probing for recovery.
+    Open,
+}
+
+/// Unknown until the gateway's head has been polled for the first time.
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct SyncStatus {
+    current_block_num: Option<BlockNumber>,
+    highest_block_num: Option<BlockNumber>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::v02::types::syncing::{NumberedBlock, Status};
+
+    #[tokio::test]
+    async fn defaults() {
+        let context = RpcContext::for_tests();
+
+        let result = status(context).await.unwrap();
+
+        assert_eq!(
+            serde_json::to_value(result).unwrap(),
+            json!({
+                "gateway": {
+                    "reachable": null,
+                    "circuit_breaker": "DISABLED",
+                },
+                "sync": {
+                    "current_block_num": null,
+                    "highest_block_num": null,
+                },
+                "execution_enabled": true,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn configured() {
+        let mut context = RpcContext::for_tests().with_config(crate::context::RpcConfig {
+            execution_disabled: true,
+            ..Default::default()
+        });
+        context.sequencer = context
+            .sequencer
+            .with_circuit_breaker(NonZeroUsize::new(1).unwrap(), Duration::from_secs(1));
+        *context.sync_status.status.write().await = Syncing::Status(Status {
+            starting: NumberedBlock::from(("aabb", 1)),
+            current: NumberedBlock::from(("ccddee", 2)),
+            highest: NumberedBlock::from(("eeffaacc", 3)),
+        });
+
+        let result = status(context).await.unwrap();
+
+        assert_eq!(
+            serde_json::to_value(result).unwrap(),
+            json!({
+                "gateway": {
+                    "reachable": true,
+                    "circuit_breaker": "CLOSED",
+                },
+                "sync": {
+                    "current_block_num": 2,
+                    "highest_block_num": 3,
+                },
+                "execution_enabled": false,
+            })
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use serde::Serialize;
+
+use crate::context::RpcContext;
+use crate::v02::types::syncing::{Status, Syncing};
+
+crate::error::generate_rpc_error_subset!(SyncingError);
+
+/// Like `starknet_syncing`, but also reports the sync rate and an estimate of the time
+/// remaining until the node has caught up.
+pub async fn syncing(context: RpcContext) -> Result<SyncingOutput, SyncingError> {
+    let status = { context.sync_status.status.read().await.clone() };
+
+    let output = match status {
+        Syncing::False(_) => SyncingOutput::False(false),
+        Syncing::Status(status) => {
+            let rate = context.sync_status.rate.lock().unwrap();
+            SyncingOutput::Status(SyncingStatus {
+                status,
+                blocks_per_second: rate.blocks_per_second(),
+                seconds_remaining: rate
+                    .time_remaining(status.highest.number)
+                    .map(|remaining| remaining.as_secs()),
+            })
+        }
+    };
+
+    Ok(output)
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum SyncingOutput {
+    False(bool),
+    Status(SyncingStatus),
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SyncingStatus {
+    #[serde(flatten)]
+    status: Status,
+    /// Blocks imported per second over the recent past, if known.
+    blocks_per_second: Option<f64>,
+    /// Estimated time until the highest block is imported, if known.
+    seconds_remaining: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::BlockNumber;
+    use serde_json::json;
+
+    use super::*;
+    use crate::v02::types::syncing::NumberedBlock;
+
+    #[tokio::test]
+    async fn not_syncing() {
+        let context = RpcContext::for_tests();
+
+        let result = syncing(context).await.unwrap();
+
+        assert_eq!(serde_json::to_value(result).unwrap(), json!(false));
+    }
+
+    #[tokio::test]
+    async fn syncing_without_rate() {
+        let context = RpcContext::for_tests();
+        *context.sync_status.status.write().await = Syncing::Status(Status {
+            starting: NumberedBlock::from(("aabb", 1)),
+            current: NumberedBlock::from(("ccddee", 2)),
+            highest: NumberedBlock::from(("eeffaacc", 3)),
+        });
+
+        let result = syncing(context).await.unwrap();
+
+        assert_eq!(
+            serde_json::to_value(result).unwrap(),
+            json!({
+                "starting_block_num": "0x1",
+                "current_block_num": "0x2",
+                "highest_block_num": "0x3",
+                "starting_block_hash": "0xaabb",
+                "current_block_hash": "0xccddee",
+                "highest_block_hash": "0xeeffaacc",
+                "blocks_per_second": null,
+                "seconds_remaining": null,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn syncing_with_rate() {
+        let context = RpcContext::for_tests();
+        *context.sync_status.status.write().await = Syncing::Status(Status {
+            starting: NumberedBlock::from(("aabb", 1)),
+            current: NumberedBlock::from(("ccddee", 2)),
+            highest: NumberedBlock::from(("eeffaacc", 1_000_000)),
+        });
+        {
+            let mut rate = context.sync_status.rate.lock().unwrap();
+            rate.record(BlockNumber::new_or_panic(1));
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            rate.record(BlockNumber::new_or_panic(2));
+        }
+
+        let result = syncing(context).await.unwrap();
+
+        let SyncingOutput::Status(status) = result else {
+            panic!("Expected a syncing status");
+        };
+        assert!(status.blocks_per_second.unwrap() > 0.0);
+        assert!(status.seconds_remaining.unwrap() > 0);
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use pathfinder_common::TransactionHash;
+use tokio::time::Instant;
+
+use crate::v02::types::request::BroadcastedInvokeTransaction;
+
+const METRIC_SIZE: &str = "rpc_recent_submissions";
+
+/// Invoke transactions recently accepted by the gateway, which lets repeated submissions of
+/// the same transaction be answered without forwarding them again.
+#[derive(Debug, Default)]
+pub struct RecentSubmissions {
+    invokes: HashMap<TransactionHash, Submission>,
+}
+
+#[derive(Debug)]
+struct Submission {
+    transaction: BroadcastedInvokeTransaction,
+    /// The hash reported by the gateway when accepting the transaction.
+    accepted_hash: TransactionHash,
+    at: Instant,
+}
+
+impl RecentSubmissions {
+    /// Returns the hash with which the gateway accepted `transaction`, if the identical
+    /// transaction was submitted less than `ttl` ago.
+    ///
+    /// `hash` is the transaction's own hash, which identifies it among the submissions.
+    pub fn get(
+        &mut self,
+        hash: TransactionHash,
+        transaction: &BroadcastedInvokeTransaction,
+        ttl: Duration,
+    ) -> Option<TransactionHash> {
+        self.get_at(hash, transaction, ttl, Instant::now())
+    }
+
+    fn get_at(
+        &mut self,
+        hash: TransactionHash,
+        transaction: &BroadcastedInvokeTransaction,
+        ttl: Duration,
+        now: Instant,
+    ) -> Option<TransactionHash> {
+        self.prune_at(ttl, now);
+
+        self.invokes
+            .get(&hash)
+            .filter(|submission| &submission.transaction == transaction)
+            .map(|submission| submission.accepted_hash)
+    }
+
+    /// Removes the submissions made `ttl` or longer ago, returning the number remaining.
+    pub fn prune(&mut self, ttl: Duration) -> usize {
+        self.prune_at(ttl, Instant::now())
+    }
+
+    fn prune_at(&mut self, ttl: Duration, now: Instant) -> usize {
+        self.invokes
+            .retain(|_, submission| now.duration_since(submission.at) < ttl);
+        self.invokes.len()
+    }
+
+    /// Records that the gateway accepted `transaction` as `accepted_hash`.
+    pub fn insert(
+        &mut self,
+        hash: TransactionHash,
+        transaction: BroadcastedInvokeTransaction,
+        accepted_hash: TransactionHash,
+    ) {
+        self.insert_at(hash, transaction, accepted_hash, Instant::now());
+    }
+
+    fn insert_at(
+        &mut self,
+        hash: TransactionHash,
+        transaction: BroadcastedInvokeTransaction,
+        accepted_hash: TransactionHash,
+        at: Instant,
+    ) {
+        self.invokes.insert(
+            hash,
+            Submission {
+                transaction,
+                accepted_hash,
+                at,
+            },
+        );
+    }
+}
+
+/// Prunes the expired submissions every `ttl`, so that these do not accumulate while no
+/// transactions are submitted. The number of submissions kept is reported by the
+/// `rpc_recent_submissions` gauge.
+pub async fn prune_periodically(
+    submissions: Arc<Mutex<RecentSubmissions>>,
+    ttl: Duration,
+) -> Infallible {
+    let mut interval = tokio::time::interval(ttl);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let remaining = submissions.lock().unwrap().prune(ttl);
+        metrics::gauge!(METRIC_SIZE, remaining as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pathfinder_common::macro_prelude::*;
+    use pathfinder_common::TransactionVersion;
+
+    use super::*;
+    use crate::v02::types::request::BroadcastedInvokeTransactionV1;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn transaction(signature: &[u8]) -> BroadcastedInvokeTransaction {
+        BroadcastedInvokeTransaction::V1(BroadcastedInvokeTransactionV1 {
+            version: TransactionVersion::ONE,
+            max_fee: fee!("0x1"),
+            signature: vec![transaction_signature_elem_bytes!(signature)],
+            nonce: transaction_nonce!("0x1"),
+            sender_address: contract_address!("0x1"),
+            calldata: vec![call_param!("0x1")],
+        })
+    }
+
+    #[test]
+    fn identical_submission() {
+        let start = Instant::now();
+        let mut submissions = RecentSubmissions::default();
+        submissions.insert_at(
+            transaction_hash!("0x1"),
+            transaction(b"signature"),
+            transaction_hash!("0x2"),
+            start,
+        );
+
+        let result = submissions.get_at(
+            transaction_hash!("0x1"),
+            &transaction(b"signature"),
+            TTL,
+            start + TTL / 2,
+        );
+        assert_eq!(result, Some(transaction_hash!("0x2")));
+    }
+
+    #[test]
+    fn different_submission() {
+        let start = Instant::now();
+        let mut submissions = RecentSubmissions::default();
+        submissions.insert_at(
+            transaction_hash!("0x1"),
+            transaction(b"signature"),
+            transaction_hash!("0x2"),
+            start,
+        );
+
+        let different_hash = submissions.get_at(
+            transaction_hash!("0x3"),
+            &transaction(b"signature"),
+            TTL,
+            start,
+        );
+        assert_eq!(different_hash, None);
+
+        // The transaction hash does not cover the signature.
+        let different_signature =
+            submissions.get_at(transaction_hash!("0x1"), &transaction(b"other"), TTL, start);
+        assert_eq!(different_signature, None);
+    }
+
+    #[test]
+    fn expired_submission() {
+        let start = Instant::now();
+        let mut submissions = RecentSubmissions::default();
+        submissions.insert_at(
+            transaction_hash!("0x1"),
+            transaction(b"signature"),
+            transaction_hash!("0x2"),
+            start,
+        );
+
+        let result = submissions.get_at(
+            transaction_hash!("0x1"),
+            &transaction(b"signature"),
+            TTL,
+            start + TTL,
+        );
+        assert_eq!(result, None);
+        assert!(submissions.invokes.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn expired_submissions_are_pruned_periodically() {
+        let submissions = Arc::new(Mutex::new(RecentSubmissions::default()));
+        submissions.lock().unwrap().insert(
+            transaction_hash!("0x1"),
+            transaction(b"signature"),
+            transaction_hash!("0x2"),
+        );
+
+        let task = tokio::spawn(prune_periodically(submissions.clone(), TTL));
+
+        tokio::time::sleep(TTL / 2).await;
+        assert_eq!(submissions.lock().unwrap().invokes.len(), 1);
+
+        tokio::time::sleep(TTL).await;
+        assert!(submissions.lock().unwrap().invokes.is_empty());
+
+        task.abort();
+    }
+}
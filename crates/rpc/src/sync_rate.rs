@@ -0,0 +1,102 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use pathfinder_common::BlockNumber;
+
+/// Tracks the import times of the most recent blocks, from which the sync rate is estimated.
+#[derive(Debug, Default)]
+pub struct SyncRate {
+    imports: VecDeque<(BlockNumber, Instant)>,
+}
+
+impl SyncRate {
+    /// Number of imports the rate is estimated over.
+    const WINDOW: usize = 64;
+
+    /// Records that `block` has just been imported.
+    pub fn record(&mut self, block: BlockNumber) {
+        self.record_at(block, Instant::now());
+    }
+
+    fn record_at(&mut self, block: BlockNumber, at: Instant) {
+        if self.imports.len() == Self::WINDOW {
+            self.imports.pop_front();
+        }
+        self.imports.push_back((block, at));
+    }
+
+    /// The number of blocks imported per second over the recent window, or [None] if there
+    /// are too few imports to tell.
+    pub fn blocks_per_second(&self) -> Option<f64> {
+        let (first_block, first_time) = self.imports.front()?;
+        let (last_block, last_time) = self.imports.back()?;
+
+        let blocks = last_block.get().checked_sub(first_block.get())?;
+        let elapsed = last_time.duration_since(*first_time).as_secs_f64();
+        if blocks == 0 || elapsed == 0.0 {
+            return None;
+        }
+
+        Some(blocks as f64 / elapsed)
+    }
+
+    /// Estimates the time left until `highest` is imported, assuming the current rate is
+    /// maintained. Returns [None] if the rate is unknown.
+    pub fn time_remaining(&self, highest: BlockNumber) -> Option<Duration> {
+        let rate = self.blocks_per_second()?;
+        let (current, _) = self.imports.back()?;
+        let remaining = highest.get().saturating_sub(current.get());
+
+        Some(Duration::from_secs_f64(remaining as f64 / rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_without_enough_imports() {
+        let mut rate = SyncRate::default();
+        assert_eq!(rate.blocks_per_second(), None);
+        assert_eq!(rate.time_remaining(BlockNumber::new_or_panic(10)), None);
+
+        rate.record(BlockNumber::new_or_panic(1));
+        assert_eq!(rate.blocks_per_second(), None);
+        assert_eq!(rate.time_remaining(BlockNumber::new_or_panic(10)), None);
+    }
+
+    #[test]
+    fn estimates_over_window() {
+        let start = Instant::now();
+        let mut rate = SyncRate::default();
+
+        // Importing slowly at first, which falls out of the window.
+        for i in 0..10 {
+            rate.record_at(
+                BlockNumber::new_or_panic(i),
+                start + Duration::from_secs(i * 10),
+            );
+        }
+        // Followed by 2 blocks per second.
+        let start = start + Duration::from_secs(100);
+        for i in 0..SyncRate::WINDOW as u64 {
+            rate.record_at(
+                BlockNumber::new_or_panic(10 + i),
+                start + Duration::from_millis(i * 500),
+            );
+        }
+
+        assert_eq!(rate.blocks_per_second(), Some(2.0));
+
+        let current = 10 + SyncRate::WINDOW as u64 - 1;
+        assert_eq!(
+            rate.time_remaining(BlockNumber::new_or_panic(current + 100)),
+            Some(Duration::from_secs(50))
+        );
+        assert_eq!(
+            rate.time_remaining(BlockNumber::new_or_panic(current)),
+            Some(Duration::ZERO)
+        );
+    }
+}
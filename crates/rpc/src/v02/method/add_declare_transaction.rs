@@ -1,4 +1,5 @@
 use crate::context::RpcContext;
+use crate::error::ClassSizeLimit;
 use crate::felt::RpcFelt;
 use crate::v02::types::request::BroadcastedDeclareTransaction;
 use pathfinder_common::{ClassHash, TransactionHash};
@@ -11,6 +12,7 @@ use starknet_gateway_types::request::add_transaction::{
 #[derive(Debug)]
 pub enum AddDeclareTransactionError {
     InvalidContractClass,
+    ContractClassSizeIsTooLarge(ClassSizeLimit),
     GatewayError(StarknetError),
     Internal(anyhow::Error),
 }
@@ -19,6 +21,9 @@ impl From<AddDeclareTransactionError> for crate::error::RpcError {
     fn from(value: AddDeclareTransactionError) -> Self {
         match value {
             AddDeclareTransactionError::InvalidContractClass => Self::InvalidContractClass,
+            AddDeclareTransactionError::ContractClassSizeIsTooLarge(limit) => {
+                Self::ContractClassSizeIsTooLarge(Some(limit))
+            }
             AddDeclareTransactionError::GatewayError(x) => Self::GatewayError(x),
             AddDeclareTransactionError::Internal(x) => Self::Internal(x),
         }
@@ -85,6 +90,14 @@ pub async fn add_declare_transaction(
             )))
         }
         Transaction::Declare(BroadcastedDeclareTransaction::V1(tx)) => {
+            // A program which cannot be decompressed is not a valid class.
+            let size = tx
+                .contract_class
+                .definition_size(context.config.max_declare_class_size)
+                .map_err(|_| AddDeclareTransactionError::InvalidContractClass)?;
+            ClassSizeLimit::check(size, context.config.max_declare_class_size)
+                .map_err(AddDeclareTransactionError::ContractClassSizeIsTooLarge)?;
+
             let contract_definition: CairoContractDefinition = tx
                 .contract_class
                 .try_into()
@@ -110,6 +123,12 @@ pub async fn add_declare_transaction(
             })
         }
         Transaction::Declare(BroadcastedDeclareTransaction::V2(tx)) => {
+            ClassSizeLimit::check(
+                tx.contract_class.definition_size()?,
+                context.config.max_declare_class_size,
+            )
+            .map_err(AddDeclareTransactionError::ContractClassSizeIsTooLarge)?;
+
             let contract_definition: SierraContractDefinition = tx
                 .contract_class
                 .try_into()
@@ -312,6 +331,39 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn class_size_is_too_large() {
+        let size = CONTRACT_CLASS.definition_size(usize::MAX).unwrap();
+        let context = RpcContext::for_tests().with_config(crate::context::RpcConfig {
+            max_declare_class_size: size - 1,
+            ..Default::default()
+        });
+
+        let declare_transaction = Transaction::Declare(BroadcastedDeclareTransaction::V1(
+            BroadcastedDeclareTransactionV1 {
+                version: TransactionVersion::ONE,
+                max_fee: Fee(Default::default()),
+                signature: vec![],
+                nonce: TransactionNonce(Default::default()),
+                contract_class: CONTRACT_CLASS.clone(),
+                sender_address: ContractAddress::new_or_panic(Felt::from_u64(1)),
+            },
+        ));
+
+        let input = AddDeclareTransactionInput {
+            declare_transaction,
+            token: None,
+        };
+        let error = add_declare_transaction(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            AddDeclareTransactionError::ContractClassSizeIsTooLarge(x) => assert_eq!(x, ClassSizeLimit {
+                size,
+                limit: size - 1,
+            })
+        );
+    }
+
     #[test_log::test(tokio::test)]
     #[ignore = "gateway 429"]
     async fn invalid_contract_definition_v1() {
@@ -58,4 +58,45 @@ mod tests {
         assert_eq!(result.block_number, BlockNumber::new_or_panic(2));
         assert_eq!(result.block_hash, block_hash_bytes!(b"latest"));
     }
+
+    #[tokio::test]
+    async fn no_blocks() {
+        let storage = pathfinder_storage::Storage::in_memory().unwrap();
+        let context = RpcContext::for_tests().with_storage(storage);
+
+        let result = block_hash_and_number(context.clone()).await;
+        assert_matches::assert_matches!(result, Err(BlockNumberError::NoBlocks));
+
+        let result = block_number(context).await;
+        assert_matches::assert_matches!(result, Err(BlockNumberError::NoBlocks));
+    }
+
+    #[tokio::test]
+    async fn read_only_storage() {
+        use pathfinder_common::BlockHeader;
+        use pathfinder_storage::{JournalMode, Storage};
+        use std::num::NonZeroU32;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("db.sqlite");
+
+        let storage = Storage::migrate(db_path.clone(), JournalMode::WAL)
+            .unwrap()
+            .create_pool(NonZeroU32::new(1).unwrap())
+            .unwrap();
+        let header = BlockHeader::builder()
+            .with_number(BlockNumber::new_or_panic(7))
+            .finalize_with_hash(block_hash_bytes!(b"replicated"));
+        let mut connection = storage.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+        tx.insert_block_header(&header).unwrap();
+        tx.commit().unwrap();
+
+        let replica = Storage::open_read_only(db_path, NonZeroU32::new(1).unwrap()).unwrap();
+        let context = RpcContext::for_tests().with_storage(replica);
+        let result = block_hash_and_number(context).await.unwrap();
+
+        assert_eq!(result.block_number, BlockNumber::new_or_panic(7));
+        assert_eq!(result.block_hash, block_hash_bytes!(b"replicated"));
+    }
 }
@@ -3,7 +3,12 @@ use crate::felt::RpcFelt;
 use anyhow::Context;
 use pathfinder_common::{BlockId, CallParam, CallResultValue, ContractAddress, EntryPoint};
 
-crate::error::generate_rpc_error_subset!(CallError: BlockNotFound, ContractNotFound, ContractError);
+crate::error::generate_rpc_error_subset!(
+    CallError: BlockNotFound,
+    ContractNotFound,
+    ContractError,
+    ExecutionDisabled
+);
 
 impl From<pathfinder_executor::CallError> for CallError {
     fn from(value: pathfinder_executor::CallError) -> Self {
@@ -66,6 +71,10 @@ impl From<FunctionCall> for crate::v02::types::request::Call {
 pub struct CallOutput(#[serde_as(as = "Vec<RpcFelt>")] Vec<CallResultValue>);
 
 pub async fn call(context: RpcContext, input: CallInput) -> Result<CallOutput, CallError> {
+    if context.config.execution_disabled {
+        return Err(CallError::ExecutionDisabled);
+    }
+
     let execution_state =
         crate::executor::execution_state(context, input.block_id, Some(1.into())).await?;
 
@@ -232,6 +241,27 @@ mod tests {
             assert_eq!(result, CallOutput(vec![CallResultValue(test_value.0)]));
         }
 
+        #[tokio::test]
+        async fn execution_disabled() {
+            let (context, _last_block_header, contract_address, test_key, _test_value) =
+                test_context().await;
+            let context = context.with_config(crate::context::RpcConfig {
+                execution_disabled: true,
+                ..Default::default()
+            });
+
+            let input = CallInput {
+                request: FunctionCall {
+                    contract_address,
+                    entry_point_selector: EntryPoint::hashed(b"get_value"),
+                    calldata: vec![CallParam(*test_key.get())],
+                },
+                block_id: BlockId::Latest,
+            };
+            let error = call(context, input).await.unwrap_err();
+            assert_matches::assert_matches!(error, CallError::ExecutionDisabled);
+        }
+
         #[tokio::test]
         async fn storage_updated_in_pending() {
             let (context, last_block_header, contract_address, test_key, test_value) =
@@ -11,7 +11,7 @@ pub struct GetBlockInput {
     block_id: BlockId,
 }
 
-crate::error::generate_rpc_error_subset!(GetBlockError: BlockNotFound);
+crate::error::generate_rpc_error_subset!(GetBlockError: BlockNotFound, NoBlocks);
 
 /// Get block information with transaction hashes given the block id
 pub async fn get_block_with_tx_hashes(
@@ -81,7 +81,11 @@ async fn get_block(
         let header = transaction
             .block_header(block_id)
             .context("Reading block from database")?
-            .ok_or(GetBlockError::BlockNotFound)?;
+            .ok_or(match block_id {
+                // An empty database only happens before the first block is synced.
+                pathfinder_storage::BlockId::Latest => GetBlockError::NoBlocks,
+                _ => GetBlockError::BlockNotFound,
+            })?;
 
         let l1_accepted = transaction.block_is_l1_accepted(header.number.into())?;
         let block_status = if l1_accepted {
@@ -395,4 +399,27 @@ mod tests {
             check(i, test_case).await;
         }
     }
+
+    #[tokio::test]
+    async fn latest_without_blocks() {
+        let storage = pathfinder_storage::Storage::in_memory().unwrap();
+        let context = RpcContext::for_tests().with_storage(storage);
+
+        let cases: &[(RpcContext, BlockId, TestCaseHandler)] = &[
+            (
+                context.clone(),
+                BlockId::Latest,
+                assert_error(GetBlockError::NoBlocks),
+            ),
+            (
+                context,
+                BlockId::Number(BlockNumber::GENESIS),
+                assert_error(GetBlockError::BlockNotFound),
+            ),
+        ];
+
+        for (i, test_case) in cases.iter().enumerate() {
+            check(i, test_case).await;
+        }
+    }
 }
@@ -10,7 +10,7 @@ pub struct GetBlockTransactionCountInput {
 
 type BlockTransactionCount = u64;
 
-crate::error::generate_rpc_error_subset!(GetBlockTransactionCountError: BlockNotFound);
+crate::error::generate_rpc_error_subset!(GetBlockTransactionCountError: BlockNotFound, NoBlocks);
 
 pub async fn get_block_transaction_count(
     context: RpcContext,
@@ -18,13 +18,16 @@ pub async fn get_block_transaction_count(
 ) -> Result<BlockTransactionCount, GetBlockTransactionCountError> {
     let block_id = match input.block_id {
         BlockId::Pending => {
-            if let Some(pending) = context.pending_data.as_ref() {
-                if let Some(block) = pending.block().await.as_ref() {
-                    return Ok(block.transactions.len() as BlockTransactionCount);
-                }
-            }
+            // Mirrors `starknet_pendingTransactions` so that the two always agree.
+            let count = match context.pending_data {
+                Some(data) => match data.block().await {
+                    Some(block) => block.transactions.len(),
+                    None => 0,
+                },
+                None => 0,
+            };
 
-            return Ok(0);
+            return Ok(count as BlockTransactionCount);
         }
         other => other.try_into().expect("Only pending cast should fail"),
     };
@@ -49,10 +52,12 @@ pub async fn get_block_transaction_count(
                 .block_header(block_id)
                 .context("Querying block existence")?;
 
-            return if header.is_some() {
-                Ok(0)
-            } else {
-                Err(GetBlockTransactionCountError::BlockNotFound)
+            return match (header, block_id) {
+                (Some(_), _) => Ok(0),
+                (None, pathfinder_storage::BlockId::Latest) => {
+                    Err(GetBlockTransactionCountError::NoBlocks)
+                }
+                (None, _) => Err(GetBlockTransactionCountError::BlockNotFound),
             };
         }
         Ok(block_transaction_count as BlockTransactionCount)
@@ -119,15 +124,6 @@ mod tests {
         assert!(result.is_err());
     }
 
-    async fn get_count(context: &RpcContext) -> BlockTransactionCount {
-        if let Some(pending) = context.pending_data.as_ref() {
-            if let Some(block) = pending.block().await.as_ref() {
-                return block.transactions.len() as BlockTransactionCount;
-            }
-        }
-        0
-    }
-
     #[tokio::test]
     async fn test_genesis() {
         let context = RpcContext::for_tests();
@@ -144,10 +140,54 @@ mod tests {
 
     #[tokio::test]
     async fn test_pending() {
+        let context = RpcContext::for_tests_with_pending().await;
+        let expected = crate::v02::method::pending_transactions(context.clone())
+            .await
+            .unwrap()
+            .len() as BlockTransactionCount;
+        check_count(context, BlockId::Pending, expected).await;
+    }
+
+    #[tokio::test]
+    async fn test_pending_disabled() {
         let context = RpcContext::for_tests();
-        let count = get_count(&context).await;
-        let block_id = BlockId::Pending;
-        check_count(context, block_id, count).await;
+        check_count(context, BlockId::Pending, 0).await;
+    }
+
+    #[tokio::test]
+    async fn test_pending_increments_while_latest_is_unchanged() {
+        use starknet_gateway_types::reply::transaction::{InvokeTransaction, Transaction};
+
+        let context = RpcContext::for_tests_with_pending().await;
+        let pending = context.pending_data.clone().unwrap();
+
+        check_count(context.clone(), BlockId::Pending, 3).await;
+        check_count(context.clone(), BlockId::Latest, 5).await;
+
+        // Add another transaction to the pending block.
+        let mut block = pending.block().await.unwrap().as_ref().clone();
+        let mut receipt = block.transaction_receipts[0].clone();
+        let transaction = match block.transactions[0].clone() {
+            Transaction::Invoke(InvokeTransaction::V0(mut tx)) => {
+                tx.transaction_hash = transaction_hash_bytes!(b"pending tx hash new");
+                receipt.transaction_hash = tx.transaction_hash;
+                Transaction::Invoke(tx.into())
+            }
+            other => unreachable!("Expected an invoke transaction, got {other:?}"),
+        };
+        block.transactions.push(transaction);
+        block.transaction_receipts.push(receipt);
+
+        let state_update = pending.state_update().await.unwrap();
+        pending.set(std::sync::Arc::new(block), state_update).await;
+
+        check_count(context.clone(), BlockId::Pending, 4).await;
+        check_count(context.clone(), BlockId::Latest, 5).await;
+
+        let pending_transactions = crate::v02::method::pending_transactions(context)
+            .await
+            .unwrap();
+        assert_eq!(pending_transactions.len(), 4);
     }
 
     #[tokio::test]
@@ -163,4 +203,15 @@ mod tests {
         let block_id = BlockId::Number(BlockNumber::new_or_panic(123));
         check_error(context, block_id).await;
     }
+
+    #[tokio::test]
+    async fn test_latest_without_blocks() {
+        let storage = pathfinder_storage::Storage::in_memory().unwrap();
+        let context = RpcContext::for_tests().with_storage(storage);
+        let input = GetBlockTransactionCountInput {
+            block_id: BlockId::Latest,
+        };
+        let result = get_block_transaction_count(context, input).await;
+        assert_matches::assert_matches!(result, Err(GetBlockTransactionCountError::NoBlocks));
+    }
 }
@@ -67,7 +67,7 @@ pub async fn get_class(
 }
 
 /// Returns true if the class is declared in the pending state.
-async fn is_pending_class(pending: &Option<PendingData>, hash: ClassHash) -> bool {
+pub(crate) async fn is_pending_class(pending: &Option<PendingData>, hash: ClassHash) -> bool {
     let state_diff = match pending {
         Some(pending) => match pending.state_update().await {
             Some(pending) => pending,
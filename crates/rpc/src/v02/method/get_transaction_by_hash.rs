@@ -8,7 +8,7 @@ use starknet_gateway_types::reply::transaction::Transaction as GatewayTransactio
 #[derive(serde::Deserialize, Debug, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct GetTransactionByHashInput {
-    transaction_hash: TransactionHash,
+    pub(crate) transaction_hash: TransactionHash,
 }
 
 crate::error::generate_rpc_error_subset!(GetTransactionByHashError: TxnHashNotFoundV03);
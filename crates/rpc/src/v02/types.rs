@@ -99,6 +99,16 @@ pub mod request {
         V2(BroadcastedDeclareTransactionV2),
     }
 
+    impl BroadcastedDeclareTransaction {
+        pub fn version(&self) -> TransactionVersion {
+            match self {
+                Self::V0(x) => x.version,
+                Self::V1(x) => x.version,
+                Self::V2(x) => x.version,
+            }
+        }
+    }
+
     impl<'de> serde::Deserialize<'de> for BroadcastedDeclareTransaction {
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where
@@ -366,6 +376,20 @@ pub mod request {
                 _ => None,
             }
         }
+
+        pub fn version(&self) -> TransactionVersion {
+            match self {
+                Self::V0(x) => x.version,
+                Self::V1(x) => x.version,
+            }
+        }
+
+        pub fn transaction_hash(&self, chain_id: ChainId) -> TransactionHash {
+            match self {
+                Self::V0(x) => x.transaction_hash(chain_id),
+                Self::V1(x) => x.transaction_hash(chain_id),
+            }
+        }
     }
 
     impl<'de> Deserialize<'de> for BroadcastedInvokeTransaction {
@@ -1389,6 +1413,26 @@ pub mod reply {
                     transactions
                 );
             }
+
+            /// Quantities beyond 2^53 cannot be represented exactly by many JSON clients, so
+            /// these must be hex strings rather than numbers.
+            #[test]
+            fn fee_estimate_beyond_safe_integers() {
+                let estimate = FeeEstimate {
+                    gas_consumed: primitive_types::U256::from((1u64 << 53) + 1),
+                    gas_price: primitive_types::U256::from(u128::MAX),
+                    overall_fee: primitive_types::U256::MAX,
+                };
+
+                assert_eq!(
+                    serde_json::to_value(estimate).unwrap(),
+                    serde_json::json!({
+                        "gas_consumed": "0x20000000000001",
+                        "gas_price": format!("0x{}", "f".repeat(32)),
+                        "overall_fee": format!("0x{}", "f".repeat(64)),
+                    })
+                );
+            }
         }
     }
 }
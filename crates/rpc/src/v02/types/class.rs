@@ -201,6 +201,34 @@ pub struct CairoContractClass {
 }
 
 impl CairoContractClass {
+    /// The size in bytes of the class's definition, i.e. its JSON with the `program`
+    /// decompressed, as in the class's definition file.
+    ///
+    /// This is what the declare class size limit applies to, so that it means the same for
+    /// Cairo 0 classes as for Sierra classes, whose programs are not compressed.
+    ///
+    /// Decompression stops once the program exceeds `limit` bytes, in which case the returned
+    /// size is only guaranteed to exceed `limit`.
+    pub fn definition_size(&self, limit: usize) -> anyhow::Result<usize> {
+        let compressed = base64::decode(&self.program).context("Decoding program")?;
+        // Streamed and bounded, so that highly compressed programs are neither held in memory
+        // nor decompressed in full.
+        let program = std::io::copy(
+            &mut flate2::read::GzDecoder::new(Cursor::new(compressed))
+                .take((limit as u64).saturating_add(1)),
+            &mut std::io::sink(),
+        )
+        .context("Decompressing program")?;
+
+        let rest = serde_json::to_vec(&serde_json::json!({
+            "entry_points_by_type": self.entry_points_by_type,
+            "abi": self.abi,
+        }))?;
+
+        // The decompressed program is the JSON of the `program` member's value.
+        Ok(rest.len() + r#""program":,"#.len() + program as usize)
+    }
+
     pub fn class_hash(&self) -> anyhow::Result<ComputedClassHash> {
         let serialized = self.serialize_to_json()?;
 
@@ -423,6 +451,12 @@ pub struct SierraContractClass {
 }
 
 impl SierraContractClass {
+    /// The size in bytes of the class's definition, i.e. its JSON, see
+    /// [CairoContractClass::definition_size].
+    pub fn definition_size(&self) -> anyhow::Result<usize> {
+        Ok(self.serialize_to_json()?.len())
+    }
+
     pub fn serialize_to_json(&self) -> anyhow::Result<Vec<u8>> {
         let json = serde_json::to_vec(self)?;
 
@@ -505,6 +539,23 @@ mod tests {
 
             assert_eq!(result, expected);
         }
+
+        #[test]
+        fn serializes_offset_as_hex() {
+            let entry_point = ContractEntryPoint {
+                selector: felt!("0x12345"),
+                offset: u64::MAX,
+            };
+
+            let result = serde_json::to_value(entry_point).unwrap();
+
+            let expected = serde_json::json!({
+                "selector": "0x12345",
+                "offset": "0xffffffffffffffff"
+            });
+
+            assert_eq!(result, expected);
+        }
     }
 
     mod declare_class_hash {
@@ -531,6 +582,58 @@ mod tests {
         }
     }
 
+    mod definition_size {
+        use starknet_gateway_test_fixtures::class_definitions::{
+            CAIRO_1_0_0_ALPHA6_SIERRA, CONTRACT_DEFINITION,
+        };
+
+        use crate::v02::types::ContractClass;
+
+        #[test]
+        fn cairo_program_is_decompressed() {
+            let definition: serde_json::Value =
+                serde_json::from_slice(CONTRACT_DEFINITION).unwrap();
+            let class = ContractClass::from_definition_bytes(CONTRACT_DEFINITION)
+                .unwrap()
+                .as_cairo()
+                .unwrap();
+
+            let expected = serde_json::to_vec(&serde_json::json!({
+                "program": definition["program"],
+                "entry_points_by_type": class.entry_points_by_type,
+                "abi": class.abi,
+            }))
+            .unwrap()
+            .len();
+
+            assert_eq!(class.definition_size(usize::MAX).unwrap(), expected);
+        }
+
+        #[test]
+        fn cairo_decompression_stops_past_limit() {
+            let class = ContractClass::from_definition_bytes(CONTRACT_DEFINITION)
+                .unwrap()
+                .as_cairo()
+                .unwrap();
+
+            let size = class.definition_size(10).unwrap();
+            assert!(size > 10);
+            assert!(size < class.definition_size(usize::MAX).unwrap());
+        }
+
+        #[test]
+        fn sierra() {
+            let class = ContractClass::from_definition_bytes(CAIRO_1_0_0_ALPHA6_SIERRA)
+                .unwrap()
+                .as_sierra()
+                .unwrap();
+
+            let expected = serde_json::to_vec(&class).unwrap().len();
+
+            assert_eq!(class.definition_size().unwrap(), expected);
+        }
+    }
+
     mod contract_class_serialization {
 
         use pathfinder_executor::parse_deprecated_class_definition;
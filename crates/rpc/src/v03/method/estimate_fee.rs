@@ -1,6 +1,7 @@
 use anyhow::Context;
 use serde_with::serde_as;
 
+use crate::fee::FeeWei;
 use crate::{context::RpcContext, v02::types::request::BroadcastedTransaction};
 use pathfinder_common::BlockId;
 
@@ -9,6 +10,20 @@ use pathfinder_common::BlockId;
 pub struct EstimateFeeInput {
     request: Vec<BroadcastedTransaction>,
     block_id: BlockId,
+    /// The unit in which the fees are estimated, which defaults to [PriceUnit::Wei].
+    #[serde(default)]
+    unit: PriceUnit,
+}
+
+/// The unit of a fee estimate.
+///
+/// Only WEI is available, as blocks do not carry a gas price in any other fee token yet. Requests
+/// for other units are therefore rejected as invalid params.
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PriceUnit {
+    #[default]
+    Wei,
 }
 
 crate::error::generate_rpc_error_subset!(
@@ -46,18 +61,18 @@ impl From<crate::executor::ExecutionStateError> for EstimateFeeError {
 pub struct FeeEstimate {
     #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
     pub gas_consumed: primitive_types::U256,
-    #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
-    pub gas_price: primitive_types::U256,
-    #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
-    pub overall_fee: primitive_types::U256,
+    pub gas_price: FeeWei,
+    pub overall_fee: FeeWei,
+    pub unit: PriceUnit,
 }
 
 impl From<pathfinder_executor::types::FeeEstimate> for FeeEstimate {
     fn from(value: pathfinder_executor::types::FeeEstimate) -> Self {
         Self {
             gas_consumed: value.gas_consumed,
-            gas_price: value.gas_price,
-            overall_fee: value.overall_fee,
+            gas_price: FeeWei(value.gas_price),
+            overall_fee: FeeWei(value.overall_fee),
+            unit: PriceUnit::Wei,
         }
     }
 }
@@ -67,6 +82,7 @@ pub async fn estimate_fee(
     input: EstimateFeeInput,
 ) -> Result<Vec<FeeEstimate>, EstimateFeeError> {
     let chain_id = context.chain_id;
+    let unit = input.unit;
 
     let execution_state = crate::executor::execution_state(context, input.block_id, None).await?;
 
@@ -88,7 +104,13 @@ pub async fn estimate_fee(
     .await
     .context("Executing transaction")??;
 
-    Ok(result.into_iter().map(Into::into).collect())
+    Ok(result
+        .into_iter()
+        .map(|estimate| FeeEstimate {
+            unit,
+            ..estimate.into()
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -142,6 +164,7 @@ pub(crate) mod tests {
             let expected = EstimateFeeInput {
                 request: vec![test_invoke_txn()],
                 block_id: BlockId::Hash(BlockHash(felt!("0xabcde"))),
+                unit: PriceUnit::Wei,
             };
             assert_eq!(input, expected);
         }
@@ -170,9 +193,44 @@ pub(crate) mod tests {
             let expected = EstimateFeeInput {
                 request: vec![test_invoke_txn()],
                 block_id: BlockId::Hash(BlockHash(felt!("0xabcde"))),
+                unit: PriceUnit::Wei,
             };
             assert_eq!(input, expected);
         }
+
+        #[test]
+        fn unit() {
+            let input = json!({"request": [], "block_id": "latest", "unit": "WEI"});
+            let input = serde_json::from_value::<EstimateFeeInput>(input).unwrap();
+            assert_eq!(input.unit, PriceUnit::Wei);
+
+            let input = json!([[], "latest", "WEI"]);
+            let input = serde_json::from_value::<EstimateFeeInput>(input).unwrap();
+            assert_eq!(input.unit, PriceUnit::Wei);
+
+            let input = json!({"request": [], "block_id": "latest", "unit": "FRI"});
+            serde_json::from_value::<EstimateFeeInput>(input).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn serialization() {
+        let estimate = FeeEstimate {
+            gas_consumed: 3700.into(),
+            gas_price: FeeWei(1.into()),
+            overall_fee: FeeWei(3700.into()),
+            unit: PriceUnit::Wei,
+        };
+
+        assert_eq!(
+            serde_json::to_value(estimate).unwrap(),
+            serde_json::json!({
+                "gas_consumed": "0xe74",
+                "gas_price": "0x1",
+                "overall_fee": "0xe74",
+                "unit": "WEI",
+            })
+        );
     }
 
     mod in_memory {
@@ -295,23 +353,27 @@ pub(crate) mod tests {
             let result = estimate_fee(context, input).await.unwrap();
             let declare_expected = FeeEstimate {
                 gas_consumed: 3700.into(),
-                gas_price: 1.into(),
-                overall_fee: 3700.into(),
+                gas_price: FeeWei(1.into()),
+                overall_fee: FeeWei(3700.into()),
+                unit: PriceUnit::Wei,
             };
             let deploy_expected = FeeEstimate {
                 gas_consumed: 4337.into(),
-                gas_price: 1.into(),
-                overall_fee: 4337.into(),
+                gas_price: FeeWei(1.into()),
+                overall_fee: FeeWei(4337.into()),
+                unit: PriceUnit::Wei,
             };
             let invoke_expected = FeeEstimate {
                 gas_consumed: 2491.into(),
-                gas_price: 1.into(),
-                overall_fee: 2491.into(),
+                gas_price: FeeWei(1.into()),
+                overall_fee: FeeWei(2491.into()),
+                unit: PriceUnit::Wei,
             };
             let invoke_v0_expected = FeeEstimate {
                 gas_consumed: 1260.into(),
-                gas_price: 1.into(),
-                overall_fee: 1260.into(),
+                gas_price: FeeWei(1.into()),
+                overall_fee: FeeWei(1260.into()),
+                unit: PriceUnit::Wei,
             };
             assert_eq!(
                 result,
@@ -10,6 +10,7 @@ use serde_with::serde_as;
 use stark_hash::Felt;
 use starknet_api::core::PatriciaKey;
 
+use crate::fee::FeeWei;
 use crate::{context::RpcContext, v02::method::call::FunctionCall};
 
 #[derive(Deserialize, Debug, PartialEq, Eq)]
@@ -55,10 +56,8 @@ impl From<crate::executor::ExecutionStateError> for EstimateMessageFeeError {
 pub struct FeeEstimate {
     #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
     pub gas_consumed: primitive_types::U256,
-    #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
-    pub gas_price: primitive_types::U256,
-    #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
-    pub overall_fee: primitive_types::U256,
+    pub gas_price: FeeWei,
+    pub overall_fee: FeeWei,
 }
 
 pub async fn estimate_message_fee(
@@ -92,8 +91,8 @@ pub async fn estimate_message_fee(
 
     Ok(FeeEstimate {
         gas_consumed: result.gas_consumed,
-        gas_price: result.gas_price,
-        overall_fee: result.overall_fee,
+        gas_price: FeeWei(result.gas_price),
+        overall_fee: FeeWei(result.overall_fee),
     })
 }
 
@@ -328,8 +327,8 @@ mod tests {
     async fn test_estimate_message_fee() {
         let expected = FeeEstimate {
             gas_consumed: 17105.into(),
-            gas_price: 1.into(),
-            overall_fee: 17105.into(),
+            gas_price: FeeWei(1.into()),
+            overall_fee: FeeWei(17105.into()),
         };
 
         let rpc = setup(Setup::Full).await.expect("RPC context");
@@ -1,6 +1,7 @@
 use std::{str::FromStr, sync::Arc};
 
 use crate::context::RpcContext;
+use crate::error::ContinuationTokenError;
 use anyhow::Context;
 use pathfinder_common::{BlockId, BlockNumber, ContractAddress, EventKey};
 use pathfinder_storage::{EventFilterError, V03KeyFilter};
@@ -13,8 +14,9 @@ pub enum GetEventsError {
     Internal(anyhow::Error),
     BlockNotFound,
     PageSizeTooBig,
-    InvalidContinuationToken,
+    InvalidContinuationToken(ContinuationTokenError),
     TooManyKeysInFilter { limit: usize, requested: usize },
+    BlockRangeTooLarge { limit: u64, requested: u64 },
 }
 
 impl From<anyhow::Error> for GetEventsError {
@@ -29,10 +31,15 @@ impl From<GetEventsError> for crate::error::RpcError {
             GetEventsError::Internal(internal) => Self::Internal(internal),
             GetEventsError::BlockNotFound => Self::BlockNotFound,
             GetEventsError::PageSizeTooBig => Self::PageSizeTooBig,
-            GetEventsError::InvalidContinuationToken => Self::InvalidContinuationToken,
+            GetEventsError::InvalidContinuationToken(reason) => {
+                Self::InvalidContinuationToken(reason)
+            }
             GetEventsError::TooManyKeysInFilter { limit, requested } => {
                 Self::TooManyKeysInFilter { limit, requested }
             }
+            GetEventsError::BlockRangeTooLarge { limit, requested } => {
+                Self::BlockRangeTooLarge { limit, requested }
+            }
         }
     }
 }
@@ -95,16 +102,20 @@ pub async fn get_events(
     let request = input.filter;
 
     let continuation_token = match &request.continuation_token {
-        Some(s) => Some(
-            s.parse::<ContinuationToken>()
-                .map_err(|_| GetEventsError::InvalidContinuationToken)?,
-        ),
+        Some(s) => Some(s.parse::<ContinuationToken>().map_err(|_| {
+            GetEventsError::InvalidContinuationToken(ContinuationTokenError::Malformed)
+        })?),
         None => None,
     };
 
-    if request.keys.len() > pathfinder_storage::EVENT_KEY_FILTER_LIMIT {
+    // Storage cannot index more segments than this, regardless of the configuration.
+    let max_key_segments = context
+        .config
+        .max_event_key_segments
+        .min(pathfinder_storage::EVENT_KEY_FILTER_LIMIT);
+    if request.keys.len() > max_key_segments {
         return Err(GetEventsError::TooManyKeysInFilter {
-            limit: pathfinder_storage::EVENT_KEY_FILTER_LIMIT,
+            limit: max_key_segments,
             requested: request.keys.len(),
         });
     }
@@ -133,6 +144,7 @@ pub async fn get_events(
 
     let storage = context.storage.clone();
     let keys = V03KeyFilter::new(request.keys.clone());
+    let max_block_range = context.config.max_events_block_range;
 
     // blocking task to perform database event query
     let span = tracing::Span::current();
@@ -148,6 +160,7 @@ pub async fn get_events(
 
         let from_block = map_from_block_to_number(&transaction, request.from_block)?;
         let to_block = map_to_block_to_number(&transaction, request.to_block)?;
+        check_block_range(&transaction, from_block, to_block, max_block_range)?;
 
         let (from_block, requested_offset) = match continuation_token {
             Some(token) => token.start_block_and_offset(from_block)?,
@@ -320,7 +333,9 @@ async fn get_pending_events(
                 })
             }
             // no pending block means we can't continue from the token offset
-            Some(_) => Err(GetEventsError::InvalidContinuationToken),
+            Some(_) => Err(GetEventsError::InvalidContinuationToken(
+                ContinuationTokenError::Stale,
+            )),
         },
         Some(pending_block) => {
             let storage = context.storage.clone();
@@ -450,6 +465,35 @@ fn map_from_block_to_number(
     }
 }
 
+/// Rejects ranges spanning more than `limit` blocks, where a range without an upper end extends
+/// to the latest block.
+fn check_block_range(
+    tx: &pathfinder_storage::Transaction<'_>,
+    from_block: Option<BlockNumber>,
+    to_block: Option<BlockNumber>,
+    limit: u64,
+) -> Result<(), GetEventsError> {
+    let to_block = match to_block {
+        Some(number) => number,
+        None => match tx
+            .block_id(pathfinder_storage::BlockId::Latest)
+            .context("Querying latest block number")?
+        {
+            Some((number, _)) => number,
+            // There are no blocks to search.
+            None => return Ok(()),
+        },
+    };
+    let from_block = from_block.unwrap_or_default();
+
+    let requested = (to_block.get() + 1).saturating_sub(from_block.get());
+    if requested > limit {
+        return Err(GetEventsError::BlockRangeTooLarge { limit, requested });
+    }
+
+    Ok(())
+}
+
 /// Append's pending events to `dst` based on the filter requirements and returns
 /// true if this was the last pending data i.e. `is_last_page`.
 fn append_pending_events(
@@ -512,6 +556,12 @@ fn append_pending_events(
     is_last_page
 }
 
+/// Points to the first event of the next page.
+///
+/// Events are totally ordered by block number, transaction index and event index. The token
+/// identifies the next event by its block and the number of matching events preceding it within
+/// that block. Since blocks are immutable once stored, the token resumes exactly after the last
+/// returned event, regardless of any blocks appended in the meantime.
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct ContinuationToken {
     block_number: BlockNumber,
@@ -551,7 +601,9 @@ impl ContinuationToken {
         if self.block_number == block_number {
             Ok(self.offset)
         } else {
-            Err(GetEventsError::InvalidContinuationToken)
+            Err(GetEventsError::InvalidContinuationToken(
+                ContinuationTokenError::Stale,
+            ))
         }
     }
 
@@ -562,7 +614,9 @@ impl ContinuationToken {
         match from_block {
             Some(from_block) => {
                 if from_block > self.block_number {
-                    Err(GetEventsError::InvalidContinuationToken)
+                    Err(GetEventsError::InvalidContinuationToken(
+                        ContinuationTokenError::OutOfRange,
+                    ))
                 } else {
                     Ok((Some(self.block_number), self.offset))
                 }
@@ -587,7 +641,9 @@ fn check_continuation_token_validity(
     events: &[types::EmittedEvent],
 ) -> Result<(), GetEventsError> {
     match continuation_token {
-        Some(_) if events.is_empty() => Err(GetEventsError::InvalidContinuationToken),
+        Some(_) if events.is_empty() => Err(GetEventsError::InvalidContinuationToken(
+            ContinuationTokenError::Stale,
+        )),
         Some(_) | None => Ok(()),
     }
 }
@@ -735,6 +791,7 @@ mod tests {
         fn eq(&self, other: &Self) -> bool {
             match (self, other) {
                 (Self::Internal(l), Self::Internal(r)) => l.to_string() == r.to_string(),
+                (Self::InvalidContinuationToken(l), Self::InvalidContinuationToken(r)) => l == r,
                 _ => core::mem::discriminant(self) == core::mem::discriminant(other),
             }
         }
@@ -839,6 +896,152 @@ mod tests {
         );
     }
 
+    mod key_segments {
+        use super::*;
+
+        fn with_limit(context: RpcContext, limit: usize) -> RpcContext {
+            context.with_config(crate::context::RpcConfig {
+                max_event_key_segments: limit,
+                ..Default::default()
+            })
+        }
+
+        fn input(segments: usize) -> GetEventsInput {
+            GetEventsInput {
+                filter: EventFilter {
+                    keys: vec![vec![event_key!("01"), event_key!("02")]; segments],
+                    chunk_size: 10,
+                    ..Default::default()
+                },
+            }
+        }
+
+        #[tokio::test]
+        async fn at_limit_is_allowed() {
+            let (context, _) = setup();
+            let context = with_limit(context, 3);
+
+            get_events(context, input(3)).await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn over_limit_is_rejected() {
+            let (context, _) = setup();
+            let context = with_limit(context, 3);
+
+            let error = get_events(context, input(4)).await.unwrap_err();
+
+            assert_eq!(
+                error,
+                GetEventsError::TooManyKeysInFilter {
+                    limit: 3,
+                    requested: 4
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn limit_is_capped_by_storage() {
+            let (context, _) = setup();
+            let context = with_limit(context, usize::MAX);
+            let limit = pathfinder_storage::EVENT_KEY_FILTER_LIMIT;
+
+            let error = get_events(context, input(limit + 1)).await.unwrap_err();
+
+            assert_eq!(
+                error,
+                GetEventsError::TooManyKeysInFilter {
+                    limit,
+                    requested: limit + 1
+                }
+            );
+        }
+    }
+
+    mod block_range {
+        use super::*;
+        use assert_matches::assert_matches;
+
+        fn with_limit(context: RpcContext, limit: u64) -> RpcContext {
+            context.with_config(crate::context::RpcConfig {
+                max_events_block_range: limit,
+                ..Default::default()
+            })
+        }
+
+        #[rstest::rstest]
+        #[case::open(None, None, 4)]
+        #[case::to_latest(
+            Some(BlockId::Number(BlockNumber::new_or_panic(1))),
+            Some(BlockId::Latest),
+            3
+        )]
+        #[case::explicit(
+            Some(BlockId::Number(BlockNumber::new_or_panic(1))),
+            Some(BlockId::Number(BlockNumber::new_or_panic(2))),
+            2
+        )]
+        #[tokio::test]
+        async fn at_limit_is_allowed(
+            #[case] from_block: Option<BlockId>,
+            #[case] to_block: Option<BlockId>,
+            #[case] limit: u64,
+        ) {
+            let (context, _) = setup();
+            let context = with_limit(context, limit);
+
+            let input = GetEventsInput {
+                filter: EventFilter {
+                    from_block,
+                    to_block,
+                    chunk_size: test_utils::NUM_EVENTS,
+                    ..Default::default()
+                },
+            };
+            get_events(context, input).await.unwrap();
+        }
+
+        #[rstest::rstest]
+        #[case::open(None, None, 4)]
+        #[case::to_latest(
+            Some(BlockId::Number(BlockNumber::new_or_panic(1))),
+            Some(BlockId::Latest),
+            3
+        )]
+        #[case::explicit(
+            Some(BlockId::Number(BlockNumber::new_or_panic(1))),
+            Some(BlockId::Number(BlockNumber::new_or_panic(2))),
+            2
+        )]
+        #[tokio::test]
+        async fn over_limit_is_rejected(
+            #[case] from_block: Option<BlockId>,
+            #[case] to_block: Option<BlockId>,
+            #[case] requested: u64,
+        ) {
+            let (context, _) = setup();
+            let context = with_limit(context, requested - 1);
+
+            let input = GetEventsInput {
+                filter: EventFilter {
+                    from_block,
+                    to_block,
+                    chunk_size: test_utils::NUM_EVENTS,
+                    ..Default::default()
+                },
+            };
+            let error = get_events(context, input).await.unwrap_err();
+
+            assert_matches!(
+                error,
+                GetEventsError::BlockRangeTooLarge { limit, requested: r } => {
+                    assert_eq!(limit, requested - 1);
+                    assert_eq!(r, requested);
+                }
+            );
+        }
+    }
+
     #[tokio::test]
     async fn get_events_with_invalid_page_size() {
         let (context, _) = setup();
@@ -885,6 +1088,52 @@ mod tests {
         );
     }
 
+    #[rstest::rstest]
+    #[case::single_event(1)]
+    #[case::uneven(3)]
+    #[case::block_sized(test_utils::EVENTS_PER_BLOCK)]
+    #[case::spanning_blocks(test_utils::EVENTS_PER_BLOCK + 1)]
+    #[tokio::test]
+    async fn paging_returns_every_event_exactly_once(#[case] chunk_size: usize) {
+        let (context, events) = setup();
+
+        // A range which excludes the first and last blocks.
+        let from_block = BlockNumber::new_or_panic(1);
+        let to_block = BlockNumber::new_or_panic(test_utils::NUM_BLOCKS as u64 - 2);
+        let expected = events
+            .into_iter()
+            .filter(|event| {
+                event
+                    .block_number
+                    .is_some_and(|number| (from_block..=to_block).contains(&number))
+            })
+            .collect::<Vec<_>>();
+
+        let mut paged = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let input = GetEventsInput {
+                filter: EventFilter {
+                    from_block: Some(from_block.into()),
+                    to_block: Some(to_block.into()),
+                    chunk_size,
+                    continuation_token,
+                    ..Default::default()
+                },
+            };
+            let result = get_events(context.clone(), input).await.unwrap();
+            assert!(result.events.len() <= chunk_size);
+            paged.extend(result.events);
+
+            continuation_token = result.continuation_token;
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(paged, expected);
+    }
+
     #[tokio::test]
     async fn get_events_by_key_with_paging() {
         let (context, events) = setup();
@@ -954,7 +1203,78 @@ mod tests {
             },
         };
         let error = get_events(context, input).await.unwrap_err();
-        assert_eq!(error, GetEventsError::InvalidContinuationToken);
+        assert_eq!(
+            error,
+            GetEventsError::InvalidContinuationToken(ContinuationTokenError::Stale)
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_continuation_token_reasons() {
+        let (context, _) = setup();
+
+        let input = |from_block, continuation_token: &str| GetEventsInput {
+            filter: EventFilter {
+                from_block,
+                chunk_size: 1,
+                continuation_token: Some(continuation_token.to_owned()),
+                ..Default::default()
+            },
+        };
+
+        let error = get_events(context.clone(), input(None, "invalid"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error,
+            GetEventsError::InvalidContinuationToken(ContinuationTokenError::Malformed)
+        );
+
+        // The token precedes the requested block range.
+        let from_block = Some(BlockId::Number(BlockNumber::new_or_panic(2)));
+        let error = get_events(context, input(from_block, "1-0"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error,
+            GetEventsError::InvalidContinuationToken(ContinuationTokenError::OutOfRange)
+        );
+    }
+
+    #[tokio::test]
+    async fn events_include_their_block_and_transaction() {
+        let (context, _) = setup();
+
+        let input = GetEventsInput {
+            filter: EventFilter {
+                chunk_size: test_utils::NUM_EVENTS,
+                ..Default::default()
+            },
+        };
+        let result = get_events(context.clone(), input).await.unwrap();
+        assert!(!result.events.is_empty());
+
+        let mut db = context.storage.connection().unwrap();
+        let tx = db.transaction().unwrap();
+        for event in result.events {
+            let block_number = event
+                .block_number
+                .expect("Confirmed events have a block number");
+            let block_hash = event
+                .block_hash
+                .expect("Confirmed events have a block hash");
+
+            let header = tx.block_header(block_number.into()).unwrap().unwrap();
+            assert_eq!(header.hash, block_hash);
+            assert_eq!(
+                tx.transaction_block_hash(event.transaction_hash).unwrap(),
+                Some(block_hash)
+            );
+
+            let event = serde_json::to_value(event).unwrap();
+            assert_eq!(event["block_number"], json!(block_number));
+            assert_eq!(event["block_hash"], json!(block_hash));
+        }
     }
 
     mod pending {
@@ -1062,13 +1382,19 @@ mod tests {
             let error = get_events(context.clone(), input.clone())
                 .await
                 .unwrap_err();
-            assert_eq!(error, GetEventsError::InvalidContinuationToken);
+            assert_eq!(
+                error,
+                GetEventsError::InvalidContinuationToken(ContinuationTokenError::Stale)
+            );
 
             // nonexistent page: block number
             input.filter.chunk_size = 123; // Does not matter
             input.filter.continuation_token = Some("4-1".to_string()); // Points to after the last event
             let error = get_events(context.clone(), input).await.unwrap_err();
-            assert_eq!(error, GetEventsError::InvalidContinuationToken);
+            assert_eq!(
+                error,
+                GetEventsError::InvalidContinuationToken(ContinuationTokenError::Stale)
+            );
         }
 
         #[tokio::test]
@@ -1108,6 +1434,48 @@ mod tests {
             assert_eq!(result.continuation_token, None);
         }
 
+        #[tokio::test]
+        async fn events_include_their_transaction() {
+            let context = RpcContext::for_tests_with_pending().await;
+            let pending = context
+                .pending_data
+                .as_ref()
+                .unwrap()
+                .block()
+                .await
+                .unwrap();
+
+            let input = GetEventsInput {
+                filter: EventFilter {
+                    from_block: Some(BlockId::Pending),
+                    to_block: Some(BlockId::Pending),
+                    chunk_size: 1024,
+                    ..Default::default()
+                },
+            };
+            let result = get_events(context.clone(), input).await.unwrap();
+
+            let expected = pending
+                .transaction_receipts
+                .iter()
+                .flat_map(|receipt| {
+                    std::iter::repeat(receipt.transaction_hash).take(receipt.events.len())
+                })
+                .collect::<Vec<_>>();
+            let transaction_hashes = result
+                .events
+                .iter()
+                .map(|event| event.transaction_hash)
+                .collect::<Vec<_>>();
+            assert_eq!(transaction_hashes, expected);
+
+            // The pending block has neither a hash nor a final number yet.
+            for event in result.events {
+                assert_eq!(event.block_hash, None);
+                assert_eq!(event.block_number, None);
+            }
+        }
+
         #[tokio::test]
         async fn invalid_pending() {
             use std::sync::Arc;
@@ -14,6 +14,9 @@ pub struct SimulateTrasactionInput {
     #[serde(alias = "transaction")]
     transactions: Vec<BroadcastedTransaction>,
     simulation_flags: dto::SimulationFlags,
+    /// A pathfinder extension, see [dto::StateOverrides].
+    #[serde(default)]
+    state_overrides: Option<dto::StateOverrides>,
 }
 
 #[derive(Debug, Serialize, Eq, PartialEq)]
@@ -55,7 +58,9 @@ pub async fn simulate_transaction(
 ) -> Result<SimulateTransactionOutput, SimulateTransactionError> {
     let chain_id = context.chain_id;
 
-    let execution_state = crate::executor::execution_state(context, input.block_id, None).await?;
+    let mut execution_state =
+        crate::executor::execution_state(context, input.block_id, None).await?;
+    execution_state.state_overrides = input.state_overrides.map(Into::into);
 
     let skip_validate = input
         .simulation_flags
@@ -84,8 +89,12 @@ pub async fn simulate_transaction(
 }
 
 pub(crate) mod dto {
+    use std::collections::HashMap;
+
+    use pathfinder_common::{ContractAddress, StateUpdate, StorageAddress, StorageValue};
     use serde_with::serde_as;
 
+    use crate::fee::FeeWei;
     use crate::felt::RpcFelt;
     use crate::v02::method::call::FunctionCall;
 
@@ -99,20 +108,18 @@ pub(crate) mod dto {
         /// The Ethereum gas cost of the transaction
         #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
         pub gas_consumed: primitive_types::U256,
-        /// The gas price (in gwei) that was used in the cost estimation (input to fee estimation)
-        #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
-        pub gas_price: primitive_types::U256,
-        /// The estimated fee for the transaction (in gwei), product of gas_consumed and gas_price
-        #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
-        pub overall_fee: primitive_types::U256,
+        /// The gas price (in wei) that was used in the cost estimation (input to fee estimation)
+        pub gas_price: FeeWei,
+        /// The estimated fee for the transaction (in wei), product of gas_consumed and gas_price
+        pub overall_fee: FeeWei,
     }
 
     impl From<pathfinder_executor::types::FeeEstimate> for FeeEstimate {
         fn from(value: pathfinder_executor::types::FeeEstimate) -> Self {
             Self {
                 gas_consumed: value.gas_consumed,
-                gas_price: value.gas_price,
-                overall_fee: value.overall_fee,
+                gas_price: FeeWei(value.gas_price),
+                overall_fee: FeeWei(value.overall_fee),
             }
         }
     }
@@ -120,6 +127,57 @@ pub(crate) mod dto {
     #[derive(Debug, Deserialize, Serialize, Eq, PartialEq)]
     pub struct SimulationFlags(pub Vec<SimulationFlag>);
 
+    /// State to override before simulating the transactions, e.g. to preview their outcome for
+    /// a funded account.
+    #[serde_as]
+    #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct StateOverrides {
+        /// Storage values by contract address and storage key.
+        #[serde(default)]
+        pub storage: HashMap<ContractAddress, HashMap<StorageAddress, StorageValue>>,
+        /// Fee token balances by account address.
+        #[serde(default)]
+        #[serde_as(as = "HashMap<_, pathfinder_serde::U256AsHexStr>")]
+        pub balances: HashMap<ContractAddress, primitive_types::U256>,
+    }
+
+    impl From<StateOverrides> for StateUpdate {
+        fn from(overrides: StateOverrides) -> Self {
+            let storage = overrides
+                .storage
+                .into_iter()
+                .flat_map(|(address, storage)| {
+                    storage
+                        .into_iter()
+                        .map(move |(key, value)| (address, key, value))
+                });
+
+            // The fee token stores each balance as a `Uint256`, whose low and high 128 bits
+            // occupy consecutive storage slots.
+            let balances = overrides
+                .balances
+                .into_iter()
+                .flat_map(|(address, balance)| {
+                    let low_key =
+                        StorageAddress::from_map_name_and_key(b"ERC20_balances", address.0);
+                    let high_key = StorageAddress::new_or_panic(low_key.0 + Felt::from_u128(1));
+                    let low = StorageValue(Felt::from_u128(balance.low_u128()));
+                    let high = StorageValue(Felt::from_u128((balance >> 128).low_u128()));
+                    [
+                        (pathfinder_executor::FEE_TOKEN_ADDRESS, low_key, low),
+                        (pathfinder_executor::FEE_TOKEN_ADDRESS, high_key, high),
+                    ]
+                });
+
+            storage
+                .chain(balances)
+                .fold(StateUpdate::default(), |update, (address, key, value)| {
+                    update.with_storage_update(address, key, value)
+                })
+        }
+    }
+
     #[derive(Debug, Deserialize, Serialize, Eq, PartialEq)]
     pub enum SimulationFlag {
         #[serde(rename = "SKIP_EXECUTE")]
@@ -389,9 +447,10 @@ pub(crate) mod dto {
 #[cfg(test)]
 mod tests {
     use pathfinder_common::{felt, TransactionVersion};
-    use pathfinder_common::{macro_prelude::*, StorageAddress};
+    use pathfinder_common::{macro_prelude::*, StateUpdate, StorageAddress, StorageValue};
     use starknet_gateway_test_fixtures::class_definitions::DUMMY_ACCOUNT_CLASS_HASH;
 
+    use crate::fee::FeeWei;
     use crate::v02::method::call::FunctionCall;
     use crate::v02::types::request::BroadcastedDeployAccountTransaction;
 
@@ -442,8 +501,8 @@ mod tests {
                     fee_estimation:
                         FeeEstimate {
                             gas_consumed: 3097.into(),
-                            gas_price: 1.into(),
-                            overall_fee: 3097.into(),
+                            gas_price: FeeWei(1.into()),
+                            overall_fee: FeeWei(3097.into()),
                         }
                     ,
                     transaction_trace:
@@ -532,4 +591,99 @@ mod tests {
         let result = simulate_transaction(context, input).await.expect("result");
         pretty_assertions::assert_eq!(result.0, expected);
     }
+
+    mod state_overrides {
+        use super::*;
+
+        const BALANCE: &str = "0x10000000000000000000000000000";
+
+        fn transaction() -> BroadcastedDeployAccountTransaction {
+            BroadcastedDeployAccountTransaction {
+                contract_address_salt: contract_address_salt!(
+                    "0x46c0d4abf0192a788aca261e58d7031576f7d8ea5229f452b0f23e691dd5971"
+                ),
+                max_fee: fee!("0x100000000000"),
+                signature: vec![],
+                class_hash: DUMMY_ACCOUNT_CLASS_HASH,
+                nonce: transaction_nonce!("0x0"),
+                version: TransactionVersion::ONE_WITH_QUERY_VERSION,
+                constructor_calldata: vec![],
+            }
+        }
+
+        /// Simulates deploying an account which is not funded in storage.
+        async fn simulate(
+            state_overrides: serde_json::Value,
+        ) -> Result<SimulateTransactionOutput, SimulateTransactionError> {
+            let (storage, _, _, _) =
+                crate::test_setup::test_storage(|state_update| state_update).await;
+            let context = RpcContext::for_tests().with_storage(storage);
+
+            let input_json = serde_json::json!({
+                "block_id": {"block_number": 1},
+                "transactions": [
+                    BroadcastedTransaction::DeployAccount(transaction()),
+                ],
+                "simulation_flags": [],
+                "state_overrides": state_overrides,
+            });
+            let input = SimulateTrasactionInput::deserialize(&input_json).unwrap();
+
+            simulate_transaction(context, input).await
+        }
+
+        #[tokio::test]
+        async fn absent() {
+            simulate(serde_json::Value::Null).await.unwrap_err();
+        }
+
+        #[tokio::test]
+        async fn storage() {
+            let account_balance_key = StorageAddress::from_map_name_and_key(
+                b"ERC20_balances",
+                transaction().deployed_contract_address().0,
+            );
+            let state_overrides = serde_json::json!({
+                "storage": {
+                    (pathfinder_executor::FEE_TOKEN_ADDRESS.to_string()): {
+                        (account_balance_key.to_string()): BALANCE,
+                    },
+                },
+            });
+
+            let result = simulate(state_overrides).await.unwrap();
+            assert_eq!(result.0[0].fee_estimation.overall_fee, FeeWei(3097.into()));
+        }
+
+        #[tokio::test]
+        async fn balance() {
+            let state_overrides = serde_json::json!({
+                "balances": {
+                    (transaction().deployed_contract_address().to_string()): BALANCE,
+                },
+            });
+
+            let result = simulate(state_overrides).await.unwrap();
+            assert_eq!(result.0[0].fee_estimation.overall_fee, FeeWei(3097.into()));
+        }
+
+        #[test]
+        fn balance_occupies_two_slots() {
+            let address = contract_address!("0xc01");
+            let overrides = dto::StateOverrides {
+                balances: [(address, primitive_types::U256::MAX)].into(),
+                ..Default::default()
+            };
+
+            let state_update = StateUpdate::from(overrides);
+
+            let low_key = StorageAddress::from_map_name_and_key(b"ERC20_balances", address.0);
+            let high_key = StorageAddress::new_or_panic(low_key.0 + Felt::from_u128(1));
+            let u128_max = StorageValue(Felt::from_u128(u128::MAX));
+            let expected = StateUpdate::default()
+                .with_storage_update(pathfinder_executor::FEE_TOKEN_ADDRESS, low_key, u128_max)
+                .with_storage_update(pathfinder_executor::FEE_TOKEN_ADDRESS, high_key, u128_max);
+            assert_eq!(state_update, expected);
+        }
+    }
 }
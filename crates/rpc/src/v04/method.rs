@@ -2,10 +2,10 @@ mod add_declare_transaction;
 mod add_deploy_account_transaction;
 mod add_invoke_transaction;
 mod estimate_message_fee;
-mod get_block_with_txs;
+pub(crate) mod get_block_with_txs;
 mod get_transaction_by_block_and_index;
 mod get_transaction_by_hash;
-mod get_transaction_receipt;
+pub(crate) mod get_transaction_receipt;
 mod pending_transactions;
 mod simulate_transactions;
 mod syncing;
@@ -25,3 +25,6 @@ pub(super) use simulate_transactions::simulate_transactions;
 pub(super) use syncing::syncing;
 pub(super) use trace_block_transactions::trace_block_transactions;
 pub(super) use trace_transaction::trace_transaction;
+
+#[cfg(test)]
+pub(crate) use trace_block_transactions::tests::setup_multi_tx_trace_test;
@@ -1,7 +1,8 @@
 use crate::context::RpcContext;
+use crate::error::{ClassSizeLimit, CompiledClassHashMismatchData, ValidationFailureData};
 use crate::felt::RpcFelt;
 use crate::v02::types::request::BroadcastedDeclareTransaction;
-use pathfinder_common::{ClassHash, TransactionHash};
+use pathfinder_common::{ClassHash, TransactionHash, TransactionVersion};
 use starknet_gateway_client::GatewayApi;
 use starknet_gateway_types::error::SequencerError;
 use starknet_gateway_types::request::add_transaction::{
@@ -14,13 +15,17 @@ pub enum AddDeclareTransactionError {
     InvalidTransactionNonce,
     InsufficientMaxFee,
     InsufficientAccountBalance,
-    ValidationFailure,
+    ValidationFailure(ValidationFailureData),
+    InvalidContractClass,
     CompilationFailed,
-    ContractClassSizeIsTooLarge,
+    /// Contains the size and limit if the class was rejected by pathfinder rather than the gateway.
+    ContractClassSizeIsTooLarge(Option<ClassSizeLimit>),
     DuplicateTransaction,
-    CompiledClassHashMismatch,
+    /// Contains both hashes if the gateway reported them.
+    CompiledClassHashMismatch(Option<CompiledClassHashMismatchData>),
     NonAccount,
-    UnsupportedTransactionVersion,
+    /// Contains the version if the transaction was rejected by pathfinder rather than the gateway.
+    UnsupportedTransactionVersion(Option<TransactionVersion>),
     UnsupportedContractClassVersion,
     UnexpectedError(String),
 }
@@ -34,17 +39,22 @@ impl From<AddDeclareTransactionError> for crate::error::RpcError {
             AddDeclareTransactionError::InsufficientAccountBalance => {
                 Self::InsufficientAccountBalance
             }
-            AddDeclareTransactionError::ValidationFailure => Self::ValidationFailure,
+            AddDeclareTransactionError::ValidationFailure(data) => {
+                Self::ValidationFailure(Some(data))
+            }
+            AddDeclareTransactionError::InvalidContractClass => Self::InvalidContractClass,
             AddDeclareTransactionError::CompilationFailed => Self::CompilationFailed,
-            AddDeclareTransactionError::ContractClassSizeIsTooLarge => {
-                Self::ContractClassSizeIsTooLarge
+            AddDeclareTransactionError::ContractClassSizeIsTooLarge(limit) => {
+                Self::ContractClassSizeIsTooLarge(limit)
             }
             AddDeclareTransactionError::DuplicateTransaction => Self::DuplicateTransaction,
-            AddDeclareTransactionError::CompiledClassHashMismatch => {
-                Self::CompiledClassHashMismatch
+            AddDeclareTransactionError::CompiledClassHashMismatch(data) => {
+                Self::CompiledClassHashMismatch(data)
             }
             AddDeclareTransactionError::NonAccount => Self::NonAccount,
-            AddDeclareTransactionError::UnsupportedTransactionVersion => Self::UnsupportedTxVersion,
+            AddDeclareTransactionError::UnsupportedTransactionVersion(version) => {
+                Self::UnsupportedTxVersion(version)
+            }
             AddDeclareTransactionError::UnsupportedContractClassVersion => {
                 Self::UnsupportedContractClassVersion
             }
@@ -65,8 +75,8 @@ impl From<SequencerError> for AddDeclareTransactionError {
             ClassAlreadyDeclared, CompilationFailed, ContractBytecodeSizeTooLarge,
             ContractClassObjectSizeTooLarge, DuplicatedTransaction, EntryPointNotFound,
             InsufficientAccountBalance, InsufficientMaxFee, InvalidCompiledClassHash,
-            InvalidContractClassVersion, InvalidTransactionNonce, InvalidTransactionVersion,
-            ValidateFailure,
+            InvalidContractClassVersion, InvalidSignature, InvalidTransactionNonce,
+            InvalidTransactionVersion, ValidateFailure,
         };
         match e {
             SequencerError::StarknetError(e) if e.code == ClassAlreadyDeclared.into() => {
@@ -79,7 +89,7 @@ impl From<SequencerError> for AddDeclareTransactionError {
                 if e.code == ContractBytecodeSizeTooLarge.into()
                     || e.code == ContractClassObjectSizeTooLarge.into() =>
             {
-                AddDeclareTransactionError::ContractClassSizeIsTooLarge
+                AddDeclareTransactionError::ContractClassSizeIsTooLarge(None)
             }
             SequencerError::StarknetError(e) if e.code == DuplicatedTransaction.into() => {
                 AddDeclareTransactionError::DuplicateTransaction
@@ -93,14 +103,20 @@ impl From<SequencerError> for AddDeclareTransactionError {
             SequencerError::StarknetError(e) if e.code == InvalidTransactionNonce.into() => {
                 AddDeclareTransactionError::InvalidTransactionNonce
             }
-            SequencerError::StarknetError(e) if e.code == ValidateFailure.into() => {
-                AddDeclareTransactionError::ValidationFailure
+            SequencerError::StarknetError(e)
+                if e.code == ValidateFailure.into() || e.code == InvalidSignature.into() =>
+            {
+                AddDeclareTransactionError::ValidationFailure(ValidationFailureData::from_gateway(
+                    e,
+                ))
             }
             SequencerError::StarknetError(e) if e.code == InvalidCompiledClassHash.into() => {
-                AddDeclareTransactionError::CompiledClassHashMismatch
+                AddDeclareTransactionError::CompiledClassHashMismatch(
+                    CompiledClassHashMismatchData::from_gateway(&e),
+                )
             }
             SequencerError::StarknetError(e) if e.code == InvalidTransactionVersion.into() => {
-                AddDeclareTransactionError::UnsupportedTransactionVersion
+                AddDeclareTransactionError::UnsupportedTransactionVersion(None)
             }
             SequencerError::StarknetError(e) if e.code == InvalidContractClassVersion.into() => {
                 AddDeclareTransactionError::UnsupportedContractClassVersion
@@ -143,11 +159,27 @@ pub async fn add_declare_transaction(
     context: RpcContext,
     input: AddDeclareTransactionInput,
 ) -> Result<AddDeclareTransactionOutput, AddDeclareTransactionError> {
+    let Transaction::Declare(tx) = &input.declare_transaction;
+    if !context.config.is_supported_tx_version(tx.version()) {
+        return Err(AddDeclareTransactionError::UnsupportedTransactionVersion(
+            Some(tx.version()),
+        ));
+    }
+
     match input.declare_transaction {
-        Transaction::Declare(BroadcastedDeclareTransaction::V0(_)) => {
-            Err(AddDeclareTransactionError::UnsupportedTransactionVersion)
-        }
+        Transaction::Declare(BroadcastedDeclareTransaction::V0(tx)) => Err(
+            AddDeclareTransactionError::UnsupportedTransactionVersion(Some(tx.version)),
+        ),
         Transaction::Declare(BroadcastedDeclareTransaction::V1(tx)) => {
+            // A program which cannot be decompressed is not a valid class.
+            let size = tx
+                .contract_class
+                .definition_size(context.config.max_declare_class_size)
+                .map_err(|_| AddDeclareTransactionError::InvalidContractClass)?;
+            ClassSizeLimit::check(size, context.config.max_declare_class_size).map_err(
+                |limit| AddDeclareTransactionError::ContractClassSizeIsTooLarge(Some(limit)),
+            )?;
+
             let contract_definition: CairoContractDefinition = tx
                 .contract_class
                 .try_into()
@@ -173,6 +205,14 @@ pub async fn add_declare_transaction(
             })
         }
         Transaction::Declare(BroadcastedDeclareTransaction::V2(tx)) => {
+            let size = tx
+                .contract_class
+                .definition_size()
+                .map_err(|_| AddDeclareTransactionError::InvalidContractClass)?;
+            ClassSizeLimit::check(size, context.config.max_declare_class_size).map_err(
+                |limit| AddDeclareTransactionError::ContractClassSizeIsTooLarge(Some(limit)),
+            )?;
+
             let contract_definition: SierraContractDefinition = tx
                 .contract_class
                 .try_into()
@@ -396,7 +436,41 @@ mod tests {
             token: None,
         };
         let error = add_declare_transaction(context, input).await.unwrap_err();
-        assert_matches::assert_matches!(error, AddDeclareTransactionError::UnexpectedError(_));
+        assert_matches::assert_matches!(error, AddDeclareTransactionError::InvalidContractClass);
+    }
+
+    #[tokio::test]
+    async fn malformed_program_is_invalid_contract_class() {
+        let context = RpcContext::for_tests();
+
+        let malformed_contract_class = CairoContractClass {
+            program: base64::encode("not a gzipped program"),
+            ..CONTRACT_CLASS.clone()
+        };
+
+        let declare_transaction = Transaction::Declare(BroadcastedDeclareTransaction::V1(
+            BroadcastedDeclareTransactionV1 {
+                version: TransactionVersion::ONE,
+                max_fee: Fee(Default::default()),
+                signature: vec![],
+                nonce: TransactionNonce(Default::default()),
+                contract_class: malformed_contract_class,
+                sender_address: ContractAddress::new_or_panic(Felt::from_u64(1)),
+            },
+        ));
+
+        let input = AddDeclareTransactionInput {
+            declare_transaction,
+            token: None,
+        };
+        let error = add_declare_transaction(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(error, AddDeclareTransactionError::InvalidContractClass);
+
+        let error = crate::jsonrpc::RpcError::from(error);
+        assert_eq!(
+            error.code(),
+            crate::error::RpcError::InvalidContractClass.code()
+        );
     }
 
     #[test_log::test(tokio::test)]
@@ -540,4 +614,82 @@ mod tests {
             AddDeclareTransactionError::InsufficientAccountBalance
         );
     }
+
+    #[tokio::test]
+    async fn class_size_is_too_large() {
+        let size = SIERRA_CLASS.definition_size().unwrap();
+        let context = RpcContext::for_tests().with_config(crate::context::RpcConfig {
+            max_declare_class_size: size - 1,
+            ..Default::default()
+        });
+
+        let declare_transaction = Transaction::Declare(BroadcastedDeclareTransaction::V2(
+            BroadcastedDeclareTransactionV2 {
+                version: TransactionVersion::TWO,
+                max_fee: Fee(felt!("0x01")),
+                signature: vec![],
+                nonce: TransactionNonce(Default::default()),
+                contract_class: SIERRA_CLASS.clone(),
+                sender_address: ContractAddress::new_or_panic(Felt::from_u64(1)),
+                compiled_class_hash: casm_hash!(
+                    "0x688e44b1d8612222a25cf742c8e1493af4640fa74b1a7707bde2002df51ea8c"
+                ),
+            },
+        ));
+
+        let input = AddDeclareTransactionInput {
+            declare_transaction,
+            token: None,
+        };
+        let err = add_declare_transaction(context, input).await.unwrap_err();
+        let expected = ClassSizeLimit {
+            size,
+            limit: size - 1,
+        };
+        assert_matches::assert_matches!(
+            err,
+            AddDeclareTransactionError::ContractClassSizeIsTooLarge(Some(x)) => assert_eq!(x, expected)
+        );
+
+        let error = crate::jsonrpc::RpcError::from(err);
+        let error = serde_json::to_value(error).unwrap();
+        assert_eq!(
+            error,
+            serde_json::json!({
+                "code": 57,
+                "message": "Contract class size it too large",
+                "data": {
+                    "size": size,
+                    "limit": size - 1,
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn compiled_class_hash_mismatch() {
+        use starknet_gateway_types::error::{KnownStarknetErrorCode, StarknetError};
+
+        let gateway_error = SequencerError::StarknetError(StarknetError {
+            code: KnownStarknetErrorCode::InvalidCompiledClassHash.into(),
+            message:
+                "Computed compiled_class_hash: 0x1234, does not match the given value: 0xabcd."
+                    .to_owned(),
+            status: None,
+        });
+
+        let error = crate::jsonrpc::RpcError::from(AddDeclareTransactionError::from(gateway_error));
+        let error = serde_json::to_value(error).unwrap();
+        assert_eq!(
+            error,
+            serde_json::json!({
+                "code": 60,
+                "message": "The compiled class hash did not match the one supplied in the transaction",
+                "data": {
+                    "expected": "0x1234",
+                    "supplied": "0xabcd",
+                }
+            })
+        );
+    }
 }
@@ -1,7 +1,8 @@
 use crate::context::RpcContext;
+use crate::error::ValidationFailureData;
 use crate::felt::{RpcFelt, RpcFelt251};
 use crate::v02::types::request::BroadcastedDeployAccountTransaction;
-use pathfinder_common::{ContractAddress, TransactionHash};
+use pathfinder_common::{ContractAddress, TransactionHash, TransactionVersion};
 use starknet_gateway_client::GatewayApi;
 use starknet_gateway_types::error::SequencerError;
 
@@ -33,10 +34,11 @@ pub enum AddDeployAccountTransactionError {
     InvalidTransactionNonce,
     InsufficientMaxFee,
     InsufficientAccountBalance,
-    ValidationFailure,
+    ValidationFailure(ValidationFailureData),
     DuplicateTransaction,
     NonAccount,
-    UnsupportedTransactionVersion,
+    /// Contains the version if the transaction was rejected by pathfinder rather than the gateway.
+    UnsupportedTransactionVersion(Option<TransactionVersion>),
     UnexpectedError(String),
 }
 
@@ -48,10 +50,10 @@ impl From<AddDeployAccountTransactionError> for crate::error::RpcError {
             InvalidTransactionNonce => Self::InvalidTransactionNonce,
             InsufficientMaxFee => Self::InsufficientMaxFee,
             InsufficientAccountBalance => Self::InsufficientAccountBalance,
-            ValidationFailure => Self::ValidationFailure,
+            ValidationFailure(data) => Self::ValidationFailure(Some(data)),
             DuplicateTransaction => Self::DuplicateTransaction,
             NonAccount => Self::NonAccount,
-            UnsupportedTransactionVersion => Self::UnsupportedTxVersion,
+            UnsupportedTransactionVersion(version) => Self::UnsupportedTxVersion(version),
             UnexpectedError(data) => Self::UnexpectedError { data },
         }
     }
@@ -67,8 +69,8 @@ impl From<SequencerError> for AddDeployAccountTransactionError {
     fn from(e: SequencerError) -> Self {
         use starknet_gateway_types::error::KnownStarknetErrorCode::{
             DuplicatedTransaction, EntryPointNotFound, InsufficientAccountBalance,
-            InsufficientMaxFee, InvalidTransactionNonce, InvalidTransactionVersion,
-            UndeclaredClass, ValidateFailure,
+            InsufficientMaxFee, InvalidSignature, InvalidTransactionNonce,
+            InvalidTransactionVersion, UndeclaredClass, ValidateFailure,
         };
         match e {
             SequencerError::StarknetError(e) if e.code == UndeclaredClass.into() => {
@@ -86,11 +88,15 @@ impl From<SequencerError> for AddDeployAccountTransactionError {
             SequencerError::StarknetError(e) if e.code == InvalidTransactionNonce.into() => {
                 AddDeployAccountTransactionError::InvalidTransactionNonce
             }
-            SequencerError::StarknetError(e) if e.code == ValidateFailure.into() => {
-                AddDeployAccountTransactionError::ValidationFailure
+            SequencerError::StarknetError(e)
+                if e.code == ValidateFailure.into() || e.code == InvalidSignature.into() =>
+            {
+                AddDeployAccountTransactionError::ValidationFailure(
+                    ValidationFailureData::from_gateway(e),
+                )
             }
             SequencerError::StarknetError(e) if e.code == InvalidTransactionVersion.into() => {
-                AddDeployAccountTransactionError::UnsupportedTransactionVersion
+                AddDeployAccountTransactionError::UnsupportedTransactionVersion(None)
             }
             SequencerError::StarknetError(e) if e.code == EntryPointNotFound.into() => {
                 AddDeployAccountTransactionError::NonAccount
@@ -105,6 +111,12 @@ pub async fn add_deploy_account_transaction(
     input: AddDeployAccountTransactionInput,
 ) -> Result<AddDeployAccountTransactionOutput, AddDeployAccountTransactionError> {
     let Transaction::DeployAccount(tx) = input.deploy_account_transaction;
+    if !context.config.is_supported_tx_version(tx.version) {
+        return Err(
+            AddDeployAccountTransactionError::UnsupportedTransactionVersion(Some(tx.version)),
+        );
+    }
+
     let response = context
         .sequencer
         .add_deploy_account(
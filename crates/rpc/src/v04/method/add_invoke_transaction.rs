@@ -1,7 +1,8 @@
 use crate::context::RpcContext;
+use crate::error::ValidationFailureData;
 use crate::felt::RpcFelt;
 use crate::v02::types::request::BroadcastedInvokeTransaction;
-use pathfinder_common::TransactionHash;
+use pathfinder_common::{TransactionHash, TransactionVersion};
 use starknet_gateway_client::GatewayApi;
 use starknet_gateway_types::error::SequencerError;
 
@@ -30,10 +31,11 @@ pub enum AddInvokeTransactionError {
     InvalidTransactionNonce,
     InsufficientMaxFee,
     InsufficientAccountBalance,
-    ValidationFailure,
+    ValidationFailure(ValidationFailureData),
     DuplicateTransaction,
     NonAccount,
-    UnsupportedTransactionVersion,
+    /// Contains the version if the transaction was rejected by pathfinder rather than the gateway.
+    UnsupportedTransactionVersion(Option<TransactionVersion>),
     UnexpectedError(String),
 }
 
@@ -45,10 +47,14 @@ impl From<AddInvokeTransactionError> for crate::error::RpcError {
             AddInvokeTransactionError::InsufficientAccountBalance => {
                 Self::InsufficientAccountBalance
             }
-            AddInvokeTransactionError::ValidationFailure => Self::ValidationFailure,
+            AddInvokeTransactionError::ValidationFailure(data) => {
+                Self::ValidationFailure(Some(data))
+            }
             AddInvokeTransactionError::DuplicateTransaction => Self::DuplicateTransaction,
             AddInvokeTransactionError::NonAccount => Self::NonAccount,
-            AddInvokeTransactionError::UnsupportedTransactionVersion => Self::UnsupportedTxVersion,
+            AddInvokeTransactionError::UnsupportedTransactionVersion(version) => {
+                Self::UnsupportedTxVersion(version)
+            }
             AddInvokeTransactionError::UnexpectedError(data) => Self::UnexpectedError { data },
         }
     }
@@ -64,8 +70,8 @@ impl From<SequencerError> for AddInvokeTransactionError {
     fn from(e: SequencerError) -> Self {
         use starknet_gateway_types::error::KnownStarknetErrorCode::{
             DuplicatedTransaction, EntryPointNotFound, InsufficientAccountBalance,
-            InsufficientMaxFee, InvalidTransactionNonce, InvalidTransactionVersion,
-            ValidateFailure,
+            InsufficientMaxFee, InvalidSignature, InvalidTransactionNonce,
+            InvalidTransactionVersion, ValidateFailure,
         };
         match e {
             SequencerError::StarknetError(e) if e.code == DuplicatedTransaction.into() => {
@@ -80,11 +86,13 @@ impl From<SequencerError> for AddInvokeTransactionError {
             SequencerError::StarknetError(e) if e.code == InvalidTransactionNonce.into() => {
                 AddInvokeTransactionError::InvalidTransactionNonce
             }
-            SequencerError::StarknetError(e) if e.code == ValidateFailure.into() => {
-                AddInvokeTransactionError::ValidationFailure
+            SequencerError::StarknetError(e)
+                if e.code == ValidateFailure.into() || e.code == InvalidSignature.into() =>
+            {
+                AddInvokeTransactionError::ValidationFailure(ValidationFailureData::from_gateway(e))
             }
             SequencerError::StarknetError(e) if e.code == InvalidTransactionVersion.into() => {
-                AddInvokeTransactionError::UnsupportedTransactionVersion
+                AddInvokeTransactionError::UnsupportedTransactionVersion(None)
             }
             SequencerError::StarknetError(e) if e.code == EntryPointNotFound.into() => {
                 AddInvokeTransactionError::NonAccount
@@ -99,6 +107,29 @@ pub async fn add_invoke_transaction(
     input: AddInvokeTransactionInput,
 ) -> Result<AddInvokeTransactionOutput, AddInvokeTransactionError> {
     let Transaction::Invoke(tx) = input.invoke_transaction;
+    if !context.config.is_supported_tx_version(tx.version()) {
+        return Err(AddInvokeTransactionError::UnsupportedTransactionVersion(
+            Some(tx.version()),
+        ));
+    }
+
+    // Identical resubmissions are answered from the cache, which lets clients retry safely.
+    let resubmission = context
+        .config
+        .resubmission_ttl
+        .map(|ttl| (ttl, tx.transaction_hash(context.chain_id)));
+    if let Some((ttl, hash)) = resubmission {
+        let cached = context
+            .recent_submissions
+            .lock()
+            .unwrap()
+            .get(hash, &tx, ttl);
+        if let Some(transaction_hash) = cached {
+            return Ok(AddInvokeTransactionOutput { transaction_hash });
+        }
+    }
+    let submitted = resubmission.map(|(_, hash)| (hash, tx.clone()));
+
     let response = match tx {
         BroadcastedInvokeTransaction::V0(v0) => {
             context
@@ -130,6 +161,14 @@ pub async fn add_invoke_transaction(
         }
     };
 
+    if let Some((hash, tx)) = submitted {
+        context
+            .recent_submissions
+            .lock()
+            .unwrap()
+            .insert(hash, tx, response.transaction_hash);
+    }
+
     Ok(AddInvokeTransactionOutput {
         transaction_hash: response.transaction_hash,
     })
@@ -140,7 +179,6 @@ mod tests {
     use super::*;
     use crate::v02::types::request::BroadcastedInvokeTransactionV1;
     use pathfinder_common::macro_prelude::*;
-    use pathfinder_common::TransactionVersion;
 
     fn test_invoke_txn() -> Transaction {
         Transaction::Invoke(BroadcastedInvokeTransaction::V1(
@@ -244,6 +282,93 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn resubmission_is_answered_from_cache() {
+        use starknet_gateway_client::test_utils::setup_with_varied_responses;
+
+        // Only a single submission is forwarded, as the mock fails once its responses run out.
+        let (_jh, sequencer) = setup_with_varied_responses([(
+            "/gateway/add_transaction".to_owned(),
+            [(
+                r#"{"code":"TRANSACTION_RECEIVED","transaction_hash":"0x1234"}"#.to_owned(),
+                200,
+            )],
+        )]);
+        let context = RpcContext {
+            sequencer: sequencer.disable_retry_for_tests(),
+            ..RpcContext::for_tests()
+        }
+        .with_config(crate::context::RpcConfig {
+            resubmission_ttl: Some(std::time::Duration::from_secs(60)),
+            ..Default::default()
+        });
+
+        let input = || AddInvokeTransactionInput {
+            invoke_transaction: test_invoke_txn(),
+        };
+        let first = add_invoke_transaction(context.clone(), input())
+            .await
+            .unwrap();
+        let second = add_invoke_transaction(context, input()).await.unwrap();
+
+        let expected = AddInvokeTransactionOutput {
+            transaction_hash: transaction_hash!("0x1234"),
+        };
+        assert_eq!(first, expected);
+        assert_eq!(second, expected);
+    }
+
+    mod unsupported_version {
+        use super::*;
+        use crate::v02::types::request::BroadcastedInvokeTransactionV0;
+
+        fn context(supported: u128) -> RpcContext {
+            RpcContext::for_tests().with_config(crate::context::RpcConfig {
+                supported_tx_versions: Some([supported].into()),
+                ..Default::default()
+            })
+        }
+
+        /// The transaction is rejected before it is forwarded to the gateway.
+        async fn reject(
+            supported: u128,
+            invoke_transaction: Transaction,
+        ) -> crate::error::RpcError {
+            let input = AddInvokeTransactionInput { invoke_transaction };
+            add_invoke_transaction(context(supported), input)
+                .await
+                .unwrap_err()
+                .into()
+        }
+
+        #[tokio::test]
+        async fn too_old() {
+            let tx = Transaction::Invoke(BroadcastedInvokeTransaction::V0(
+                BroadcastedInvokeTransactionV0 {
+                    version: TransactionVersion::ZERO,
+                    max_fee: fee!("0x1"),
+                    signature: vec![],
+                    contract_address: contract_address!("0x123"),
+                    entry_point_selector: entry_point!("0x456"),
+                    calldata: vec![],
+                },
+            ));
+
+            let error = reject(1, tx).await;
+
+            assert_eq!(error.code(), 61);
+            assert_eq!(error.data(), Some(serde_json::json!({"version": "0x0"})));
+        }
+
+        #[tokio::test]
+        async fn too_new() {
+            let error = reject(0, test_invoke_txn()).await;
+
+            assert_eq!(error.code(), 61);
+            assert_eq!(error.data(), Some(serde_json::json!({"version": "0x1"})));
+        }
+    }
+
     #[tokio::test]
     #[ignore = "gateway 429"]
     async fn duplicate_transaction() {
@@ -13,7 +13,7 @@ pub struct GetBlockInput {
     block_id: BlockId,
 }
 
-crate::error::generate_rpc_error_subset!(GetBlockError: BlockNotFound);
+crate::error::generate_rpc_error_subset!(GetBlockError: BlockNotFound, NoBlocks);
 
 /// Get block information with full transactions given the block id
 pub async fn get_block_with_txs(
@@ -54,7 +54,11 @@ pub async fn get_block_with_txs(
         let header = transaction
             .block_header(block_id)
             .context("Reading block from database")?
-            .ok_or(GetBlockError::BlockNotFound)?;
+            .ok_or(match block_id {
+                // An empty database only happens before the first block is synced.
+                pathfinder_storage::BlockId::Latest => GetBlockError::NoBlocks,
+                _ => GetBlockError::BlockNotFound,
+            })?;
 
         let l1_accepted = transaction.block_is_l1_accepted(header.number.into())?;
         let block_status = if l1_accepted {
@@ -87,7 +91,7 @@ fn get_block_transactions(
     Ok(txs)
 }
 
-mod types {
+pub(crate) mod types {
     use crate::felt::RpcFelt;
     use crate::v02::types::reply::BlockStatus;
     use crate::v04::types::TransactionWithHash;
@@ -296,4 +300,27 @@ mod tests {
             check(i, test_case).await;
         }
     }
+
+    #[tokio::test]
+    async fn latest_without_blocks() {
+        let storage = pathfinder_storage::Storage::in_memory().unwrap();
+        let context = RpcContext::for_tests().with_storage(storage);
+
+        let cases: &[(RpcContext, BlockId, TestCaseHandler)] = &[
+            (
+                context.clone(),
+                BlockId::Latest,
+                assert_error(GetBlockError::NoBlocks),
+            ),
+            (
+                context,
+                BlockId::Number(BlockNumber::GENESIS),
+                assert_error(GetBlockError::BlockNotFound),
+            ),
+        ];
+
+        for (i, test_case) in cases.iter().enumerate() {
+            check(i, test_case).await;
+        }
+    }
 }
@@ -96,6 +96,7 @@ pub async fn simulate_transactions(
 pub mod dto {
     use serde_with::serde_as;
 
+    use crate::fee::FeeWei;
     use crate::felt::RpcFelt;
     use crate::v02::method::call::FunctionCall;
 
@@ -112,20 +113,18 @@ pub mod dto {
         /// The Ethereum gas cost of the transaction
         #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
         pub gas_consumed: primitive_types::U256,
-        /// The gas price (in gwei) that was used in the cost estimation (input to fee estimation)
-        #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
-        pub gas_price: primitive_types::U256,
-        /// The estimated fee for the transaction (in gwei), product of gas_consumed and gas_price
-        #[serde_as(as = "pathfinder_serde::U256AsHexStr")]
-        pub overall_fee: primitive_types::U256,
+        /// The gas price (in wei) that was used in the cost estimation (input to fee estimation)
+        pub gas_price: FeeWei,
+        /// The estimated fee for the transaction (in wei), product of gas_consumed and gas_price
+        pub overall_fee: FeeWei,
     }
 
     impl From<pathfinder_executor::types::FeeEstimate> for FeeEstimate {
         fn from(value: pathfinder_executor::types::FeeEstimate) -> Self {
             Self {
                 gas_consumed: value.gas_consumed,
-                gas_price: value.gas_price,
-                overall_fee: value.overall_fee,
+                gas_price: FeeWei(value.gas_price),
+                overall_fee: FeeWei(value.overall_fee),
             }
         }
     }
@@ -401,6 +400,7 @@ pub mod dto {
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use crate::fee::FeeWei;
     use crate::v02::method::call::FunctionCall;
     use crate::v02::types::request::{
         BroadcastedDeclareTransaction, BroadcastedDeclareTransactionV2,
@@ -447,8 +447,8 @@ pub(crate) mod tests {
                 fee_estimation:
                     FeeEstimate {
                         gas_consumed: 3097.into(),
-                        gas_price: 1.into(),
-                        overall_fee: 3097.into(),
+                        gas_price: FeeWei(1.into()),
+                        overall_fee: FeeWei(3097.into()),
                     }
                 ,
                 transaction_trace:
@@ -609,8 +609,8 @@ pub(crate) mod tests {
                 SimulatedTransaction {
                     fee_estimation: FeeEstimate {
                         gas_consumed: DECLARE_GAS_CONSUMED.into(),
-                        gas_price: 1.into(),
-                        overall_fee: DECLARE_GAS_CONSUMED.into(),
+                        gas_price: FeeWei(1.into()),
+                        overall_fee: FeeWei(DECLARE_GAS_CONSUMED.into()),
                     },
                     transaction_trace: TransactionTrace::Declare(DeclareTxnTrace {
                         fee_transfer_invocation: Some(declare_fee_transfer(
@@ -628,8 +628,8 @@ pub(crate) mod tests {
                 SimulatedTransaction {
                     fee_estimation: FeeEstimate {
                         gas_consumed: DECLARE_GAS_CONSUMED.into(),
-                        gas_price: 1.into(),
-                        overall_fee: DECLARE_GAS_CONSUMED.into(),
+                        gas_price: FeeWei(1.into()),
+                        overall_fee: FeeWei(DECLARE_GAS_CONSUMED.into()),
                     },
                     transaction_trace: TransactionTrace::Declare(DeclareTxnTrace {
                         fee_transfer_invocation: None,
@@ -645,8 +645,8 @@ pub(crate) mod tests {
                 SimulatedTransaction {
                     fee_estimation: FeeEstimate {
                         gas_consumed: DECLARE_GAS_CONSUMED.into(),
-                        gas_price: 1.into(),
-                        overall_fee: DECLARE_GAS_CONSUMED.into(),
+                        gas_price: FeeWei(1.into()),
+                        overall_fee: FeeWei(DECLARE_GAS_CONSUMED.into()),
                     },
                     transaction_trace: TransactionTrace::Declare(DeclareTxnTrace {
                         fee_transfer_invocation: Some(declare_fee_transfer(
@@ -721,8 +721,8 @@ pub(crate) mod tests {
                 SimulatedTransaction {
                     fee_estimation: FeeEstimate {
                         gas_consumed: UNIVERSAL_DEPLOYER_GAS_CONSUMED.into(),
-                        gas_price: 1.into(),
-                        overall_fee: UNIVERSAL_DEPLOYER_GAS_CONSUMED.into(),
+                        gas_price: FeeWei(1.into()),
+                        overall_fee: FeeWei(UNIVERSAL_DEPLOYER_GAS_CONSUMED.into()),
                     },
                     transaction_trace: TransactionTrace::Invoke(InvokeTxnTrace {
                         validate_invocation: Some(universal_deployer_validate(
@@ -750,8 +750,8 @@ pub(crate) mod tests {
                 SimulatedTransaction {
                     fee_estimation: FeeEstimate {
                         gas_consumed: UNIVERSAL_DEPLOYER_GAS_CONSUMED.into(),
-                        gas_price: 1.into(),
-                        overall_fee: UNIVERSAL_DEPLOYER_GAS_CONSUMED.into(),
+                        gas_price: FeeWei(1.into()),
+                        overall_fee: FeeWei(UNIVERSAL_DEPLOYER_GAS_CONSUMED.into()),
                     },
                     transaction_trace: TransactionTrace::Invoke(InvokeTxnTrace {
                         validate_invocation: Some(universal_deployer_validate(
@@ -777,8 +777,8 @@ pub(crate) mod tests {
                 SimulatedTransaction {
                     fee_estimation: FeeEstimate {
                         gas_consumed: UNIVERSAL_DEPLOYER_GAS_CONSUMED.into(),
-                        gas_price: 1.into(),
-                        overall_fee: UNIVERSAL_DEPLOYER_GAS_CONSUMED.into(),
+                        gas_price: FeeWei(1.into()),
+                        overall_fee: FeeWei(UNIVERSAL_DEPLOYER_GAS_CONSUMED.into()),
                     },
                     transaction_trace: TransactionTrace::Invoke(InvokeTxnTrace {
                         validate_invocation: None,
@@ -968,8 +968,8 @@ pub(crate) mod tests {
                 SimulatedTransaction {
                     fee_estimation: FeeEstimate {
                         gas_consumed: INVOKE_GAS_CONSUMED.into(),
-                        gas_price: 1.into(),
-                        overall_fee: INVOKE_GAS_CONSUMED.into(),
+                        gas_price: FeeWei(1.into()),
+                        overall_fee: FeeWei(INVOKE_GAS_CONSUMED.into()),
                     },
                     transaction_trace: TransactionTrace::Invoke(InvokeTxnTrace {
                         validate_invocation: Some(invoke_validate(account_contract_address)),
@@ -992,8 +992,8 @@ pub(crate) mod tests {
                 SimulatedTransaction {
                     fee_estimation: FeeEstimate {
                         gas_consumed: INVOKE_GAS_CONSUMED.into(),
-                        gas_price: 1.into(),
-                        overall_fee: INVOKE_GAS_CONSUMED.into(),
+                        gas_price: FeeWei(1.into()),
+                        overall_fee: FeeWei(INVOKE_GAS_CONSUMED.into()),
                     },
                     transaction_trace: TransactionTrace::Invoke(InvokeTxnTrace {
                         validate_invocation: Some(invoke_validate(account_contract_address)),
@@ -1014,8 +1014,8 @@ pub(crate) mod tests {
                 SimulatedTransaction {
                     fee_estimation: FeeEstimate {
                         gas_consumed: INVOKE_GAS_CONSUMED.into(),
-                        gas_price: 1.into(),
-                        overall_fee: INVOKE_GAS_CONSUMED.into(),
+                        gas_price: FeeWei(1.into()),
+                        overall_fee: FeeWei(INVOKE_GAS_CONSUMED.into()),
                     },
                     transaction_trace: TransactionTrace::Invoke(InvokeTxnTrace {
                         validate_invocation: None,
@@ -13,7 +13,18 @@ use super::simulate_transactions::dto::TransactionTrace;
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct TraceBlockTransactionsInput {
-    block_hash: BlockHash,
+    /// [None] if the block hash is malformed, which is reported as an invalid block hash
+    /// rather than as invalid params.
+    #[serde(deserialize_with = "deserialize_block_hash")]
+    block_hash: Option<BlockHash>,
+}
+
+fn deserialize_block_hash<'de, D>(deserializer: D) -> Result<Option<BlockHash>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(BlockHash::deserialize(value).ok())
 }
 
 #[derive(Debug, Serialize, Eq, PartialEq)]
@@ -26,13 +37,14 @@ pub struct Trace {
 pub struct TraceBlockTransactionsOutput(pub Vec<Trace>);
 
 crate::error::generate_rpc_error_subset!(
-    TraceBlockTransactionsError: InvalidBlockHash
+    TraceBlockTransactionsError: InvalidBlockHash,
+    BlockNotFound
 );
 
 impl From<ExecutionStateError> for TraceBlockTransactionsError {
     fn from(value: ExecutionStateError) -> Self {
         match value {
-            ExecutionStateError::BlockNotFound => Self::InvalidBlockHash,
+            ExecutionStateError::BlockNotFound => Self::BlockNotFound,
             ExecutionStateError::Internal(e) => Self::Internal(e),
         }
     }
@@ -61,6 +73,10 @@ pub async fn trace_block_transactions(
     context: RpcContext,
     input: TraceBlockTransactionsInput,
 ) -> Result<TraceBlockTransactionsOutput, TraceBlockTransactionsError> {
+    let block_hash = input
+        .block_hash
+        .ok_or(TraceBlockTransactionsError::InvalidBlockHash)?;
+
     let (transactions, gas_price, parent_block_hash): (Vec<_>, Option<U256>, BlockHash) = {
         let span = tracing::Span::current();
 
@@ -71,19 +87,19 @@ pub async fn trace_block_transactions(
             let mut db = storage.connection()?;
             let tx = db.transaction()?;
 
-            let header = tx.block_header(pathfinder_storage::BlockId::Hash(input.block_hash))?;
+            let header = tx.block_header(pathfinder_storage::BlockId::Hash(block_hash))?;
 
             let parent_block_hash = header
                 .as_ref()
                 .map(|h| h.parent_hash)
-                .ok_or(TraceBlockTransactionsError::InvalidBlockHash)?;
+                .ok_or(TraceBlockTransactionsError::BlockNotFound)?;
 
             let gas_price: Option<U256> =
                 header.as_ref().map(|header| U256::from(header.gas_price.0));
 
             let (transactions, _): (Vec<_>, Vec<_>) = tx
-                .transaction_data_for_block(BlockId::Hash(input.block_hash))?
-                .ok_or(TraceBlockTransactionsError::InvalidBlockHash)?
+                .transaction_data_for_block(BlockId::Hash(block_hash))?
+                .ok_or(TraceBlockTransactionsError::BlockNotFound)?
                 .into_iter()
                 .unzip();
 
@@ -131,6 +147,7 @@ pub(crate) mod tests {
     };
 
     use super::*;
+    use pathfinder_common::macro_prelude::block_hash_bytes;
 
     impl From<crate::v02::types::request::BroadcastedTransaction>
         for starknet_gateway_types::reply::transaction::Transaction
@@ -357,7 +374,7 @@ pub(crate) mod tests {
         let (context, next_block_header, traces) = setup_multi_tx_trace_test().await?;
 
         let input = TraceBlockTransactionsInput {
-            block_hash: next_block_header.hash,
+            block_hash: Some(next_block_header.hash),
         };
         let output = trace_block_transactions(context, input).await.unwrap();
         let expected = TraceBlockTransactionsOutput(traces);
@@ -365,4 +382,25 @@ pub(crate) mod tests {
         pretty_assertions::assert_eq!(output, expected);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn malformed_block_hash() {
+        let context = RpcContext::for_tests();
+        let input: TraceBlockTransactionsInput =
+            serde_json::from_value(serde_json::json!({"block_hash": "0xZZ"})).unwrap();
+
+        let error = trace_block_transactions(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(error, TraceBlockTransactionsError::InvalidBlockHash);
+    }
+
+    #[tokio::test]
+    async fn absent_block_hash() {
+        let context = RpcContext::for_tests();
+        let input: TraceBlockTransactionsInput =
+            serde_json::from_value(serde_json::json!({"block_hash": block_hash_bytes!(b"absent")}))
+                .unwrap();
+
+        let error = trace_block_transactions(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(error, TraceBlockTransactionsError::BlockNotFound);
+    }
 }
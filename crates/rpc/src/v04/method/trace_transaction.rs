@@ -80,6 +80,19 @@ pub async fn trace_transaction(
     context: RpcContext,
     input: TraceTransactionInput,
 ) -> Result<TraceTransactionOutput, TraceTransactionError> {
+    if let Some(pending) = &context.pending_data {
+        let is_pending = pending.block().await.is_some_and(|block| {
+            block
+                .transactions
+                .iter()
+                .any(|tx| tx.hash() == input.transaction_hash)
+        });
+
+        if is_pending {
+            return Err(TraceTransactionError::NoTraceAvailable(TraceError::Pending));
+        }
+    }
+
     let (transactions, parent_block_hash, gas_price): (Vec<Transaction>, BlockHash, Option<U256>) = {
         let span = tracing::Span::current();
 
@@ -161,4 +174,20 @@ pub mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn pending_transaction() {
+        use pathfinder_common::macro_prelude::*;
+
+        let context = RpcContext::for_tests_with_pending().await;
+        let input = TraceTransactionInput {
+            transaction_hash: transaction_hash_bytes!(b"pending tx hash 0"),
+        };
+
+        let error = trace_transaction(context, input).await.unwrap_err();
+        assert_matches::assert_matches!(
+            error,
+            TraceTransactionError::NoTraceAvailable(TraceError::Pending)
+        );
+    }
 }
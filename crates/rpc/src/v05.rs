@@ -1,4 +1,4 @@
-use crate::jsonrpc::{RpcRouter, RpcRouterBuilder};
+use crate::jsonrpc::{MethodOptions, RpcRouter, RpcRouterBuilder};
 
 pub mod method;
 
@@ -7,8 +7,9 @@ use crate::v04::method as v04_method;
 #[rustfmt::skip]
 pub fn register_routes() -> RpcRouterBuilder {
     RpcRouter::builder("v0.5")
-        .register("starknet_addDeclareTransaction"           , v04_method::add_declare_transaction)
-        .register("starknet_addDeployAccountTransaction"     , v04_method::add_deploy_account_transaction)
-        .register("starknet_addInvokeTransaction"            , v04_method::add_invoke_transaction)
-        .register("starknet_specVersion"                     , method::spec_version)
+        .register_with("starknet_addDeclareTransaction"       , v04_method::add_declare_transaction, MethodOptions::default().strict())
+        .register_with("starknet_addDeployAccountTransaction" , v04_method::add_deploy_account_transaction, MethodOptions::default().strict())
+        .register_with("starknet_addInvokeTransaction"        , v04_method::add_invoke_transaction, MethodOptions::default().strict())
+        .register_with("starknet_specVersion"                 , method::spec_version, MethodOptions::default().unlogged())
+        .register_method_schema("pathfinder_methodSchema")
 }
@@ -0,0 +1,51 @@
+//! This test was separated because the `metrics` crate uses a singleton recorder, so keeping a test
+//! that relies on metric values in a separate binary makes more sense than using an inter-test
+//! locking mechanism which can cause weird test failures without any obvious clue to what might
+//! have caused those failures in the first place.
+
+use pathfinder_rpc::context::RpcContext;
+use pathfinder_rpc::{DefaultVersion, RpcServer};
+use serde_json::json;
+
+#[tokio::test]
+async fn failed_calls_are_labeled_with_error_class() {
+    use pathfinder_common::test_utils::metrics::{FakeRecorder, ScopedRecorderGuard};
+
+    let recorder = FakeRecorder::new_for(&["starknet_getBlockWithTxHashes"]);
+    let handle = recorder.handle();
+
+    // Automatically deregister the recorder
+    let _guard = ScopedRecorderGuard::new(recorder);
+
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let (_jh, addr) = RpcServer::new(addr, RpcContext::for_tests(), DefaultVersion::V04)
+        .spawn()
+        .unwrap();
+
+    let response: serde_json::Value = reqwest::Client::new()
+        .post(format!("http://{addr}/rpc/v0.4"))
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "method": "starknet_getBlockWithTxHashes",
+            "params": {"block_id": {"block_number": 9999}},
+            "id": 1
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    // Sanity check that this is the expected BlockNotFound error.
+    assert_eq!(response["error"]["code"], json!(24));
+
+    let failed = handle.get_counter_value_by_label(
+        "rpc_method_calls_failed_total",
+        [
+            ("method", "starknet_getBlockWithTxHashes"),
+            ("version", "v0.4"),
+            ("error_class", "client"),
+        ],
+    );
+    assert_eq!(failed, 1);
+}
@@ -0,0 +1,43 @@
+//! Separated from the other metrics tests because the `metrics` crate uses a singleton recorder,
+//! see `metrics.rs`.
+
+use pathfinder_rpc::context::RpcContext;
+use pathfinder_rpc::{DefaultVersion, RpcServer};
+
+#[tokio::test]
+async fn requests_which_never_reach_a_method_are_counted() {
+    use pathfinder_common::test_utils::metrics::{FakeRecorder, ScopedRecorderGuard};
+
+    // Records all counters, as these are not labeled by method.
+    let recorder = FakeRecorder::default();
+    let handle = recorder.handle();
+
+    // Automatically deregister the recorder
+    let _guard = ScopedRecorderGuard::new(recorder);
+
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let (_jh, addr) = RpcServer::new(addr, RpcContext::for_tests(), DefaultVersion::V04)
+        .spawn()
+        .unwrap();
+
+    let client = reqwest::Client::new();
+    for body in [
+        r#"{"jsonrpc": "2.0", "method": "#,
+        r#"{"jsonrpc": "1.0", "method": "starknet_chainId", "id": 1}"#,
+        r#"[]"#,
+        r#"{"jsonrpc": "2.0", "method": "starknet_unknown", "id": 1}"#,
+    ] {
+        client
+            .post(format!("http://{addr}/rpc/v0.4"))
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+    }
+
+    let count = |counter| handle.get_counter_value_by_label(counter, [("version", "v0.4")]);
+    assert_eq!(count("rpc_parse_errors_total"), 1);
+    assert_eq!(count("rpc_invalid_requests_total"), 2);
+    assert_eq!(count("rpc_method_not_found_total"), 1);
+}
@@ -54,6 +54,19 @@ mod tests {
         assert_eq!(serde_json::from_str::<Felt>(r#""0x""#).unwrap(), Felt::ZERO);
     }
 
+    /// Inputs which are not in the canonical form are accepted as well.
+    #[test]
+    fn non_canonical() {
+        let expected = Felt::from_be_slice(&[0x0a]).unwrap();
+        for input in [r#""0x0a""#, r#""a""#, r#""0x00a""#] {
+            assert_eq!(
+                serde_json::from_str::<Felt>(input).unwrap(),
+                expected,
+                "{input}"
+            );
+        }
+    }
+
     #[test]
     fn zero() {
         let original = Felt::ZERO;
@@ -240,6 +240,16 @@ impl<'inner> Transaction<'inner> {
         class::classes_exist(self, classes)
     }
 
+    /// Returns whether each of the classes has been declared at `block_id`, without reading
+    /// their definitions.
+    pub fn classes_declared_at(
+        &self,
+        block_id: BlockId,
+        classes: &[ClassHash],
+    ) -> anyhow::Result<Vec<bool>> {
+        class::classes_declared_at(self, block_id, classes)
+    }
+
     /// Returns the uncompressed class definition.
     pub fn class_definition(&self, class_hash: ClassHash) -> anyhow::Result<Option<Vec<u8>>> {
         class::class_definition(self, class_hash)
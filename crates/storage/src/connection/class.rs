@@ -119,6 +119,36 @@ pub(super) fn classes_exist(
         .collect::<Result<Vec<_>, _>>()?)
 }
 
+pub(super) fn classes_declared_at(
+    transaction: &Transaction<'_>,
+    block_id: BlockId,
+    classes: &[ClassHash],
+) -> anyhow::Result<Vec<bool>> {
+    let mut stmt = match block_id {
+        BlockId::Latest => transaction.inner().prepare(
+            "SELECT 1 FROM class_definitions WHERE hash = ? AND block_number IS NOT NULL",
+        ),
+        BlockId::Number(_) => transaction
+            .inner()
+            .prepare("SELECT 1 FROM class_definitions WHERE hash = ? AND block_number <= ?"),
+        BlockId::Hash(_) => transaction.inner().prepare(
+            r"SELECT 1 FROM class_definitions
+                WHERE hash = ? AND block_number <= (SELECT number from canonical_blocks WHERE hash = ?)",
+        ),
+    }
+    .context("Preparing class declaration query")?;
+
+    classes
+        .iter()
+        .map(|class_hash| match block_id {
+            BlockId::Latest => stmt.exists(params![class_hash]),
+            BlockId::Number(number) => stmt.exists(params![class_hash, &number]),
+            BlockId::Hash(hash) => stmt.exists(params![class_hash, &hash]),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .context("Querying for class declarations")
+}
+
 pub(super) fn class_definition(
     transaction: &Transaction<'_>,
     class_hash: ClassHash,
@@ -19,6 +19,7 @@ pub use connection::*;
 
 use pathfinder_common::{BlockHash, BlockNumber};
 use rusqlite::functions::FunctionFlags;
+use rusqlite::OpenFlags;
 
 use anyhow::Context;
 use r2d2::Pool;
@@ -27,6 +28,10 @@ use r2d2_sqlite::SqliteConnectionManager;
 /// Sqlite key used for the PRAGMA user version.
 const VERSION_KEY: &str = "user_version";
 
+/// How long a connection waits for a conflicting lock to be released before failing with
+/// "database is locked". Sqlite retries with an increasing backoff in the meantime.
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Specifies the [journal mode](https://sqlite.org/pragma.html#pragma_journal_mode)
 /// of the [Storage].
 #[derive(Clone, Copy)]
@@ -173,6 +178,42 @@ impl Storage {
         storage.create_pool(NonZeroU32::new(5).unwrap())
     }
 
+    /// Opens a read-only connection pool to an existing database, such as a replica of the
+    /// primary database which is kept up to date externally.
+    ///
+    /// The database is not migrated, and must already be at the schema version expected by
+    /// this application. Connections from this pool cannot write to the database.
+    pub fn open_read_only(database_path: PathBuf, capacity: NonZeroU32) -> anyhow::Result<Self> {
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY
+            | OpenFlags::SQLITE_OPEN_URI
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+
+        let connection = rusqlite::Connection::open_with_flags(&database_path, flags)
+            .context("Opening read-only DB")?;
+        let current_revision = schema_version(&connection)?;
+        let latest_revision = schema::BASE_SCHEMA_REVISION + schema::migrations().len();
+        anyhow::ensure!(
+            current_revision == latest_revision,
+            "Read-only database version {current_revision} does not match the expected version {latest_revision}"
+        );
+        connection
+            .close()
+            .map_err(|(_connection, error)| error)
+            .context("Closing read-only DB after checking its version")?;
+
+        let pool_manager = SqliteConnectionManager::file(&database_path)
+            .with_flags(flags)
+            .with_init(setup_functions);
+        let pool = Pool::builder()
+            .max_size(capacity.get())
+            .build(pool_manager)?;
+
+        Ok(Storage(Inner {
+            database_path: Arc::new(database_path),
+            pool,
+        }))
+    }
+
     pub fn path(&self) -> &Path {
         &self.0.database_path
     }
@@ -201,6 +242,27 @@ fn setup_connection(
     connection: &mut rusqlite::Connection,
     journal_mode: JournalMode,
 ) -> Result<(), rusqlite::Error> {
+    setup_functions(connection)?;
+
+    match journal_mode {
+        JournalMode::Rollback => {
+            // According to the documentation FULL is the recommended setting for rollback mode.
+            connection.pragma_update(None, "synchronous", "full")?;
+        }
+        JournalMode::WAL => {
+            // According to the documentation NORMAL is a good choice for WAL mode.
+            connection.pragma_update(None, "synchronous", "normal")?;
+        }
+    };
+
+    Ok(())
+}
+
+/// Sets up the parts of a connection which are independent of the journal mode,
+/// and which are therefore also required by read-only connections.
+fn setup_functions(connection: &mut rusqlite::Connection) -> Result<(), rusqlite::Error> {
+    connection.busy_timeout(BUSY_TIMEOUT)?;
+
     // enable foreign keys
     connection.set_db_config(
         rusqlite::config::DbConfig::SQLITE_DBCONFIG_ENABLE_FKEY,
@@ -222,17 +284,6 @@ fn setup_connection(
         },
     )?;
 
-    match journal_mode {
-        JournalMode::Rollback => {
-            // According to the documentation FULL is the recommended setting for rollback mode.
-            connection.pragma_update(None, "synchronous", "full")?;
-        }
-        JournalMode::WAL => {
-            // According to the documentation NORMAL is a good choice for WAL mode.
-            connection.pragma_update(None, "synchronous", "normal")?;
-        }
-    };
-
     Ok(())
 }
 
@@ -339,6 +390,23 @@ fn migrate_database(connection: &mut rusqlite::Connection) -> anyhow::Result<()>
     Ok(())
 }
 
+/// Returns true if `error` was caused by the database being locked by another connection, in
+/// which case the operation may succeed if retried.
+///
+/// This is only the case once [BUSY_TIMEOUT] has elapsed, or immediately for conflicting table
+/// locks of in-memory databases, which share their cache.
+pub fn is_busy(error: &anyhow::Error) -> bool {
+    use rusqlite::ErrorCode::{DatabaseBusy, DatabaseLocked};
+
+    error.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<rusqlite::Error>(),
+            Some(rusqlite::Error::SqliteFailure(error, _))
+                if matches!(error.code, DatabaseBusy | DatabaseLocked)
+        )
+    })
+}
+
 /// Returns the current schema version of the existing database,
 /// or `0` if database does not yet exist.
 fn schema_version(connection: &rusqlite::Connection) -> anyhow::Result<usize> {
@@ -359,6 +427,42 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn locked_database_is_busy() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("db.sqlite");
+        let storage = Storage::migrate(db_path.clone(), JournalMode::Rollback)
+            .unwrap()
+            .create_pool(NonZeroU32::new(1).unwrap())
+            .unwrap();
+
+        let mut connection = storage.connection().unwrap();
+        let _lock = connection
+            .transaction_with_behavior(TransactionBehavior::Exclusive)
+            .unwrap();
+
+        // Does not wait for the lock, unlike the connections of the pool.
+        let other = rusqlite::Connection::open(&db_path).unwrap();
+        other.busy_timeout(std::time::Duration::ZERO).unwrap();
+        let error = schema_version(&other)
+            .context("Reading schema version")
+            .unwrap_err();
+
+        assert!(is_busy(&error));
+    }
+
+    #[test]
+    fn other_errors_are_not_busy() {
+        let connection = rusqlite::Connection::open_in_memory().unwrap();
+        let error = connection
+            .execute("SELECT * FROM missing", [])
+            .context("Querying missing table")
+            .unwrap_err();
+
+        assert!(!is_busy(&error));
+        assert!(!is_busy(&anyhow::anyhow!("database is locked")));
+    }
+
     #[test]
     fn schema_version_defaults_to_zero() {
         let mut conn = rusqlite::Connection::open_in_memory().unwrap();
@@ -473,4 +577,42 @@ mod tests {
 
         assert_eq!(version, expected, "RPC database fixture needs migrating");
     }
+
+    #[test]
+    fn read_only_pool() {
+        use pathfinder_common::macro_prelude::*;
+        use pathfinder_common::BlockHeader;
+
+        let db_dir = tempfile::TempDir::new().unwrap();
+        let db_path = db_dir.path().join("primary.sqlite");
+
+        let primary = Storage::migrate(db_path.clone(), JournalMode::Rollback)
+            .unwrap()
+            .create_pool(NonZeroU32::new(1).unwrap())
+            .unwrap();
+        let header = BlockHeader::builder().finalize_with_hash(block_hash_bytes!(b"genesis"));
+        let mut connection = primary.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+        tx.insert_block_header(&header).unwrap();
+        tx.commit().unwrap();
+
+        let replica = Storage::open_read_only(db_path, NonZeroU32::new(1).unwrap()).unwrap();
+        let mut connection = replica.connection().unwrap();
+        let tx = connection.transaction().unwrap();
+
+        let latest = tx.block_id(BlockId::Latest).unwrap();
+        assert_eq!(latest, Some((BlockNumber::GENESIS, header.hash)));
+
+        let other = BlockHeader::builder().finalize_with_hash(block_hash_bytes!(b"other"));
+        tx.insert_block_header(&other).unwrap_err();
+    }
+
+    #[test]
+    fn read_only_pool_requires_migrated_db() {
+        let db_dir = tempfile::TempDir::new().unwrap();
+        let db_path = db_dir.path().join("empty.sqlite");
+        rusqlite::Connection::open(&db_path).unwrap();
+
+        Storage::open_read_only(db_path, NonZeroU32::new(1).unwrap()).unwrap_err();
+    }
 }